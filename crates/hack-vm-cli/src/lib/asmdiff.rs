@@ -0,0 +1,343 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Assembly Diff Module
+//!
+//! Compares two Hack assembly files while ignoring differences that cannot
+//! change what the program does, for the `diff-asm` subcommand:
+//!
+//! - Comments, blank lines, and whitespace.
+//! - The names of labels: labels are renamed in order of their first
+//!   appearance, so `(CRASH_7)` and `(TRUE_0)` match if they are used alike.
+//! - Predefined symbols, which are replaced by their addresses, so `@SP`
+//!   matches `@0` and `@R13` matches `@13`.
+//!
+//! Whatever still differs is reported as divergences, each listing the lines
+//! of either file that the other lacks:
+//!
+//! ```text
+//! divergence at a.asm:12 and b.asm:12
+//! - D=M
+//! + D=A
+//! ```
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Display};
+
+/// The most pairs of lines compared to find where two files diverge, beyond
+/// which their differing middle is reported as a single divergence.
+const MAX_COMPARISONS: usize = 1 << 22;
+
+/// The symbols predefined by the Hack assembler, other than `R0` to `R15`,
+/// and their addresses.
+const PREDEFINED: [(&str, u16); 7] = [
+    ("SP", 0),
+    ("LCL", 1),
+    ("ARG", 2),
+    ("THIS", 3),
+    ("THAT", 4),
+    ("SCREEN", 0x4000),
+    ("KBD", 0x6000),
+];
+
+/// A single meaningful line of an assembly file.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Line {
+    /// The line number in the file, counting from 1.
+    number: usize,
+    /// The line as written, without surrounding whitespace.
+    text: String,
+    /// The line with whitespace removed, labels renamed, and predefined
+    /// symbols replaced, which is what is compared.
+    canonical: String,
+}
+
+/// The meaningful lines of an assembly file.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct Assembly {
+    /// The file, as it is displayed.
+    name: String,
+    /// Its lines that are not blank or comments.
+    lines: Vec<Line>,
+}
+
+impl Assembly {
+    /// Reads the meaningful lines of the assembly `contents` of the file
+    /// displayed as `name`.
+    pub(crate) fn new(name: String, contents: &str) -> Self {
+        let mut lines: Vec<Line> = contents
+            .lines()
+            .zip(1_usize..)
+            .filter_map(|(line, number): (&str, usize)| {
+                let code: &str = line.split("//").next().unwrap_or_default();
+                let canonical: String = code
+                    .chars()
+                    .filter(|character: &char| !character.is_whitespace())
+                    .collect();
+                (!canonical.is_empty()).then(|| Line {
+                    number,
+                    text: line.trim().to_owned(),
+                    canonical,
+                })
+            })
+            .collect();
+
+        let labels: Vec<String> = lines
+            .iter()
+            .filter_map(|line: &Line| declared(&line.canonical))
+            .map(str::to_owned)
+            .collect();
+        let mut renamed: BTreeMap<String, String> = BTreeMap::new();
+        for line in &mut lines {
+            let (symbol, declaration): (&str, bool) =
+                match declared(&line.canonical) {
+                    Some(label) => (label, true),
+                    None => match line.canonical.strip_prefix('@') {
+                        Some(symbol) => (symbol, false),
+                        None => continue,
+                    },
+                };
+            let replacement: String =
+                if labels.iter().any(|label| label == symbol) {
+                    let next: String = format!("L{}", renamed.len());
+                    renamed.entry(symbol.to_owned()).or_insert(next).clone()
+                } else if let Some(address) = predefined(symbol) {
+                    address.to_string()
+                } else {
+                    continue;
+                };
+            line.canonical = if declaration {
+                format!("({replacement})")
+            } else {
+                format!("@{replacement}")
+            };
+        }
+        Self { name, lines }
+    }
+}
+
+/// A run of lines in which two assembly files diverge.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Divergence {
+    /// The line number at which it starts in the first file.
+    left_start: usize,
+    /// The line number at which it starts in the second file.
+    right_start: usize,
+    /// The lines only in the first file.
+    removed: Vec<Line>,
+    /// The lines only in the second file.
+    added: Vec<Line>,
+}
+
+/// What differs between two assembly files.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct AssemblyDiff {
+    /// The first file, as it is displayed.
+    left: String,
+    /// The second file, as it is displayed.
+    right: String,
+    /// Each run of lines in which the files diverge, in order.
+    divergences: Vec<Divergence>,
+}
+
+impl AssemblyDiff {
+    /// Compares two assembly files.
+    pub(crate) fn new(left: Assembly, right: Assembly) -> Self {
+        let matched: Vec<(usize, usize)> =
+            common_lines(&left.lines, &right.lines);
+        let mut divergences: Vec<Divergence> = Vec::new();
+        let (mut left_index, mut right_index): (usize, usize) = (0, 0);
+        let end: (usize, usize) = (left.lines.len(), right.lines.len());
+        for (left_match, right_match) in matched.into_iter().chain([end]) {
+            let removed: &[Line] =
+                left.lines.get(left_index..left_match).unwrap_or_default();
+            let added: &[Line] = right
+                .lines
+                .get(right_index..right_match)
+                .unwrap_or_default();
+            if !removed.is_empty() || !added.is_empty() {
+                divergences.push(Divergence {
+                    left_start: start(&left.lines, left_index),
+                    right_start: start(&right.lines, right_index),
+                    removed: removed.to_vec(),
+                    added: added.to_vec(),
+                });
+            }
+            left_index = left_match.saturating_add(1);
+            right_index = right_match.saturating_add(1);
+        }
+        Self {
+            left: left.name,
+            right: right.name,
+            divergences,
+        }
+    }
+
+    /// How many runs of lines the files diverge in.
+    pub(crate) const fn divergences(&self) -> usize {
+        self.divergences.len()
+    }
+}
+
+impl Display for AssemblyDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for divergence in &self.divergences {
+            writeln!(
+                f,
+                "divergence at {}:{} and {}:{}",
+                self.left,
+                divergence.left_start,
+                self.right,
+                divergence.right_start
+            )?;
+            for line in &divergence.removed {
+                writeln!(f, "- {}", line.text)?;
+            }
+            for line in &divergence.added {
+                writeln!(f, "+ {}", line.text)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Gets the label a canonical line declares, if it does.
+fn declared(canonical: &str) -> Option<&str> {
+    canonical.strip_prefix('(')?.strip_suffix(')')
+}
+
+/// Gets the address of a predefined symbol, if it is one.
+pub(crate) fn predefined(symbol: &str) -> Option<u16> {
+    if let Some(register) = symbol.strip_prefix('R')
+        && let Ok(number) = register.parse::<u16>()
+        && number <= 15
+        && register == number.to_string()
+    {
+        return Some(number);
+    }
+    PREDEFINED
+        .iter()
+        .find(|&&(name, _)| name == symbol)
+        .map(|&(_, address)| address)
+}
+
+/// Gets the line number at which a divergence starting at `index` in
+/// `lines` begins.
+fn start(lines: &[Line], index: usize) -> usize {
+    lines.get(index).map_or_else(
+        || {
+            lines
+                .last()
+                .map_or(1, |line: &Line| line.number.saturating_add(1))
+        },
+        |line: &Line| line.number,
+    )
+}
+
+/// Finds the longest sequence of lines the two files have in common, as
+/// pairs of indices into `left` and `right`, in order.
+///
+/// Lines they start and end with in common are matched first, so only what
+/// remains between them needs to be compared; see [`common_middle`].
+fn common_lines(left: &[Line], right: &[Line]) -> Vec<(usize, usize)> {
+    let same = |left: &Line, right: &Line| left.canonical == right.canonical;
+    let prefix: usize = left
+        .iter()
+        .zip(right)
+        .take_while(|&(left, right)| same(left, right))
+        .count();
+    let left_rest: &[Line] = left.get(prefix..).unwrap_or_default();
+    let right_rest: &[Line] = right.get(prefix..).unwrap_or_default();
+    let suffix: usize = left_rest
+        .iter()
+        .rev()
+        .zip(right_rest.iter().rev())
+        .take_while(|&(left, right)| same(left, right))
+        .count();
+    let left_middle: &[Line] = left_rest
+        .get(..left_rest.len().saturating_sub(suffix))
+        .unwrap_or_default();
+    let right_middle: &[Line] = right_rest
+        .get(..right_rest.len().saturating_sub(suffix))
+        .unwrap_or_default();
+
+    let mut matched: Vec<(usize, usize)> =
+        (0..prefix).map(|index: usize| (index, index)).collect();
+    matched.extend(common_middle(left_middle, right_middle).into_iter().map(
+        |(left_index, right_index): (usize, usize)| {
+            (
+                prefix.saturating_add(left_index),
+                prefix.saturating_add(right_index),
+            )
+        },
+    ));
+    let left_suffix: usize = left.len().saturating_sub(suffix);
+    let right_suffix: usize = right.len().saturating_sub(suffix);
+    matched.extend((0..suffix).map(|offset: usize| {
+        (
+            left_suffix.saturating_add(offset),
+            right_suffix.saturating_add(offset),
+        )
+    }));
+    matched
+}
+
+/// Finds the longest sequence of lines two runs of lines have in common, as
+/// with [`common_lines`], unless it would take more than [`MAX_COMPARISONS`].
+fn common_middle(left: &[Line], right: &[Line]) -> Vec<(usize, usize)> {
+    let columns: usize = right.len().saturating_add(1);
+    let cells: usize = left.len().saturating_add(1).saturating_mul(columns);
+    if cells > MAX_COMPARISONS {
+        return Vec::new();
+    }
+    // The length of the longest common subsequence of what follows each
+    // pair of indices, row by row.
+    let mut lengths: Vec<u32> = vec![0; cells];
+    let cell = |lengths: &[u32], row: usize, column: usize| -> u32 {
+        lengths
+            .get(row.saturating_mul(columns).saturating_add(column))
+            .copied()
+            .unwrap_or_default()
+    };
+    for (left_index, left_line) in left.iter().enumerate().rev() {
+        let below: usize = left_index.saturating_add(1);
+        for (right_index, right_line) in right.iter().enumerate().rev() {
+            let after: usize = right_index.saturating_add(1);
+            let length: u32 = if left_line.canonical == right_line.canonical {
+                cell(&lengths, below, after).saturating_add(1)
+            } else {
+                cell(&lengths, below, right_index)
+                    .max(cell(&lengths, left_index, after))
+            };
+            if let Some(slot) = lengths.get_mut(
+                left_index
+                    .saturating_mul(columns)
+                    .saturating_add(right_index),
+            ) {
+                *slot = length;
+            }
+        }
+    }
+
+    let mut matched: Vec<(usize, usize)> = Vec::new();
+    let (mut left_index, mut right_index): (usize, usize) = (0, 0);
+    while let (Some(left_line), Some(right_line)) =
+        (left.get(left_index), right.get(right_index))
+    {
+        let below: usize = left_index.saturating_add(1);
+        let after: usize = right_index.saturating_add(1);
+        if left_line.canonical == right_line.canonical {
+            matched.push((left_index, right_index));
+            left_index = below;
+            right_index = after;
+        } else if cell(&lengths, below, right_index)
+            >= cell(&lengths, left_index, after)
+        {
+            left_index = below;
+        } else {
+            right_index = after;
+        }
+    }
+    matched
+}
@@ -0,0 +1,411 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Builder Module
+//!
+//! Configures a run from a program, rather than from the command line. Each
+//! option has a typed setter, named after it, and the [`Config`] is checked
+//! once it is built:
+//!
+//! ```
+//! use hack_vm_cli::Config;
+//! use hack_vm_syntax::error::HackError;
+//! use hack_vm_syntax::mode::Mode;
+//! use hack_vm_syntax::target::Target;
+//!
+//! let config: Config = Config::builder()
+//!     .path("./project")
+//!     .mode(Mode::Permissive)
+//!     .target(Target::HACK_EXTENDED)
+//!     .labels("true-end")
+//!     .annotate(true)
+//!     .build()?;
+//! # Ok::<(), HackError>(())
+//! ```
+//!
+//! Unlike [`Config::build`], no default options are read from a
+//! `translator.toml` or the environment; see [`crate::defaults`].
+
+use std::path::PathBuf;
+
+use hack_vm_codegen::labels::{LabelStyle, Numbering};
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::extensions::Extensions;
+use hack_vm_syntax::mode::Mode;
+use hack_vm_syntax::parser::Symbol;
+use hack_vm_syntax::target::Target;
+
+use crate::color::ColorChoice;
+use crate::compat::Compat;
+use crate::progress::ProgressPolicy;
+use crate::rewrite::Rewrites;
+use crate::sandbox::Sandbox;
+use crate::summary::Report;
+use crate::verbosity::Verbosity;
+use crate::verify::Verification;
+use crate::{Config, Subcommand, SymlinkPolicy};
+
+/// Builds a [`Config`] from typed options; see [`Config::builder`].
+#[derive(Debug, Default, Hash)]
+pub struct ConfigBuilder {
+    /// The configuration, apart from the options checked when it is built.
+    config: Config,
+    /// The path to translate, if one was given.
+    path: Option<PathBuf>,
+    /// The style of the labels generated for comparisons, as written for
+    /// `--labels`, if one was given.
+    labels: Option<String>,
+    /// The name of the code read from standard input, if one was given.
+    source_name: Option<String>,
+    /// The directory everything written must be inside of, if one was
+    /// given.
+    sandbox: Option<PathBuf>,
+    /// The file of rewrite rules, if one was given.
+    rewrites: Option<PathBuf>,
+    /// The passes to disable, enable, or run, as written for `--passes`, if
+    /// they were given.
+    passes: Option<String>,
+}
+
+impl ConfigBuilder {
+    /// Sets the Hack VM file, directory, or zip archive to translate, or `-`
+    /// for standard input.
+    #[must_use]
+    pub fn path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config.subcommand = Subcommand::Translate;
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Sets the project directory to translate according to its
+    /// `hackvm.toml` manifest, as with `build`.
+    #[must_use]
+    pub fn project<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.config.subcommand = Subcommand::Build;
+        self.path = Some(directory.into());
+        self
+    }
+
+    /// Sets the directory to cache generated assembly in, as with
+    /// `--cache-dir`.
+    #[must_use]
+    pub fn cache_dir<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.config.cache_dir = Some(directory.into());
+        self
+    }
+
+    /// Adds a directory to search for functions the program calls but does
+    /// not define, as with `-L`.
+    #[must_use]
+    pub fn library<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.config.libraries.push(directory.into());
+        self
+    }
+
+    /// Sets the directory holding the Jack OS to link the program with, as
+    /// with `--with-os`.
+    #[must_use]
+    pub fn with_os<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.config.os = Some(directory.into());
+        self
+    }
+
+    /// Sets the file to write a summary of the run to, as with
+    /// `--summary-json`.
+    #[must_use]
+    pub fn summary_json<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config.summary_json = Some(path.into());
+        self
+    }
+
+    /// Sets whether the time spent in each phase is reported, as with
+    /// `--time`.
+    #[must_use]
+    pub fn time(self, time: bool) -> Self {
+        self.report(Report::Time, time)
+    }
+
+    /// Sets whether the symbols of the assembly are listed, as with
+    /// `--dump-symbols`.
+    #[must_use]
+    pub fn dump_symbols(self, dump_symbols: bool) -> Self {
+        self.report(Report::Symbols, dump_symbols)
+    }
+
+    /// Sets whether `report` is printed once the run is over.
+    fn report(mut self, report: Report, enabled: bool) -> Self {
+        if enabled {
+            let _new: bool = self.config.reports.insert(report);
+        } else {
+            let _present: bool = self.config.reports.remove(&report);
+        }
+        self
+    }
+
+    /// Sets the files of a directory to translate before the others, by the
+    /// names they have without `.vm`, as with `--first`.
+    #[must_use]
+    pub fn first<I: IntoIterator<Item = S>, S: Into<String>>(
+        mut self,
+        names: I,
+    ) -> Self {
+        self.config.first = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets whether the assembly for the files in a zip archive is written
+    /// next to it, as with `--write-asm`.
+    #[must_use]
+    pub const fn write_asm(mut self, write_asm: bool) -> Self {
+        self.config.write_asm = write_asm;
+        self
+    }
+
+    /// Sets whether symbolic links in a directory are translated, as with
+    /// `--follow-symlinks`.
+    #[must_use]
+    pub const fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.config.symlinks = if follow {
+            SymlinkPolicy::Follow
+        } else {
+            SymlinkPolicy::Skip
+        };
+        self
+    }
+
+    /// Sets the name static variables are named after when reading from
+    /// standard input, as with `--source-name`.
+    #[must_use]
+    pub fn source_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.source_name = Some(name.into());
+        self
+    }
+
+    /// Sets the style of the labels generated for comparisons, written as
+    /// for `--labels`: `crash-burn`, `true-end`, or a prefix.
+    #[must_use]
+    pub fn labels<S: Into<String>>(mut self, style: S) -> Self {
+        self.labels = Some(style.into());
+        self
+    }
+
+    /// Sets how the labels generated for comparisons are numbered, as with
+    /// `--label-numbering`.
+    #[must_use]
+    pub const fn label_numbering(mut self, numbering: Numbering) -> Self {
+        self.config.labels.set_numbering(numbering);
+        self
+    }
+
+    /// Sets whether generated labels are renumbered in order of appearance,
+    /// as with `--renumber-labels`.
+    #[must_use]
+    pub const fn renumber_labels(mut self, renumber: bool) -> Self {
+        self.config.labels.set_renumber(renumber);
+        self
+    }
+
+    /// Sets whether and how the extended arithmetic commands are translated,
+    /// as with `--extensions`.
+    #[must_use]
+    pub const fn extensions(mut self, extensions: Extensions) -> Self {
+        self.config.extensions = extensions;
+        self
+    }
+
+    /// Sets how strictly Hack VM code is parsed, as with `--mode`.
+    #[must_use]
+    pub const fn mode(mut self, mode: Mode) -> Self {
+        self.config.mode = mode;
+        self
+    }
+
+    /// Sets whether Hack assembly written inline is passed through, as with
+    /// `--inline-asm`.
+    #[must_use]
+    pub const fn inline_asm(mut self, inline_asm: bool) -> Self {
+        self.config.inline_asm = inline_asm;
+        self
+    }
+
+    /// Sets which other translator's conventions are matched, as with
+    /// `--compat`.
+    #[must_use]
+    pub const fn compat(mut self, compat: Compat) -> Self {
+        self.config.compat = compat;
+        self
+    }
+
+    /// Sets whether each VM command is written as a comment above its
+    /// assembly, as with `--annotate`.
+    #[must_use]
+    pub const fn annotate(mut self, annotate: bool) -> Self {
+        self.config.annotate = annotate;
+        self
+    }
+
+    /// Sets whether each file is checked to run the same when translated as
+    /// when interpreted, as with `--verify`.
+    #[must_use]
+    pub const fn verify(mut self, verify: bool) -> Self {
+        self.config.verification = if verify {
+            Verification::Checked
+        } else {
+            Verification::Skipped
+        };
+        self
+    }
+
+    /// Sets the file of rules rewriting instructions before they are
+    /// translated, as with `--rewrites`.
+    #[must_use]
+    pub fn rewrites<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.rewrites = Some(path.into());
+        self
+    }
+
+    /// Sets which passes run over each file, as written for `--passes`.
+    #[must_use]
+    pub fn passes<S: Into<String>>(mut self, passes: S) -> Self {
+        self.passes = Some(passes.into());
+        self
+    }
+
+    /// Sets the variant of the Hack CPU to generate assembly for, as with
+    /// `--target`.
+    #[must_use]
+    pub const fn target(mut self, target: Target) -> Self {
+        self.config.target = Some(target);
+        self
+    }
+
+    /// Sets the most bytes each file read may have, as with
+    /// `--max-file-size`.
+    #[must_use]
+    pub const fn max_file_size(mut self, bytes: usize) -> Self {
+        self.config.limits.set_file_size(bytes);
+        self
+    }
+
+    /// Sets the most characters each line may have, as with
+    /// `--max-line-length`.
+    #[must_use]
+    pub const fn max_line_length(mut self, characters: usize) -> Self {
+        self.config.limits.set_line_length(characters);
+        self
+    }
+
+    /// Sets the most instructions each file may be parsed into, as with
+    /// `--max-instructions`.
+    #[must_use]
+    pub const fn max_instructions(mut self, instructions: usize) -> Self {
+        self.config.limits.set_instructions(instructions);
+        self
+    }
+
+    /// Sets whether invalid UTF-8 in each file is replaced, rather than
+    /// rejected, as with `--lossy-utf8`.
+    #[must_use]
+    pub const fn lossy_utf8(mut self, lossy: bool) -> Self {
+        self.config.limits.set_lossy_utf8(lossy);
+        self
+    }
+
+    /// Sets how many stages of translation are printed, from 0 to 3, as with
+    /// `-v`, `-vv`, and `-vvv`.
+    #[must_use]
+    pub fn verbosity(mut self, levels: usize) -> Self {
+        self.config.verbosity = Verbosity::default().raised(levels);
+        self
+    }
+
+    /// Sets when diagnostics are styled, as with `--color`.
+    #[must_use]
+    pub const fn color(mut self, color: ColorChoice) -> Self {
+        self.config.color = color;
+        self
+    }
+
+    /// Sets whether the progress of translating many files is hidden, as
+    /// with `--quiet`.
+    #[must_use]
+    pub const fn quiet(mut self, quiet: bool) -> Self {
+        self.config.progress = if quiet {
+            ProgressPolicy::Quiet
+        } else {
+            ProgressPolicy::Auto
+        };
+        self
+    }
+
+    /// Sets the directory everything written must be inside of, as with
+    /// `--sandbox`.
+    #[must_use]
+    pub fn sandbox<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.sandbox = Some(directory.into());
+        self
+    }
+
+    /// Checks the options and builds the [`Config`].
+    ///
+    /// # Errors
+    ///
+    /// If no path was given, the error received will be a
+    /// [`HackError::Misconfiguration`], as though no positional arguments
+    /// were passed.
+    ///
+    /// If the style of the labels or the source name is not a valid symbol,
+    /// a source name was given without reading from standard input, files
+    /// read from standard input are to be verified, or a pass is not
+    /// recognized, the error received will be a
+    /// [`HackError::InvalidOption`]. The same goes
+    /// for a sandbox that is not an existing directory, while a cache
+    /// directory or summary outside of it is a [`HackError::WriteError`].
+    ///
+    /// Any error from reading the rewrite rules is propagated.
+    pub fn build(self) -> Result<Config, HackError> {
+        let mut config: Config = self.config;
+        config.file_path = self.path.ok_or(HackError::Misconfiguration(0))?;
+        if let Some(labels) = self.labels {
+            config.labels.set_style(Config::parsed_value::<LabelStyle>(
+                "labels", &labels,
+            )?);
+        }
+        if let Some(source_name) = self.source_name {
+            if !config.reads_stdin() {
+                return Err(HackError::InvalidOption(
+                    "a source name only applies when reading from standard \
+                    input, with \"-\" as the path"
+                        .to_owned(),
+                ));
+            }
+            config.source_name = Some(Config::parsed_value::<Symbol>(
+                "source name",
+                &source_name,
+            )?);
+        }
+        if config.verifies() && config.reads_stdin() {
+            return Err(HackError::InvalidOption(
+                "standard input cannot be verified, as it cannot be read again"
+                    .to_owned(),
+            ));
+        }
+        if let Some(rewrites) = self.rewrites {
+            config.rewrites = Rewrites::load(&rewrites)?;
+        }
+        if let Some(passes) = self.passes {
+            config.passes.apply(&passes).map_err(|error: HackError| {
+                HackError::InvalidOption(format!(
+                    "passes \"{passes}\" were given, but {error}"
+                ))
+            })?;
+        }
+        config.sandbox = self
+            .sandbox
+            .map(|directory: PathBuf| Sandbox::new(&directory))
+            .transpose()?;
+        config.check_sandbox()?;
+        Ok(config)
+    }
+}
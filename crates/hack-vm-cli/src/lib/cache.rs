@@ -0,0 +1,149 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Cache Module
+//!
+//! Persists the generated assembly for each translated file, keyed by a hash
+//! of its contents, so repeated runs over a large project only translate the
+//! files that actually changed. The warnings of the lints about each file are
+//! stored with it, so that they are printed on every run, not only the first.
+
+use core::fmt::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::trace;
+
+use crate::Settings;
+use crate::lints::Warning;
+
+/// A directory of previously generated assembly fragments.
+///
+/// Entries are content-addressed: the name of each entry is derived from the
+/// source text, the name used for its static variables, the stem of its file,
+/// whether it is the entry file of the program, the offset applied to its
+/// generated labels, the [`Settings`] it is translated with, and the version
+/// of the translator that produced it. An entry never needs to be
+/// invalidated, since any change to those inputs simply produces a different
+/// name. Stale entries are left in place and can be cleaned up by deleting
+/// the directory.
+#[derive(Debug, Clone, Hash)]
+pub(crate) struct Cache {
+    /// The directory the entries are stored in.
+    directory: PathBuf,
+}
+
+impl Cache {
+    /// The offset basis of the 64-bit FNV-1a hash.
+    const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    /// The prime of the 64-bit FNV-1a hash.
+    const FNV_PRIME: u64 = 0x0100_0000_01B3;
+
+    /// Opens the cache stored in `directory`, creating it if needed.
+    pub(crate) fn open(directory: &Path) -> Result<Self, HackError> {
+        fs::create_dir_all(directory)?;
+        Ok(Self {
+            directory: directory.to_path_buf(),
+        })
+    }
+
+    /// Computes the key for a source file.
+    ///
+    /// The `stem` and `entry` do not change the assembly, but they do change
+    /// the warnings stored with it; see [`crate::lints::lint`].
+    ///
+    /// A stable hash is used rather than [`std::hash::DefaultHasher`], whose
+    /// output may change between Rust releases and would silently invalidate
+    /// every entry.
+    pub(crate) fn key(
+        source: &str,
+        file_name: &str,
+        stem: Option<&str>,
+        entry: bool,
+        label_offset: usize,
+        settings: &Settings,
+    ) -> u64 {
+        let stem: String =
+            stem.map_or_else(String::new, |stem: &str| format!("stem={stem}"));
+        let entry: &str = if entry { "entry" } else { "" };
+        let label_offset: String = label_offset.to_string();
+        let settings: String = settings.to_string();
+        [
+            env!("CARGO_PKG_VERSION"),
+            file_name,
+            &stem,
+            entry,
+            &label_offset,
+            &settings,
+            source,
+        ]
+        .iter()
+        .fold(Self::FNV_OFFSET_BASIS, |hash: u64, part: &&str| {
+            // Terminate each part so that ("ab", "c") and ("a", "bc")
+            // hash differently.
+            part.bytes().chain([0]).fold(hash, |hash: u64, byte: u8| {
+                (hash ^ u64::from(byte)).wrapping_mul(Self::FNV_PRIME)
+            })
+        })
+    }
+
+    /// The path of the entry for the given key, with the given `extension`.
+    fn entry(&self, key: u64, extension: &str) -> PathBuf {
+        self.directory.join(format!("{key:016X}.{extension}"))
+    }
+
+    /// Fetches the assembly previously stored under `key` and the warnings
+    /// stored with it, about the file displayed as `file`, if any.
+    pub(crate) fn get(
+        &self,
+        key: u64,
+        file: &str,
+    ) -> Result<Option<(String, Vec<Warning>)>, HackError> {
+        // The warnings are written first, so an entry whose assembly exists
+        // has its warnings too, unless they were deleted since.
+        let read = |extension: &str| match fs::read_to_string(
+            self.entry(key, extension),
+        ) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(HackError::from(error)),
+        };
+        let Some(assembly) = read("asm")? else {
+            trace::event!(Debug, "cache miss for {key:016X}");
+            return Ok(None);
+        };
+        let Some(warnings) = read("warnings")? else {
+            trace::event!(Debug, "cache miss for {key:016X}, without warnings");
+            return Ok(None);
+        };
+        trace::event!(Debug, "cache hit for {key:016X}");
+        let warnings: Vec<Warning> = warnings
+            .lines()
+            .filter_map(|line: &str| Warning::from_cached(file, line))
+            .collect();
+        Ok(Some((assembly, warnings)))
+    }
+
+    /// Stores `assembly` and the `warnings` about it under `key`, replacing
+    /// any existing entry.
+    pub(crate) fn insert(
+        &self,
+        key: u64,
+        assembly: &str,
+        warnings: &[Warning],
+    ) -> Result<(), HackError> {
+        let warnings: String = warnings.iter().fold(
+            String::new(),
+            |mut warnings: String, warning: &Warning| {
+                let _written: fmt::Result =
+                    writeln!(warnings, "{}", warning.to_cached());
+                warnings
+            },
+        );
+        fs::write(self.entry(key, "warnings"), warnings)
+            .and_then(|()| fs::write(self.entry(key, "asm"), assembly))
+            .map_err(|error| HackError::WriteError(error.to_string()))
+    }
+}
@@ -0,0 +1,162 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Color Module
+//!
+//! Styles the errors and warnings printed to stderr, so that long output
+//! from a broken submission can be scanned at a glance: errors are labeled
+//! in bold red, warnings in bold yellow, and where a problem is in bold.
+//!
+//! Whether to style them is chosen with `--color`:
+//!
+//! - `auto` (the default): Only when stderr is a terminal, and the
+//!   `NO_COLOR` environment variable is unset or empty.
+//! - `always`: Even when stderr is redirected, such as into a CI log that
+//!   renders colors.
+//! - `never`: Not at all.
+//!
+//! Problems with the arguments themselves are reported before `--color` is
+//! known, so they are styled as with `auto`.
+
+use core::fmt::{self, Display};
+use core::str::FromStr;
+use std::env;
+use std::io::{self, IsTerminal as _};
+
+use hack_vm_syntax::error::HackError;
+
+/// When diagnostics are styled.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Diagnostics are styled if stderr is a terminal and `NO_COLOR` is not
+    /// set.
+    #[default]
+    Auto,
+    /// Diagnostics are always styled.
+    Always,
+    /// Diagnostics are never styled.
+    Never,
+}
+
+impl ColorChoice {
+    /// The name of the `auto` choice.
+    const AUTO: &str = "auto";
+    /// The name of the `always` choice.
+    const ALWAYS: &str = "always";
+    /// The name of the `never` choice.
+    const NEVER: &str = "never";
+}
+
+impl FromStr for ColorChoice {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::AUTO => Ok(Self::Auto),
+            Self::ALWAYS => Ok(Self::Always),
+            Self::NEVER => Ok(Self::Never),
+            other => Err(HackError::FromStrError(format!(
+                "\"{other}\" is not a recognized color choice, expected \
+                \"{}\", \"{}\", or \"{}\"",
+                Self::AUTO,
+                Self::ALWAYS,
+                Self::NEVER
+            ))),
+        }
+    }
+}
+
+impl Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Auto => write!(f, "{}", Self::AUTO),
+            Self::Always => write!(f, "{}", Self::ALWAYS),
+            Self::Never => write!(f, "{}", Self::NEVER),
+        }
+    }
+}
+
+/// The styles diagnostics are printed with, which are empty if they are not
+/// styled.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Palette {
+    /// Whether diagnostics are styled.
+    enabled: bool,
+}
+
+impl Palette {
+    /// The palette that does not style anything.
+    pub(crate) const PLAIN: Self = Self { enabled: false };
+
+    /// The escape sequence for errors, in bold red.
+    const ERROR: &str = "\x1b[1;31m";
+    /// The escape sequence for warnings, in bold yellow.
+    const WARNING: &str = "\x1b[1;33m";
+    /// The escape sequence for where a problem is, in bold.
+    const LOCATION: &str = "\x1b[1m";
+    /// The escape sequence ending a style.
+    const RESET: &str = "\x1b[0m";
+
+    /// Determines whether diagnostics are styled according to `choice`.
+    pub fn new(choice: ColorChoice) -> Self {
+        let enabled: bool = match choice {
+            ColorChoice::Auto => {
+                io::stderr().is_terminal()
+                    && env::var_os("NO_COLOR")
+                        .is_none_or(|no_color| no_color.is_empty())
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+        Self { enabled }
+    }
+
+    /// Styles `text` as the label of an error.
+    pub const fn error<T: Display>(self, text: T) -> Styled<T> {
+        self.styled(Self::ERROR, text)
+    }
+
+    /// Styles `text` as the label of a warning.
+    pub const fn warning<T: Display>(self, text: T) -> Styled<T> {
+        self.styled(Self::WARNING, text)
+    }
+
+    /// Styles `text` as where a problem is.
+    pub const fn location<T: Display>(self, text: T) -> Styled<T> {
+        self.styled(Self::LOCATION, text)
+    }
+
+    /// Styles `text` with the escape sequence `style`, if styling is
+    /// enabled.
+    const fn styled<T: Display>(
+        self,
+        style: &'static str,
+        text: T,
+    ) -> Styled<T> {
+        Styled {
+            style: if self.enabled { Some(style) } else { None },
+            text,
+        }
+    }
+}
+
+/// Text styled by a [`Palette`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Styled<T: Display> {
+    /// The escape sequence starting the style, if styling is enabled.
+    style: Option<&'static str>,
+    /// The text.
+    text: T,
+}
+
+impl<T: Display> Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.style {
+            Some(style) => {
+                write!(f, "{style}{}{}", self.text, Palette::RESET)
+            }
+            None => write!(f, "{}", self.text),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Compatibility Module
+//!
+//! Matches the observable conventions of other translators, chosen with
+//! `--compat`, so that the output lands where their tooling expects it.
+//!
+//! - `nand2tetris`: The conventions of the course's reference translator. A
+//!   directory `Foo` is translated into the single file `Foo/Foo.asm`, rather
+//!   than one assembly file per VM file.
+//!
+//! The reference translator also writes bootstrap code calling `Sys.init`,
+//! which this translator does not, so programs relying on it still need
+//! their stack pointer set up by the test script.
+
+use core::fmt::{self, Display};
+use core::str::FromStr;
+use std::path::{Path, PathBuf};
+
+use hack_vm_syntax::error::HackError;
+
+/// Which other translator's conventions are matched, if any.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum Compat {
+    /// This translator's own conventions are followed.
+    #[default]
+    Disabled,
+    /// The conventions of the nand2tetris reference translator are matched.
+    Nand2tetris,
+}
+
+impl Compat {
+    /// The name of the nand2tetris conventions.
+    const NAND2TETRIS: &str = "nand2tetris";
+
+    /// Gets the single file the VM files in `directory` are translated into,
+    /// if they are not each translated into their own.
+    pub(crate) fn combined_output(self, directory: &Path) -> Option<PathBuf> {
+        match self {
+            Self::Disabled => None,
+            Self::Nand2tetris => {
+                let name: &Path = directory.file_name()?.as_ref();
+                Some(directory.join(name).with_extension("asm"))
+            }
+        }
+    }
+}
+
+impl FromStr for Compat {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::NAND2TETRIS => Ok(Self::Nand2tetris),
+            other => Err(HackError::FromStrError(format!(
+                "\"{other}\" is not a recognized translator to be compatible \
+                with, expected \"{}\"",
+                Self::NAND2TETRIS
+            ))),
+        }
+    }
+}
+
+impl Display for Compat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Disabled => write!(f, "disabled"),
+            Self::Nand2tetris => write!(f, "{}", Self::NAND2TETRIS),
+        }
+    }
+}
@@ -0,0 +1,424 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - CPU Module
+//!
+//! Assembles Hack assembly and runs it on an emulated Hack CPU, so what
+//! translated code does can be checked without the tools of the course; see
+//! [`crate::verify`].
+//!
+//! The assembler resolves the predefined symbols, labels, and variables as
+//! the course's does, allocating variables from address 16 in order of their
+//! first use. It also accepts a computation with its operands the other way
+//! around, such as `M+D` for `D+M`. A program runs until it steps past its
+//! last instruction, or for at most [`MAX_CYCLES`] instructions.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use core::str::FromStr;
+
+use hack_vm_syntax::error::HackError;
+
+use crate::asmdiff;
+
+/// The most instructions a program may run.
+pub const MAX_CYCLES: usize = 10_000_000;
+
+/// How many words of memory the CPU has, as addressed by the `A` register.
+const MEMORY_SIZE: usize = 0x8000;
+
+/// The address of the first variable.
+const VARIABLE_BASE: u16 = 16;
+
+/// The computations of the ALU that read `A`, with their control bits: `zx`,
+/// `nx`, `zy`, `ny`, `f`, and `no`, from the highest bit. Those reading `M`
+/// instead have the same bits.
+const COMPUTATIONS: [(&str, u8); 18] = [
+    ("0", 0b10_1010),
+    ("1", 0b11_1111),
+    ("-1", 0b11_1010),
+    ("D", 0b00_1100),
+    ("A", 0b11_0000),
+    ("!D", 0b00_1101),
+    ("!A", 0b11_0001),
+    ("-D", 0b00_1111),
+    ("-A", 0b11_0011),
+    ("D+1", 0b01_1111),
+    ("A+1", 0b11_0111),
+    ("D-1", 0b00_1110),
+    ("A-1", 0b11_0010),
+    ("D+A", 0b00_0010),
+    ("D-A", 0b01_0011),
+    ("A-D", 0b00_0111),
+    ("D&A", 0b00_0000),
+    ("D|A", 0b01_0101),
+];
+
+/// The jumps, in the order of their bits.
+const JUMPS: [&str; 8] = ["", "JGT", "JEQ", "JGE", "JLT", "JNE", "JLE", "JMP"];
+
+/// A single assembled instruction.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+enum Operation {
+    /// An A-instruction, loading a value into `A`.
+    Address(i16),
+    /// A C-instruction.
+    Compute {
+        /// Whether the computation reads `M` rather than `A`.
+        memory: bool,
+        /// The control bits of the ALU; see [`COMPUTATIONS`].
+        control: u8,
+        /// Where the result is stored: `A`, `D`, and `M`, from the highest
+        /// bit.
+        destination: u8,
+        /// When to jump: if the result is negative, zero, or positive, from
+        /// the highest bit.
+        jump: u8,
+    },
+}
+
+/// An emulated Hack CPU, with an assembled program in its ROM.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Cpu {
+    /// The program.
+    rom: Vec<Operation>,
+    /// Each word of memory, by its address.
+    ram: Vec<i16>,
+    /// The addresses of the variables, by their names.
+    variables: BTreeMap<String, u16>,
+    /// The `A` register.
+    address: i16,
+    /// The `D` register.
+    data: i16,
+    /// The address in [`Cpu::rom`] of the next instruction.
+    counter: usize,
+    /// The address in [`Cpu::rom`] of the instruction after each line of
+    /// the program that is only a comment, in order.
+    comments: Vec<usize>,
+}
+
+impl Cpu {
+    /// Gets the word of memory at `address`, or 0 if there is none.
+    pub fn get(&self, address: u16) -> i16 {
+        self.ram
+            .get(usize::from(address))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Sets the word of memory at `address` to `value`, if there is one.
+    pub fn set(&mut self, address: u16, value: i16) {
+        if let Some(word) = self.ram.get_mut(usize::from(address)) {
+            *word = value;
+        }
+    }
+
+    /// Gets the address of the variable `name`, if the program uses it.
+    pub fn variable(&self, name: &str) -> Option<u16> {
+        self.variables.get(name).copied()
+    }
+
+    /// Gets the address of the next instruction to run.
+    pub const fn counter(&self) -> usize {
+        self.counter
+    }
+
+    /// Gets the address of the instruction after each line of the program
+    /// that is only a comment, such as the annotation of a VM command, in
+    /// order.
+    pub fn comments(&self) -> &[usize] {
+        &self.comments
+    }
+
+    /// Runs the program from its first instruction until it steps past its
+    /// last, and gets how many instructions it ran.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] if an instruction
+    /// addresses memory that does not exist, or if the program runs for more
+    /// than [`MAX_CYCLES`] instructions.
+    pub fn run(&mut self) -> Result<usize, HackError> {
+        self.counter = 0;
+        self.run_to(&BTreeSet::new())
+    }
+
+    /// Runs the program from the next instruction until the one after that
+    /// is at one of the addresses `stops`, or it steps past its last, and
+    /// gets how many instructions it ran. At least one is run, if any are
+    /// left.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] if an instruction
+    /// addresses memory that does not exist, or if the program runs for more
+    /// than [`MAX_CYCLES`] instructions.
+    pub fn run_to(
+        &mut self,
+        stops: &BTreeSet<usize>,
+    ) -> Result<usize, HackError> {
+        let mut cycles: usize = 0;
+        while let Some(&operation) = self.rom.get(self.counter) {
+            if cycles == MAX_CYCLES {
+                return Err(HackError::IllegalInstruction(format!(
+                    "the program ran for more than {MAX_CYCLES} instructions"
+                )));
+            }
+            cycles = cycles.saturating_add(1);
+            self.step(operation).map_err(|error: HackError| {
+                if let HackError::IllegalInstruction(ref message) = error {
+                    HackError::IllegalInstruction(format!(
+                        "instruction {}: {message}",
+                        self.counter
+                    ))
+                } else {
+                    error
+                }
+            })?;
+            if stops.contains(&self.counter) {
+                break;
+            }
+        }
+        Ok(cycles)
+    }
+
+    /// Runs `operation`, the instruction at [`Cpu::counter`].
+    fn step(&mut self, operation: Operation) -> Result<(), HackError> {
+        self.counter = self.counter.saturating_add(1);
+        let Operation::Compute {
+            memory,
+            control,
+            destination,
+            jump,
+        } = operation
+        else {
+            if let Operation::Address(value) = operation {
+                self.address = value;
+            }
+            return Ok(());
+        };
+        let address: i16 = self.address;
+        let operand: i16 = if memory { self.read(address)? } else { address };
+        let result: i16 = alu(self.data, operand, control);
+        if destination & 0b001 != 0 {
+            self.write(address, result)?;
+        }
+        if destination & 0b100 != 0 {
+            self.address = result;
+        }
+        if destination & 0b010 != 0 {
+            self.data = result;
+        }
+        let taken: bool = (jump & 0b100 != 0 && result < 0)
+            || (jump & 0b010 != 0 && result == 0)
+            || (jump & 0b001 != 0 && result > 0);
+        if taken {
+            self.counter = usize::from(address.cast_unsigned());
+        }
+        Ok(())
+    }
+
+    /// Reads the word of memory at `address`.
+    fn read(&self, address: i16) -> Result<i16, HackError> {
+        self.ram
+            .get(usize::from(address.cast_unsigned()))
+            .copied()
+            .ok_or_else(|| outside(address))
+    }
+
+    /// Writes `value` to the word of memory at `address`.
+    fn write(&mut self, address: i16, value: i16) -> Result<(), HackError> {
+        let word: &mut i16 = self
+            .ram
+            .get_mut(usize::from(address.cast_unsigned()))
+            .ok_or_else(|| outside(address))?;
+        *word = value;
+        Ok(())
+    }
+
+    /// Assembles the A-instruction `@symbol`, allocating a variable if
+    /// `symbol` is neither a number, a predefined symbol, nor in `labels`.
+    fn resolve(
+        &mut self,
+        symbol: &str,
+        labels: &BTreeMap<&str, usize>,
+    ) -> Option<Operation> {
+        let value: u16 = if symbol
+            .starts_with(|character: char| character.is_ascii_digit())
+        {
+            symbol.parse().ok()?
+        } else if let Some(address) = asmdiff::predefined(symbol) {
+            address
+        } else if let Some(&label) = labels.get(symbol) {
+            u16::try_from(label).ok()?
+        } else {
+            let next: u16 = u16::try_from(self.variables.len()).ok().and_then(
+                |allocated: u16| VARIABLE_BASE.checked_add(allocated),
+            )?;
+            *self.variables.entry(symbol.to_owned()).or_insert(next)
+        };
+        i16::try_from(value).ok().map(Operation::Address)
+    }
+}
+
+impl FromStr for Cpu {
+    type Err = HackError;
+
+    /// Assembles the Hack assembly `s` into the ROM of a CPU whose memory is
+    /// all 0.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::FromStrError`] naming the line of the first
+    /// instruction that cannot be assembled, or of a label declared more
+    /// than once.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Lines that are only a comment are kept, with no code, to find the
+        // instruction after each.
+        let lines: Vec<(usize, String)> = s
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line): (usize, &str)| {
+                let (code, commented): (&str, bool) = line
+                    .split_once("//")
+                    .map_or((line, false), |(code, _)| (code, true));
+                let code: String = code
+                    .chars()
+                    .filter(|character: &char| !character.is_whitespace())
+                    .collect();
+                (commented || !code.is_empty())
+                    .then_some((index.saturating_add(1), code))
+            })
+            .collect();
+
+        let mut labels: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut comments: Vec<usize> = Vec::new();
+        let mut count: usize = 0;
+        for &(number, ref code) in &lines {
+            if code.is_empty() {
+                comments.push(count);
+            } else if let Some(label) = code
+                .strip_prefix('(')
+                .and_then(|rest: &str| rest.strip_suffix(')'))
+            {
+                if labels.insert(label, count).is_some() {
+                    return Err(HackError::FromStrError(format!(
+                        "line {number}: the label \"{label}\" is declared \
+                        more than once"
+                    )));
+                }
+            } else {
+                count = count.saturating_add(1);
+            }
+        }
+
+        let mut cpu: Self = Self {
+            rom: Vec::with_capacity(count),
+            ram: vec![0; MEMORY_SIZE],
+            variables: BTreeMap::new(),
+            address: 0,
+            data: 0,
+            counter: 0,
+            comments,
+        };
+        for &(number, ref code) in &lines {
+            let operation: Operation =
+                if code.is_empty() || code.starts_with('(') {
+                    continue;
+                } else if let Some(symbol) = code.strip_prefix('@') {
+                    cpu.resolve(symbol, &labels)
+                } else {
+                    compute(code)
+                }
+                .ok_or_else(|| {
+                    HackError::FromStrError(format!(
+                        "line {number}: \"{code}\" is not a Hack instruction"
+                    ))
+                })?;
+            cpu.rom.push(operation);
+        }
+        Ok(cpu)
+    }
+}
+
+/// Assembles the C-instruction `code`, as `dest=comp;jump`.
+fn compute(code: &str) -> Option<Operation> {
+    let (destination, rest): (&str, &str) =
+        code.split_once('=').unwrap_or(("", code));
+    let (computation, jump): (&str, &str) =
+        rest.split_once(';').unwrap_or((rest, ""));
+    let memory: bool = computation.contains('M');
+    if memory && computation.contains('A') {
+        return None;
+    }
+    let canonical: String = computation.replace('M', "A");
+    let control: u8 = control(&canonical).or_else(|| {
+        ["+", "&", "|"].iter().find_map(|&operator: &&str| {
+            let (left, right): (&str, &str) = canonical.split_once(operator)?;
+            control(&format!("{right}{operator}{left}"))
+        })
+    })?;
+    let mut stored: u8 = 0;
+    for (register, bit) in [('A', 0b100), ('D', 0b010), ('M', 0b001)] {
+        if destination.contains(register) {
+            stored |= bit;
+        }
+    }
+    if destination
+        .chars()
+        .any(|character: char| !"ADM".contains(character))
+    {
+        return None;
+    }
+    Some(Operation::Compute {
+        memory,
+        control,
+        destination: stored,
+        jump: u8::try_from(JUMPS.iter().position(|&name: &&str| name == jump)?)
+            .ok()?,
+    })
+}
+
+/// Gets the control bits of `computation`, written with `A` rather than
+/// `M`.
+fn control(computation: &str) -> Option<u8> {
+    COMPUTATIONS
+        .iter()
+        .find(|&&(name, _)| name == computation)
+        .map(|&(_, bits)| bits)
+}
+
+/// Computes what the ALU does to `data` and `operand` under `control`.
+const fn alu(data: i16, operand: i16, control: u8) -> i16 {
+    let zeroed: i16 = if control & 0b10_0000 != 0 { 0 } else { data };
+    let left: i16 = if control & 0b01_0000 != 0 {
+        !zeroed
+    } else {
+        zeroed
+    };
+    let zeroed: i16 = if control & 0b00_1000 != 0 { 0 } else { operand };
+    let right: i16 = if control & 0b00_0100 != 0 {
+        !zeroed
+    } else {
+        zeroed
+    };
+    let result: i16 = if control & 0b00_0010 != 0 {
+        left.wrapping_add(right)
+    } else {
+        left & right
+    };
+    if control & 0b00_0001 != 0 {
+        !result
+    } else {
+        result
+    }
+}
+
+/// Creates the error for accessing `address`, which is outside of memory.
+fn outside(address: i16) -> HackError {
+    HackError::IllegalInstruction(format!(
+        "address {} is outside of memory",
+        address.cast_unsigned()
+    ))
+}
@@ -0,0 +1,528 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Daemon Module
+//!
+//! Answers requests to translate files for the `daemon` subcommand, so that
+//! editors and build tools don't start the translator again for every change.
+//! Requests are [JSON-RPC 2.0](https://www.jsonrpc.org/specification), one per
+//! line, and are read from standard input until it is closed, or from each
+//! connection to the address given with `--listen` in turn:
+//!
+//! ```bash
+//! hack-vm-translator daemon --listen 127.0.0.1:9257
+//! ```
+//!
+//! Each response is written on its own line, as is each request. Every
+//! method is given the `path` of a `.vm` file, along with its `text` if it
+//! has not been saved:
+//!
+//! - `translate`: Gets the `assembly` of the file, and its `warnings`.
+//! - `diagnostics`: Gets the `errors` and `warnings` of the file, each with
+//!   the `file` and `line` it is about, if any, its `code` or `lint`, and its
+//!   `message`.
+//! - `function`: Gets the `assembly` of the function `name`, with each command
+//!   written as a comment above its assembly, as with `--annotate`.
+//!
+//! A request and its response, each wrapped onto several lines here:
+//!
+//! ```text
+//! → {"jsonrpc": "2.0", "id": 1, "method": "diagnostics",
+//!    "params": {"path": "Main.vm", "text": "push local"}}
+//! ← {"id":1,"jsonrpc":"2.0","result":{"errors":[{"code":"E0020",
+//!    "file":"Main.vm","line":1,
+//!    "message":"expected an index after \"push local\", found end of line"}],
+//!    "warnings":[]}}
+//! ```
+//!
+//! Files are translated with the options the daemon was started with. The
+//! daemon remembers what each translated to, and translates it again only
+//! once it, or a file it includes, has changed. A file that cannot be
+//! translated is reported as an error with the code `-32000`, carrying the
+//! code of the problem as its `data`.
+
+use alloc::collections::BTreeMap;
+use core::iter;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::parser::Symbol;
+use hack_vm_syntax::paths;
+
+use crate::diagnostics::{split_location, split_site};
+use crate::json::Value;
+use crate::lints::Warning;
+use crate::summary::FileSummary;
+use crate::symbols::SymbolTable;
+use crate::{Config, Output, Settings, Source, run_for_file, static_prefix};
+
+/// The JSON-RPC error for a request that is not valid JSON.
+const PARSE_ERROR: i64 = -32_700;
+
+/// The JSON-RPC error for a request that is not a request.
+const INVALID_REQUEST: i64 = -32_600;
+
+/// The JSON-RPC error for a method that does not exist.
+const METHOD_NOT_FOUND: i64 = -32_601;
+
+/// The JSON-RPC error for a method given the wrong parameters.
+const INVALID_PARAMS: i64 = -32_602;
+
+/// The error for a file that cannot be translated.
+const TRANSLATION_FAILED: i64 = -32_000;
+
+/// Why a request could not be answered.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Failure {
+    /// The JSON-RPC error code.
+    code: i64,
+    /// What went wrong.
+    message: String,
+    /// The code of the [`HackError`] that went wrong, if one did.
+    data: Option<&'static str>,
+}
+
+impl Failure {
+    /// Creates a failure that is not about translating a file.
+    fn new(code: i64, message: &str) -> Self {
+        Self {
+            code,
+            message: message.to_owned(),
+            data: None,
+        }
+    }
+
+    /// Writes the failure as a JSON-RPC error object.
+    fn to_json(&self) -> Value {
+        let code: Value = Value::Number(self.code.to_string());
+        let message: Value = self.message.as_str().into();
+        match self.data {
+            Some(data) => Value::object([
+                ("code", code),
+                ("message", message),
+                ("data", data.into()),
+            ]),
+            None => Value::object([("code", code), ("message", message)]),
+        }
+    }
+}
+
+impl From<HackError> for Failure {
+    fn from(value: HackError) -> Self {
+        Self {
+            code: TRANSLATION_FAILED,
+            message: value.to_string(),
+            data: Some(value.code()),
+        }
+    }
+}
+
+/// The assembly a file was translated to, and any warnings about it.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Translated {
+    /// The assembly.
+    assembly: String,
+    /// The warnings about the file.
+    warnings: Vec<Warning>,
+}
+
+/// What a file translated to, remembered until it changes.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Translation {
+    /// The contents of the file, and those of the files it includes.
+    contents: String,
+    /// What the file translated to, or why it did not.
+    result: Result<Translated, HackError>,
+}
+
+/// The files translated so far, and how to translate them.
+#[derive(Debug)]
+struct Daemon<'config> {
+    /// The options the daemon was started with.
+    config: &'config Config,
+    /// How files are translated.
+    settings: Settings,
+    /// How files are translated to find their functions, with every command
+    /// annotated.
+    annotated: Settings,
+    /// What each file translated to, by its path and whether it was
+    /// annotated.
+    translations: BTreeMap<(PathBuf, bool), Translation>,
+}
+
+impl<'config> Daemon<'config> {
+    /// Creates a daemon translating files according to `config`.
+    fn new(config: &'config Config) -> Self {
+        let settings: Settings = Settings::new(config);
+        let mut annotated: Settings = settings.clone();
+        annotated.annotate = true;
+        Self {
+            config,
+            settings,
+            annotated,
+            translations: BTreeMap::new(),
+        }
+    }
+
+    /// Answers each request read from `reader`, writing the responses to
+    /// `writer`, until there are no more.
+    ///
+    /// # Errors
+    ///
+    /// Any error from reading the requests or writing the responses is
+    /// propagated.
+    fn answer<R: BufRead, W: Write>(
+        &mut self,
+        reader: R,
+        mut writer: W,
+    ) -> Result<(), HackError> {
+        for line in reader.lines() {
+            let line: String = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.respond(&line) {
+                writeln!(writer, "{response}")?;
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers the request on `line`, unless it is a notification, which has
+    /// no `id` and gets no response.
+    fn respond(&mut self, line: &str) -> Option<Value> {
+        let request: Value = match line.parse() {
+            Ok(request @ Value::Object(_)) => request,
+            Ok(_) => {
+                return Some(response(
+                    Value::Null,
+                    Err(Failure::new(
+                        INVALID_REQUEST,
+                        "expected the request to be an object",
+                    )),
+                ));
+            }
+            Err(error) => {
+                return Some(response(
+                    Value::Null,
+                    Err(Failure::new(PARSE_ERROR, &error.to_string())),
+                ));
+            }
+        };
+        let result: Result<Value, Failure> = self.call(&request);
+        request
+            .get("id")
+            .cloned()
+            .map(|id: Value| response(id, result))
+    }
+
+    /// Calls the method named by `request`.
+    fn call(&mut self, request: &Value) -> Result<Value, Failure> {
+        if request.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+            return Err(Failure::new(
+                INVALID_REQUEST,
+                "expected \"jsonrpc\" to be \"2.0\"",
+            ));
+        }
+        let method: &str = request
+            .get("method")
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                Failure::new(INVALID_REQUEST, "expected a \"method\" to call")
+            })?;
+        let params: &Value = request.get("params").unwrap_or(&Value::Null);
+        match method {
+            "translate" => {
+                let translated: Translated =
+                    self.translate(&source(params)?, false)?;
+                Ok(Value::object([
+                    ("assembly", translated.assembly.as_str().into()),
+                    ("warnings", warnings(&translated.warnings)),
+                ]))
+            }
+            "diagnostics" => {
+                let source: Source = source(params)?;
+                let file: String = paths::display(source.path()).to_string();
+                Ok(match self.translate(&source, false) {
+                    Ok(translated) => Value::object([
+                        ("errors", Value::Array(Vec::new())),
+                        ("warnings", warnings(&translated.warnings)),
+                    ]),
+                    Err(error) => Value::object([
+                        ("errors", errors(&error, &file)),
+                        ("warnings", Value::Array(Vec::new())),
+                    ]),
+                })
+            }
+            "function" => {
+                let name: &str = params
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| {
+                        Failure::new(
+                            INVALID_PARAMS,
+                            "expected the \"name\" of a function",
+                        )
+                    })?;
+                let source: Source = source(params)?;
+                let translated: Translated = self.translate(&source, true)?;
+                function_assembly(&translated.assembly, name)
+                    .map(|assembly: String| {
+                        Value::object([("assembly", assembly.as_str().into())])
+                    })
+                    .ok_or_else(|| {
+                        Failure::new(
+                            INVALID_PARAMS,
+                            &format!(
+                                "\"{name}\" is not defined in \"{}\"",
+                                source.path().display()
+                            ),
+                        )
+                    })
+            }
+            other => Err(Failure::new(
+                METHOD_NOT_FOUND,
+                &format!("there is no method \"{other}\""),
+            )),
+        }
+    }
+
+    /// Translates `source`, with every command annotated if `annotated` is
+    /// set, unless it has not changed since it was last translated.
+    ///
+    /// # Errors
+    ///
+    /// Any error from reading or translating the file is propagated.
+    fn translate(
+        &mut self,
+        source: &Source,
+        annotated: bool,
+    ) -> Result<Translated, HackError> {
+        let contents: String = source
+            .read(self.settings.limits)
+            .map_err(|error: HackError| {
+                if let HackError::CannotReadFileFromPath(ref message) = error {
+                    HackError::CannotReadFileFromPath(format!(
+                        "could not read \"{}\": {message}",
+                        paths::display(source.path())
+                    ))
+                } else {
+                    error
+                }
+            })?
+            .contents_with_includes()?;
+        let key: (PathBuf, bool) = (source.path().to_path_buf(), annotated);
+        if let Some(translation) = self.translations.get(&key)
+            && translation.contents == contents
+        {
+            return translation.result.clone();
+        }
+        let settings: &Settings = if annotated {
+            &self.annotated
+        } else {
+            &self.settings
+        };
+        let result: Result<Translated, HackError> =
+            translate(source, self.config, settings);
+        let _previous: Option<Translation> = self.translations.insert(
+            key,
+            Translation {
+                contents,
+                result: result.clone(),
+            },
+        );
+        result
+    }
+}
+
+/// Answers requests according to `config`, from standard input or each
+/// connection to the address it gives in turn, until there are no more.
+///
+/// # Errors
+///
+/// Returns a [`HackError::InvalidOption`] if the address cannot be listened
+/// on. Any error from reading from standard input or writing to standard
+/// output is propagated, while errors from a connection are printed, and the
+/// next one answered.
+pub(crate) fn daemon(config: &Config) -> Result<(), HackError> {
+    let mut daemon: Daemon<'_> = Daemon::new(config);
+    let Some(address) = config.listen() else {
+        return daemon.answer(io::stdin().lock(), io::stdout().lock());
+    };
+    let listener: TcpListener =
+        TcpListener::bind(address).map_err(|error: io::Error| {
+            HackError::InvalidOption(format!(
+                "could not listen on \"{address}\": {error}"
+            ))
+        })?;
+    let mut stderr: io::Stderr = io::stderr();
+    writeln!(stderr, "Answering requests at {address}")?;
+    for stream in listener.incoming() {
+        if let Err(error) =
+            stream
+                .map_err(HackError::from)
+                .and_then(|stream: TcpStream| {
+                    daemon.answer(BufReader::new(&stream), &stream)
+                })
+        {
+            writeln!(stderr, "Problem answering requests: {error}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the response to the request with `id`.
+fn response(id: Value, result: Result<Value, Failure>) -> Value {
+    let (name, value): (&str, Value) = match result {
+        Ok(result) => ("result", result),
+        Err(failure) => ("error", failure.to_json()),
+    };
+    Value::object([("jsonrpc", "2.0".into()), ("id", id), (name, value)])
+}
+
+/// Gets the file named by the `path` of `params`, holding their `text` if
+/// they have any.
+fn source(params: &Value) -> Result<Source, Failure> {
+    let path: PathBuf = params
+        .get("path")
+        .and_then(Value::as_str)
+        .map(PathBuf::from)
+        .ok_or_else(|| {
+            Failure::new(INVALID_PARAMS, "expected the \"path\" of a file")
+        })?;
+    match params.get("text") {
+        None | Some(&Value::Null) => Ok(Source::File(path)),
+        Some(text) => Ok(Source::Archived {
+            path,
+            contents: text
+                .as_str()
+                .ok_or_else(|| {
+                    Failure::new(INVALID_PARAMS, "expected \"text\" to be text")
+                })?
+                .to_owned(),
+        }),
+    }
+}
+
+/// Translates `source` as a program of its own, according to `config` and
+/// `settings`.
+///
+/// # Errors
+///
+/// Any error from reading or translating the file is propagated.
+fn translate(
+    source: &Source,
+    config: &Config,
+    settings: &Settings,
+) -> Result<Translated, HackError> {
+    let stem: String = source
+        .path()
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .ok_or(HackError::BadFileTypeError)?;
+    let static_prefix: Symbol =
+        static_prefix(config, &settings.namespaces, &stem);
+    let mut output: Output = Output::Buffer(String::new());
+    let summary: FileSummary = run_for_file(
+        source,
+        Some(&static_prefix),
+        settings,
+        None,
+        &mut output,
+        &mut SymbolTable::default(),
+        true,
+    )?;
+    let Output::Buffer(assembly) = output else {
+        return Err(HackError::Internal);
+    };
+    Ok(Translated {
+        assembly,
+        warnings: summary.warnings().to_vec(),
+    })
+}
+
+/// Gets the assembly of the function `name` from `annotated` assembly, from
+/// the annotation of its `function` command up to that of the next one.
+fn function_assembly(annotated: &str, name: &str) -> Option<String> {
+    let start: String = format!("// function {name} ");
+    let mut lines = annotated
+        .lines()
+        .skip_while(|line: &&str| !line.starts_with(&start));
+    let first: &str = lines.next()?;
+    let mut assembly: String = iter::once(first)
+        .chain(
+            lines.take_while(|line: &&str| !line.starts_with("// function ")),
+        )
+        .collect::<Vec<&str>>()
+        .join("\n");
+    assembly.push('\n');
+    Some(assembly)
+}
+
+/// Writes each line of `error` as a diagnostic. Lines located only by their
+/// number, as for a file whose text was given, are about `file`.
+fn errors(error: &HackError, file: &str) -> Value {
+    Value::Array(
+        error
+            .to_string()
+            .lines()
+            .map(|line: &str| {
+                split_location(line)
+                    .or_else(|| {
+                        let (number, message): (&str, &str) =
+                            line.strip_prefix("line ")?.split_once(": ")?;
+                        Some((file, number.parse().ok()?, message))
+                    })
+                    .map_or_else(
+                        || {
+                            Value::object([
+                                ("code", error.code().into()),
+                                ("message", line.into()),
+                            ])
+                        },
+                        |(file, number, message): (&str, usize, &str)| {
+                            Value::object([
+                                ("file", file.into()),
+                                ("line", number.into()),
+                                ("code", error.code().into()),
+                                ("message", message.into()),
+                            ])
+                        },
+                    )
+            })
+            .collect(),
+    )
+}
+
+/// Writes each of `warnings` as a diagnostic.
+fn warnings(warnings: &[Warning]) -> Value {
+    Value::Array(
+        warnings
+            .iter()
+            .map(|warning: &Warning| {
+                let lint: Value = warning.lint().to_string().as_str().into();
+                let message: Value = warning.message().into();
+                split_site(warning.site()).map_or_else(
+                    || {
+                        Value::object([
+                            ("lint", lint.clone()),
+                            ("message", message.clone()),
+                        ])
+                    },
+                    |(file, number): (&str, usize)| {
+                        Value::object([
+                            ("file", file.into()),
+                            ("line", number.into()),
+                            ("lint", lint.clone()),
+                            ("message", message.clone()),
+                        ])
+                    },
+                )
+            })
+            .collect(),
+    )
+}
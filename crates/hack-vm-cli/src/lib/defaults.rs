@@ -0,0 +1,263 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Defaults Module
+//!
+//! Reads default options from a `translator.toml` file, so that settings
+//! shared by a whole tree of projects don't have to be repeated on every
+//! invocation. The file is found by searching upward from the path being
+//! translated, and the first one found is used. Each key is the name of an
+//! option, and options given on the command line take precedence:
+//!
+//! ```toml
+//! labels = "true-end"
+//! label-numbering = "sequential"
+//! renumber-labels = true
+//! extensions = true
+//! mode = "permissive"
+//! target = "hack-extended"
+//! compat = "nand2tetris"
+//! color = "always"
+//! diagnostics-format = "short"
+//! inline-asm = true
+//! annotate = true
+//! quiet = true
+//! lossy-utf8 = true
+//! max-file-size = 1048576
+//! max-line-length = 1000
+//! max-instructions = 100000
+//!
+//! # Relative to the directory holding this file.
+//! cache-dir = ".cache/hack-vm"
+//! rewrites = "rewrites.toml"
+//! with-os = "tools/OS"
+//!
+//! # As given to `--passes`.
+//! passes = "-rewrite"
+//!
+//! # Reserved for future use; only the current behavior is accepted.
+//! optimization = 0
+//! ```
+//!
+//! A boolean option set to `false` is simply not given, as every such option
+//! is off by default. The search can be skipped with `--no-config`.
+//!
+//! Options can also be given in the environment, for CI jobs and Makefiles:
+//! `HACK_VM_FLAGS` holds options separated by whitespace, and each option
+//! taking a value has its own variable, such as `HACK_VM_LABELS` for
+//! `--labels`. Those take precedence over `translator.toml`, but not over the
+//! command line.
+
+use core::fmt::Display;
+use core::str::FromStr;
+use std::env::{self, VarError};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use hack_vm_codegen::labels::{LabelStyle, Numbering};
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::mode::Mode;
+use hack_vm_syntax::paths;
+use hack_vm_syntax::target::Target;
+
+use crate::color::ColorChoice;
+use crate::compat::Compat;
+use crate::diagnostics::DiagnosticsFormat;
+use crate::manifest::Manifest;
+use crate::toml::Table;
+
+/// The environment variable holding options separated by whitespace.
+const FLAGS_VARIABLE: &str = "HACK_VM_FLAGS";
+
+/// The options taking a value that each have their own environment variable,
+/// named `HACK_VM_` followed by the option in upper case, such as
+/// `HACK_VM_LABEL_NUMBERING` for `--label-numbering`.
+const VALUE_VARIABLES: [&str; 10] = [
+    "labels",
+    "label-numbering",
+    "mode",
+    "target",
+    "compat",
+    "diagnostics-format",
+    "cache-dir",
+    "with-os",
+    "reference",
+    "emulator",
+];
+
+/// Gets the options given in the environment, as they would be written on
+/// the command line. Variables that are unset or empty are ignored.
+///
+/// # Errors
+///
+/// Returns an error if a variable is not valid Unicode.
+pub(crate) fn environment() -> Result<Vec<String>, HackError> {
+    let mut args: Vec<String> = Vec::new();
+    for option in VALUE_VARIABLES {
+        let name: String =
+            format!("HACK_VM_{}", option.to_uppercase().replace('-', "_"));
+        if let Some(value) = variable(&name)? {
+            args.push(format!("--{option}={value}"));
+        }
+    }
+    if let Some(flags) = variable(FLAGS_VARIABLE)? {
+        args.extend(flags.split_whitespace().map(str::to_owned));
+    }
+    Ok(args)
+}
+
+/// Gets the value of the environment variable `name`, if it is set and not
+/// empty.
+fn variable(name: &str) -> Result<Option<String>, HackError> {
+    match env::var(name) {
+        Ok(value) if value.trim().is_empty() => Ok(None),
+        Ok(value) => Ok(Some(value)),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(VarError::NotUnicode(_)) => Err(HackError::InvalidOption(format!(
+            "environment variable \"{name}\" is not valid Unicode"
+        ))),
+    }
+}
+
+/// Whether a `translator.toml` is searched for.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub(crate) enum Discovery {
+    /// The first `translator.toml` found upward from the path is used.
+    #[default]
+    Upward,
+    /// No `translator.toml` is used, as with `--no-config`.
+    Disabled,
+}
+
+/// The default options read from a `translator.toml`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct Defaults {
+    /// The file the options were read from.
+    path: PathBuf,
+    /// The options, as they would be written on the command line.
+    args: Vec<String>,
+}
+
+impl Defaults {
+    /// The name of the file holding the default options.
+    pub(crate) const FILE_NAME: &str = "translator.toml";
+
+    /// Finds and reads the `translator.toml` closest to `path`, searching
+    /// its directory and then each directory above it, if there is one.
+    /// Standard input, as `-`, is searched for from the current directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file found cannot be read or understood.
+    pub(crate) fn discover(path: &Path) -> Result<Option<Self>, HackError> {
+        let start: PathBuf = if path.as_os_str() == "-" {
+            env::current_dir()?
+        } else {
+            paths::join(&env::current_dir()?, &path.to_string_lossy())
+        };
+        let directory: &Path = if start.is_dir() {
+            &start
+        } else {
+            start.parent().unwrap_or(&start)
+        };
+        directory
+            .ancestors()
+            .map(|ancestor: &Path| ancestor.join(Self::FILE_NAME))
+            .find(|candidate: &PathBuf| candidate.is_file())
+            .map(|candidate: PathBuf| Self::load(candidate))
+            .transpose()
+    }
+
+    /// Reads the default options in the file at `path`.
+    fn load(path: PathBuf) -> Result<Self, HackError> {
+        let text: String = fs::read_to_string(&path).map_err(|error| {
+            HackError::CannotReadFileFromPath(format!(
+                "could not read \"{}\": {error}",
+                paths::display(&path)
+            ))
+        })?;
+        let mut table: Table = Table::parse(&path, &text)?;
+        let mut args: Vec<String> = Vec::new();
+
+        Self::take_option::<LabelStyle>(&mut table, "labels", &mut args)?;
+        Self::take_option::<Numbering>(
+            &mut table,
+            "label-numbering",
+            &mut args,
+        )?;
+        Self::take_option::<Mode>(&mut table, "mode", &mut args)?;
+        Self::take_option::<Target>(&mut table, "target", &mut args)?;
+        Self::take_option::<Compat>(&mut table, "compat", &mut args)?;
+        Self::take_option::<ColorChoice>(&mut table, "color", &mut args)?;
+        Self::take_option::<DiagnosticsFormat>(
+            &mut table,
+            "diagnostics-format",
+            &mut args,
+        )?;
+        for flag in [
+            "renumber-labels",
+            "extensions",
+            "inline-asm",
+            "annotate",
+            "quiet",
+            "lossy-utf8",
+        ] {
+            if table.take_bool(flag)? == Some(true) {
+                args.push(format!("--{flag}"));
+            }
+        }
+        for limit in ["max-file-size", "max-line-length", "max-instructions"] {
+            if let Some(value) = table.take_integer(limit)? {
+                args.push(format!("--{limit}={value}"));
+            }
+        }
+        for option in ["cache-dir", "rewrites", "with-os"] {
+            if let Some(value) = table.take_string(option)? {
+                let base: &Path = path.parent().unwrap_or(&path);
+                let value: PathBuf = paths::join(base, &value);
+                args.push(format!("--{option}={}", value.display()));
+            }
+        }
+        if let Some(passes) = table.take_string("passes")? {
+            args.push(format!("--passes={passes}"));
+        }
+        if let Some(level) = table.take_integer("optimization")?
+            && level != 0
+        {
+            return Err(table.unsupported(
+                "optimization",
+                &format!(
+                    "cannot be {level}, as only level 0 (no optimization) \
+                    exists"
+                ),
+            ));
+        }
+        table.finish()?;
+
+        Ok(Self { path, args })
+    }
+
+    /// Removes the value of the option `key`, if present, checking that it
+    /// parses as a `T` before adding it to `args`.
+    fn take_option<T: FromStr<Err = HackError> + Display>(
+        table: &mut Table,
+        key: &str,
+        args: &mut Vec<String>,
+    ) -> Result<(), HackError> {
+        if let Some(value) = Manifest::take_parsed::<T>(table, key)? {
+            args.push(format!("--{key}={value}"));
+        }
+        Ok(())
+    }
+
+    /// Gets the file the options were read from.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the options, as they would be written on the command line.
+    pub(crate) fn args(&self) -> &[String] {
+        &self.args
+    }
+}
@@ -0,0 +1,179 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Diagnostics Module
+//!
+//! Formats the errors and warnings printed to stderr, as chosen with
+//! `--diagnostics-format`:
+//!
+//! - `human` (the default): Errors are introduced by what failed, and
+//!   warnings are followed by their lint; see [`crate::lints`]. Each is
+//!   wrapped onto a second line here:
+//!
+//!   ```text
+//!   Problem running: Main.vm:2: expected an index after "push local",
+//!     found end of line
+//!   Main.vm:4: warning: function "Math.double" should be named
+//!     "Main.double", after its file [naming]
+//!   ```
+//!
+//! - `short`: Each problem on its own line, starting with where it is as
+//!   `FILE:LINE:COLUMN`, as GCC reports them. Editors pick these up without a
+//!   plugin, such as with Vim's quickfix list or the problem matchers of VS
+//!   Code. Errors are tagged with their code, and warnings with their lint,
+//!   as in these, each wrapped onto a second line here:
+//!
+//!   ```text
+//!   Main.vm:2:4: error[E0020]: expected an index after "push local",
+//!     found end of line
+//!   Main.vm:4:1: warning[naming]: function "Math.double" should be named
+//!     "Main.double", after its file
+//!   ```
+//!
+//!   The column is where the command on the line starts. Errors that are not
+//!   about a line of a file are reported against the translator itself, as
+//!   `hack-vm-translator: error[E0005]: ...`.
+//!
+//! Problems with the arguments themselves are reported before
+//! `--diagnostics-format` is known, so they are formatted as with `human`.
+
+use core::fmt::{self, Display};
+use core::str::FromStr;
+use std::fs;
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::lexer;
+
+use crate::color::Palette;
+use crate::lints::Warning;
+
+/// How errors and warnings are formatted.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum DiagnosticsFormat {
+    /// Diagnostics are formatted to be read.
+    #[default]
+    Human,
+    /// Diagnostics are formatted as `FILE:LINE:COLUMN: ...` lines, to be
+    /// matched by editors.
+    Short,
+}
+
+impl DiagnosticsFormat {
+    /// The name of the `human` format.
+    const HUMAN: &str = "human";
+    /// The name of the `short` format.
+    const SHORT: &str = "short";
+    /// What errors that are not about a line of a file are reported against
+    /// in the `short` format.
+    const PROGRAM: &str = "hack-vm-translator";
+
+    /// Formats `error`, which stopped the run, styled with `palette`.
+    pub fn error(self, error: &HackError, palette: Palette) -> String {
+        match self {
+            Self::Human => {
+                format!("{} {error}", palette.error("Problem running:"))
+            }
+            Self::Short => {
+                let label: String = format!("error[{}]:", error.code());
+                error
+                    .to_string()
+                    .lines()
+                    .map(|line: &str| {
+                        let (location, message): (String, &str) =
+                            split_location(line).map_or_else(
+                                || (Self::PROGRAM.to_owned(), line),
+                                |(file, number, message): (
+                                    &str,
+                                    usize,
+                                    &str,
+                                )| {
+                                    (locate(file, number), message)
+                                },
+                            );
+                        format!(
+                            "{}: {} {message}",
+                            palette.location(location),
+                            palette.error(&label)
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            }
+        }
+    }
+
+    /// Formats `warning`, styled with `palette`.
+    pub fn warning(self, warning: &Warning, palette: Palette) -> String {
+        match self {
+            Self::Human => warning.styled(palette).to_string(),
+            Self::Short => {
+                let location: String = split_site(warning.site()).map_or_else(
+                    || warning.site().to_owned(),
+                    |(file, number): (&str, usize)| locate(file, number),
+                );
+                format!(
+                    "{}: {} {}",
+                    palette.location(location),
+                    palette.warning(format!("warning[{}]:", warning.lint())),
+                    warning.message()
+                )
+            }
+        }
+    }
+}
+
+impl FromStr for DiagnosticsFormat {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::HUMAN => Ok(Self::Human),
+            Self::SHORT => Ok(Self::Short),
+            other => Err(HackError::FromStrError(format!(
+                "\"{other}\" is not a recognized diagnostics format, expected \
+                \"{}\" or \"{}\"",
+                Self::HUMAN,
+                Self::SHORT
+            ))),
+        }
+    }
+}
+
+impl Display for DiagnosticsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Human => write!(f, "{}", Self::HUMAN),
+            Self::Short => write!(f, "{}", Self::SHORT),
+        }
+    }
+}
+
+/// Splits a site written as `FILE:LINE` into the file and line number.
+pub(crate) fn split_site(site: &str) -> Option<(&str, usize)> {
+    let (file, number): (&str, &str) = site.rsplit_once(':')?;
+    Some((file, number.parse().ok()?))
+}
+
+/// Splits a line of an error message that starts with where the error is,
+/// as `FILE:LINE: `, into the file, line number, and the rest of the message.
+pub(crate) fn split_location(line: &str) -> Option<(&str, usize, &str)> {
+    let (site, message): (&str, &str) = line.split_once(": ")?;
+    let (file, number): (&str, usize) = split_site(site)?;
+    Some((file, number, message))
+}
+
+/// Writes where line `number` of `file` is, as `FILE:LINE:COLUMN`, where the
+/// column is where its command starts. If the file cannot be read, such as
+/// standard input, the column is 1.
+fn locate(file: &str, number: usize) -> String {
+    let column: usize = fs::read_to_string(file)
+        .ok()
+        .and_then(|text: String| {
+            text.lines()
+                .nth(number.saturating_sub(1))
+                .map(lexer::column)
+        })
+        .unwrap_or(1);
+    format!("{file}:{number}:{column}")
+}
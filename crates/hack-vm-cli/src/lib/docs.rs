@@ -0,0 +1,259 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Documentation Module
+//!
+//! Documents a program in Markdown, which renders as a browsable page on
+//! most forges and can be converted to HTML by any Markdown tool. The
+//! documentation is produced by the `doc` subcommand, and lists:
+//!
+//! - Each file, with the comment it starts with and how many instructions it
+//!   has.
+//! - Each function, with the comment directly above its definition, how many
+//!   local variables it has, how many arguments it is called with, and how
+//!   complex its control flow is; see [`crate::metrics`].
+//! - The call graph: which functions each function calls.
+//! - Each static variable, with the functions that use it.
+//!
+//! Functions that are called but not defined in the files, such as those of
+//! the OS, are listed as well, since their observed arity is still useful.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::fmt::{self, Display};
+
+use hack_vm_syntax::parser::{Functional, Instruction, StackManipulation};
+
+use crate::metrics::{self, Metrics};
+
+/// How code outside of any function is referred to.
+const OUTSIDE: &str = "(outside any function)";
+
+/// A single file of the program.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct File {
+    /// The file, as it is displayed.
+    name: String,
+    /// The comment the file starts with, line by line.
+    comment: Vec<String>,
+    /// How many instructions the file has.
+    instructions: usize,
+}
+
+/// A single function of the program.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+struct Function {
+    /// Where the function is defined, as `FILE:LINE`, if it is.
+    site: Option<String>,
+    /// How many local variables the function has, if it is defined.
+    locals: Option<u16>,
+    /// The comment directly above the function's definition, line by line.
+    comment: Vec<String>,
+    /// The numbers of arguments the function is called with.
+    arities: BTreeSet<u16>,
+    /// How complex the function's control flow is, if it is defined.
+    metrics: Option<Metrics>,
+}
+
+/// Everything documented about a program.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub(crate) struct Documentation {
+    /// The files, in the order they were added.
+    files: Vec<File>,
+    /// The functions, by name.
+    functions: BTreeMap<String, Function>,
+    /// The functions each function calls, by the name of the caller.
+    calls: BTreeMap<String, BTreeSet<String>>,
+    /// The functions using each static variable, by its name.
+    statics: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Documentation {
+    /// Documents a file, given its unparsed `contents` and its instructions
+    /// paired with their line numbers. Its static variables are named with
+    /// `static_prefix`.
+    pub(crate) fn add_file(
+        &mut self,
+        file: &str,
+        static_prefix: &str,
+        contents: &str,
+        instructions: &[(usize, Instruction)],
+    ) {
+        let lines: Vec<&str> = contents.lines().collect();
+        self.files.push(File {
+            name: file.to_owned(),
+            comment: comment(lines.iter().copied()),
+            instructions: instructions.len(),
+        });
+        let mut current: &str = OUTSIDE;
+        for &(line, ref instruction) in instructions {
+            match *instruction {
+                Instruction::Functional(Functional::Function {
+                    ref symbol,
+                    value,
+                }) => {
+                    current = symbol.literal_representation();
+                    let above: &[&str] =
+                        lines.get(..line.saturating_sub(1)).unwrap_or_default();
+                    let function: &mut Function =
+                        self.functions.entry(symbol.to_string()).or_default();
+                    function.site = Some(format!("{file}:{line}"));
+                    function.locals = Some(value.literal_representation());
+                    function.comment = comment(above.iter().rev().copied());
+                    function.comment.reverse();
+                }
+                Instruction::Functional(Functional::Call {
+                    ref symbol,
+                    value,
+                }) => {
+                    let _new: bool = self
+                        .functions
+                        .entry(symbol.to_string())
+                        .or_default()
+                        .arities
+                        .insert(value.literal_representation());
+                    let _new: bool = self
+                        .calls
+                        .entry(current.to_owned())
+                        .or_default()
+                        .insert(symbol.to_string());
+                }
+                Instruction::StackManipulation(
+                    StackManipulation::Push { ref symbol, value }
+                    | StackManipulation::Pop { ref symbol, value },
+                ) if symbol.literal_representation() == "static" => {
+                    let _new: bool = self
+                        .statics
+                        .entry(format!("{static_prefix}.{value}"))
+                        .or_default()
+                        .insert(current.to_owned());
+                }
+                Instruction::Functional(Functional::Return)
+                | Instruction::StackManipulation(_)
+                | Instruction::Arithmetic(_)
+                | Instruction::Branching(_)
+                | Instruction::Assembly(_)
+                | _ => {}
+            }
+        }
+        for body in metrics::function_bodies(instructions) {
+            if let Some(&(
+                _,
+                Instruction::Functional(Functional::Function {
+                    ref symbol,
+                    ..
+                }),
+            )) = body.first()
+            {
+                self.functions
+                    .entry(symbol.to_string())
+                    .or_default()
+                    .metrics = Some(Metrics::measure(body));
+            }
+        }
+    }
+
+    /// Writes the section documenting the files.
+    fn write_files(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(formatter, "## Files")?;
+        for file in &self.files {
+            writeln!(formatter, "\n### {}\n", file.name)?;
+            write_comment(formatter, &file.comment)?;
+            writeln!(formatter, "{} instructions.", file.instructions)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the section documenting the functions.
+    fn write_functions(
+        &self,
+        formatter: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        writeln!(formatter, "\n## Functions")?;
+        for (name, function) in &self.functions {
+            writeln!(formatter, "\n### {name}\n")?;
+            write_comment(formatter, &function.comment)?;
+            match (function.site.as_ref(), function.locals) {
+                (Some(site), Some(locals)) => {
+                    writeln!(
+                        formatter,
+                        "Defined at {site} with {locals} local variables."
+                    )?;
+                }
+                (Some(_) | None, _) => {
+                    writeln!(formatter, "Not defined in these files.")?;
+                }
+            }
+            let arities: Vec<String> = function
+                .arities
+                .iter()
+                .map(|arity: &u16| arity.to_string())
+                .collect();
+            if let Some(metrics) = function.metrics {
+                writeln!(formatter, "{metrics}.")?;
+            }
+            if arities.is_empty() {
+                writeln!(formatter, "Never called.")?;
+            } else {
+                writeln!(
+                    formatter,
+                    "Called with {} arguments.",
+                    arities.join(" or ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a section listing, for each name in `entries`, the names it is
+    /// related to.
+    fn write_list(
+        formatter: &mut fmt::Formatter<'_>,
+        heading: &str,
+        entries: &BTreeMap<String, BTreeSet<String>>,
+    ) -> fmt::Result {
+        writeln!(formatter, "\n## {heading}\n")?;
+        if entries.is_empty() {
+            writeln!(formatter, "None.")?;
+        }
+        for (name, related) in entries {
+            let related: Vec<&str> =
+                related.iter().map(String::as_str).collect();
+            writeln!(formatter, "- {name}: {}", related.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Documentation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Documentation\n")?;
+        self.write_files(f)?;
+        self.write_functions(f)?;
+        Self::write_list(f, "Call Graph", &self.calls)?;
+        Self::write_list(f, "Statics", &self.statics)
+    }
+}
+
+/// Collects the comment at the start of `lines`, without its slashes.
+fn comment<T: AsRef<str>>(lines: impl Iterator<Item = T>) -> Vec<String> {
+    lines
+        .map_while(|line: T| {
+            line.as_ref()
+                .trim()
+                .strip_prefix("//")
+                .map(|text: &str| text.trim().to_owned())
+        })
+        .collect()
+}
+
+/// Writes a comment as a paragraph, if there is one.
+fn write_comment(
+    formatter: &mut fmt::Formatter<'_>,
+    comment: &[String],
+) -> fmt::Result {
+    if !comment.is_empty() {
+        writeln!(formatter, "{}\n", comment.join("\n"))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,464 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Eval Module
+//!
+//! Runs a few Hack VM commands directly, without translating them, to show
+//! what they do to the stack and the segments, such as in a notebook or a
+//! teaching tool:
+//!
+//! ```
+//! use hack_vm_cli::eval::{self, MachineState};
+//! use hack_vm_syntax::error::HackError;
+//!
+//! let state: MachineState = eval::evaluate_snippet(
+//!     "push constant 7\npush constant 8\nadd\npop temp 0",
+//!     MachineState::default(),
+//! )?;
+//! assert_eq!(state.segment("temp", 0), Some(15));
+//! assert!(state.stack().is_empty());
+//! # Ok::<(), HackError>(())
+//! ```
+//!
+//! The commands run against the memory of the Hack computer, laid out as
+//! usual: `SP`, `LCL`, `ARG`, `THIS`, and `THAT` at addresses 0 to 4, `temp`
+//! from 5, and the stack from 256. The static variable `static i` is at
+//! address `16 + i`, as though the snippet were the only file of a program.
+//!
+//! Branching commands run within the snippet, which may loop, but not for
+//! more than [`MAX_STEPS`] commands. The extended arithmetic commands run as
+//! described in [`hack_vm_syntax::extensions`], without having to be enabled.
+//! Functions, calls, and inline assembly cannot run in a snippet.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use core::fmt::{self, Display};
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::limits::Limits;
+use hack_vm_syntax::mode::Mode;
+use hack_vm_syntax::parser::{
+    Arithmetic, Branching, Instruction, Parser, StackManipulation,
+};
+use hack_vm_syntax::target::Target;
+
+/// The most commands a snippet may run, counting each time one is run again
+/// by a loop.
+pub const MAX_STEPS: usize = 1_000_000;
+
+/// The address of the static variable `static 0`.
+const STATIC_BASE: u16 = 16;
+
+/// The memory of the Hack computer, as left by running a snippet.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct MachineState {
+    /// Each word of memory, by its address.
+    ram: Vec<i16>,
+}
+
+impl MachineState {
+    /// The address of `SP`, the stack pointer.
+    pub const SP: u16 = 0;
+    /// The address of `LCL`, the base of the `local` segment.
+    pub const LCL: u16 = 1;
+    /// The address of `ARG`, the base of the `argument` segment.
+    pub const ARG: u16 = 2;
+    /// The address of `THIS`, the base of the `this` segment.
+    pub const THIS: u16 = 3;
+    /// The address of `THAT`, the base of the `that` segment.
+    pub const THAT: u16 = 4;
+
+    /// Gets the word at `address`, or 0 if there is no such address.
+    pub fn get(&self, address: u16) -> i16 {
+        self.ram
+            .get(usize::from(address))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Sets the word at `address` to `value`, such as to give a segment
+    /// values before running a snippet. Addresses past the end of memory
+    /// are ignored.
+    pub fn set(&mut self, address: u16, value: i16) {
+        if let Some(word) = self.ram.get_mut(usize::from(address)) {
+            *word = value;
+        }
+    }
+
+    /// Gets every word of memory, by its address.
+    pub(crate) fn ram(&self) -> &[i16] {
+        &self.ram
+    }
+
+    /// Gets the values on the stack, from the bottom up.
+    pub fn stack(&self) -> &[i16] {
+        let base: usize = usize::from(Target::default().stack().base());
+        let top: usize = self.pointer(Self::SP).map_or(base, usize::from);
+        self.ram.get(base..top).unwrap_or_default()
+    }
+
+    /// Gets the value at `index` of the segment named `segment`, or
+    /// [`None`] if there is no such segment or address.
+    pub fn segment(&self, segment: &str, index: u16) -> Option<i16> {
+        let address: u16 = self.address(segment, index).ok()?;
+        self.ram.get(usize::from(address)).copied()
+    }
+
+    /// Gets the address held by the word at `address`, if it is one.
+    fn pointer(&self, address: u16) -> Option<u16> {
+        u16::try_from(self.get(address)).ok()
+    }
+
+    /// Gets the address of the value at `index` of the segment named
+    /// `segment`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] if there is no such
+    /// segment, or the address is not in memory.
+    fn address(&self, segment: &str, index: u16) -> Result<u16, HackError> {
+        let target: Target = Target::default();
+        let base: u16 = match segment {
+            "local" => self.base(Self::LCL)?,
+            "argument" => self.base(Self::ARG)?,
+            "this" => self.base(Self::THIS)?,
+            "that" => self.base(Self::THAT)?,
+            "pointer" => Self::THIS,
+            "temp" => target.temp_base(),
+            "static" => STATIC_BASE,
+            other => {
+                return Err(HackError::IllegalInstruction(format!(
+                    "the segment \"{other}\" cannot be used in a snippet"
+                )));
+            }
+        };
+        base.checked_add(index)
+            .filter(|&address: &u16| usize::from(address) < self.ram.len())
+            .ok_or_else(|| {
+                HackError::IllegalInstruction(format!(
+                    "\"{segment} {index}\" is not in memory"
+                ))
+            })
+    }
+
+    /// Gets the base of a segment, held by the word at `address`.
+    fn base(&self, address: u16) -> Result<u16, HackError> {
+        self.pointer(address).ok_or_else(|| {
+            HackError::IllegalInstruction(format!(
+                "address {address} holds {}, which is not an address",
+                self.get(address)
+            ))
+        })
+    }
+
+    /// Pushes `value` onto the stack.
+    fn push(&mut self, value: i16) -> Result<(), HackError> {
+        let top: u16 = self.base(Self::SP)?;
+        let Some(word) = self.ram.get_mut(usize::from(top)) else {
+            return Err(HackError::IllegalInstruction(
+                "the stack overflowed memory".to_owned(),
+            ));
+        };
+        *word = value;
+        self.set(Self::SP, self.get(Self::SP).wrapping_add(1));
+        Ok(())
+    }
+
+    /// Pops the value on top of the stack.
+    fn pop(&mut self) -> Result<i16, HackError> {
+        let top: u16 = self.base(Self::SP)?;
+        if top <= Target::default().stack().base() {
+            return Err(HackError::IllegalInstruction(
+                "popped a value off of an empty stack".to_owned(),
+            ));
+        }
+        let top: u16 = top.saturating_sub(1);
+        self.set(Self::SP, self.get(Self::SP).wrapping_sub(1));
+        Ok(self.get(top))
+    }
+
+    /// Runs `manipulation`.
+    fn manipulate(
+        &mut self,
+        manipulation: &StackManipulation,
+    ) -> Result<(), HackError> {
+        match *manipulation {
+            StackManipulation::Push { ref symbol, value }
+                if symbol.literal_representation() == "constant" =>
+            {
+                self.push(value.literal_representation().cast_signed())
+            }
+            StackManipulation::Push { ref symbol, value } => {
+                let address: u16 = self.address(
+                    symbol.literal_representation(),
+                    value.literal_representation(),
+                )?;
+                self.push(self.get(address))
+            }
+            StackManipulation::Pop { ref symbol, value } => {
+                let address: u16 = self.address(
+                    symbol.literal_representation(),
+                    value.literal_representation(),
+                )?;
+                let popped: i16 = self.pop()?;
+                self.set(address, popped);
+                Ok(())
+            }
+        }
+    }
+
+    /// Runs `operation`.
+    fn compute(&mut self, operation: Arithmetic) -> Result<(), HackError> {
+        let unary: Option<fn(i16) -> i16> = match operation {
+            Arithmetic::Negative => Some(i16::wrapping_neg),
+            Arithmetic::Not => Some(|value: i16| !value),
+            Arithmetic::ShiftLeft => Some(|value: i16| value.wrapping_shl(1)),
+            Arithmetic::ShiftRight => Some(|value: i16| value >> 1_u32),
+            Arithmetic::Add
+            | Arithmetic::Subtract
+            | Arithmetic::Equal
+            | Arithmetic::GreaterThan
+            | Arithmetic::Lessthan
+            | Arithmetic::And
+            | Arithmetic::Or
+            | Arithmetic::Multiply
+            | Arithmetic::Divide
+            | Arithmetic::Modulo
+            | _ => None,
+        };
+        if let Some(unary) = unary {
+            let value: i16 = self.pop()?;
+            return self.push(unary(value));
+        }
+        let right: i16 = self.pop()?;
+        let left: i16 = self.pop()?;
+        let result: i16 = match operation {
+            Arithmetic::Add => left.wrapping_add(right),
+            Arithmetic::Subtract => left.wrapping_sub(right),
+            Arithmetic::Equal => truth(left == right),
+            Arithmetic::GreaterThan => truth(left > right),
+            Arithmetic::Lessthan => truth(left < right),
+            Arithmetic::And => left & right,
+            Arithmetic::Or => left | right,
+            Arithmetic::Multiply => left.wrapping_mul(right),
+            // Only dividing by zero, or the lowest value by -1, overflows.
+            Arithmetic::Divide => match (left.checked_div(right), right) {
+                (Some(quotient), _) => quotient,
+                (None, 0) => 0,
+                (None, _) => left,
+            },
+            Arithmetic::Modulo => match (left.checked_rem(right), right) {
+                (Some(remainder), _) => remainder,
+                (None, 0) => left,
+                (None, _) => 0,
+            },
+            Arithmetic::Negative
+            | Arithmetic::Not
+            | Arithmetic::ShiftLeft
+            | Arithmetic::ShiftRight
+            | _ => {
+                return Err(HackError::UnrecognizedInstruction(
+                    operation.to_string(),
+                ));
+            }
+        };
+        self.push(result)
+    }
+}
+
+impl Default for MachineState {
+    /// Memory as the test scripts of the course start it for code outside of
+    /// any function: `SP` at 256, `LCL` at 300, `ARG` at 400, `THIS` at
+    /// 3000, and `THAT` at 3010, with every other word 0.
+    fn default() -> Self {
+        let highest: usize = usize::from(Target::default().highest_address());
+        let mut state: Self = Self {
+            ram: vec![0; highest.saturating_add(1)],
+        };
+        for (address, value) in [
+            (Self::SP, 256),
+            (Self::LCL, 300),
+            (Self::ARG, 400),
+            (Self::THIS, 3000),
+            (Self::THAT, 3010),
+        ] {
+            state.set(address, value);
+        }
+        state
+    }
+}
+
+impl Display for MachineState {
+    /// Describes the pointers, the stack, and the `temp` segment, one per
+    /// line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "SP: {}, LCL: {}, ARG: {}, THIS: {}, THAT: {}",
+            self.get(Self::SP),
+            self.get(Self::LCL),
+            self.get(Self::ARG),
+            self.get(Self::THIS),
+            self.get(Self::THAT)
+        )?;
+        writeln!(f, "stack: {}", list(self.stack()))?;
+        let temp: Vec<i16> = (0..Target::default().temp().size())
+            .filter_map(|index: u16| self.segment("temp", index))
+            .collect();
+        writeln!(f, "temp: {}", list(&temp))
+    }
+}
+
+/// The commands of a snippet, parsed and with its labels found, so they can
+/// be run one at a time; see [`evaluate_snippet`].
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct Program {
+    /// The commands, paired with their line numbers.
+    instructions: Vec<(usize, Instruction)>,
+    /// The index in [`Program::instructions`] of each label, by its name.
+    labels: BTreeMap<String, usize>,
+}
+
+impl Program {
+    /// Parses the Hack VM commands in `vm_code`.
+    ///
+    /// # Errors
+    ///
+    /// Any error from parsing the code is propagated. Returns a
+    /// [`HackError::IllegalInstruction`] if a label is declared more than
+    /// once.
+    pub(crate) fn parse(vm_code: &str) -> Result<Self, HackError> {
+        let instructions: Vec<(usize, Instruction)> = Parser::limited(
+            vm_code.to_owned(),
+            "<snippet>",
+            Limits::default(),
+        )?
+        .parse_numbered(Mode::default())?;
+        let mut labels: BTreeMap<String, usize> = BTreeMap::new();
+        for (index, &(line, ref instruction)) in instructions.iter().enumerate()
+        {
+            if let Instruction::Branching(Branching::Label { ref symbol }) =
+                *instruction
+                && labels
+                    .insert(symbol.literal_representation().to_owned(), index)
+                    .is_some()
+            {
+                return Err(HackError::IllegalInstruction(format!(
+                    "line {line}: the label \"{symbol}\" is declared more \
+                    than once"
+                )));
+            }
+        }
+        Ok(Self {
+            instructions,
+            labels,
+        })
+    }
+
+    /// Gets the commands, paired with their line numbers.
+    pub(crate) fn instructions(&self) -> &[(usize, Instruction)] {
+        &self.instructions
+    }
+
+    /// Runs the command at `index` on `state`, and gets the index of the
+    /// command to run next, which is past the last once the snippet ends.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] naming the line of the
+    /// command if it cannot run, such as popping off of an empty stack or
+    /// jumping to a label that is not declared.
+    pub(crate) fn step(
+        &self,
+        state: &mut MachineState,
+        index: usize,
+    ) -> Result<usize, HackError> {
+        let Some(&(line, ref instruction)) = self.instructions.get(index)
+        else {
+            return Ok(index);
+        };
+        let jump: Option<&str> = match *instruction {
+            Instruction::StackManipulation(ref manipulation) => {
+                state.manipulate(manipulation).map(|()| None)
+            }
+            Instruction::Arithmetic(operation) => {
+                state.compute(operation).map(|()| None)
+            }
+            Instruction::Branching(Branching::Label { .. }) => Ok(None),
+            Instruction::Branching(Branching::GoTo { ref symbol }) => {
+                Ok(Some(symbol.literal_representation()))
+            }
+            Instruction::Branching(Branching::IfGoTo { ref symbol }) => {
+                state.pop().map(|condition: i16| {
+                    (condition != 0).then(|| symbol.literal_representation())
+                })
+            }
+            Instruction::Functional(_) | Instruction::Assembly(_) | _ => {
+                Err(HackError::IllegalInstruction(format!(
+                    "\"{instruction}\" cannot run in a snippet"
+                )))
+            }
+        }
+        .map_err(|error: HackError| {
+            if let HackError::IllegalInstruction(ref message) = error {
+                HackError::IllegalInstruction(format!("line {line}: {message}"))
+            } else {
+                error
+            }
+        })?;
+        jump.map_or_else(
+            || Ok(index.saturating_add(1)),
+            |label: &str| {
+                self.labels.get(label).copied().ok_or_else(|| {
+                    HackError::IllegalInstruction(format!(
+                        "line {line}: the label \"{label}\" is not declared"
+                    ))
+                })
+            },
+        )
+    }
+}
+
+/// Runs the Hack VM commands in `vm_code`, starting from `initial_state`,
+/// and gets the state they leave memory in.
+///
+/// # Errors
+///
+/// Any error from parsing the code is propagated. Returns a
+/// [`HackError::IllegalInstruction`] naming the line of the command that
+/// failed if a command cannot run, such as popping off of an empty stack or
+/// jumping to a label that is not declared, or if the snippet runs for more
+/// than [`MAX_STEPS`] commands.
+pub fn evaluate_snippet(
+    vm_code: &str,
+    initial_state: MachineState,
+) -> Result<MachineState, HackError> {
+    let program: Program = Program::parse(vm_code)?;
+    let mut state: MachineState = initial_state;
+    let mut next: usize = 0;
+    let mut steps: usize = 0;
+    while let Some(&(line, _)) = program.instructions.get(next) {
+        steps = steps.saturating_add(1);
+        if steps > MAX_STEPS {
+            return Err(HackError::IllegalInstruction(format!(
+                "line {line}: the snippet ran for more than {MAX_STEPS} \
+                commands"
+            )));
+        }
+        next = program.step(&mut state, next)?;
+    }
+    Ok(state)
+}
+
+/// Gets the value of a comparison that is `true` or not, as the Hack VM
+/// represents it.
+const fn truth(condition: bool) -> i16 {
+    if condition { -1 } else { 0 }
+}
+
+/// Writes `values` as a list, such as `[7, 8]`.
+fn list(values: &[i16]) -> String {
+    let values: Vec<String> = values.iter().map(i16::to_string).collect();
+    format!("[{}]", values.join(", "))
+}
@@ -0,0 +1,319 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Fuzz Module
+//!
+//! A differential fuzzing harness, which checks random programs as
+//! [`crate::verify`] does, running them both through the interpreter and
+//! through the translator, the assembler, and the CPU. Only available with
+//! the `fuzz` feature.
+//!
+//! A fuzz target, such as one run by `cargo fuzz`, only has to hand its input
+//! to [`differential`] and unwrap what it returns, which shows the program and
+//! how it diverged:
+//!
+//! ```
+//! use hack_vm_cli::fuzz;
+//! use hack_vm_cli::verify::Divergence;
+//!
+//! fuzz::differential(&[0, 42, 0, 1, 0, 7, 1, 3, 2])?;
+//! # Ok::<(), Divergence>(())
+//! ```
+//!
+//! The programs are straight-line code of pushes, pops, and arithmetic,
+//! including the extended arithmetic commands, which are translated inline.
+//! They never pop off of an empty stack, nor pop to `pointer`, so that the
+//! segments stay where the interpreter starts them; see
+//! [`MachineState::default`].
+//!
+//! Each program is translated once for every combination of the passes over
+//! the assembly that are off by default, and of `renumber-labels`, as those
+//! rewrite the most; see [`crate::passes`].
+
+use core::fmt::{self, Display};
+use core::iter;
+use std::path::PathBuf;
+
+use arbitrary::{Arbitrary, Error, Unstructured};
+use hack_vm_codegen::Options;
+use hack_vm_codegen::labels::LabelScheme;
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::extensions::Extensions;
+use hack_vm_syntax::parser::{
+    Arithmetic, Constant, Instruction, StackManipulation, Symbol,
+};
+use hack_vm_syntax::segments::Segments;
+
+use crate::eval::MachineState;
+use crate::passes::{Pass, Pipeline};
+use crate::verify::{self, Divergence};
+use crate::{Settings, Source};
+
+/// The name of the file each program is translated as.
+const FILE_NAME: &str = "Fuzz";
+
+/// The most commands in a program.
+const MAX_LENGTH: usize = 64;
+
+/// The most values on the stack at once, so that it stays below `local`.
+const MAX_DEPTH: usize = 32;
+
+/// The highest index generated for each segment.
+const MAX_INDEX: u16 = 7;
+
+/// The segments generated commands push from and pop to, besides `constant`.
+const SEGMENTS: [&str; 6] =
+    ["local", "argument", "this", "that", "temp", "static"];
+
+/// The passes that each program is translated both with and without.
+const OPTIONAL_PASSES: [Pass; 4] = [
+    Pass::StrengthReduce,
+    Pass::AllocateRegisters,
+    Pass::Outline,
+    Pass::RenumberLabels,
+];
+
+/// A random, well-formed program; see [`crate::fuzz`].
+///
+/// Displays as VM code.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Snippet {
+    /// The commands, in order.
+    instructions: Vec<Instruction>,
+}
+
+impl Snippet {
+    /// Gets the commands, in order.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+}
+
+impl<'data> Arbitrary<'data> for Snippet {
+    /// Generates commands until the data runs out or there are
+    /// [`MAX_LENGTH`] of them.
+    fn arbitrary(u: &mut Unstructured<'data>) -> Result<Self, Error> {
+        let mut instructions: Vec<Instruction> = Vec::new();
+        let mut depth: usize = 0;
+        while instructions.len() < MAX_LENGTH && !u.is_empty() {
+            let choice: u8 = u.int_in_range(0_u8..=3)?;
+            let instruction: Instruction = match choice {
+                0 | 1 if depth < MAX_DEPTH => {
+                    depth = depth.saturating_add(1);
+                    let segment: &str = if choice == 0 {
+                        "constant"
+                    } else {
+                        u.choose(&SEGMENTS)?
+                    };
+                    let value: Constant = if choice == 0 {
+                        u.arbitrary()?
+                    } else {
+                        constant(u.int_in_range(0..=MAX_INDEX)?)?
+                    };
+                    Instruction::StackManipulation(StackManipulation::Push {
+                        symbol: symbol(segment)?,
+                        value,
+                    })
+                }
+                2 if depth > 0 => {
+                    depth = depth.saturating_sub(1);
+                    Instruction::StackManipulation(StackManipulation::Pop {
+                        symbol: symbol(u.choose(&SEGMENTS)?)?,
+                        value: constant(u.int_in_range(0..=MAX_INDEX)?)?,
+                    })
+                }
+                _ => {
+                    let operation: Arithmetic = u.arbitrary()?;
+                    if operation.is_unary() && depth >= 1 {
+                        Instruction::Arithmetic(operation)
+                    } else if depth >= 2 {
+                        depth = depth.saturating_sub(1);
+                        Instruction::Arithmetic(operation)
+                    } else {
+                        continue;
+                    }
+                }
+            };
+            instructions.push(instruction);
+        }
+        Ok(Self { instructions })
+    }
+}
+
+impl Display for Snippet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Instruction::to_vm_source(&self.instructions))
+    }
+}
+
+/// Generates a [`Snippet`] from `data` and checks it, as a fuzz target does.
+/// Data that no program can be generated from passes.
+///
+/// # Errors
+///
+/// Returns the [`Divergence`] if the program runs differently when
+/// translated than when interpreted.
+pub fn differential(data: &[u8]) -> Result<(), Divergence> {
+    Snippet::arbitrary(&mut Unstructured::new(data))
+        .map_or(Ok(()), |snippet: Snippet| check(&snippet))
+}
+
+/// Checks that `snippet` runs the same translated as interpreted.
+///
+/// It is checked as [`crate::verify`] checks programs, translated with every
+/// combination of [`OPTIONAL_PASSES`].
+///
+/// # Errors
+///
+/// Returns a [`Divergence`] listing every word of memory that differs with
+/// the first combination of passes it differs with, or the error from the
+/// side that failed, as none should.
+pub fn check(snippet: &Snippet) -> Result<(), Divergence> {
+    let program: String = snippet.to_string();
+    let failed = |side: &str, error: HackError| {
+        Divergence::new(
+            program.clone(),
+            vec![format!("{side} failed: {error}")],
+        )
+    };
+    let source: Source = Source::Archived {
+        path: PathBuf::from(format!("{FILE_NAME}.vm")),
+        contents: program.clone(),
+    };
+    for passes in combinations() {
+        let named: String = if passes.is_empty() {
+            "the usual passes".to_owned()
+        } else {
+            format!(
+                "the passes {}",
+                passes
+                    .iter()
+                    .map(|pass: &Pass| pass.name())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            )
+        };
+        let settings: Settings = settings(&passes)
+            .map_err(|error: HackError| failed(&named, error))?;
+        let differences: Vec<String> = verify::check(
+            &source,
+            &Symbol::sanitized(FILE_NAME),
+            &MachineState::default(),
+            &settings,
+        )
+        .map_err(|error: HackError| failed("the interpreter", error))?;
+        if !differences.is_empty() {
+            return Err(Divergence::new(
+                program,
+                iter::once(format!("with {named}:"))
+                    .chain(differences)
+                    .collect(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Gets every combination of [`OPTIONAL_PASSES`], each in the order they
+/// usually run.
+fn combinations() -> Vec<Vec<Pass>> {
+    OPTIONAL_PASSES.into_iter().rev().fold(
+        vec![Vec::new()],
+        |combinations: Vec<Vec<Pass>>, pass: Pass| {
+            combinations
+                .into_iter()
+                .flat_map(|without: Vec<Pass>| {
+                    let with: Vec<Pass> =
+                        iter::once(pass).chain(without.clone()).collect();
+                    [without, with]
+                })
+                .collect()
+        },
+    )
+}
+
+/// Gets the settings programs are translated with: the usual passes and
+/// those of `passes`, with the extended arithmetic commands translated
+/// inline.
+///
+/// # Errors
+///
+/// Any error from enabling the passes is propagated.
+fn settings(passes: &[Pass]) -> Result<Settings, HackError> {
+    let mut labels: LabelScheme = LabelScheme::default();
+    labels.set_renumber(passes.contains(&Pass::RenumberLabels));
+    let mut pipeline: Pipeline = Pipeline::default();
+    for pass in passes {
+        pipeline.apply(&format!("+{pass}"))?;
+    }
+    Ok(Settings {
+        translation: Options::new(
+            labels,
+            Extensions::Inline,
+            false,
+            Segments::default(),
+            None,
+        ),
+        passes: pipeline,
+        ..Settings::default()
+    })
+}
+
+/// Creates the [`Symbol`] `string`, which is known to be valid.
+fn symbol(string: &str) -> Result<Symbol, Error> {
+    string
+        .parse()
+        .map_err(|_error: HackError| Error::IncorrectFormat)
+}
+
+/// Creates the [`Constant`] `value`, which is known to be in range.
+fn constant(value: u16) -> Result<Constant, Error> {
+    Constant::try_from(value)
+        .map_err(|_error: HackError| Error::IncorrectFormat)
+}
+
+#[cfg(test)]
+mod tests {
+    //! Checks programs that once ran differently when translated.
+
+    use hack_vm_syntax::limits::Limits;
+    use hack_vm_syntax::mode::Mode;
+    use hack_vm_syntax::parser::{Instruction, Parser};
+
+    use super::{Divergence, Snippet, check};
+
+    /// Creates the [`Snippet`] of `vm_code`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `vm_code` cannot be parsed.
+    fn snippet(vm_code: &str) -> Snippet {
+        let parser: Parser =
+            Parser::limited(vm_code.to_owned(), "<snippet>", Limits::default())
+                .unwrap();
+        Snippet {
+            instructions: parser
+                .parse_numbered(Mode::default())
+                .unwrap()
+                .into_iter()
+                .map(|numbered: (usize, Instruction)| numbered.1)
+                .collect(),
+        }
+    }
+
+    /// `gt` and `lt` of operands whose difference overflows, which were
+    /// compared by the sign of the difference.
+    #[test]
+    fn comparisons_do_not_overflow() -> Result<(), Divergence> {
+        for comparison in ["gt", "lt"] {
+            for (x, y) in [("32767", "1"), ("1", "32767"), ("0", "32767")] {
+                check(&snippet(&format!(
+                    "push constant {x}\nneg\npush constant {y}\n{comparison}\n\
+                    push constant {y}\npush constant {x}\nneg\n{comparison}\n"
+                )))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Ignore Module
+//!
+//! Reads `.vmignore` files, which exclude entries from translation when a
+//! directory is scanned for Hack VM files. They follow `.gitignore` syntax:
+//!
+//! ```text
+//! # Comments start with a hash.
+//! Scratch*.vm
+//! reference/
+//! !ReferenceMain.vm
+//! ```
+//!
+//! Each line is a pattern matched against the names of entries in the
+//! directory, where `*` matches any run of characters, `?` matches any single
+//! character, and `[...]` matches one character from a set or range (negated
+//! with `[!...]`). A leading `!` re-includes entries excluded by an earlier
+//! pattern, and a trailing `/` restricts the pattern to directories. When
+//! several patterns match, the last one wins.
+
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::paths;
+
+/// A single pattern from a `.vmignore` file.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Rule {
+    /// The glob pattern.
+    pattern: Vec<char>,
+    /// Whether a match re-includes the entry, rather than excluding it.
+    negated: bool,
+    /// Whether the pattern only applies to directories.
+    directory_only: bool,
+}
+
+impl Rule {
+    /// Parses a line of a `.vmignore` file, returning [`None`] for blank lines
+    /// and comments.
+    fn parse(line: &str) -> Option<Self> {
+        let line: &str = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line): (bool, &str) = line.strip_prefix('!').map_or_else(
+            || (false, line.strip_prefix('\\').unwrap_or(line)),
+            |rest: &str| (true, rest),
+        );
+        let (directory_only, line): (bool, &str) = line
+            .strip_suffix('/')
+            .map_or((false, line), |rest: &str| (true, rest));
+        let line: &str = line.strip_prefix('/').unwrap_or(line);
+        Some(Self {
+            pattern: line.chars().collect(),
+            negated,
+            directory_only,
+        })
+    }
+
+    /// Whether this rule applies to an entry with the given name.
+    fn matches(&self, name: &[char], is_dir: bool) -> bool {
+        (is_dir || !self.directory_only) && glob(&self.pattern, name)
+    }
+}
+
+/// The patterns read from a directory's `.vmignore` file.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub(crate) struct IgnoreFile {
+    /// The patterns, in the order they appear in the file.
+    rules: Vec<Rule>,
+}
+
+impl IgnoreFile {
+    /// The name of the file patterns are read from.
+    pub(crate) const FILE_NAME: &str = ".vmignore";
+
+    /// Reads the `.vmignore` file in `directory`. If there is none, nothing is
+    /// ignored.
+    pub(crate) fn load(directory: &Path) -> Result<Self, HackError> {
+        let path: PathBuf = directory.join(Self::FILE_NAME);
+        let text: String = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(error) => {
+                return Err(HackError::CannotReadFileFromPath(format!(
+                    "could not read \"{}\": {error}",
+                    paths::display(&path)
+                )));
+            }
+        };
+        Ok(Self {
+            rules: text.lines().filter_map(Rule::parse).collect(),
+        })
+    }
+
+    /// Whether the entry with the given name should be excluded.
+    pub(crate) fn is_ignored(&self, name: &str, is_dir: bool) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule: &&Rule| rule.matches(&name, is_dir))
+            .is_some_and(|rule: &Rule| !rule.negated)
+    }
+}
+
+/// Whether `name` matches the glob `pattern` in its entirety.
+fn glob(pattern: &[char], name: &[char]) -> bool {
+    match *pattern {
+        [] => name.is_empty(),
+        ['*', ref rest @ ..] => (0..=name.len()).any(|skipped: usize| {
+            name.get(skipped..)
+                .is_some_and(|tail: &[char]| glob(rest, tail))
+        }),
+        ['?', ref rest @ ..] => name
+            .split_first()
+            .is_some_and(|(_, tail): (&char, &[char])| glob(rest, tail)),
+        ['\\', escaped, ref rest @ ..] => literal(escaped, rest, name),
+        ['[', ref rest @ ..] => {
+            let (negated, body): (bool, &[char]) = match *rest {
+                ['!' | '^', ref body @ ..] => (true, body),
+                _ => (false, rest),
+            };
+            // A `]` immediately after the opening bracket is part of the set.
+            let close: Option<usize> = body
+                .iter()
+                .skip(1)
+                .position(|&character: &char| character == ']')
+                .map(|position: usize| position.saturating_add(1));
+            match close.map(|close: usize| body.split_at(close)) {
+                Some((set, &[_, ref rest @ ..])) => name
+                    .split_first()
+                    .is_some_and(|(&character, tail): (&char, &[char])| {
+                        in_set(set, character) != negated && glob(rest, tail)
+                    }),
+                _ => literal('[', rest, name),
+            }
+        }
+        [character, ref rest @ ..] => literal(character, rest, name),
+    }
+}
+
+/// Whether `name` starts with `character` and the remainder matches `rest`.
+fn literal(character: char, rest: &[char], name: &[char]) -> bool {
+    name.split_first()
+        .is_some_and(|(&first, tail): (&char, &[char])| {
+            first == character && glob(rest, tail)
+        })
+}
+
+/// Whether `character` is in the set from a `[...]` pattern, such as `abc`
+/// or `a-z0-9`.
+fn in_set(set: &[char], character: char) -> bool {
+    match *set {
+        [] => false,
+        [start, '-', end, ref rest @ ..] => {
+            (start..=end).contains(&character) || in_set(rest, character)
+        }
+        [member, ref rest @ ..] => {
+            member == character || in_set(rest, character)
+        }
+    }
+}
@@ -0,0 +1,337 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Inflate Module
+//!
+//! Decompresses DEFLATE streams, as described in RFC 1951, which is how files
+//! in zip archives are usually compressed. Only decompression is supported,
+//! and speed is not a goal: the inputs are small text files.
+//!
+//! Decompression stops as soon as the output is larger than the size the
+//! archive states for it, so a small, malicious stream cannot exhaust memory.
+
+use core::iter;
+
+use hack_vm_syntax::error::HackError;
+
+/// The most bits a Huffman code can have.
+const MAX_BITS: usize = 15;
+
+/// The base lengths for length symbols 257 through 285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59,
+    67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+
+/// The number of extra bits added to the base for length symbols 257 through
+/// 285.
+const LENGTH_EXTRA: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5,
+    5, 5, 5, 0,
+];
+
+/// The base distances for distance symbols 0 through 29.
+#[expect(
+    clippy::decimal_literal_representation,
+    reason = "RFC 1951 lists these in decimal"
+)]
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513,
+    769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+
+/// The number of extra bits added to the base for distance symbols 0 through
+/// 29.
+const DISTANCE_EXTRA: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10,
+    11, 11, 12, 12, 13, 13,
+];
+
+/// The order the code lengths of the code length alphabet are stored in.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// The symbol ending a block.
+const END_OF_BLOCK: u16 = 256;
+
+/// Creates a [`HackError::BadArchive`] describing corrupt compressed data.
+fn corrupt(message: &str) -> HackError {
+    HackError::BadArchive(format!("corrupt compressed data: {message}"))
+}
+
+/// Reads a DEFLATE stream bit by bit, least significant bit first.
+#[derive(Debug)]
+struct Bits<'data> {
+    /// The compressed data.
+    data: &'data [u8],
+    /// The index of the next byte to load.
+    position: usize,
+    /// Bits loaded but not yet read, in the low bits.
+    buffer: u32,
+    /// The number of bits in `buffer`.
+    count: u32,
+}
+
+impl Bits<'_> {
+    /// Reads `count` bits, at most 16, as an integer.
+    fn bits(&mut self, count: u32) -> Result<u32, HackError> {
+        while self.count < count {
+            let byte: u8 = *self
+                .data
+                .get(self.position)
+                .ok_or_else(|| corrupt("unexpected end of data"))?;
+            self.position = self.position.saturating_add(1);
+            self.buffer |= u32::from(byte).wrapping_shl(self.count);
+            self.count = self.count.saturating_add(8);
+        }
+        let value: u32 =
+            self.buffer & 1_u32.wrapping_shl(count).wrapping_sub(1);
+        self.buffer = self.buffer.wrapping_shr(count);
+        self.count = self.count.saturating_sub(count);
+        Ok(value)
+    }
+
+    /// Discards the bits remaining in the current byte.
+    const fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+
+    /// Reads `length` whole bytes. Must only be used after [`Bits::align`].
+    fn bytes(&mut self, length: usize) -> Result<&[u8], HackError> {
+        let end: usize = self
+            .position
+            .checked_add(length)
+            .ok_or_else(|| corrupt("unexpected end of data"))?;
+        let bytes: &[u8] = self
+            .data
+            .get(self.position..end)
+            .ok_or_else(|| corrupt("unexpected end of data"))?;
+        self.position = end;
+        Ok(bytes)
+    }
+}
+
+/// A canonical Huffman code.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Huffman {
+    /// The number of codes of each length, indexed by length.
+    counts: [usize; MAX_BITS + 1],
+    /// The symbols, ordered by their codes.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code from the code length of each symbol, where a length of
+    /// zero means the symbol is unused.
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts: [usize; MAX_BITS + 1] = [0; MAX_BITS + 1];
+        let mut symbols: Vec<u16> = Vec::with_capacity(lengths.len());
+        for (length, count) in counts.iter_mut().enumerate().skip(1) {
+            for (symbol, _) in
+                lengths.iter().enumerate().filter(|&(_, &symbol_length)| {
+                    usize::from(symbol_length) == length
+                })
+            {
+                *count = count.saturating_add(1);
+                symbols.push(u16::try_from(symbol).unwrap_or(u16::MAX));
+            }
+        }
+        Self { counts, symbols }
+    }
+
+    /// Reads and decodes a single symbol.
+    fn decode(&self, bits: &mut Bits<'_>) -> Result<u16, HackError> {
+        let mut code: usize = 0;
+        let mut first: usize = 0;
+        let mut index: usize = 0;
+        for &count in self.counts.iter().skip(1) {
+            code |= to_usize(bits.bits(1)?)?;
+            if code < first.saturating_add(count) {
+                return self
+                    .symbols
+                    .get(index.saturating_add(code.saturating_sub(first)))
+                    .copied()
+                    .ok_or_else(|| corrupt("invalid Huffman code"));
+            }
+            index = index.saturating_add(count);
+            first = first.saturating_add(count).saturating_mul(2);
+            code = code.saturating_mul(2);
+        }
+        Err(corrupt("invalid Huffman code"))
+    }
+}
+
+/// Decompresses a raw DEFLATE stream, which is stated to decompress into
+/// `size` bytes.
+///
+/// # Errors
+///
+/// Returns a [`HackError::BadArchive`] if the data is corrupt or truncated,
+/// or decompresses into more than `size` bytes.
+pub(crate) fn inflate(data: &[u8], size: usize) -> Result<Vec<u8>, HackError> {
+    let mut bits: Bits<'_> = Bits {
+        data,
+        position: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut output: Vec<u8> = Vec::new();
+    loop {
+        let last: bool = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => stored(&mut bits, &mut output, size)?,
+            1 => {
+                let (literals, distances): (Huffman, Huffman) = fixed_codes();
+                block(&mut bits, &mut output, size, (&literals, &distances))?;
+            }
+            2 => {
+                let (literals, distances): (Huffman, Huffman) =
+                    dynamic_codes(&mut bits)?;
+                block(&mut bits, &mut output, size, (&literals, &distances))?;
+            }
+            _ => return Err(corrupt("invalid block type")),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+/// Copies an uncompressed block to `output`, which may hold at most `size`
+/// bytes.
+fn stored(
+    bits: &mut Bits<'_>,
+    output: &mut Vec<u8>,
+    size: usize,
+) -> Result<(), HackError> {
+    bits.align();
+    let length: u32 = bits.bits(16)?;
+    let complement: u32 = bits.bits(16)?;
+    if length != !complement & 0xFFFF {
+        return Err(corrupt("stored block length does not match"));
+    }
+    let length: usize = to_usize(length)?;
+    if output.len().saturating_add(length) > size {
+        return Err(corrupt("output is larger than stated"));
+    }
+    output.extend_from_slice(bits.bytes(length)?);
+    Ok(())
+}
+
+/// The Huffman codes used by blocks compressed with fixed codes.
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths: [u8; 288] = [8; 288];
+    for (symbol, length) in lengths.iter_mut().enumerate() {
+        *length = match symbol {
+            0..=143 | 280.. => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+        };
+    }
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+/// Reads the Huffman codes at the start of a block compressed with dynamic
+/// codes.
+fn dynamic_codes(bits: &mut Bits<'_>) -> Result<(Huffman, Huffman), HackError> {
+    let literal_count: usize = to_usize(bits.bits(5)?)?.saturating_add(257);
+    let distance_count: usize = to_usize(bits.bits(5)?)?.saturating_add(1);
+    let code_length_count: usize = to_usize(bits.bits(4)?)?.saturating_add(4);
+
+    let mut code_lengths: [u8; 19] = [0; 19];
+    for &symbol in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        if let Some(length) = code_lengths.get_mut(symbol) {
+            *length = u8::try_from(bits.bits(3)?)
+                .map_err(|_error| HackError::Internal)?;
+        }
+    }
+    let code_lengths: Huffman = Huffman::new(&code_lengths);
+
+    let total: usize = literal_count.saturating_add(distance_count);
+    let mut lengths: Vec<u8> = Vec::with_capacity(total);
+    while lengths.len() < total {
+        let (length, repeat): (u8, usize) = match code_lengths.decode(bits)? {
+            16 => (
+                *lengths.last().ok_or_else(|| {
+                    corrupt("repeated code length is missing")
+                })?,
+                to_usize(bits.bits(2)?)?.saturating_add(3),
+            ),
+            17 => (0, to_usize(bits.bits(3)?)?.saturating_add(3)),
+            18 => (0, to_usize(bits.bits(7)?)?.saturating_add(11)),
+            length => (
+                u8::try_from(length).map_err(|_error| HackError::Internal)?,
+                1,
+            ),
+        };
+        if lengths.len().saturating_add(repeat) > total {
+            return Err(corrupt("too many code lengths"));
+        }
+        lengths.extend(iter::repeat_n(length, repeat));
+    }
+
+    let (literals, distances): (&[u8], &[u8]) = lengths.split_at(literal_count);
+    Ok((Huffman::new(literals), Huffman::new(distances)))
+}
+
+/// Decompresses a block compressed with the given Huffman codes for
+/// literals and distances into `output`, which may hold at most `size`
+/// bytes.
+fn block(
+    bits: &mut Bits<'_>,
+    output: &mut Vec<u8>,
+    size: usize,
+    (literals, distances): (&Huffman, &Huffman),
+) -> Result<(), HackError> {
+    loop {
+        let symbol: u16 = literals.decode(bits)?;
+        if let Ok(byte) = u8::try_from(symbol) {
+            if output.len() >= size {
+                return Err(corrupt("output is larger than stated"));
+            }
+            output.push(byte);
+            continue;
+        }
+        if symbol == END_OF_BLOCK {
+            return Ok(());
+        }
+
+        let index: usize = usize::from(symbol.saturating_sub(257));
+        let (&base, &extra): (&u16, &u32) = LENGTH_BASE
+            .get(index)
+            .zip(LENGTH_EXTRA.get(index))
+            .ok_or_else(|| corrupt("invalid length symbol"))?;
+        let length: usize =
+            usize::from(base).saturating_add(to_usize(bits.bits(extra)?)?);
+
+        let index: usize = usize::from(distances.decode(bits)?);
+        let (&base, &extra): (&u16, &u32) = DISTANCE_BASE
+            .get(index)
+            .zip(DISTANCE_EXTRA.get(index))
+            .ok_or_else(|| corrupt("invalid distance symbol"))?;
+        let distance: usize =
+            usize::from(base).saturating_add(to_usize(bits.bits(extra)?)?);
+
+        let start: usize = output
+            .len()
+            .checked_sub(distance)
+            .ok_or_else(|| corrupt("distance is too far back"))?;
+        if output.len().saturating_add(length) > size {
+            return Err(corrupt("output is larger than stated"));
+        }
+        // The copy may overlap what it produces, so it goes byte by byte.
+        for offset in start..start.saturating_add(length) {
+            let byte: u8 =
+                *output.get(offset).ok_or_else(|| corrupt("invalid copy"))?;
+            output.push(byte);
+        }
+    }
+}
+
+/// Converts bits read from the stream into a [`usize`].
+fn to_usize(value: u32) -> Result<usize, HackError> {
+    usize::try_from(value).map_err(|_error| HackError::Internal)
+}
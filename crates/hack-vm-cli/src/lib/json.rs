@@ -0,0 +1,368 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - JSON Module
+//!
+//! Reads and writes the JSON exchanged with other tools, such as the requests
+//! answered by the `daemon` subcommand; see [`crate::daemon`]. Numbers are
+//! kept as they were written, since they are only ever passed back.
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Display};
+use core::iter::Peekable;
+use core::str::{Chars, FromStr};
+
+use hack_vm_syntax::error::HackError;
+
+/// A JSON value.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) enum Value {
+    /// `null`.
+    Null,
+    /// `true` or `false`.
+    Boolean(bool),
+    /// A number, as it was written.
+    Number(String),
+    /// A string.
+    String(String),
+    /// An array of values.
+    Array(Vec<Self>),
+    /// An object, with its members in order of their names.
+    Object(BTreeMap<String, Self>),
+}
+
+impl Value {
+    /// Creates an object from its `members`.
+    pub(crate) fn object<const N: usize>(members: [(&str, Self); N]) -> Self {
+        Self::Object(
+            members
+                .into_iter()
+                .map(|(name, value): (&str, Self)| (name.to_owned(), value))
+                .collect(),
+        )
+    }
+
+    /// Gets the member named `name`, if this is an object that has it.
+    pub(crate) fn get(&self, name: &str) -> Option<&Self> {
+        match *self {
+            Self::Object(ref members) => members.get(name),
+            Self::Null
+            | Self::Boolean(_)
+            | Self::Number(_)
+            | Self::String(_)
+            | Self::Array(_) => None,
+        }
+    }
+
+    /// Gets the string this is, if it is one.
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match *self {
+            Self::String(ref string) => Some(string),
+            Self::Null
+            | Self::Boolean(_)
+            | Self::Number(_)
+            | Self::Array(_)
+            | Self::Object(_) => None,
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Self::Number(value.to_string())
+    }
+}
+
+impl FromStr for Value {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut reader: Reader<'_> = Reader {
+            chars: s.chars().peekable(),
+            column: 1,
+        };
+        let value: Self = reader.value()?;
+        reader
+            .skip_whitespace()
+            .map_or(Ok(value), |character: char| {
+                Err(reader.error(&format!(
+                    "expected the end of the JSON, found \"{character}\""
+                )))
+            })
+    }
+}
+
+impl Display for Value {
+    /// Writes the value as compact JSON, on a single line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Null => write!(f, "null"),
+            Self::Boolean(boolean) => write!(f, "{boolean}"),
+            Self::Number(ref number) => write!(f, "{number}"),
+            Self::String(ref string) => write!(f, "{}", JsonString(string)),
+            Self::Array(ref values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Self::Object(ref members) => {
+                write!(f, "{{")?;
+                for (index, (name, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{value}", JsonString(name))?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Displays a string as a JSON string literal.
+struct JsonString<'string>(&'string str);
+
+impl Display for JsonString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for character in self.0.chars() {
+            match character {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '\t' => write!(f, "\\t")?,
+                control if control.is_control() => {
+                    write!(f, "\\u{:04x}", u32::from(control))?;
+                }
+                other => write!(f, "{other}")?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+/// Reads a JSON document one character at a time.
+#[derive(Debug)]
+struct Reader<'text> {
+    /// The remaining characters.
+    chars: Peekable<Chars<'text>>,
+    /// The column of the next character, starting from 1.
+    column: usize,
+}
+
+impl Reader<'_> {
+    /// Creates a [`HackError::FromStrError`] at the current column.
+    fn error(&self, message: &str) -> HackError {
+        HackError::FromStrError(format!(
+            "invalid JSON at column {}: {message}",
+            self.column
+        ))
+    }
+
+    /// Consumes the next character.
+    fn next(&mut self) -> Option<char> {
+        let character: Option<char> = self.chars.next();
+        if character.is_some() {
+            self.column = self.column.saturating_add(1);
+        }
+        character
+    }
+
+    /// Skips whitespace, returning the next character without consuming it.
+    fn skip_whitespace(&mut self) -> Option<char> {
+        while let Some(&character) = self.chars.peek() {
+            if !matches!(character, ' ' | '\t' | '\r' | '\n') {
+                return Some(character);
+            }
+            let _whitespace: Option<char> = self.next();
+        }
+        None
+    }
+
+    /// Consumes `word`, or fails.
+    fn expect(&mut self, word: &str) -> Result<(), HackError> {
+        for expected in word.chars() {
+            if self.next() != Some(expected) {
+                return Err(self.error(&format!("expected \"{word}\"")));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a value.
+    fn value(&mut self) -> Result<Value, HackError> {
+        match self.skip_whitespace() {
+            Some('n') => self.expect("null").map(|()| Value::Null),
+            Some('t') => self.expect("true").map(|()| Value::Boolean(true)),
+            Some('f') => self.expect("false").map(|()| Value::Boolean(false)),
+            Some('"') => self.string().map(Value::String),
+            Some('[') => self.array().map(Value::Array),
+            Some('{') => self.object().map(Value::Object),
+            Some('-' | '0'..='9') => self.number().map(Value::Number),
+            Some(character) => {
+                Err(self
+                    .error(&format!("expected a value, found \"{character}\"")))
+            }
+            None => Err(self.error("expected a value, found the end")),
+        }
+    }
+
+    /// Reads a number, checking that it is one.
+    fn number(&mut self) -> Result<String, HackError> {
+        let mut number: String = String::new();
+        while let Some(&character) = self.chars.peek() {
+            if !matches!(character, '0'..='9' | '-' | '+' | '.' | 'e' | 'E') {
+                break;
+            }
+            number.push(character);
+            let _digit: Option<char> = self.next();
+        }
+        match number.parse::<f64>() {
+            Ok(_) => Ok(number),
+            Err(error) => {
+                Err(self
+                    .error(&format!("invalid number \"{number}\": {error}")))
+            }
+        }
+    }
+
+    /// Reads a string, interpreting escapes.
+    fn string(&mut self) -> Result<String, HackError> {
+        let _open: Option<char> = self.next();
+        let mut string: String = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let escaped: char = match self.next() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => self.unicode_escape()?,
+                        Some(character @ ('"' | '\\' | '/')) => character,
+                        Some(other) => {
+                            return Err(self.error(&format!(
+                                "unsupported escape sequence \"\\{other}\""
+                            )));
+                        }
+                        None => break,
+                    };
+                    string.push(escaped);
+                }
+                Some(control) if control.is_control() => {
+                    return Err(self.error("unescaped control character"));
+                }
+                Some(character) => string.push(character),
+                None => break,
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    /// Reads the code unit of a `\u` escape, or two for a surrogate pair,
+    /// after the `\u`.
+    fn unicode_escape(&mut self) -> Result<char, HackError> {
+        let first: u32 = self.code_unit()?;
+        let code: u32 = if (0xD800..0xDC00).contains(&first) {
+            self.expect("\\u")?;
+            let second: u32 = self.code_unit()?;
+            if !(0xDC00..0xE000).contains(&second) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x1_0000_u32
+                .saturating_add(((first & 0x3FF) << 10_u32) | (second & 0x3FF))
+        } else {
+            first
+        };
+        char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate"))
+    }
+
+    /// Reads the four hexadecimal digits of a code unit.
+    fn code_unit(&mut self) -> Result<u32, HackError> {
+        let digits: String = (0..4).filter_map(|_| self.next()).collect();
+        u32::from_str_radix(&digits, 16).map_err(|error| {
+            self.error(&format!(
+                "invalid unicode escape \"\\u{digits}\": {error}"
+            ))
+        })
+    }
+
+    /// Reads an array.
+    fn array(&mut self) -> Result<Vec<Value>, HackError> {
+        let _open: Option<char> = self.next();
+        let mut array: Vec<Value> = Vec::new();
+        if self.skip_whitespace() == Some(']') {
+            let _close: Option<char> = self.next();
+            return Ok(array);
+        }
+        loop {
+            array.push(self.value()?);
+            match self.skip_whitespace() {
+                Some(',') => {
+                    let _comma: Option<char> = self.next();
+                }
+                Some(']') => {
+                    let _close: Option<char> = self.next();
+                    return Ok(array);
+                }
+                Some(character) => {
+                    return Err(self.error(&format!(
+                        "expected \",\" or \"]\", found \"{character}\""
+                    )));
+                }
+                None => return Err(self.error("unterminated array")),
+            }
+        }
+    }
+
+    /// Reads an object. A member named more than once takes its last value.
+    fn object(&mut self) -> Result<BTreeMap<String, Value>, HackError> {
+        let _open: Option<char> = self.next();
+        let mut members: BTreeMap<String, Value> = BTreeMap::new();
+        if self.skip_whitespace() == Some('}') {
+            let _close: Option<char> = self.next();
+            return Ok(members);
+        }
+        loop {
+            if self.skip_whitespace() != Some('"') {
+                return Err(self.error("expected the name of a member"));
+            }
+            let name: String = self.string()?;
+            if self.skip_whitespace() != Some(':') {
+                return Err(self.error("expected \":\" after a member's name"));
+            }
+            let _colon: Option<char> = self.next();
+            let _previous: Option<Value> = members.insert(name, self.value()?);
+            match self.skip_whitespace() {
+                Some(',') => {
+                    let _comma: Option<char> = self.next();
+                }
+                Some('}') => {
+                    let _close: Option<char> = self.next();
+                    return Ok(members);
+                }
+                Some(character) => {
+                    return Err(self.error(&format!(
+                        "expected \",\" or \"}}\", found \"{character}\""
+                    )));
+                }
+                None => return Err(self.error("unterminated object")),
+            }
+        }
+    }
+}
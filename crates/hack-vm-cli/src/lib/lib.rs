@@ -0,0 +1,2633 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator
+//!
+//! A VM translator that parses Hack VM commands and generates Hack assembly.
+//! Based on the nand2tetris course.
+
+#![expect(
+    unused_crate_dependencies,
+    clippy::module_name_repetitions,
+    clippy::missing_errors_doc,
+    reason = "error_set is not in use yet"
+)]
+#![allow(clippy::missing_docs_in_private_items, reason = "todo later")]
+// Lints that are still unstable, and so only checked with the `nightly`
+// feature, keeping the default build working on stable.
+#![cfg_attr(
+    feature = "nightly",
+    feature(
+        multiple_supertrait_upcastable,
+        must_not_suspend,
+        non_exhaustive_omitted_patterns_lint,
+        strict_provenance_lints,
+        supertrait_item_shadowing,
+        unqualified_local_imports
+    ),
+    // Casting integers to pointers messes with provenance (#130351)
+    warn(fuzzy_provenance_casts),
+    // Casting pointers to integers loses with provenance (#130351)
+    warn(lossy_provenance_casts),
+    // Adds overhead. Is it worth it? (#150833)
+    warn(multiple_supertrait_upcastable),
+    // Causes problems in async (#83310)
+    warn(must_not_suspend),
+    // Explicit matches are preferred (#89554)
+    warn(non_exhaustive_omitted_patterns),
+    // Shadowing silently leads to unexpected behavior (#89151)
+    warn(shadowing_supertrait_items),
+    warn(resolving_to_items_shadowing_supertrait_items),
+    // Explicitness provides clarity, and this assists import grouping in
+    // rustfmt (#138299)
+    warn(unqualified_local_imports)
+)]
+
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use core::fmt::{self, Display};
+use core::iter;
+use core::str::FromStr;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use hack_vm_codegen::Options;
+use hack_vm_codegen::labels::{self, LabelScheme};
+use hack_vm_codegen::translator::Translator;
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::extensions::Extensions;
+use hack_vm_syntax::limits::Limits;
+use hack_vm_syntax::mode::Mode;
+use hack_vm_syntax::parser::{self, Parser, Symbol};
+use hack_vm_syntax::paths;
+use hack_vm_syntax::provenance::Provenance;
+use hack_vm_syntax::segments::{Segments, UserSegment};
+use hack_vm_syntax::target::Target;
+use hack_vm_syntax::trace::{self, Span};
+
+use crate::asmdiff::{Assembly, AssemblyDiff};
+use crate::builder::ConfigBuilder;
+use crate::cache::Cache;
+use crate::color::{ColorChoice, Palette};
+use crate::compat::Compat;
+use crate::defaults::{Defaults, Discovery};
+use crate::diagnostics::DiagnosticsFormat;
+use crate::docs::Documentation;
+use crate::eval::MachineState;
+use crate::ignore::IgnoreFile;
+use crate::link::Library;
+use crate::lints::Warning;
+use crate::manifest::Manifest;
+use crate::namespace::Namespaces;
+use crate::passes::{Pass, PassManager, Pipeline, Unit};
+use crate::progress::{Progress, ProgressPolicy};
+use crate::reference::Comparison;
+use crate::rewrite::Rewrites;
+use crate::sandbox::Sandbox;
+use crate::summary::{FileSummary, Phase, Report, Summary, Timings};
+use crate::symbols::SymbolTable;
+use crate::verbosity::Verbosity;
+use crate::verify::Verification;
+use crate::xref::CrossReference;
+use crate::zip::ArchivedFile;
+
+pub mod asmdiff;
+pub mod builder;
+pub mod cache;
+pub mod color;
+pub mod compat;
+pub mod cpu;
+pub mod daemon;
+pub mod defaults;
+pub mod diagnostics;
+pub mod docs;
+pub mod eval;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod ignore;
+pub mod inflate;
+pub mod json;
+pub mod link;
+pub mod lints;
+pub mod manifest;
+pub mod metrics;
+pub mod namespace;
+pub mod outline;
+pub mod passes;
+pub mod progress;
+pub mod reduce;
+pub mod reference;
+pub mod registers;
+pub mod rewrite;
+pub mod sandbox;
+#[cfg(feature = "serve")]
+pub mod serve;
+#[cfg(feature = "test-util")]
+pub mod snapshot;
+pub mod summary;
+pub mod symbols;
+pub mod toml;
+pub mod verbosity;
+pub mod verify;
+pub mod xref;
+pub mod zip;
+
+/// The basic configuration of the binary, storing the results from a successful
+/// command-line invocation.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Config {
+    /// The path to the target Hack `.vm` file, a directory or zip archive
+    /// containing several, or the project directory for the `build`
+    /// subcommand.
+    file_path: PathBuf,
+    /// Further paths translated along with [`Config::file_path`] as a single
+    /// program.
+    other_paths: Vec<PathBuf>,
+    /// The subcommand that was used, if any.
+    subcommand: Subcommand,
+    /// For the `diff-asm` subcommand, the assembly file compared with
+    /// [`Config::file_path`].
+    compared_path: Option<PathBuf>,
+    /// The directory to cache generated assembly in, if caching is enabled.
+    cache_dir: Option<PathBuf>,
+    /// The directory holding the Jack OS to link the program with, if any.
+    os: Option<PathBuf>,
+    /// The directories searched for functions the program calls but does
+    /// not define, in order.
+    libraries: Vec<PathBuf>,
+    /// For the `compare` subcommand, the reference translator, if one was
+    /// given.
+    reference: Option<PathBuf>,
+    /// For the `compare` subcommand, the CPU emulator, if one was given.
+    emulator: Option<PathBuf>,
+    /// For the `serve` and `daemon` subcommands, the address to listen on,
+    /// if one was given.
+    listen: Option<String>,
+    /// The reports to print once the run is over.
+    reports: BTreeSet<Report>,
+    /// The file to write a machine-readable summary of the run to, if any.
+    summary_json: Option<PathBuf>,
+    /// The stems of files in a directory to translate before all others, in
+    /// order.
+    first: Vec<String>,
+    /// Whether to write the assembly for files in a zip archive next to it.
+    write_asm: bool,
+    /// How files in a directory that are symbolic links are handled.
+    symlinks: SymlinkPolicy,
+    /// Which other translator's conventions are matched, if any.
+    compat: Compat,
+    /// Whether default options are read from a `translator.toml`.
+    discovery: Discovery,
+    /// The prefixes to name static variables with, by the stem of the file
+    /// they are used in, overriding the stem itself.
+    static_prefixes: BTreeMap<String, Symbol>,
+    /// The prefix to name static variables with when reading from standard
+    /// input, where there is no file to name them after.
+    source_name: Option<Symbol>,
+    /// The namespaces to rename, which take precedence over any renamed by
+    /// the project's manifest.
+    namespaces: Namespaces,
+    /// How the labels generated for comparisons are named, which takes
+    /// precedence over the project's manifest.
+    labels: LabelScheme,
+    /// Whether and how the extended arithmetic commands are translated.
+    extensions: Extensions,
+    /// How strictly Hack VM code is parsed.
+    mode: Mode,
+    /// Whether Hack assembly written inline is passed through.
+    inline_asm: bool,
+    /// The rules rewriting instructions before they are translated, which
+    /// take precedence over the project's manifest.
+    rewrites: Rewrites,
+    /// The variant of the Hack CPU to generate assembly for, which takes
+    /// precedence over the project's manifest.
+    target: Option<Target>,
+    /// Whether each VM command is written as a comment above its assembly.
+    annotate: bool,
+    /// Whether each file is checked to run the same when translated as when
+    /// interpreted.
+    verification: Verification,
+    /// Which passes run over each file, in order.
+    passes: Pipeline,
+    /// The passes after which what they produced is printed.
+    print_after: BTreeSet<Pass>,
+    /// The limits on the input accepted.
+    limits: Limits,
+    /// The directory everything written must be inside of, if any.
+    sandbox: Option<Sandbox>,
+    /// Whether the progress of the run is shown.
+    progress: ProgressPolicy,
+    /// When diagnostics are styled.
+    color: ColorChoice,
+    /// How diagnostics are formatted.
+    diagnostics: DiagnosticsFormat,
+    /// How many stages of translation are printed.
+    verbosity: Verbosity,
+}
+
+impl Config {
+    /// Attempts to build a valid [`Config`] from the arguments passed on the
+    /// command line.
+    ///
+    /// A valid [`Config`] consists of a single positional argument - the path
+    /// to a Hack VM file or a directory or zip archive containing several -
+    /// along with any number of options.
+    ///
+    /// Example:
+    /// ```bash
+    /// hack-vm-translator ./foo.vm
+    /// ```
+    ///
+    /// If the path is `-`, Hack VM code is read from standard input, and the
+    /// assembly written to standard output.
+    ///
+    /// Several paths may be given, which are then translated as a single
+    /// program: functions may call those defined under any of the paths, each
+    /// file gets its own static variables, and the assembly is written to a
+    /// single file named after the first path; see [`find_program_files`].
+    ///
+    /// ```bash
+    /// hack-vm-translator ./Game ./lib/Util.vm
+    /// ```
+    ///
+    /// Alternatively, the first argument may be `build`, optionally followed
+    /// by the path to a project directory (by default, the current one). The
+    /// project is then translated according to its `hackvm.toml` manifest; see
+    /// [`crate::manifest`].
+    ///
+    /// ```bash
+    /// hack-vm-translator build ./project
+    /// ```
+    ///
+    /// The first argument may also be `xref`, followed by a path as above (by
+    /// default, the current directory). Rather than translating the files,
+    /// where everything in them is defined and referenced is then printed;
+    /// see [`crate::xref`].
+    ///
+    /// ```bash
+    /// hack-vm-translator xref ./project
+    /// ```
+    ///
+    /// The first argument may instead be `diff-asm`, followed by the paths to
+    /// two assembly files, which are then compared while ignoring comments,
+    /// whitespace, and how labels are named; see [`crate::asmdiff`]. The run
+    /// fails if they diverge.
+    ///
+    /// ```bash
+    /// hack-vm-translator diff-asm ./old/Main.asm ./new/Main.asm
+    /// ```
+    ///
+    /// The first argument may also be `compare`, followed by a path as for
+    /// `xref`, in which case the program is translated by both this
+    /// translator and the nand2tetris reference translator, and its test
+    /// scripts run against either; see [`crate::reference`]. The run fails if
+    /// it behaves differently.
+    ///
+    /// ```bash
+    /// hack-vm-translator compare ./StackArithmetic/StackTest
+    /// ```
+    ///
+    /// With the `serve` feature, the first argument may instead be `serve`,
+    /// without a path, which hosts a playground in the browser until the
+    /// translator is stopped; see [`crate::serve`].
+    ///
+    /// ```bash
+    /// hack-vm-translator serve --listen 127.0.0.1:8080
+    /// ```
+    ///
+    /// The first argument may also be `daemon`, without a path, which answers
+    /// JSON-RPC requests to translate files until standard input is closed,
+    /// or over connections to `--listen` until the translator is stopped;
+    /// see [`crate::daemon`].
+    ///
+    /// ```bash
+    /// hack-vm-translator daemon --listen 127.0.0.1:9257
+    /// ```
+    ///
+    /// Likewise, the first argument may be `doc`, in which case Markdown
+    /// documentation of the files is printed instead; see [`crate::docs`].
+    ///
+    /// ```bash
+    /// hack-vm-translator doc ./project > DOCS.md
+    /// ```
+    ///
+    /// The following options are recognized. Options taking a value accept it
+    /// either as the next argument or joined with an `=`. They may also be
+    /// given in `HACK_VM_*` environment variables or a `translator.toml`,
+    /// though the arguments take precedence; see [`crate::defaults`].
+    ///
+    /// - `--cache-dir <DIR>`: Reuse assembly generated by previous runs for
+    ///   files that have not changed, storing it in `DIR` along with the
+    ///   warnings about them. Not used while `--print-after` or `-v` is given.
+    ///
+    /// - `-L <DIR>`: Link the program with the files in `DIR` defining
+    ///   functions it calls but does not define, writing it to a single file;
+    ///   see [`crate::link`]. The value may also be joined, as in `-Llib`.
+    ///   May be given several times, to search each directory in order.
+    ///   Calls that no directory resolves are reported as errors, but as
+    ///   calls are not translated yet, a program that does resolve fails at
+    ///   its first `function`.
+    ///
+    /// - `--with-os <DIR>`: Link the program with the classes of the Jack OS
+    ///   in `DIR` that it calls but does not define, after searching any
+    ///   other library. As calls are not translated yet, this only finds the
+    ///   classes for `xref` and `doc`; translating fails at the program's
+    ///   first `function`.
+    ///
+    /// - `--reference <PATH>`: For `compare`, the reference translator to run,
+    ///   by default `VMTranslator`.
+    ///
+    /// - `--emulator <PATH>`: For `compare`, the CPU emulator to run the test
+    ///   scripts with, by default `CPUEmulator.sh`.
+    ///
+    /// - `--listen <ADDR>`: For `serve`, the address to listen on, by default
+    ///   `127.0.0.1:8080`. For `daemon`, the address to listen on rather than
+    ///   standard input.
+    ///
+    /// - `--time`: Report the time spent reading, parsing, translating, and
+    ///   writing each file.
+    ///
+    /// - `--summary-json <PATH>`: Write a summary of the run to `PATH` as a
+    ///   JSON object, even if the run fails; see [`crate::summary`].
+    ///
+    /// - `--dump-symbols`: List every symbol the generated assembly defines
+    ///   or refers to; see [`crate::symbols`].
+    ///
+    /// - `--first <NAME,...>`: When translating a directory, translate the
+    ///   files with these names (without the `.vm` extension) before the
+    ///   others, in the order given. The remaining files are always
+    ///   translated in order of their names.
+    ///
+    /// - `--write-asm`: When translating a zip archive, write the assembly for
+    ///   each file in it next to the archive. Otherwise, the files are only
+    ///   checked to translate successfully.
+    ///
+    /// - `--static-prefix <[NAME=]PREFIX>`: Name the static variables of the
+    ///   file named `NAME` (without the `.vm` extension) `PREFIX.i`, rather
+    ///   than after the file. `NAME` may be left out when translating a single
+    ///   file. May be given several times.
+    ///
+    /// - `--source-name <NAME>`: When reading from standard input, name static
+    ///   variables `NAME.i`, as though the code was read from `NAME.vm`. This
+    ///   is required if the code uses the `static` segment.
+    ///
+    /// - `--follow-symlinks`: When translating a directory, also translate
+    ///   files in it that are symbolic links, rather than skipping them.
+    ///
+    /// - `--namespace <OLD=NEW>`: Rename the namespace `OLD` to `NEW`,
+    ///   such that the functions `OLD.f` become `NEW.f` and the static
+    ///   variables of `OLD.vm` are named `NEW.i`; see [`crate::namespace`].
+    ///   May be given several times.
+    ///
+    /// - `--labels <STYLE>`: Name the labels generated for comparisons in the
+    ///   given style: `crash-burn` (the default), `true-end`, or a custom
+    ///   prefix; see [`hack_vm_codegen::labels`].
+    ///
+    /// - `--label-numbering <NUMBERING>`: Number the labels generated for
+    ///   comparisons after their `line` (the default), or `sequential`ly.
+    ///
+    /// - `--renumber-labels`: After translating each file, renumber generated
+    ///   labels in order of appearance, so that inserting an instruction does
+    ///   not change every label after it.
+    ///
+    /// - `--extensions[=inline]`: Accept the extended arithmetic commands
+    ///   `mul`, `div`, `mod`, `shiftleft`, and `shiftright`, translating them
+    ///   into inline loops; see [`hack_vm_syntax::extensions`]. Unlike other
+    ///   options, the value may only be joined with an `=`.
+    ///
+    /// - `--mode <MODE>`: Parse only the official VM language (`strict`, the
+    ///   default), or also accept conveniences such as negative constants
+    ///   (`permissive`); see [`hack_vm_syntax::mode`].
+    ///
+    /// - `--inline-asm`: Pass Hack assembly written inline, in `asm { ... }`
+    ///   blocks or after `//!asm`, through to the output; see
+    ///   [`hack_vm_syntax::assembly`].
+    ///
+    /// - `--no-config`: Do not read default options from the closest
+    ///   `translator.toml`; see [`crate::defaults`].
+    ///
+    /// - `--compat <TRANSLATOR>`: Match the conventions of another
+    ///   translator, such as `nand2tetris`; see [`crate::compat`].
+    ///
+    /// - `--annotate`: Write each VM command as a comment above its assembly,
+    ///   along with its stack effect and the stack depth after it; see
+    ///   [`crate::metrics`].
+    ///
+    /// - `--verify`: Check that each file runs the same when translated, then
+    ///   assembled and run on a CPU, as when interpreted, reporting any
+    ///   difference to stderr and failing if there are any; see
+    ///   [`crate::verify`]. Files the interpreter cannot run are skipped.
+    ///
+    /// - `--rewrites <FILE>`: Rewrite the instructions of each file with the
+    ///   rules in `FILE` before translating them; see [`crate::rewrite`].
+    ///
+    /// - `--passes <PASS,...>`: Disable passes named with a `-`, enable those
+    ///   named with a `+` again, or run only the passes named without either,
+    ///   in the order given; see [`crate::passes`].
+    ///
+    /// - `--print-after <PASS>`: Print the instructions or assembly of each
+    ///   file after the given pass, to stderr. May be given several times.
+    ///
+    /// - `--target <PROFILE>`: Generate assembly for the given variant of the
+    ///   Hack CPU: `hack` (the default), `hack-extended`, or `hack-32k`; see
+    ///   [`hack_vm_syntax::target`].
+    ///
+    /// - `-v`, `-vv`, `-vvv`, `--verbose`: Print the tokens of each line, then
+    ///   also the parsed instructions, then also the generated assembly, to
+    ///   stderr; see [`crate::verbosity`]. `--verbose` raises the level by
+    ///   one each time it is given.
+    ///
+    /// - `--color <WHEN>`: Style errors and warnings when stderr is a terminal
+    ///   (`auto`, the default), `always`, or `never`; see [`crate::color`].
+    ///
+    /// - `--diagnostics-format <FORMAT>`: Format errors and warnings to be
+    ///   read (`human`, the default), or as `FILE:LINE:COLUMN` lines for
+    ///   editors to match (`short`); see [`crate::diagnostics`].
+    ///
+    /// - `--quiet`: Do not show the progress of translating many files; see
+    ///   [`crate::progress`].
+    ///
+    /// - `--sandbox <DIR>`: Refuse to write anything outside of `DIR`, once
+    ///   paths are canonicalized; see [`crate::sandbox`].
+    ///
+    /// - `--max-file-size <BYTES>`, `--max-line-length <CHARS>`,
+    ///   `--max-instructions <N>`: Reject files exceeding these limits, for
+    ///   translating untrusted files; see [`hack_vm_syntax::limits`].
+    ///
+    /// - `--lossy-utf8`: Replace bytes that are not valid UTF-8 with
+    ///   `\u{FFFD}`, rather than rejecting the file; see
+    ///   [`hack_vm_syntax::limits`].
+    ///
+    /// # Errors
+    ///
+    /// If no positional arguments or more than one positional argument were
+    /// passed, the error received will be a [`HackError::Misconfiguration`]
+    /// holding the number of positional arguments that were passed.
+    ///
+    /// If an option is not recognized or is missing its value, the error
+    /// received will be a [`HackError::InvalidOption`].
+    pub fn build<A: Iterator<Item = String>>(
+        mut args: A,
+    ) -> Result<Self, HackError> {
+        let _self_path_unused: Option<String> = args.next();
+        let args: Vec<String> = args.collect();
+
+        let environment: Vec<String> = defaults::environment()?;
+        if !environment.is_empty() {
+            trace::event!(
+                Info,
+                "using options {environment:?} from the environment"
+            );
+        }
+        let given: usize = environment.len();
+        let args: Vec<String> = environment.into_iter().chain(args).collect();
+        let config: Self = Self::from_args(args.iter().cloned()).map_err(
+            |error: HackError| Self::blame_environment(error, &args, given),
+        )?;
+        if config.discovery == Discovery::Disabled {
+            return Ok(config);
+        }
+        let Some(defaults) = Defaults::discover(&config.file_path)? else {
+            return Ok(config);
+        };
+        trace::event!(
+            Info,
+            "using default options {:?} from {}",
+            defaults.args(),
+            paths::display(defaults.path())
+        );
+        Self::from_args(defaults.args().iter().cloned().chain(args)).map_err(
+            |error: HackError| {
+                HackError::InvalidOption(format!(
+                    "{error}, with the default options from \"{}\"",
+                    paths::display(defaults.path())
+                ))
+            },
+        )
+    }
+
+    /// Points out that `error`, from building a [`Config`] from `args`
+    /// starting with the `given` options from the environment, is down to
+    /// those options if the arguments alone are fine.
+    fn blame_environment(
+        error: HackError,
+        args: &[String],
+        given: usize,
+    ) -> HackError {
+        if given == 0
+            || Self::from_args(args.iter().skip(given).cloned()).is_err()
+        {
+            error
+        } else {
+            HackError::InvalidOption(format!(
+                "{error}, with the options from the environment"
+            ))
+        }
+    }
+
+    /// Starts building a [`Config`] from typed options, rather than from
+    /// command-line arguments; see [`crate::builder`].
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    /// Builds a [`Config`] from the arguments following the path of the
+    /// executable, as with [`Config::build`] but without reading any default
+    /// options.
+    fn from_args<A: Iterator<Item = String>>(
+        mut args: A,
+    ) -> Result<Self, HackError> {
+        let mut config: Self = Self::default();
+        let mut paths: Vec<PathBuf> = Vec::new();
+        let mut static_prefixes: Vec<(Option<String>, Symbol)> = Vec::new();
+
+        while let Some(arg) = args.next() {
+            if let Some(levels) = Verbosity::flag_levels(&arg) {
+                config.verbosity = config.verbosity.raised(levels);
+                continue;
+            }
+            if let Some(directory) = arg.strip_prefix("-L") {
+                let inline_value: Option<&str> =
+                    (!directory.is_empty()).then_some(directory);
+                config.libraries.push(PathBuf::from(Self::option_value(
+                    "-L",
+                    inline_value,
+                    &mut args,
+                )?));
+                continue;
+            }
+            if !arg.starts_with("--") {
+                paths.push(PathBuf::from(arg));
+                continue;
+            }
+            let (option, inline_value): (&str, Option<&str>) =
+                match arg.split_once('=') {
+                    Some((option, value)) => (option, Some(value)),
+                    None => (arg.as_str(), None),
+                };
+            config.set_option(
+                option,
+                inline_value,
+                &mut args,
+                &mut static_prefixes,
+            )?;
+        }
+
+        (
+            config.file_path,
+            config.other_paths,
+            config.subcommand,
+            config.compared_path,
+        ) = Self::positional(paths)?;
+        config.static_prefixes =
+            Self::resolve_static_prefixes(static_prefixes, &config.file_path)?;
+        if config.source_name.is_some() && config.file_path.as_os_str() != "-" {
+            return Err(HackError::InvalidOption(
+                "option \"--source-name\" only applies when reading from \
+                standard input, with \"-\" as the path"
+                    .to_owned(),
+            ));
+        }
+        if config.serves() && !cfg!(feature = "serve") {
+            return Err(HackError::InvalidOption(
+                "\"serve\" needs the translator to be built with the \"serve\" \
+                feature"
+                    .to_owned(),
+            ));
+        }
+        if config.verifies() && config.reads_stdin() {
+            return Err(HackError::InvalidOption(
+                "option \"--verify\" reads each file again, so it cannot be \
+                used when reading from standard input"
+                    .to_owned(),
+            ));
+        }
+        if config.compares() && config.sandbox.is_some() {
+            return Err(HackError::InvalidOption(
+                "\"compare\" runs other programs, so it cannot be used with \
+                option \"--sandbox\""
+                    .to_owned(),
+            ));
+        }
+        config.check_sandbox()?;
+        Ok(config)
+    }
+
+    /// Applies a single option to the [`Config`] being built, getting its
+    /// value from `args` if it takes one that was not given inline. Values of
+    /// `--static-prefix` are collected into `static_prefixes` to be resolved
+    /// once the path is known.
+    fn set_option<A: Iterator<Item = String>>(
+        &mut self,
+        option: &str,
+        inline_value: Option<&str>,
+        args: &mut A,
+        static_prefixes: &mut Vec<(Option<String>, Symbol)>,
+    ) -> Result<(), HackError> {
+        match option {
+            "--first" => {
+                self.first = Self::option_value(option, inline_value, args)?
+                    .split(',')
+                    .map(str::to_owned)
+                    .collect();
+            }
+            "--follow-symlinks" => {
+                Self::no_value(option, inline_value)?;
+                self.symlinks = SymlinkPolicy::Follow;
+            }
+            "--static-prefix" => {
+                static_prefixes.push(Self::static_prefix_value(
+                    option,
+                    &Self::option_value(option, inline_value, args)?,
+                )?);
+            }
+            "--namespace" => {
+                let (old, new): (String, Symbol) = Self::namespace_value(
+                    option,
+                    &Self::option_value(option, inline_value, args)?,
+                )?;
+                self.namespaces.insert(old, new);
+            }
+            "--source-name" => {
+                self.source_name =
+                    Some(Self::parsed_option(option, inline_value, args)?);
+            }
+            "--labels" => {
+                self.labels.set_style(Self::parsed_option(
+                    option,
+                    inline_value,
+                    args,
+                )?);
+            }
+            "--renumber-labels" => {
+                Self::no_value(option, inline_value)?;
+                self.labels.set_renumber(true);
+            }
+            "--label-numbering" => {
+                self.labels.set_numbering(Self::parsed_option(
+                    option,
+                    inline_value,
+                    args,
+                )?);
+            }
+            "--inline-asm" => {
+                Self::no_value(option, inline_value)?;
+                self.inline_asm = true;
+            }
+            "--rewrites" => {
+                self.rewrites = Rewrites::load(Path::new(
+                    &Self::option_value(option, inline_value, args)?,
+                ))?;
+            }
+            "--passes" => {
+                let list: String =
+                    Self::option_value(option, inline_value, args)?;
+                self.passes.apply(&list).map_err(|error: HackError| {
+                    HackError::InvalidOption(format!(
+                        "option \"{option}\" was given \"{list}\", but {error}"
+                    ))
+                })?;
+            }
+            "--target" => {
+                self.target =
+                    Some(Self::parsed_option(option, inline_value, args)?);
+            }
+            "--mode" => {
+                self.mode = Self::parsed_option(option, inline_value, args)?;
+            }
+            "--extensions" => {
+                self.extensions = inline_value
+                    .map_or(Ok(Extensions::Inline), |value: &str| {
+                        Self::parsed_value(option, value)
+                    })?;
+            }
+            other => return self.set_output_option(other, inline_value, args),
+        }
+        Ok(())
+    }
+
+    /// Applies a single option affecting what is written, and where, to the
+    /// [`Config`] being built, as with [`Config::set_option`].
+    fn set_output_option<A: Iterator<Item = String>>(
+        &mut self,
+        option: &str,
+        inline_value: Option<&str>,
+        args: &mut A,
+    ) -> Result<(), HackError> {
+        match option {
+            "--summary-json" => {
+                self.summary_json = Some(PathBuf::from(Self::option_value(
+                    option,
+                    inline_value,
+                    args,
+                )?));
+            }
+            "--cache-dir" => {
+                self.cache_dir = Some(PathBuf::from(Self::option_value(
+                    option,
+                    inline_value,
+                    args,
+                )?));
+            }
+            "--with-os" => {
+                self.os = Some(PathBuf::from(Self::option_value(
+                    option,
+                    inline_value,
+                    args,
+                )?));
+            }
+            "--time" => {
+                Self::no_value(option, inline_value)?;
+                let _new: bool = self.reports.insert(Report::Time);
+            }
+            "--dump-symbols" => {
+                Self::no_value(option, inline_value)?;
+                let _new: bool = self.reports.insert(Report::Symbols);
+            }
+            "--write-asm" => {
+                Self::no_value(option, inline_value)?;
+                self.write_asm = true;
+            }
+            "--annotate" => {
+                Self::no_value(option, inline_value)?;
+                self.annotate = true;
+            }
+            "--verify" => {
+                Self::no_value(option, inline_value)?;
+                self.verification = Verification::Checked;
+            }
+            "--print-after" => {
+                let _new: bool = self.print_after.insert(Self::parsed_option(
+                    option,
+                    inline_value,
+                    args,
+                )?);
+            }
+            "--no-config" => {
+                Self::no_value(option, inline_value)?;
+                self.discovery = Discovery::Disabled;
+            }
+            "--compat" => {
+                self.compat = Self::parsed_option(option, inline_value, args)?;
+            }
+            "--verbose" => {
+                Self::no_value(option, inline_value)?;
+                self.verbosity = self.verbosity.raised(1);
+            }
+            "--color" => {
+                self.color = Self::parsed_option(option, inline_value, args)?;
+            }
+            "--diagnostics-format" => {
+                self.diagnostics =
+                    Self::parsed_option(option, inline_value, args)?;
+            }
+            "--quiet" => {
+                Self::no_value(option, inline_value)?;
+                self.progress = ProgressPolicy::Quiet;
+            }
+            "--listen" => {
+                self.listen =
+                    Some(Self::option_value(option, inline_value, args)?);
+            }
+            "--sandbox" => {
+                self.sandbox = Some(Sandbox::new(Path::new(
+                    &Self::option_value(option, inline_value, args)?,
+                ))?);
+            }
+            "--max-file-size" => self.limits.set_file_size(Self::limit_option(
+                option,
+                inline_value,
+                args,
+            )?),
+            "--max-line-length" => self.limits.set_line_length(
+                Self::limit_option(option, inline_value, args)?,
+            ),
+            "--max-instructions" => self.limits.set_instructions(
+                Self::limit_option(option, inline_value, args)?,
+            ),
+            "--lossy-utf8" => {
+                Self::no_value(option, inline_value)?;
+                self.limits.set_lossy_utf8(true);
+            }
+            other => return self.set_tool_option(other, inline_value, args),
+        }
+        Ok(())
+    }
+
+    /// Applies a single option naming another program the translator runs
+    /// to the [`Config`] being built, as with [`Config::set_option`].
+    fn set_tool_option<A: Iterator<Item = String>>(
+        &mut self,
+        option: &str,
+        inline_value: Option<&str>,
+        args: &mut A,
+    ) -> Result<(), HackError> {
+        let tool: &mut Option<PathBuf> = match option {
+            "--reference" => &mut self.reference,
+            "--emulator" => &mut self.emulator,
+            unrecognized => {
+                return Err(HackError::InvalidOption(format!(
+                    "unrecognized option \"{unrecognized}\""
+                )));
+            }
+        };
+        *tool = Some(PathBuf::from(Self::option_value(
+            option,
+            inline_value,
+            args,
+        )?));
+        Ok(())
+    }
+
+    /// Determines the path and subcommand from the positional arguments,
+    /// along with any further paths translated as part of the same program,
+    /// and the second path for the `diff-asm` subcommand.
+    fn positional(
+        mut paths: Vec<PathBuf>,
+    ) -> Result<(PathBuf, Vec<PathBuf>, Subcommand, Option<PathBuf>), HackError>
+    {
+        let subcommand: Subcommand = match paths.first() {
+            Some(first) if first.as_os_str() == "build" => Subcommand::Build,
+            Some(first) if first.as_os_str() == "xref" => Subcommand::Xref,
+            Some(first) if first.as_os_str() == "doc" => Subcommand::Doc,
+            Some(first) if first.as_os_str() == "compare" => {
+                Subcommand::Compare
+            }
+            Some(first) if first.as_os_str() == "serve" => Subcommand::Serve,
+            Some(first) if first.as_os_str() == "daemon" => Subcommand::Daemon,
+            Some(first) if first.as_os_str() == "diff-asm" => {
+                Subcommand::DiffAsm
+            }
+            Some(_) | None => Subcommand::Translate,
+        };
+        if subcommand == Subcommand::DiffAsm {
+            let _subcommand: PathBuf = paths.remove(0);
+            let count: usize = paths.len();
+            let Ok([left, right]): Result<[PathBuf; 2], _> = paths.try_into()
+            else {
+                return Err(HackError::InvalidOption(format!(
+                    "\"diff-asm\" expects 2 assembly files, found {count}"
+                )));
+            };
+            return Ok((left, Vec::new(), subcommand, Some(right)));
+        }
+        if subcommand == Subcommand::Serve && paths.len() > 1 {
+            return Err(HackError::InvalidOption(
+                "\"serve\" does not take a path".to_owned(),
+            ));
+        }
+        if subcommand == Subcommand::Daemon && paths.len() > 1 {
+            return Err(HackError::InvalidOption(
+                "\"daemon\" does not take a path".to_owned(),
+            ));
+        }
+        if subcommand != Subcommand::Translate {
+            let _subcommand: PathBuf = paths.remove(0);
+            if paths.is_empty() {
+                paths.push(PathBuf::from("."));
+            }
+        }
+
+        if subcommand == Subcommand::Translate && paths.len() > 1 {
+            if paths.iter().any(|path: &PathBuf| path.as_os_str() == "-") {
+                return Err(HackError::InvalidOption(
+                    "standard input cannot be translated along with other \
+                    paths"
+                        .to_owned(),
+                ));
+            }
+            let file_path: PathBuf = paths.remove(0);
+            return Ok((file_path, paths, subcommand, None));
+        }
+        let count: usize = paths.len();
+        let Ok([file_path]): Result<[PathBuf; 1], _> = paths.try_into() else {
+            return Err(HackError::Misconfiguration(count));
+        };
+        Ok((file_path, Vec::new(), subcommand, None))
+    }
+
+    /// Gets the value of an option, as with [`Config::option_value`], and
+    /// parses it, as with [`Config::parsed_value`].
+    fn parsed_option<
+        T: FromStr<Err = HackError>,
+        A: Iterator<Item = String>,
+    >(
+        option: &str,
+        inline_value: Option<&str>,
+        args: &mut A,
+    ) -> Result<T, HackError> {
+        Self::parsed_value(
+            option,
+            &Self::option_value(option, inline_value, args)?,
+        )
+    }
+
+    /// Gets the value of an option, which was either joined to it with an `=`
+    /// or is the next argument.
+    fn option_value<A: Iterator<Item = String>>(
+        option: &str,
+        inline_value: Option<&str>,
+        args: &mut A,
+    ) -> Result<String, HackError> {
+        inline_value
+            .map(str::to_owned)
+            .or_else(|| args.next())
+            .ok_or_else(|| {
+                HackError::InvalidOption(format!(
+                    "option \"{option}\" requires a value"
+                ))
+            })
+    }
+
+    /// Parses the value of `--static-prefix`, which is a prefix, optionally
+    /// preceded by the stem of the file it applies to and an `=`.
+    fn static_prefix_value(
+        option: &str,
+        value: &str,
+    ) -> Result<(Option<String>, Symbol), HackError> {
+        let (stem, prefix): (Option<&str>, &str) = match value.split_once('=') {
+            Some((stem, prefix)) => (Some(stem), prefix),
+            None => (None, value),
+        };
+        Ok((stem.map(str::to_owned), Self::parsed_value(option, prefix)?))
+    }
+
+    /// Parses the value of `--namespace`, which is an `OLD=NEW` mapping.
+    fn namespace_value(
+        option: &str,
+        value: &str,
+    ) -> Result<(String, Symbol), HackError> {
+        Namespaces::parse_mapping(value).map_err(|message: String| {
+            HackError::InvalidOption(format!(
+                "option \"{option}\" was given \"{value}\", which {message}"
+            ))
+        })
+    }
+
+    /// Parses the value of an option, such as a [`Symbol`].
+    fn parsed_value<T: FromStr<Err = HackError>>(
+        option: &str,
+        value: &str,
+    ) -> Result<T, HackError> {
+        T::from_str(value).map_err(|error| {
+            HackError::InvalidOption(format!(
+                "option \"{option}\" was given \"{value}\", but {error}"
+            ))
+        })
+    }
+
+    /// Checks that the files given as options, which are written before or
+    /// after the run, are inside of [`Config::sandbox`], if there is one.
+    pub(crate) fn check_sandbox(&self) -> Result<(), HackError> {
+        let Some(ref sandbox) = self.sandbox else {
+            return Ok(());
+        };
+        self.cache_dir
+            .iter()
+            .chain(&self.summary_json)
+            .try_for_each(|path: &PathBuf| sandbox.check(path))
+    }
+
+    /// Gets the value of an option setting a limit, which is a number.
+    fn limit_option<A: Iterator<Item = String>>(
+        option: &str,
+        inline_value: Option<&str>,
+        args: &mut A,
+    ) -> Result<usize, HackError> {
+        let value: String = Self::option_value(option, inline_value, args)?;
+        value.parse().map_err(|error| {
+            HackError::InvalidOption(format!(
+                "option \"{option}\" was given \"{value}\", but {error}"
+            ))
+        })
+    }
+
+    /// Determines which file each `--static-prefix` applies to. Those that
+    /// don't name a file apply to `file_path`, which must then be a single
+    /// Hack VM file.
+    fn resolve_static_prefixes(
+        static_prefixes: Vec<(Option<String>, Symbol)>,
+        file_path: &Path,
+    ) -> Result<BTreeMap<String, Symbol>, HackError> {
+        let lone_file: Option<String> = file_path
+            .extension()
+            .is_some_and(|ext| ext == "vm")
+            .then(|| file_path.file_stem())
+            .flatten()
+            .map(|stem| stem.to_string_lossy().into_owned());
+        static_prefixes
+            .into_iter()
+            .map(|(stem, prefix): (Option<String>, Symbol)| {
+                stem.or_else(|| lone_file.clone())
+                    .map(|stem: String| (stem, prefix))
+                    .ok_or_else(|| {
+                        HackError::InvalidOption(
+                            "option \"--static-prefix\" must name the file it \
+                            applies to, as in \"NAME=PREFIX\", unless a single \
+                            file is translated"
+                                .to_owned(),
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Ensures a flag, which takes no value, was not given one with an `=`.
+    fn no_value(
+        option: &str,
+        inline_value: Option<&str>,
+    ) -> Result<(), HackError> {
+        match inline_value {
+            Some(_) => Err(HackError::InvalidOption(format!(
+                "option \"{option}\" does not take a value"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Gets a shared reference to [`Config::file_path`].
+    ///
+    /// This is the path to the target Hack `.asm` file, as a borrowed
+    /// [`PathBuf`].
+    pub(crate) const fn file_path(&self) -> &PathBuf {
+        &self.file_path
+    }
+
+    /// Whether the `build` subcommand was used, such that
+    /// [`Config::file_path`] is a project directory with a manifest.
+    pub(crate) fn use_manifest(&self) -> bool {
+        self.subcommand == Subcommand::Build
+    }
+
+    /// Whether the `xref` subcommand was used, such that a cross-reference
+    /// report is printed rather than translating.
+    pub(crate) fn cross_references(&self) -> bool {
+        self.subcommand == Subcommand::Xref
+    }
+
+    /// Gets the assembly file compared with [`Config::file_path`], if the
+    /// `diff-asm` subcommand was used.
+    pub(crate) fn compared_path(&self) -> Option<&Path> {
+        self.compared_path.as_deref()
+    }
+
+    /// Whether the `doc` subcommand was used, such that documentation is
+    /// printed rather than translating.
+    pub(crate) fn documents(&self) -> bool {
+        self.subcommand == Subcommand::Doc
+    }
+
+    /// Whether the `compare` subcommand was used, such that the program is
+    /// compared with its translation by the reference translator.
+    pub(crate) fn compares(&self) -> bool {
+        self.subcommand == Subcommand::Compare
+    }
+
+    /// Whether the `serve` subcommand was used, such that a playground is
+    /// hosted rather than translating.
+    pub(crate) fn serves(&self) -> bool {
+        self.subcommand == Subcommand::Serve
+    }
+
+    /// Whether the `daemon` subcommand was used, such that requests are
+    /// answered rather than translating.
+    pub(crate) fn daemonizes(&self) -> bool {
+        self.subcommand == Subcommand::Daemon
+    }
+
+    /// Gets the address the `serve` or `daemon` subcommands listen on, if
+    /// one was given.
+    pub(crate) fn listen(&self) -> Option<&str> {
+        self.listen.as_deref()
+    }
+
+    /// Gets the reference translator run by the `compare` subcommand.
+    pub(crate) fn reference(&self) -> &Path {
+        self.reference
+            .as_deref()
+            .unwrap_or_else(|| Path::new(reference::DEFAULT_TRANSLATOR))
+    }
+
+    /// Gets the CPU emulator run by the `compare` subcommand.
+    pub(crate) fn emulator(&self) -> &Path {
+        self.emulator
+            .as_deref()
+            .unwrap_or_else(|| Path::new(reference::DEFAULT_EMULATOR))
+    }
+
+    /// Gets a [`Config`] translating `path` as the nand2tetris reference
+    /// translator would, with the rest of this one's options, for the
+    /// `compare` subcommand.
+    fn translating(&self, path: &Path) -> Self {
+        Self {
+            file_path: path.to_path_buf(),
+            other_paths: Vec::new(),
+            subcommand: Subcommand::Translate,
+            compat: Compat::Nand2tetris,
+            ..self.clone()
+        }
+    }
+
+    /// Gets the libraries given with `-L`, in the order they are searched.
+    pub(crate) fn libraries(&self) -> Vec<Library> {
+        self.libraries.iter().cloned().map(Library::new).collect()
+    }
+
+    /// Gets a shared reference to [`Config::os`].
+    pub(crate) const fn os(&self) -> Option<&PathBuf> {
+        self.os.as_ref()
+    }
+
+    /// Gets a shared reference to [`Config::cache_dir`].
+    ///
+    /// This is the directory generated assembly is cached in, if caching was
+    /// requested.
+    pub(crate) const fn cache_dir(&self) -> Option<&PathBuf> {
+        self.cache_dir.as_ref()
+    }
+
+    /// Whether a report of the time spent in each phase of the run was
+    /// requested.
+    pub fn time(&self) -> bool {
+        self.reports.contains(&Report::Time)
+    }
+
+    /// Whether a list of the symbols of the generated assembly was
+    /// requested.
+    pub fn dump_symbols(&self) -> bool {
+        self.reports.contains(&Report::Symbols)
+    }
+
+    /// Gets a shared reference to [`Config::summary_json`].
+    ///
+    /// This is the file a machine-readable summary of the run should be
+    /// written to, if one was requested.
+    pub const fn summary_json(&self) -> Option<&PathBuf> {
+        self.summary_json.as_ref()
+    }
+
+    /// Gets a shared reference to [`Config::first`].
+    ///
+    /// These are the stems of the files in a directory to translate before all
+    /// others, in order.
+    pub(crate) fn first(&self) -> &[String] {
+        &self.first
+    }
+
+    /// Gets [`Config::write_asm`].
+    ///
+    /// This is whether the assembly for files in a zip archive should be
+    /// written next to it, rather than only checked.
+    pub(crate) const fn write_asm(&self) -> bool {
+        self.write_asm
+    }
+
+    /// Gets [`Config::symlinks`].
+    ///
+    /// This is how files in a directory that are symbolic links are handled.
+    pub(crate) const fn symlinks(&self) -> SymlinkPolicy {
+        self.symlinks
+    }
+
+    /// Whether Hack VM code is read from standard input, rather than from
+    /// [`Config::file_path`].
+    pub(crate) fn reads_stdin(&self) -> bool {
+        !self.use_manifest() && self.file_path.as_os_str() == "-"
+    }
+
+    /// Gets a shared reference to [`Config::source_name`].
+    ///
+    /// This is the prefix to name static variables with when reading from
+    /// standard input, if one was given.
+    pub(crate) const fn source_name(&self) -> Option<&Symbol> {
+        self.source_name.as_ref()
+    }
+
+    /// Gets a shared reference to [`Config::namespaces`].
+    pub(crate) const fn namespaces(&self) -> &Namespaces {
+        &self.namespaces
+    }
+
+    /// Gets a shared reference to [`Config::labels`].
+    pub(crate) const fn labels(&self) -> &LabelScheme {
+        &self.labels
+    }
+
+    /// Gets [`Config::extensions`].
+    pub(crate) const fn extensions(&self) -> Extensions {
+        self.extensions
+    }
+
+    /// Gets [`Config::mode`].
+    pub(crate) const fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Gets [`Config::inline_asm`].
+    pub(crate) const fn inline_asm(&self) -> bool {
+        self.inline_asm
+    }
+
+    /// Gets a shared reference to [`Config::rewrites`].
+    pub(crate) const fn rewrites(&self) -> &Rewrites {
+        &self.rewrites
+    }
+
+    /// Gets a shared reference to [`Config::passes`].
+    pub(crate) const fn passes(&self) -> &Pipeline {
+        &self.passes
+    }
+
+    /// Gets a shared reference to [`Config::print_after`].
+    pub(crate) const fn print_after(&self) -> &BTreeSet<Pass> {
+        &self.print_after
+    }
+
+    /// Gets [`Config::compat`].
+    pub(crate) const fn compat(&self) -> Compat {
+        self.compat
+    }
+
+    /// Gets [`Config::annotate`].
+    pub(crate) const fn annotate(&self) -> bool {
+        self.annotate
+    }
+
+    /// Whether each file is checked once it is translated, according to
+    /// [`Config::verification`].
+    pub(crate) fn verifies(&self) -> bool {
+        self.verification == Verification::Checked
+    }
+
+    /// Gets the styles diagnostics are printed with, according to
+    /// [`Config::color`]; see [`crate::color`].
+    pub fn palette(&self) -> Palette {
+        Palette::new(self.color)
+    }
+
+    /// Gets how diagnostics are formatted; see [`crate::diagnostics`].
+    pub const fn diagnostics(&self) -> DiagnosticsFormat {
+        self.diagnostics
+    }
+
+    /// Gets [`Config::verbosity`].
+    pub(crate) const fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    /// Gets [`Config::progress`].
+    pub(crate) const fn progress(&self) -> ProgressPolicy {
+        self.progress
+    }
+
+    /// Gets a shared reference to [`Config::sandbox`].
+    pub(crate) const fn sandbox(&self) -> Option<&Sandbox> {
+        self.sandbox.as_ref()
+    }
+
+    /// Gets [`Config::limits`].
+    pub(crate) const fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Gets [`Config::target`].
+    pub(crate) const fn target(&self) -> Option<Target> {
+        self.target
+    }
+
+    /// Gets the prefix from [`Config::static_prefixes`] to name the static
+    /// variables of the file with the given stem, if one was given.
+    pub(crate) fn static_prefix(&self, stem: &str) -> Option<&Symbol> {
+        self.static_prefixes.get(stem)
+    }
+}
+
+/// The subcommands that may be given as the first argument.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub(crate) enum Subcommand {
+    /// No subcommand: translate the files at the path.
+    #[default]
+    Translate,
+    /// `build`: translate the project at the path according to its manifest.
+    Build,
+    /// `xref`: print where everything in the files at the path is defined
+    /// and referenced, without translating them; see [`crate::xref`].
+    Xref,
+    /// `doc`: print documentation of the files at the path, without
+    /// translating them; see [`crate::docs`].
+    Doc,
+    /// `diff-asm`: compare the assembly files at two paths, ignoring
+    /// differences that cannot change what they do; see [`crate::asmdiff`].
+    DiffAsm,
+    /// `compare`: compare what the program at the path does when translated
+    /// by this translator and the reference one; see [`crate::reference`].
+    Compare,
+    /// `serve`: host a playground in the browser, with the `serve` feature.
+    Serve,
+    /// `daemon`: answer requests to translate files; see [`crate::daemon`].
+    Daemon,
+}
+
+/// How symbolic links found when translating a directory are handled.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub(crate) enum SymlinkPolicy {
+    /// Symbolic links are skipped, as though they were not there.
+    #[default]
+    Skip,
+    /// Symbolic links are followed, and the files they lead to translated.
+    Follow,
+}
+
+/// A Hack VM file to translate.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+enum Source {
+    /// A file on disk.
+    File(PathBuf),
+    /// A file read from a zip archive.
+    Archived {
+        /// Where the file would be if the archive were extracted next to
+        /// itself, which determines where its assembly is written.
+        path: PathBuf,
+        /// The contents of the file.
+        contents: String,
+    },
+    /// Standard input.
+    Stdin,
+}
+
+impl Source {
+    /// Gets the path of the file, or where it would be if it was archived.
+    fn path(&self) -> &Path {
+        match *self {
+            Self::File(ref path) | Self::Archived { ref path, .. } => path,
+            Self::Stdin => Path::new("<stdin>"),
+        }
+    }
+
+    /// Reads the file into a [`Parser`], within `limits`.
+    fn read(&self, limits: Limits) -> Result<Parser, HackError> {
+        match *self {
+            Self::File(ref path) => {
+                if path.extension().is_none_or(|ext| ext != "vm") {
+                    return Err(HackError::BadFileTypeError);
+                }
+                Parser::read(path, limits)
+            }
+            Self::Stdin => {
+                Parser::limited(limits.read_stdin()?, "<stdin>", limits)
+            }
+            Self::Archived {
+                ref path,
+                ref contents,
+            } => Parser::limited(
+                contents.clone(),
+                &paths::display(path).to_string(),
+                limits,
+            ),
+        }
+    }
+}
+
+/// How each file is translated, from the [`Config`] merged with the
+/// project's [`Manifest`], if any.
+#[derive(Debug, Clone, Default, Hash)]
+pub(crate) struct Settings {
+    /// The namespaces to rename.
+    namespaces: Namespaces,
+    /// How strictly Hack VM code is parsed.
+    mode: Mode,
+    /// How instructions are translated into assembly.
+    translation: Options,
+    /// Whether each VM command is written as a comment above its assembly.
+    annotate: bool,
+    /// The rules rewriting instructions before they are translated.
+    rewrites: Rewrites,
+    /// Which passes run over each file, in order.
+    passes: Pipeline,
+    /// The passes after which what they produced is printed, which does not
+    /// affect the assembly.
+    print_after: BTreeSet<Pass>,
+    /// The limits on the input accepted, which do not affect the assembly
+    /// and so are not part of how the settings are described.
+    limits: Limits,
+    /// The directory everything written must be inside of, if any, which
+    /// does not affect the assembly either.
+    sandbox: Option<Sandbox>,
+    /// How many stages of translation are printed, which does not affect
+    /// the assembly either.
+    verbosity: Verbosity,
+    /// How warnings are printed to stderr as soon as each file is linted, if
+    /// they are, which does not affect the assembly either.
+    warnings: Option<(DiagnosticsFormat, Palette)>,
+}
+
+impl Settings {
+    /// Gathers the settings given on the command line.
+    fn new(config: &Config) -> Self {
+        Self {
+            namespaces: config.namespaces().clone(),
+            mode: config.mode(),
+            translation: Options::new(
+                config.labels().clone(),
+                config.extensions(),
+                config.inline_asm(),
+                Segments::default(),
+                config.target(),
+            ),
+            annotate: config.annotate(),
+            rewrites: config.rewrites().clone(),
+            passes: config.passes().clone(),
+            print_after: config.print_after().clone(),
+            limits: config.limits(),
+            sandbox: config.sandbox().cloned(),
+            verbosity: config.verbosity(),
+            warnings: None,
+        }
+    }
+
+    /// Whether a cache may be used to skip translating files. Only translating
+    /// them prints what `--print-after` and `-v` ask for, so it may not be
+    /// used with either.
+    fn caches(&self) -> bool {
+        self.print_after.is_empty()
+            && matches!(self.verbosity, Verbosity::Quiet)
+    }
+
+    /// Prints `warnings` to stderr, if the settings print them as each file
+    /// is linted.
+    pub(crate) fn print_warnings(&self, warnings: &[Warning]) {
+        let Some((format, palette)) = self.warnings else {
+            return;
+        };
+        if !warnings.is_empty() {
+            Progress::clear();
+        }
+        let mut stderr: io::StderrLock<'_> = io::stderr().lock();
+        for warning in warnings {
+            let _printed: io::Result<()> =
+                writeln!(stderr, "{}", format.warning(warning, palette));
+        }
+    }
+}
+
+impl Display for Settings {
+    /// Describes the settings, separated by semicolons. Rewrite rules are
+    /// only described if there are any, and passes only if they differ from
+    /// the usual ones.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{};{};{};{};{};{};{};{}",
+            self.namespaces,
+            self.translation.labels(),
+            self.translation.extensions(),
+            self.mode,
+            self.translation.inline_asm(),
+            self.translation.segments(),
+            self.translation.target(),
+            self.annotate
+        )?;
+        if !self.rewrites.is_empty() {
+            write!(f, ";{}", self.rewrites)?;
+        }
+        if !self.passes.is_default() {
+            write!(f, ";passes={}", self.passes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where generated assembly is written.
+#[derive(Debug)]
+enum Output {
+    /// Each `foo.vm` file is translated into its own `foo.asm` file.
+    PerFile,
+    /// All files are translated into a single file.
+    Combined {
+        /// The file being written to.
+        file: File,
+        /// The numbers used by the labels generated in the files written so
+        /// far, which offsets those of later files so they stay unique.
+        label_offset: usize,
+    },
+    /// Nothing is written; files are only checked to translate successfully.
+    Discard,
+    /// Everything is written to standard output.
+    Stdout,
+    /// Everything is collected in memory, for `serve` and `daemon`.
+    Buffer(String),
+}
+
+/// Attempts to translate a single given file.
+///
+/// Given a borrowed [`Source`], attempts to read the file it corresponds to
+/// and translates each line to Hack assembly instructions. These are either
+/// written to a new file with the same name/location but using the `*.asm`
+/// extension, appended to a combined output file, or discarded, depending on
+/// `output`.
+///
+/// If a [`Cache`] is given and already holds the assembly for the file's
+/// current contents, that assembly is written instead of translating again,
+/// and the warnings stored with it are printed. It is not used if `settings`
+/// print anything else as the file is translated, which only translating
+/// again can print.
+///
+/// Static variables are named with `static_prefix`; see
+/// [`assign_static_prefixes`]. It may only be missing if the file does not
+/// use the `static` segment. It is parsed and translated according to
+/// `settings`, and linted as the `entry` file of the program if it is one;
+/// see [`crate::lints`]. The symbols of its assembly are
+/// recorded in `symbols`.
+///
+/// # Errors
+///
+/// The majority of errors can that occur will be propagated here - some may be
+/// internal. See [`hack_vm_syntax::error`] for more information of the errors.
+fn run_for_file(
+    source: &Source,
+    static_prefix: Option<&Symbol>,
+    settings: &Settings,
+    cache: Option<&Cache>,
+    output: &mut Output,
+    symbols: &mut SymbolTable,
+    entry: bool,
+) -> Result<FileSummary, HackError> {
+    let file: &Path = source.path();
+    let _span: Span = trace::span!(Debug, "file {}", paths::display(file));
+    let mut timings: Timings = Timings::default();
+    let parser: Parser =
+        timings.measure(Phase::Reading, || source.read(settings.limits))?;
+    let file_name: &str = match static_prefix {
+        Some(static_prefix) => static_prefix.literal_representation(),
+        None if parser
+            .lines()
+            .any(|parts: Vec<&str>| parts.get(1) == Some(&"static")) =>
+        {
+            return Err(HackError::InvalidOption(
+                "code read from standard input uses the \"static\" segment, \
+                whose variables are named after the file they are in; name \
+                them with \"--source-name NAME\""
+                    .to_owned(),
+            ));
+        }
+        // Without static variables, nothing is named after the file.
+        None => "",
+    };
+    let label_offset: usize = match *output {
+        Output::PerFile
+        | Output::Discard
+        | Output::Stdout
+        | Output::Buffer(_) => 0,
+        Output::Combined { label_offset, .. } => label_offset,
+    };
+
+    // The files it includes determine its translation as much as its own
+    // contents do.
+    let contents: String =
+        timings.measure(Phase::Reading, || parser.contents_with_includes())?;
+    let stem: Option<String> = static_prefix
+        .and_then(|_| file.file_stem())
+        .map(|stem| stem.to_string_lossy().into_owned());
+    let displayed: String = paths::display(file).to_string();
+    let key: u64 = Cache::key(
+        &contents,
+        file_name,
+        stem.as_deref(),
+        entry,
+        label_offset,
+        settings,
+    );
+    let cache: Option<&Cache> = cache.filter(|_| settings.caches());
+    let cached: Option<(String, Vec<Warning>)> = match cache {
+        Some(cache) => {
+            timings.measure(Phase::Reading, || cache.get(key, &displayed))?
+        }
+        None => None,
+    };
+    let mut parsed: Option<usize> = None;
+    let warnings: Vec<Warning>;
+    let assembly: String = if let Some((assembly, cached)) = cached {
+        settings.print_warnings(&cached);
+        warnings = cached;
+        assembly
+    } else {
+        settings.verbosity.tokens(&displayed, &parser);
+        let located: Vec<(Provenance, parser::Instruction)> = timings
+            .measure(Phase::Parsing, || parser.parse_located(settings.mode))?;
+        settings.verbosity.instructions(&located);
+        parsed = Some(located.len());
+        let mut passes: PassManager<'_> = PassManager::new(
+            settings,
+            Unit::new(
+                &displayed,
+                stem.as_deref(),
+                file_name,
+                entry,
+                label_offset,
+            ),
+        );
+        let located: Vec<(Provenance, parser::Instruction)> =
+            timings.measure(Phase::Translating, || passes.run_vm(located))?;
+        let assembly: String = timings.measure(Phase::Translating, || {
+            translate_file(located, file, file_name, label_offset, settings)
+                .map(|assembly: String| passes.run_assembly(assembly))
+        })?;
+        warnings = passes.into_warnings();
+        if let Some(cache) = cache {
+            timings.measure(Phase::Writing, || {
+                cache.insert(key, &assembly, &warnings)
+            })?;
+        }
+        assembly
+    };
+    let asm_file: PathBuf = file.with_extension("asm");
+    symbols.add_assembly(&paths::display(&asm_file).to_string(), &assembly);
+
+    timings.measure(Phase::Writing, || {
+        write_output(output, file, &assembly, settings)
+    })?;
+    let vm_lines: usize = parser.lines().count();
+    Ok(FileSummary::new(
+        file.to_path_buf(),
+        timings,
+        vm_lines,
+        parsed,
+        &assembly,
+        warnings,
+    ))
+}
+
+/// Writes the `assembly` generated for a file to `output`.
+///
+/// # Errors
+///
+/// Any error from writing the assembly is propagated.
+fn write_output(
+    output: &mut Output,
+    file: &Path,
+    assembly: &str,
+    settings: &Settings,
+) -> Result<(), HackError> {
+    match *output {
+        Output::PerFile => {
+            let new_file: PathBuf = file.with_extension("asm");
+            if let Some(ref sandbox) = settings.sandbox {
+                sandbox.check(&new_file)?;
+            }
+            trace::event!(
+                Debug,
+                "writing {} bytes to {}",
+                assembly.len(),
+                paths::display(&new_file)
+            );
+            let mut new_file: File = File::create(new_file)?;
+            new_file.write_all(assembly.as_bytes())?;
+        }
+        Output::Combined {
+            ref mut file,
+            ref mut label_offset,
+        } => {
+            trace::event!(
+                Debug,
+                "appending {} bytes to the combined output",
+                assembly.len()
+            );
+            file.write_all(assembly.as_bytes())?;
+            *label_offset = label_offset.saturating_add(
+                settings
+                    .translation
+                    .labels()
+                    .numbers_used(assembly, *label_offset),
+            );
+        }
+        Output::Stdout => {
+            io::stdout().lock().write_all(assembly.as_bytes())?;
+        }
+        Output::Buffer(ref mut buffer) => buffer.push_str(assembly),
+        Output::Discard => {
+            trace::event!(
+                Debug,
+                "discarding {} bytes of checked assembly",
+                assembly.len()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Translates parsed instructions into Hack assembly, annotating each with
+/// its [`Provenance`] if the [`Settings`] annotate the output.
+///
+/// Static variables are named with `file_name`, and generated labels are
+/// named according to the [`Settings`] with `label_offset` added to their
+/// numbers. Anything else done to the instructions or the assembly, such as
+/// renaming namespaces or renumbering labels, is a pass; see
+/// [`crate::passes`].
+///
+/// The assembly is printed as that of `file`, if the [`Settings`] print it;
+/// see [`crate::verbosity`].
+///
+/// # Errors
+///
+/// Any translation error is propagated, and a
+/// [`HackError::IllegalInstruction`] names where the instruction was
+/// written.
+fn translate_file(
+    instructions: Vec<(Provenance, parser::Instruction)>,
+    file: &Path,
+    file_name: &str,
+    label_offset: usize,
+    settings: &Settings,
+) -> Result<String, HackError> {
+    let labels: &LabelScheme = settings.translation.labels();
+    let mut output: String = String::new();
+    let mut labelled: usize = 0;
+    let stack: UserSegment = settings
+        .translation
+        .segments()
+        .stack(settings.translation.target());
+    let depths: Vec<Option<usize>> = if settings.annotate {
+        metrics::file_stack_depths(&numbered(&instructions))
+    } else {
+        Vec::new()
+    };
+
+    // Generated labels are numbered by the index of the instruction, rather
+    // than by its line, as they always have been.
+    for (line_number, (provenance, instruction)) in
+        instructions.into_iter().enumerate()
+    {
+        if settings.annotate {
+            metrics::annotate(
+                &mut output,
+                &provenance,
+                &instruction,
+                depths.get(line_number).copied().flatten(),
+                stack,
+            )?;
+        }
+        let label_number: usize = labels
+            .number(line_number, labelled)
+            .saturating_add(label_offset);
+        if labels::generates_labels(&instruction) {
+            labelled = labelled.saturating_add(1);
+        }
+        Translator::translate(
+            &mut output,
+            label_number,
+            &instruction,
+            file_name,
+            &settings.translation,
+        )
+        .map_err(|error: HackError| {
+            if let HackError::IllegalInstruction(ref message) = error {
+                HackError::IllegalInstruction(format!(
+                    "{}:{}: {message}",
+                    provenance.file().unwrap_or("<stdin>"),
+                    provenance.line()
+                ))
+            } else {
+                error
+            }
+        })?;
+        output.push('\n');
+    }
+    settings.verbosity.assembly(&paths::display(file), &output);
+    Ok(output)
+}
+
+/// Pairs each of the `located` instructions with the line of the file being
+/// parsed that it came from, as the lints and metrics expect.
+fn numbered(
+    located: &[(Provenance, parser::Instruction)],
+) -> Vec<(usize, parser::Instruction)> {
+    located
+        .iter()
+        .map(|located: &(Provenance, parser::Instruction)| {
+            (located.0.entry_line(), located.1.clone())
+        })
+        .collect()
+}
+
+/// Finds the Hack VM files directly inside `directory`.
+///
+/// Anything that is not a file with the `.vm` extension, such as the `.tst`,
+/// `.cmp`, and `.asm` files found alongside them in nand2tetris projects, or
+/// any subdirectories, is skipped. So is anything matching a pattern in the
+/// directory's `.vmignore` file; see [`crate::ignore`].
+///
+/// Symbolic links are handled according to [`Config::symlinks`]. Files read
+/// through a followed link still have their assembly written into
+/// `directory`, next to the link.
+///
+/// The files are then ordered by name and [`Config::first`]; see
+/// [`order_files`].
+///
+/// # Errors
+///
+/// Any error from reading the directory, its entries, or its `.vmignore` file
+/// is propagated, as is any from resolving a followed symbolic link. If no
+/// Hack VM files are found, a [`HackError::NoVmFiles`] is returned listing
+/// what was found instead.
+fn find_vm_files(
+    directory: &Path,
+    config: &Config,
+) -> Result<Vec<Source>, HackError> {
+    let ignore: IgnoreFile = IgnoreFile::load(directory)?;
+    let mut vm_files: Vec<Source> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    let entries: Result<fs::ReadDir, io::Error> = directory.read_dir();
+    for entry in entries? {
+        let entry: fs::DirEntry = entry?;
+        let file: PathBuf = entry.path();
+        let mut name: String = entry.file_name().to_string_lossy().into_owned();
+        let target: PathBuf =
+            match (entry.file_type()?.is_symlink(), config.symlinks()) {
+                (false, _) => file.clone(),
+                (true, SymlinkPolicy::Follow) => resolve_symlink(&file)?,
+                (true, SymlinkPolicy::Skip) => {
+                    trace::event!(
+                        Debug,
+                        "skipping symbolic link {}",
+                        paths::display(&file)
+                    );
+                    name.push_str(" (symbolic link)");
+                    skipped.push(name);
+                    continue;
+                }
+            };
+
+        if ignore.is_ignored(&name, target.is_dir()) {
+            trace::event!(Debug, "ignoring {}", paths::display(&file));
+            name.push_str(" (ignored)");
+            skipped.push(name);
+        } else if target.is_file()
+            && file.extension().is_some_and(|ext| ext == "vm")
+        {
+            vm_files.push(Source::File(file));
+        } else {
+            trace::event!(Debug, "skipping {}", paths::display(&file));
+            if target.is_dir() {
+                name.push('/');
+            }
+            skipped.push(name);
+        }
+    }
+
+    if vm_files.is_empty() {
+        skipped.sort_unstable();
+        return Err(HackError::NoVmFiles {
+            directory: paths::display(directory).to_string(),
+            found: skipped,
+        });
+    }
+
+    order_files(&mut vm_files, config.first());
+    Ok(vm_files)
+}
+
+/// Follows the chain of symbolic links starting at `link` to the file or
+/// directory it ends at.
+///
+/// # Errors
+///
+/// Returns a [`HackError::CannotReadFileFromPath`] if a link in the chain is
+/// broken, or if the chain loops back on itself.
+fn resolve_symlink(link: &Path) -> Result<PathBuf, HackError> {
+    /// The most links followed before a chain is assumed to be a cycle, as
+    /// links may also loop through paths that are not equal.
+    const MAX_LINKS: usize = 40;
+
+    let mut chain: Vec<PathBuf> = vec![link.to_path_buf()];
+    let mut current: PathBuf = link.to_path_buf();
+    loop {
+        let metadata: fs::Metadata =
+            current.symlink_metadata().map_err(|error| {
+                HackError::CannotReadFileFromPath(format!(
+                    "broken symbolic link \"{}\" to \"{}\": {error}",
+                    paths::display(link),
+                    paths::display(&current)
+                ))
+            })?;
+        if !metadata.file_type().is_symlink() {
+            return Ok(current);
+        }
+        let target: PathBuf = current.read_link()?;
+        current = current
+            .parent()
+            .map_or_else(|| target.clone(), |parent| parent.join(&target));
+        if chain.contains(&current) || chain.len() >= MAX_LINKS {
+            let chain: Vec<String> = chain
+                .iter()
+                .map(|path: &PathBuf| paths::display(path).to_string())
+                .collect();
+            return Err(HackError::CannotReadFileFromPath(format!(
+                "symbolic link cycle: {} -> {}",
+                chain.join(" -> "),
+                paths::display(&current)
+            )));
+        }
+        chain.push(current.clone());
+    }
+}
+
+/// Reads the Hack VM files in the zip archive at `archive`, placing each as
+/// though the archive had been extracted next to itself.
+///
+/// The files are ordered as they would be in a directory; see
+/// [`find_vm_files`].
+///
+/// # Errors
+///
+/// Any error from reading the archive is propagated. If it contains no Hack
+/// VM files, or several with the same name in different directories of the
+/// archive, a [`HackError::BadArchive`] is returned.
+fn find_archived_vm_files(
+    archive: &Path,
+    first: &[String],
+    limits: Limits,
+) -> Result<Vec<Source>, HackError> {
+    let archived: Vec<ArchivedFile> = zip::read_vm_files(archive, limits)?;
+    if archived.is_empty() {
+        return Err(HackError::BadArchive(format!(
+            "no \".vm\" files found in \"{}\"",
+            paths::display(archive)
+        )));
+    }
+
+    let mut vm_files: Vec<Source> = Vec::with_capacity(archived.len());
+    for (index, file) in archived.iter().enumerate() {
+        if let Some(other) = archived
+            .iter()
+            .skip(index.saturating_add(1))
+            .find(|other: &&ArchivedFile| other.file_name() == file.file_name())
+        {
+            return Err(HackError::BadArchive(format!(
+                "{}: \"{}\" and \"{}\" have the same name",
+                paths::display(archive),
+                file.name(),
+                other.name()
+            )));
+        }
+    }
+    for file in archived {
+        trace::event!(Debug, "read {} from the archive", file.name());
+        vm_files.push(Source::Archived {
+            path: zip::extracted_path(archive, &file),
+            contents: file.into_contents(),
+        });
+    }
+
+    order_files(&mut vm_files, first);
+    Ok(vm_files)
+}
+
+/// Sorts files by name to keep output reproducible, then moves those whose
+/// stems appear in `first` to the front, in the order given there.
+fn order_files(files: &mut [Source], first: &[String]) {
+    files.sort_unstable_by(|left: &Source, right: &Source| {
+        left.path().cmp(right.path())
+    });
+    files.sort_by_key(|file: &Source| {
+        first
+            .iter()
+            .position(|stem: &String| {
+                file.path().file_stem() == Some(OsStr::new(stem))
+            })
+            .unwrap_or(first.len())
+    });
+}
+
+/// Determines the prefix the static variables of each file are named with.
+///
+/// This is the file's stem, sanitized into a valid symbol, unless the
+/// [`Config`] gives a different prefix for it or `namespaces` renames it.
+///
+/// # Errors
+///
+/// Returns a [`HackError::InvalidOption`] if the [`Config`] gives a prefix
+/// for a file that is not among `files`, so that misspelled names are not
+/// silently ignored.
+///
+/// Returns a [`HackError::StaticPrefixCollision`] if two files would use the
+/// same prefix, as their static variables would then be shared.
+fn assign_static_prefixes(
+    config: &Config,
+    namespaces: &Namespaces,
+    files: &[Source],
+) -> Result<Vec<Symbol>, HackError> {
+    let stems: Vec<String> = files
+        .iter()
+        .map(|file: &Source| {
+            file.path()
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .ok_or(HackError::Internal)
+        })
+        .collect::<Result<Vec<String>, HackError>>()?;
+    if let Some(unused) = config
+        .static_prefixes
+        .keys()
+        .find(|stem: &&String| !stems.contains(stem))
+    {
+        return Err(HackError::InvalidOption(format!(
+            "option \"--static-prefix\" names \"{unused}\", but no file with \
+            that name is translated"
+        )));
+    }
+
+    let mut assigned: BTreeMap<String, &Path> = BTreeMap::new();
+    let mut static_prefixes: Vec<Symbol> = Vec::with_capacity(files.len());
+    for (file, stem) in files.iter().zip(&stems) {
+        let static_prefix: Symbol = static_prefix(config, namespaces, stem);
+        if let Some(other) = assigned.insert(
+            static_prefix.literal_representation().to_owned(),
+            file.path(),
+        ) {
+            return Err(HackError::StaticPrefixCollision {
+                prefix: static_prefix.literal_representation().to_owned(),
+                first: paths::display(other).to_string(),
+                second: paths::display(file.path()).to_string(),
+            });
+        }
+        static_prefixes.push(static_prefix);
+    }
+    Ok(static_prefixes)
+}
+
+/// Names the static variables of the file with the given `stem`.
+///
+/// They are named as given with `--static-prefix`, as its namespace is
+/// renamed to, or else after the file itself, made a valid symbol if it is
+/// not one. The `naming` lint warns about the last; see [`crate::lints`].
+fn static_prefix(
+    config: &Config,
+    namespaces: &Namespaces,
+    stem: &str,
+) -> Symbol {
+    config
+        .static_prefix(stem)
+        .or_else(|| namespaces.renamed(stem))
+        .cloned()
+        .unwrap_or_else(|| Symbol::sanitized(stem))
+}
+
+/// Checks that `file` runs the same when translated according to `settings`
+/// as when interpreted, if `config` asks for it with `--verify`; see
+/// [`crate::verify`].
+///
+/// How it runs differently is printed to stderr, as is why it was skipped if
+/// the interpreter cannot run it. Returns whether it runs the same, which a
+/// file that was not checked is taken to.
+fn verify_file(
+    config: &Config,
+    file: &Source,
+    static_prefix: Option<&Symbol>,
+    settings: &Settings,
+) -> bool {
+    // Only standard input has no prefix, which cannot be read again.
+    let Some(static_prefix) = static_prefix.filter(|_| config.verifies())
+    else {
+        return true;
+    };
+    let differences: Vec<String> = match verify::check(
+        file,
+        static_prefix,
+        &MachineState::default(),
+        settings,
+    ) {
+        Ok(differences) if differences.is_empty() => return true,
+        Ok(differences) => differences,
+        Err(error) => {
+            Progress::clear();
+            let _printed: io::Result<()> = writeln!(
+                io::stderr().lock(),
+                "{}: not verified, as the interpreter cannot run it: {error}",
+                paths::display(file.path())
+            );
+            return true;
+        }
+    };
+    Progress::clear();
+    let mut stderr: io::StderrLock<'_> = io::stderr().lock();
+    let _printed: io::Result<()> = writeln!(
+        stderr,
+        "{}: runs differently when translated than when interpreted:",
+        paths::display(file.path())
+    );
+    for difference in differences {
+        let _printed: io::Result<()> = writeln!(stderr, "  {difference}");
+    }
+    false
+}
+
+/// Checks that the static variables allocated by the assembly in `symbols`
+/// fit in the `static` segment, as laid out for the target in `settings`.
+///
+/// # Errors
+///
+/// Returns a [`HackError::IllegalInstruction`] if there are more variables
+/// than words in the segment.
+fn check_statics(
+    symbols: &SymbolTable,
+    settings: &Settings,
+) -> Result<(), HackError> {
+    let target: Target = settings.translation.target();
+    let statics: UserSegment = settings.translation.segments().statics(target);
+    let variables: usize = symbols.variables();
+    if variables > usize::from(statics.size()) {
+        return Err(HackError::IllegalInstruction(format!(
+            "the assembly allocates {variables} static variables, but the \
+            static segment of layout \"{}\" on target \"{target}\" holds \
+            {} words, at {statics}",
+            target.layout(),
+            statics.size()
+        )));
+    }
+    Ok(())
+}
+
+/// Finds the files to translate for the path in a [`Config`].
+///
+/// Where their assembly is written is set in `output`, if it is not to
+/// separate files. Any namespaces renamed by the project's manifest, how it
+/// names labels, the segments it declares, its target, and its rewrite rules
+/// are merged into `settings`, and its library is searched after those in
+/// `libraries` given with `-L`; see [`crate::link`].
+///
+/// # Errors
+///
+/// Any error from finding the files is propagated, such as if the path does
+/// not exist.
+fn find_files(
+    config: &Config,
+    output: &mut Output,
+    settings: &mut Settings,
+    libraries: &mut Vec<Library>,
+) -> Result<Vec<Source>, HackError> {
+    let path: PathBuf = paths::canonicalize(config.file_path())?;
+    if !path.try_exists()? {
+        Err(HackError::CannotReadFileFromPath(
+            "path does not point to a file or directory".to_owned(),
+        ))
+    } else if !config.other_paths.is_empty() {
+        find_program_files(config, output)
+    } else if config.use_manifest() {
+        let manifest: Manifest = Manifest::load(&path)?;
+        settings.namespaces.merge(manifest.namespaces());
+        settings.translation.labels_mut().merge(manifest.labels());
+        settings
+            .translation
+            .segments_mut()
+            .merge(manifest.segments());
+        settings.translation.default_target(manifest.target());
+        settings.rewrites.merge(manifest.rewrites());
+        if let Some(combined) = manifest.output() {
+            if let Some(sandbox) = config.sandbox() {
+                sandbox.check(combined)?;
+            }
+            *output = Output::Combined {
+                file: File::create(combined)?,
+                label_offset: 0,
+            };
+        }
+        let files: Vec<Source> = manifest.files().map_or_else(
+            || find_vm_files(&path, config),
+            |files: &[PathBuf]| {
+                files
+                    .iter()
+                    .map(|file: &PathBuf| {
+                        paths::canonicalize(file).map(Source::File).map_err(
+                            |error| {
+                                HackError::CannotReadFileFromPath(format!(
+                                    "could not read \"{}\": {error}",
+                                    paths::display(file)
+                                ))
+                            },
+                        )
+                    })
+                    .collect()
+            },
+        )?;
+        if let Some(library) = manifest.library() {
+            libraries.push(Library::new(library.to_path_buf()));
+        }
+        Ok(files)
+    } else if path.is_dir() {
+        if let Some(combined) = config.compat().combined_output(&path) {
+            if let Some(sandbox) = config.sandbox() {
+                sandbox.check(&combined)?;
+            }
+            *output = Output::Combined {
+                file: File::create(combined)?,
+                label_offset: 0,
+            };
+        }
+        find_vm_files(&path, config)
+    } else if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+    {
+        if !config.write_asm() {
+            *output = Output::Discard;
+        }
+        find_archived_vm_files(&path, config.first(), config.limits())
+    } else if path.is_file() {
+        Ok(vec![Source::File(path)])
+    } else {
+        Err(HackError::CannotReadFileFromPath(
+            "path does not point to a file or directory".to_owned(),
+        ))
+    }
+}
+
+/// Finds the files of a program spread over several paths.
+///
+/// Each path may be a file, or a directory or zip archive containing several.
+/// The program is written to a single file named after the first path; see
+/// [`combined_output`].
+///
+/// As the files are translated into one program, they may call each other's
+/// functions, and their static variables are allocated together, so two
+/// files with the same name are an error, even under different paths; see
+/// [`assign_static_prefixes`]. A file found under more than one path is only
+/// translated once.
+///
+/// # Errors
+///
+/// Returns a [`HackError::CannotReadFileFromPath`] if any path does not
+/// point to a file or directory. Any error from finding the files or creating
+/// the output is propagated.
+fn find_program_files(
+    config: &Config,
+    output: &mut Output,
+) -> Result<Vec<Source>, HackError> {
+    let mut files: Vec<Source> = Vec::new();
+    for given in iter::once(config.file_path()).chain(&config.other_paths) {
+        let path: PathBuf = paths::canonicalize(given).map_err(|error| {
+            HackError::CannotReadFileFromPath(format!(
+                "could not read \"{}\": {error}",
+                paths::display(given)
+            ))
+        })?;
+        let found: Vec<Source> = if path.is_dir() {
+            find_vm_files(&path, config)?
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+        {
+            find_archived_vm_files(&path, config.first(), config.limits())?
+        } else if path.is_file() {
+            vec![Source::File(path)]
+        } else {
+            return Err(HackError::CannotReadFileFromPath(format!(
+                "\"{}\" does not point to a file or directory",
+                paths::display(given)
+            )));
+        };
+        for file in found {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    let combined: PathBuf = combined_output(config)?;
+    if let Some(sandbox) = config.sandbox() {
+        sandbox.check(&combined)?;
+    }
+    *output = Output::Combined {
+        file: File::create(combined)?,
+        label_offset: 0,
+    };
+    Ok(files)
+}
+
+/// Gets the single file a program is written to, named after the path in
+/// the [`Config`].
+///
+/// A directory `Foo` is written to `Foo/Foo.asm`, and any other path to one
+/// with its extension replaced by `.asm`.
+///
+/// # Errors
+///
+/// Any error from canonicalizing the path is propagated.
+fn combined_output(config: &Config) -> Result<PathBuf, HackError> {
+    let path: PathBuf = paths::canonicalize(config.file_path())?;
+    Ok(if path.is_dir() {
+        path.join(path.file_name().unwrap_or_default())
+            .with_extension("asm")
+    } else {
+        path.with_extension("asm")
+    })
+}
+
+/// Adds the files of the `libraries` that the program made of `files` needs;
+/// see [`crate::link`].
+///
+/// A linked program is only complete once combined, so if each file would be
+/// written to its own `.asm` file, the program is instead written to one
+/// named after the path in the [`Config`]; see [`combined_output`].
+///
+/// # Errors
+///
+/// Any error from linking the files or creating the output is propagated.
+fn link_files(
+    files: Vec<Source>,
+    libraries: &[Library],
+    config: &Config,
+    output: &mut Output,
+    settings: &Settings,
+) -> Result<Vec<Source>, HackError> {
+    if matches!(*output, Output::PerFile) {
+        let combined: PathBuf = combined_output(config)?;
+        if let Some(sandbox) = config.sandbox() {
+            sandbox.check(&combined)?;
+        }
+        *output = Output::Combined {
+            file: File::create(combined)?,
+            label_offset: 0,
+        };
+    }
+    link::link(files, libraries, settings)
+}
+
+/// Reads and parses a file to report on, renaming its functions according
+/// to `settings`. Its [`Parser`] is returned along with its instructions,
+/// paired with their line numbers.
+///
+/// # Errors
+///
+/// Any error from reading or parsing the file is propagated.
+fn read_for_report(
+    file: &Source,
+    settings: &Settings,
+) -> Result<(Parser, Vec<(usize, parser::Instruction)>), HackError> {
+    let parser: Parser = file.read(settings.limits)?;
+    let mut instructions: Vec<(usize, parser::Instruction)> =
+        parser.parse_numbered(settings.mode)?;
+    for &mut (_, ref mut instruction) in &mut instructions {
+        settings.namespaces.apply(instruction)?;
+    }
+    Ok((parser, instructions))
+}
+
+/// Records where everything in `files` is defined and referenced.
+///
+/// As they would be translated, their static variables are named with
+/// `static_prefixes`, and they are parsed and their functions renamed
+/// according to `settings`.
+///
+/// # Errors
+///
+/// Any error from reading or parsing the files is propagated.
+fn cross_reference(
+    files: &[Source],
+    static_prefixes: &[Option<Symbol>],
+    settings: &Settings,
+) -> Result<CrossReference, HackError> {
+    let mut report: CrossReference = CrossReference::default();
+    for (file, static_prefix) in files.iter().zip(static_prefixes) {
+        let (_, instructions): (Parser, Vec<(usize, parser::Instruction)>) =
+            read_for_report(file, settings)?;
+        let name: String = paths::display(file.path()).to_string();
+        let static_prefix: &str = static_prefix
+            .as_ref()
+            .map_or(name.as_str(), Symbol::literal_representation);
+        report.add_file(&name, static_prefix, &instructions);
+    }
+    Ok(report)
+}
+
+/// Documents everything in `files`, which are read as with
+/// [`cross_reference`].
+///
+/// # Errors
+///
+/// Any error from reading or parsing the files is propagated.
+fn document(
+    files: &[Source],
+    static_prefixes: &[Option<Symbol>],
+    settings: &Settings,
+) -> Result<Documentation, HackError> {
+    let mut documentation: Documentation = Documentation::default();
+    for (file, static_prefix) in files.iter().zip(static_prefixes) {
+        let (parser, instructions): (
+            Parser,
+            Vec<(usize, parser::Instruction)>,
+        ) = read_for_report(file, settings)?;
+        let name: String = paths::display(file.path()).to_string();
+        let static_prefix: &str = static_prefix
+            .as_ref()
+            .map_or(name.as_str(), Symbol::literal_representation);
+        documentation.add_file(
+            &name,
+            static_prefix,
+            parser.contents(),
+            &instructions,
+        );
+    }
+    Ok(documentation)
+}
+
+/// Reads an assembly file to compare with another.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+fn read_assembly(path: &Path) -> Result<Assembly, HackError> {
+    let contents: String = fs::read_to_string(path)?;
+    Ok(Assembly::new(paths::display(path).to_string(), &contents))
+}
+
+/// Compares the assembly files at `left` and `right`, for the `diff-asm`
+/// subcommand, printing where they diverge; see [`crate::asmdiff`].
+///
+/// # Errors
+///
+/// Returns a [`HackError::AssemblyDiverges`] if they diverge. Any error from
+/// reading the files is propagated.
+fn diff_asm(
+    left: &Path,
+    right: &Path,
+    start: Instant,
+) -> Result<Summary, HackError> {
+    let diff: AssemblyDiff =
+        AssemblyDiff::new(read_assembly(left)?, read_assembly(right)?);
+    io::stdout().lock().write_all(diff.to_string().as_bytes())?;
+    let mut summary: Summary = Summary::default();
+    summary.set_elapsed(start.elapsed());
+    match diff.divergences() {
+        0 => Ok(summary),
+        divergences => Err(HackError::AssemblyDiverges(divergences)),
+    }
+}
+
+/// Compares the program at the path in `config` with its translation by the
+/// reference translator, for the `compare` subcommand.
+///
+/// How each test script went is printed; see [`crate::reference`].
+///
+/// # Errors
+///
+/// Returns a [`HackError::BehaviorDiverges`] if it behaves differently under
+/// any test script. Any error from translating or running it is propagated.
+fn compare_with_reference(
+    config: &Config,
+    start: Instant,
+) -> Result<Summary, HackError> {
+    let comparison: Comparison = reference::compare(
+        config.file_path(),
+        config.reference(),
+        config.emulator(),
+        |copy: &Path| run(&config.translating(copy)).map(|_: Summary| ()),
+    )?;
+    io::stdout()
+        .lock()
+        .write_all(comparison.to_string().as_bytes())?;
+    let mut summary: Summary = Summary::default();
+    summary.set_elapsed(start.elapsed());
+    match comparison.differences() {
+        0 => Ok(summary),
+        scripts => Err(HackError::BehaviorDiverges(scripts)),
+    }
+}
+
+/// Given a borrow of a valid [`Config`], runs the main program logic.
+///
+/// If the [`Config`] is targeting a valid Hack VM file, it will be read into
+/// memory and have each line deserialized into an
+/// [`hack_vm_syntax::parser::Instruction`].
+///
+/// If the input file was `foo.vm`, the program will try to write the output to
+/// `foo.asm`. If  the file exists, it will be overwritten. If the [`Config`]
+/// is targeting a directory, this is done for each Hack VM file in it.
+///
+/// If the [`Config`] is targeting a zip archive, each Hack VM file in it is
+/// only checked to translate successfully, unless writing the assembly next
+/// to the archive was requested.
+///
+/// For the `build` subcommand, the files to translate and where to write them
+/// are instead taken from the project's [`Manifest`]. If the path is `-`,
+/// standard input is translated, and the assembly written to standard output.
+///
+/// On success, a [`Summary`] of the run is returned.
+///
+/// # Errors
+///
+/// Any non-[`Config`] error that can happen is eventually propagated here. See
+/// the [`hack_vm_syntax::error`] module for more details.
+pub fn run(config: &Config) -> Result<Summary, HackError> {
+    let _span: Span = trace::span!(Info, "run");
+    let start: Instant = Instant::now();
+    let mut summary: Summary = Summary::default();
+    if let Some(compared_path) = config.compared_path() {
+        return diff_asm(config.file_path(), compared_path, start);
+    }
+    #[cfg(feature = "serve")]
+    if config.serves() {
+        return serve::serve(
+            config.listen().unwrap_or(serve::DEFAULT_ADDRESS),
+            &Settings::new(config),
+        )
+        .map(|()| summary);
+    }
+    if config.daemonizes() {
+        return daemon::daemon(config).map(|()| summary);
+    }
+    if config.compares() {
+        return compare_with_reference(config, start);
+    }
+    let cache: Option<Cache> =
+        config.cache_dir().map(|dir| Cache::open(dir)).transpose()?;
+    let mut output: Output = Output::PerFile;
+    let mut settings: Settings = Settings::new(config);
+    // Printed as each file is linted, so that they are shown even if it then
+    // fails to translate.
+    settings.warnings = Some((config.diagnostics(), config.palette()));
+
+    let (files, static_prefixes): (Vec<Source>, Vec<Option<Symbol>>) = if config
+        .reads_stdin()
+    {
+        output = Output::Stdout;
+        (vec![Source::Stdin], vec![config.source_name().cloned()])
+    } else {
+        let mut libraries: Vec<Library> = config.libraries();
+        let mut files: Vec<Source> =
+            find_files(config, &mut output, &mut settings, &mut libraries)?;
+        libraries.extend(config.os().cloned().map(Library::os));
+        if !libraries.is_empty() {
+            files =
+                link_files(files, &libraries, config, &mut output, &settings)?;
+        }
+        let static_prefixes: Vec<Symbol> =
+            assign_static_prefixes(config, &settings.namespaces, &files)?;
+        (files, static_prefixes.into_iter().map(Some).collect())
+    };
+
+    if config.cross_references() {
+        let report: CrossReference =
+            cross_reference(&files, &static_prefixes, &settings)?;
+        io::stdout()
+            .lock()
+            .write_all(report.to_string().as_bytes())?;
+        summary.set_elapsed(start.elapsed());
+        return Ok(summary);
+    }
+
+    if config.documents() {
+        let documentation: Documentation =
+            document(&files, &static_prefixes, &settings)?;
+        io::stdout()
+            .lock()
+            .write_all(documentation.to_string().as_bytes())?;
+        summary.set_elapsed(start.elapsed());
+        return Ok(summary);
+    }
+
+    if config.file_path().is_dir() {
+        summary.set_directory(config.file_path().clone());
+    }
+    let mut symbols: SymbolTable = SymbolTable::default();
+    let mut diverging: usize = 0;
+    let mut progress: Progress = Progress::new(files.len(), config.progress());
+    for (index, (file, static_prefix)) in
+        files.iter().zip(&static_prefixes).enumerate()
+    {
+        progress.start(file.path());
+        // Only files combined into one are a single program, which starts
+        // in the first of them.
+        let entry: bool =
+            index == 0 || !matches!(output, Output::Combined { .. });
+        summary.push(
+            run_for_file(
+                file,
+                static_prefix.as_ref(),
+                &settings,
+                cache.as_ref(),
+                &mut output,
+                &mut symbols,
+                entry,
+            )
+            .inspect_err(|_| progress.finish())?,
+        );
+        if !verify_file(config, file, static_prefix.as_ref(), &settings) {
+            diverging = diverging.saturating_add(1);
+        }
+    }
+    progress.finish();
+    check_statics(&symbols, &settings)?;
+    if diverging > 0 {
+        return Err(HackError::TranslationDiverges(diverging));
+    }
+    if config.dump_symbols() {
+        summary.set_symbols(symbols);
+    }
+    summary.set_elapsed(start.elapsed());
+    Ok(summary)
+}
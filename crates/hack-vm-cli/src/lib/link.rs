@@ -0,0 +1,239 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Link Module
+//!
+//! Pulls the files of libraries, such as the Jack OS, into a program, as the
+//! linking step of the Jack toolchain. Libraries are directories of `.vm`
+//! files, searched in order:
+//!
+//! 1. Those given with `-L <DIR>`, in the order given.
+//! 2. The one named by a project's manifest, when building it:
+//!
+//!    ```toml
+//!    library = "../tools/OS"
+//!    ```
+//!
+//! 3. The Jack OS, in the directory given with `--with-os`. Only the classes
+//!    of the OS are looked for there, as listed in [`OS_CLASSES`].
+//!
+//! ```bash
+//! hack-vm-translator -L ./lib --with-os ./tools/OS ./Pong
+//! ```
+//!
+//! Each function the program calls but does not define is looked for in the
+//! file named after its class, such that `Math.multiply` is looked for in
+//! `Math.vm`, and taken from the first library that has it. The files pulled
+//! in may call further functions, which are resolved the same way, so only
+//! the files the program needs are translated. They follow the files of the
+//! program, in order of their names, and are combined with them into the
+//! manifest's `output`, or into a file named after the path being translated
+//! if there is none.
+//!
+//! A class the program has a file for is never looked for in a library. A
+//! function still called but not defined once linked is an error, reported
+//! where it is first called, as in this error, wrapped onto a second line
+//! here:
+//!
+//! ```text
+//! Main.vm:3: "Foo.bar" is called, but not defined by the program or any
+//!   library
+//! ```
+//!
+//! Functions and calls are not translated yet, so translating a linked
+//! program fails at its first `function`, naming where it is. Until they are,
+//! linking is of use to `xref` and `doc`, which report on the linked files
+//! without translating them.
+//!
+//! The OS is not bundled with the translator, since its `.vm` files are
+//! distributed with the nand2tetris software suite.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::parser::{Functional, Instruction};
+use hack_vm_syntax::paths;
+
+use crate::{Settings, Source};
+
+/// The classes of the Jack OS.
+pub(crate) const OS_CLASSES: [&str; 8] = [
+    "Array", "Keyboard", "Math", "Memory", "Output", "Screen", "String", "Sys",
+];
+
+/// A directory of `.vm` files that a program can be linked with.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct Library {
+    /// The directory holding the files.
+    directory: PathBuf,
+    /// The classes looked for in it, or [`None`] if any are.
+    classes: Option<&'static [&'static str]>,
+}
+
+impl Library {
+    /// Looks for any class in `directory`.
+    pub(crate) const fn new(directory: PathBuf) -> Self {
+        Self {
+            directory,
+            classes: None,
+        }
+    }
+
+    /// Looks for the classes of the Jack OS in `directory`.
+    pub(crate) const fn os(directory: PathBuf) -> Self {
+        Self {
+            directory,
+            classes: Some(&OS_CLASSES),
+        }
+    }
+
+    /// Gets the file of the library defining `class`, if it has one.
+    fn find(&self, class: &str) -> Option<PathBuf> {
+        if self
+            .classes
+            .is_some_and(|classes: &[&str]| !classes.contains(&class))
+        {
+            return None;
+        }
+        let path: PathBuf = self.directory.join(format!("{class}.vm"));
+        path.is_file().then_some(path)
+    }
+}
+
+/// The functions a program defines and calls, by name.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+struct Functions {
+    /// The functions defined.
+    defined: BTreeSet<String>,
+    /// The functions called, with where each is first called, as
+    /// `FILE:LINE`.
+    called: BTreeMap<String, String>,
+}
+
+impl Functions {
+    /// Records the functions defined and called by `source`, parsed
+    /// according to `settings`.
+    fn add(
+        &mut self,
+        source: &Source,
+        settings: &Settings,
+    ) -> Result<(), HackError> {
+        let instructions: Vec<(usize, Instruction)> = source
+            .read(settings.limits)?
+            .parse_numbered(settings.mode)?;
+        for numbered in instructions {
+            match numbered.1 {
+                Instruction::Functional(Functional::Function {
+                    symbol,
+                    ..
+                }) => {
+                    let _new: bool = self.defined.insert(symbol.to_string());
+                }
+                Instruction::Functional(Functional::Call {
+                    symbol, ..
+                }) => {
+                    let _site: &mut String = self
+                        .called
+                        .entry(symbol.to_string())
+                        .or_insert_with(|| {
+                            format!(
+                                "{}:{}",
+                                paths::display(source.path()),
+                                numbered.0
+                            )
+                        });
+                }
+                Instruction::Functional(Functional::Return)
+                | Instruction::StackManipulation(_)
+                | Instruction::Arithmetic(_)
+                | Instruction::Branching(_)
+                | Instruction::Assembly(_)
+                | _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the functions called but not defined, with where each is first
+    /// called.
+    fn unresolved(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.called
+            .iter()
+            .filter(|&(function, _)| !self.defined.contains(function))
+            .map(|(function, site): (&String, &String)| {
+                (function.as_str(), site.as_str())
+            })
+    }
+}
+
+/// Adds the files of the `libraries` that the `program` needs, read and
+/// parsed according to `settings`, after its own files.
+///
+/// # Errors
+///
+/// Returns a [`HackError::IllegalInstruction`] if a function is still called
+/// but not defined once linked. Any error from reading or parsing the files
+/// of the program, or those pulled in from the libraries, is propagated.
+pub(crate) fn link(
+    mut program: Vec<Source>,
+    libraries: &[Library],
+    settings: &Settings,
+) -> Result<Vec<Source>, HackError> {
+    let mut functions: Functions = Functions::default();
+    let mut searched: BTreeSet<String> = BTreeSet::new();
+    for source in &program {
+        functions.add(source, settings)?;
+        if let Some(stem) = source.path().file_stem() {
+            let _new: bool =
+                searched.insert(stem.to_string_lossy().into_owned());
+        }
+    }
+    let mut linked: BTreeMap<String, Source> = BTreeMap::new();
+    loop {
+        let classes: BTreeSet<String> = functions
+            .unresolved()
+            .map(|(function, _): (&str, &str)| class(function))
+            .filter(|class: &&str| !searched.contains(*class))
+            .map(str::to_owned)
+            .collect();
+        if classes.is_empty() {
+            break;
+        }
+        for class in classes {
+            if let Some(path) = libraries
+                .iter()
+                .find_map(|library: &Library| library.find(&class))
+            {
+                let source: Source = Source::File(paths::canonicalize(&path)?);
+                functions.add(&source, settings)?;
+                let _previous: Option<Source> =
+                    linked.insert(class.clone(), source);
+            }
+            let _new: bool = searched.insert(class);
+        }
+    }
+    let unresolved: Vec<String> = functions
+        .unresolved()
+        .map(|(function, site): (&str, &str)| {
+            format!(
+                "{site}: \"{function}\" is called, but not defined by the \
+                 program or any library"
+            )
+        })
+        .collect();
+    if !unresolved.is_empty() {
+        return Err(HackError::IllegalInstruction(unresolved.join("\n")));
+    }
+    program.extend(linked.into_values());
+    Ok(program)
+}
+
+/// Gets the class of a function, which is what its name starts with before
+/// the first `.`, or the whole name if it has none.
+fn class(function: &str) -> &str {
+    function
+        .split_once('.')
+        .map_or(function, |(class, _): (&str, &str)| class)
+}
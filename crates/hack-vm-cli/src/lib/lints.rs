@@ -0,0 +1,401 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Lints Module
+//!
+//! Warns about code that translates, but is likely to break once it is
+//! combined with other files or the OS later in the course. Warnings are
+//! printed to stderr as soon as each file is linted, before it is
+//! translated, so that they are shown even if translating it fails. They are
+//! also counted in the summary of the run, as this warning is, wrapped onto a
+//! second line here:
+//!
+//! ```text
+//! Main.vm:4: warning: function "Math.double" should be named "Main.double",
+//!   after its file [naming]
+//! ```
+//!
+//! - `naming`: A `function` or `call` whose name does not follow the
+//!   `FileName.functionName` convention, or a function named after a file
+//!   other than the one defining it. Static variables are named after the
+//!   file they are in, and the OS is found by the file part of its
+//!   functions' names, so both assume the convention holds. A file whose
+//!   name is not a valid symbol is warned about where it first uses the
+//!   `static` segment, as its static variables are named after the file with
+//!   each forbidden character replaced instead.
+//! - `shadowing`: A `label` named like a function, like the labels generated
+//!   for comparisons, or, inside a function `F`, like the return addresses
+//!   `F$ret.i` of the calls in it. The symbols only collide once files are
+//!   combined, where the assembler's error no longer points at the label.
+//! - `outside-function`: Instructions before the first `function` of a file
+//!   in a program combined from several files, other than the first file
+//!   translated, which `--first` can choose. Once the program is started by
+//!   calling `Sys.init`, they can never run, which usually means code was
+//!   moved from a single-file test without wrapping it in a function. Files
+//!   without any `function` are not meant to start at `Sys.init`, as no
+//!   bootstrap code is generated to call it yet, so they are not checked.
+//!
+//! Files whose assembly was reused from the cache are not linted again. The
+//! warnings stored with it are printed instead, as though they were.
+
+use alloc::collections::BTreeSet;
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+use hack_vm_codegen::labels::LabelScheme;
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::parser::{
+    Branching, Functional, Instruction, StackManipulation, Symbol,
+};
+
+use crate::color::Palette;
+
+/// The kinds of problems that are warned about.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub(crate) enum Lint {
+    /// A function name not following the `FileName.functionName` convention.
+    Naming,
+    /// A label named like another symbol in the assembly.
+    Shadowing,
+    /// Instructions outside of any function where they can never run.
+    OutsideFunction,
+}
+
+impl Lint {
+    /// The name of the `naming` lint.
+    const NAMING: &str = "naming";
+    /// The name of the `shadowing` lint.
+    const SHADOWING: &str = "shadowing";
+    /// The name of the `outside-function` lint.
+    const OUTSIDE_FUNCTION: &str = "outside-function";
+}
+
+impl FromStr for Lint {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::NAMING => Ok(Self::Naming),
+            Self::SHADOWING => Ok(Self::Shadowing),
+            Self::OUTSIDE_FUNCTION => Ok(Self::OutsideFunction),
+            other => Err(HackError::FromStrError(format!(
+                "\"{other}\" is not a recognized lint"
+            ))),
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Naming => write!(f, "{}", Self::NAMING),
+            Self::Shadowing => write!(f, "{}", Self::SHADOWING),
+            Self::OutsideFunction => write!(f, "{}", Self::OUTSIDE_FUNCTION),
+        }
+    }
+}
+
+/// A single warning about a line of a file.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Warning {
+    /// Where the problem is, as `FILE:LINE`.
+    site: String,
+    /// What kind of problem it is.
+    lint: Lint,
+    /// What the problem is.
+    message: String,
+}
+
+impl Warning {
+    /// Displays the warning styled with `palette`; see [`crate::color`].
+    pub const fn styled(&self, palette: Palette) -> StyledWarning<'_> {
+        StyledWarning {
+            warning: self,
+            palette,
+        }
+    }
+
+    /// Gets where the problem is, as `FILE:LINE`.
+    pub(crate) fn site(&self) -> &str {
+        &self.site
+    }
+
+    /// Gets what kind of problem it is.
+    pub(crate) const fn lint(&self) -> Lint {
+        self.lint
+    }
+
+    /// Gets what the problem is.
+    pub(crate) fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Writes the warning to be stored in the cache, as `LINE LINT MESSAGE`,
+    /// without the file it is about, which may move.
+    pub(crate) fn to_cached(&self) -> String {
+        let line: &str = self
+            .site
+            .rsplit_once(':')
+            .map_or("", |(_, line): (&str, &str)| line);
+        format!("{line} {} {}", self.lint, self.message)
+    }
+
+    /// Reads a warning about `file` that was stored in the cache, as written
+    /// by [`Warning::to_cached`], if it is one.
+    pub(crate) fn from_cached(file: &str, cached: &str) -> Option<Self> {
+        let (number, rest): (&str, &str) = cached.split_once(' ')?;
+        let (lint, message): (&str, &str) = rest.split_once(' ')?;
+        Some(Self {
+            site: format!("{file}:{number}"),
+            lint: lint.parse().ok()?,
+            message: message.to_owned(),
+        })
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.styled(Palette::PLAIN))
+    }
+}
+
+/// A [`Warning`] styled with a [`Palette`]. See [`Warning::styled`].
+#[derive(Debug, Clone, Copy)]
+pub struct StyledWarning<'warning> {
+    /// The warning being displayed.
+    warning: &'warning Warning,
+    /// The styles to display it with.
+    palette: Palette,
+}
+
+impl Display for StyledWarning<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {} [{}]",
+            self.palette.location(&self.warning.site),
+            self.palette.warning("warning:"),
+            self.warning.message,
+            self.warning.lint
+        )
+    }
+}
+
+/// Lints a file displayed as `file`, given its instructions paired with
+/// their line numbers, as written.
+///
+/// Its functions are expected to be named after `stem`, if it was read from
+/// a file, its static variables are prefixed with `static_prefix`, and the
+/// labels generated for its comparisons are named according to `labels`.
+/// Instructions outside of any function are only expected in the `entry`
+/// file of the program, or in a file without any function.
+pub(crate) fn lint(
+    file: &str,
+    stem: Option<&str>,
+    static_prefix: &str,
+    instructions: &[(usize, Instruction)],
+    labels: &LabelScheme,
+    entry: bool,
+) -> Vec<Warning> {
+    let functions: BTreeSet<&str> = instructions
+        .iter()
+        .filter_map(|numbered: &(usize, Instruction)| match numbered.1 {
+            Instruction::Functional(
+                Functional::Function { ref symbol, .. }
+                | Functional::Call { ref symbol, .. },
+            ) => Some(symbol.literal_representation()),
+            Instruction::Functional(Functional::Return)
+            | Instruction::StackManipulation(_)
+            | Instruction::Arithmetic(_)
+            | Instruction::Branching(_)
+            | Instruction::Assembly(_)
+            | _ => None,
+        })
+        .collect();
+    let mut warnings: Vec<Warning> = Vec::new();
+    let outside: usize = instructions
+        .iter()
+        .take_while(|numbered: &&(usize, Instruction)| {
+            !matches!(
+                numbered.1,
+                Instruction::Functional(Functional::Function { .. })
+            )
+        })
+        .count();
+    let declares_function: bool = outside < instructions.len();
+    if let (true, Some(&(line, _))) = (
+        !entry && declares_function && outside > 0,
+        instructions.first(),
+    ) {
+        warnings.push(Warning {
+            site: format!("{file}:{line}"),
+            lint: Lint::OutsideFunction,
+            message: format!(
+                "{outside} instruction(s) are outside of any function, so \
+                they never run once the program starts at \"Sys.init\""
+            ),
+        });
+    }
+    if let Some((line, message)) = sanitized(stem, static_prefix, instructions)
+    {
+        warnings.push(Warning {
+            site: format!("{file}:{line}"),
+            lint: Lint::Naming,
+            message,
+        });
+    }
+    let mut current: Option<&str> = None;
+    for &(line, ref instruction) in instructions {
+        let mut warn = |lint: Lint, message: String| {
+            warnings.push(Warning {
+                site: format!("{file}:{line}"),
+                lint,
+                message,
+            });
+        };
+        if let Some(message) = naming(stem, instruction) {
+            warn(Lint::Naming, message);
+        }
+        match *instruction {
+            Instruction::Functional(Functional::Function {
+                ref symbol,
+                ..
+            }) => current = Some(symbol.literal_representation()),
+            Instruction::Branching(Branching::Label { ref symbol }) => {
+                if let Some(message) = shadowing(
+                    symbol.literal_representation(),
+                    current,
+                    &functions,
+                    labels,
+                ) {
+                    warn(Lint::Shadowing, message);
+                }
+            }
+            Instruction::Functional(_)
+            | Instruction::StackManipulation(_)
+            | Instruction::Arithmetic(_)
+            | Instruction::Branching(_)
+            | Instruction::Assembly(_)
+            | _ => {}
+        }
+    }
+    warnings
+}
+
+/// Checks that a `label` named `name` is not named like another symbol.
+///
+/// The label is in the function `current`, if it is in one, and must not be
+/// named like the `functions` of its file, like the labels generated for
+/// comparisons according to `labels`, or like the return addresses of calls.
+fn shadowing(
+    name: &str,
+    current: Option<&str>,
+    functions: &BTreeSet<&str>,
+    labels: &LabelScheme,
+) -> Option<String> {
+    let symbol: String = current.map_or_else(
+        || name.to_owned(),
+        |function: &str| format!("{function}${name}"),
+    );
+    let is_return_address: bool = current.is_some()
+        && name.strip_prefix("ret.").is_some_and(|number: &str| {
+            !number.is_empty()
+                && number.bytes().all(|byte: u8| byte.is_ascii_digit())
+        });
+    if functions.contains(name) {
+        Some(format!("label \"{name}\" is named like a function"))
+    } else if labels.is_generated(&symbol) {
+        Some(format!(
+            "label \"{symbol}\" is named like the labels generated for \
+            comparisons"
+        ))
+    } else if is_return_address {
+        Some(format!(
+            "label \"{name}\" becomes \"{symbol}\", named like the return \
+            address of a call"
+        ))
+    } else {
+        None
+    }
+}
+
+/// Checks whether a file is not named as its static variables are.
+///
+/// They are prefixed with `static_prefix`, which is checked against the
+/// file's `stem` made a valid symbol. If they are named so, the line of the
+/// first of `instructions` to use them is given.
+///
+/// A prefix chosen with `--static-prefix`, or by renaming the file's
+/// namespace, is not warned about unless it happens to be the same one.
+fn sanitized(
+    stem: Option<&str>,
+    static_prefix: &str,
+    instructions: &[(usize, Instruction)],
+) -> Option<(usize, String)> {
+    let stem: &str = stem?;
+    if stem == static_prefix
+        || Symbol::sanitized(stem).literal_representation() != static_prefix
+    {
+        return None;
+    }
+    let &(line, _) =
+        instructions
+            .iter()
+            .find(|numbered: &&(usize, Instruction)| match numbered.1 {
+                Instruction::StackManipulation(
+                    StackManipulation::Push { ref symbol, .. }
+                    | StackManipulation::Pop { ref symbol, .. },
+                ) => symbol.literal_representation() == "static",
+                Instruction::Functional(_)
+                | Instruction::Arithmetic(_)
+                | Instruction::Branching(_)
+                | Instruction::Assembly(_)
+                | _ => false,
+            })?;
+    Some((
+        line,
+        format!(
+            "file name \"{stem}\" is not a valid symbol, so its static \
+            variables are named \"{static_prefix}.i\" instead"
+        ),
+    ))
+}
+
+/// Checks that a `function` or `call` follows the `FileName.functionName`
+/// convention, and that a `function` in a file with the given `stem` is
+/// named after it.
+fn naming(stem: Option<&str>, instruction: &Instruction) -> Option<String> {
+    let (command, name): (&str, &str) = match *instruction {
+        Instruction::Functional(Functional::Function {
+            ref symbol, ..
+        }) => ("function", symbol.literal_representation()),
+        Instruction::Functional(Functional::Call { ref symbol, .. }) => {
+            ("call", symbol.literal_representation())
+        }
+        Instruction::Functional(Functional::Return)
+        | Instruction::StackManipulation(_)
+        | Instruction::Arithmetic(_)
+        | Instruction::Branching(_)
+        | Instruction::Assembly(_)
+        | _ => return None,
+    };
+    let Some((prefix, function)) =
+        name.split_once('.').filter(|&(prefix, function)| {
+            !prefix.is_empty()
+                && !function.is_empty()
+                && !function.contains('.')
+        })
+    else {
+        return Some(format!(
+            "{command} \"{name}\" is not named as \"FileName.functionName\""
+        ));
+    };
+    match stem {
+        Some(stem) if command == "function" && prefix != stem => Some(format!(
+            "function \"{name}\" should be named \"{stem}.{function}\", after \
+            its file"
+        )),
+        Some(_) | None => None,
+    }
+}
@@ -0,0 +1,299 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Manifest Module
+//!
+//! Reads the `hackvm.toml` project manifest used by the `build` subcommand,
+//! which records how a project is translated so the right options don't have
+//! to be remembered on every invocation.
+//!
+//! ```toml
+//! # The files to translate, in order. Defaults to every `.vm` file in the
+//! # project directory, in order of their names.
+//! files = ["Sys.vm", "Main.vm"]
+//!
+//! # Write all files into this single assembly file, rather than one
+//! # assembly file per input file.
+//! output = "Project.asm"
+//!
+//! # Namespaces to rename, as OLD=NEW; see `crate::namespace`.
+//! namespaces = ["Math=LibMath"]
+//!
+//! # How the labels generated for comparisons are named and numbered; see
+//! # `hack_vm_codegen::labels`.
+//! labels = "true-end"
+//! label-numbering = "sequential"
+//! renumber-labels = true
+//!
+//! # The variant of the Hack CPU to generate assembly for; see
+//! # `hack_vm_syntax::target`.
+//! target = "hack-extended"
+//!
+//! # Rules rewriting instructions before they are translated; see
+//! # `crate::rewrite`.
+//! rewrites = "rewrites.toml"
+//!
+//! # The library to pull the files the program needs from, such as the Jack
+//! # OS; see `crate::link`.
+//! library = "../tools/OS"
+//!
+//! # Segments beyond those of the VM language, or where those of the VM
+//! # language are moved to; see `hack_vm_syntax::segments`.
+//! [segments.leds]
+//! base = 24577
+//! size = 8
+//!
+//! [segments.temp]
+//! base = 16
+//! size = 16
+//!
+//! [segments.static]
+//! base = 32
+//! size = 224
+//!
+//! # Where the stack starts, which SP is initialized to, and how far it may
+//! # grow; see `hack_vm_syntax::segments`.
+//! [stack]
+//! base = 256
+//! size = 1792
+//!
+//! # Reserved for future use; only the current behavior is accepted.
+//! bootstrap = false
+//! optimization = 0
+//! ```
+
+use core::str::FromStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use hack_vm_codegen::labels::LabelScheme;
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::parser::Symbol;
+use hack_vm_syntax::paths;
+use hack_vm_syntax::segments::{Segments, UserSegment};
+use hack_vm_syntax::target::Target;
+
+use crate::namespace::Namespaces;
+use crate::rewrite::Rewrites;
+use crate::toml::Table;
+
+/// The settings read from a project's `hackvm.toml`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct Manifest {
+    /// The files to translate, in order, relative to the project directory.
+    files: Option<Vec<PathBuf>>,
+    /// The single file to write all output to, relative to the project
+    /// directory.
+    output: Option<PathBuf>,
+    /// The namespaces to rename.
+    namespaces: Namespaces,
+    /// How the labels generated for comparisons are named.
+    labels: LabelScheme,
+    /// The segments declared beyond those of the VM language.
+    segments: Segments,
+    /// The variant of the Hack CPU to generate assembly for.
+    target: Option<Target>,
+    /// The rules rewriting instructions before they are translated.
+    rewrites: Rewrites,
+    /// The directory holding the library the program is linked with.
+    library: Option<PathBuf>,
+}
+
+impl Manifest {
+    /// The name of the manifest file in the project directory.
+    pub(crate) const FILE_NAME: &str = "hackvm.toml";
+
+    /// Reads the manifest in `project`.
+    pub(crate) fn load(project: &Path) -> Result<Self, HackError> {
+        let path: PathBuf = project.join(Self::FILE_NAME);
+        let text: String = fs::read_to_string(&path).map_err(|error| {
+            HackError::CannotReadFileFromPath(format!(
+                "could not read \"{}\": {error}",
+                paths::display(&path)
+            ))
+        })?;
+        let mut table: Table = Table::parse(&path, &text)?;
+
+        let files: Option<Vec<PathBuf>> =
+            table.take_strings("files")?.map(|files: Vec<String>| {
+                files
+                    .into_iter()
+                    .map(|file: String| paths::join(project, &file))
+                    .collect()
+            });
+        let output: Option<PathBuf> = table
+            .take_string("output")?
+            .map(|output: String| paths::join(project, &output));
+
+        let mut namespaces: Namespaces = Namespaces::default();
+        for mapping in table.take_strings("namespaces")?.unwrap_or_default() {
+            let (old, new): (String, Symbol) = Namespaces::parse_mapping(
+                &mapping,
+            )
+            .map_err(|message: String| {
+                table.unsupported(
+                    "namespaces",
+                    &format!("contains \"{mapping}\", which {message}"),
+                )
+            })?;
+            namespaces.insert(old, new);
+        }
+
+        let mut labels: LabelScheme = LabelScheme::default();
+        if let Some(style) = Self::take_parsed(&mut table, "labels")? {
+            labels.set_style(style);
+        }
+        if let Some(numbering) =
+            Self::take_parsed(&mut table, "label-numbering")?
+        {
+            labels.set_numbering(numbering);
+        }
+        if let Some(renumber) = table.take_bool("renumber-labels")? {
+            labels.set_renumber(renumber);
+        }
+
+        let segments: Segments = Self::take_segments(&mut table)?;
+        let target: Option<Target> = Self::take_parsed(&mut table, "target")?;
+        segments.check(target.unwrap_or_default()).map_err(
+            |message: String| table.unsupported("segments", &message),
+        )?;
+        let rewrites: Rewrites = table
+            .take_string("rewrites")?
+            .map(|rewrites: String| {
+                Rewrites::load(&paths::join(project, &rewrites))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let library: Option<PathBuf> = table
+            .take_string("library")?
+            .map(|library: String| paths::join(project, &library));
+
+        if table.take_bool("bootstrap")? == Some(true) {
+            return Err(table.unsupported(
+                "bootstrap",
+                "cannot be enabled, as function calls are not translated yet",
+            ));
+        }
+        if let Some(level) = table.take_integer("optimization")?
+            && level != 0
+        {
+            return Err(table.unsupported(
+                "optimization",
+                &format!(
+                    "cannot be {level}, as only level 0 (no optimization) \
+                    exists"
+                ),
+            ));
+        }
+        table.finish()?;
+
+        Ok(Self {
+            files,
+            output,
+            namespaces,
+            labels,
+            segments,
+            target,
+            rewrites,
+            library,
+        })
+    }
+
+    /// Removes the segments declared or moved as `[segments.NAME]` tables,
+    /// and where the stack was moved by a `[stack]` table.
+    fn take_segments(table: &mut Table) -> Result<Segments, HackError> {
+        let mut segments: Segments = Segments::default();
+        for name in table.tables("segments") {
+            let key: String = format!("segments.{name}");
+            let [base, size] = ["base", "size"].map(|field: &str| {
+                let field: String = format!("{key}.{field}");
+                table.take_integer(&field)?.ok_or_else(|| {
+                    table.unsupported(&key, &format!("is missing \"{field}\""))
+                })
+            });
+            let segment: UserSegment = UserSegment::new(base?, size?)
+                .map_err(|message: String| table.unsupported(&key, &message))?;
+            segments
+                .insert(&name, segment)
+                .map_err(|message: String| table.unsupported(&key, &message))?;
+        }
+        let [base, size] = ["base", "size"]
+            .map(|field: &str| table.take_integer(&format!("stack.{field}")));
+        match (base?, size?) {
+            (None, None) => {}
+            (Some(base), Some(size)) => {
+                segments.set_stack(UserSegment::new(base, size).map_err(
+                    |message: String| table.unsupported("stack", &message),
+                )?);
+            }
+            (None, Some(_)) | (Some(_), None) => {
+                return Err(table.unsupported(
+                    "stack",
+                    "must have both \"stack.base\" and \"stack.size\"",
+                ));
+            }
+        }
+        Ok(segments)
+    }
+
+    /// Removes the value of `key`, if present, parsing it from a string.
+    pub(crate) fn take_parsed<T: FromStr<Err = HackError>>(
+        table: &mut Table,
+        key: &str,
+    ) -> Result<Option<T>, HackError> {
+        table
+            .take_string(key)?
+            .map(|value: String| {
+                T::from_str(&value).map_err(|error| {
+                    table.unsupported(
+                        key,
+                        &format!("is \"{value}\", but {error}"),
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    /// Gets the files to translate in order, if they were listed.
+    pub(crate) fn files(&self) -> Option<&[PathBuf]> {
+        self.files.as_deref()
+    }
+
+    /// Gets the directory holding the library the program is linked with, if
+    /// one was named.
+    pub(crate) fn library(&self) -> Option<&Path> {
+        self.library.as_deref()
+    }
+
+    /// Gets the single file to write all output to, if one was named.
+    pub(crate) fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    /// Gets the namespaces to rename.
+    pub(crate) const fn namespaces(&self) -> &Namespaces {
+        &self.namespaces
+    }
+
+    /// Gets how the labels generated for comparisons are named.
+    pub(crate) const fn labels(&self) -> &LabelScheme {
+        &self.labels
+    }
+
+    /// Gets the segments declared beyond those of the VM language.
+    pub(crate) const fn segments(&self) -> &Segments {
+        &self.segments
+    }
+
+    /// Gets the variant of the Hack CPU to generate assembly for, if one was
+    /// chosen.
+    pub(crate) const fn target(&self) -> Option<Target> {
+        self.target
+    }
+
+    /// Gets the rules rewriting instructions before they are translated.
+    pub(crate) const fn rewrites(&self) -> &Rewrites {
+        &self.rewrites
+    }
+}
@@ -0,0 +1,261 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Metrics Module
+//!
+//! Measures how tangled the control flow of a function is, for grading
+//! rubrics that penalize it:
+//!
+//! - Branches: how many `goto` and `if-goto` commands it has.
+//! - Cyclomatic complexity: how many independent paths there are through it,
+//!   which is one more than the number of `if-goto` commands.
+//! - Stack depth: the most values it keeps on the stack at once, estimated by
+//!   following each path through it once. Values pushed by a loop that does
+//!   not pop them again are only counted for its first iteration.
+//!
+//! With `--annotate`, the stack effect of each command and the estimated
+//! stack depth after it are also written into the assembly, along with where
+//! the command was written, as in
+//! `// push constant 7 (stack +1, depth 1) at Main.vm:3:5`. Commands that
+//! can never run are marked as unreachable instead, and a depth that no
+//! longer fits in the stack, as the target or manifest place it, is marked as
+//! overflowing it.
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Display, Write};
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::parser::{
+    Branching, Functional, Instruction, StackManipulation,
+};
+use hack_vm_syntax::provenance::Provenance;
+use hack_vm_syntax::segments::UserSegment;
+
+/// The metrics of a single function.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub(crate) struct Metrics {
+    /// How many `goto` and `if-goto` commands the function has.
+    branches: usize,
+    /// How many independent paths there are through the function.
+    complexity: usize,
+    /// The most values the function keeps on the stack at once.
+    stack_depth: usize,
+}
+
+impl Metrics {
+    /// Measures the body of a function, given its instructions paired with
+    /// their line numbers, starting with its `function` command.
+    pub(crate) fn measure(body: &[(usize, Instruction)]) -> Self {
+        let mut branches: usize = 0;
+        let mut conditional: usize = 0;
+        for numbered in body {
+            match numbered.1 {
+                Instruction::Branching(Branching::GoTo { .. }) => {
+                    branches = branches.saturating_add(1);
+                }
+                Instruction::Branching(Branching::IfGoTo { .. }) => {
+                    branches = branches.saturating_add(1);
+                    conditional = conditional.saturating_add(1);
+                }
+                Instruction::Branching(Branching::Label { .. })
+                | Instruction::StackManipulation(_)
+                | Instruction::Functional(_)
+                | Instruction::Arithmetic(_)
+                | Instruction::Assembly(_)
+                | _ => {}
+            }
+        }
+        Self {
+            branches,
+            complexity: conditional.saturating_add(1),
+            stack_depth: stack_depth(body),
+        }
+    }
+}
+
+impl Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} branches, cyclomatic complexity {}, stack depth up to {}",
+            self.branches, self.complexity, self.stack_depth
+        )
+    }
+}
+
+/// How many values executing an [`Instruction`] adds to the stack, or
+/// removes from it if negative.
+///
+/// Inline assembly is assumed to leave the stack as it found it.
+pub(crate) fn stack_effect(instruction: &Instruction) -> isize {
+    match *instruction {
+        Instruction::StackManipulation(StackManipulation::Push { .. }) => 1,
+        Instruction::Arithmetic(arithmetic) if arithmetic.is_unary() => 0,
+        Instruction::StackManipulation(StackManipulation::Pop { .. })
+        | Instruction::Branching(Branching::IfGoTo { .. })
+        | Instruction::Arithmetic(_) => -1,
+        Instruction::Functional(Functional::Call { value, .. }) => 1_isize
+            .saturating_sub_unsigned(usize::from(
+                value.literal_representation(),
+            )),
+        Instruction::Branching(
+            Branching::Label { .. } | Branching::GoTo { .. },
+        )
+        | Instruction::Functional(
+            Functional::Function { .. } | Functional::Return,
+        )
+        | Instruction::Assembly(_)
+        | _ => 0,
+    }
+}
+
+/// Estimates the stack depth before each instruction of a function body.
+///
+/// Each path through the body is followed until it reaches an instruction
+/// already visited. Instructions that cannot be reached have no depth.
+pub(crate) fn stack_depths(
+    body: &[(usize, Instruction)],
+) -> Vec<Option<usize>> {
+    let labels: BTreeMap<&str, usize> = body
+        .iter()
+        .enumerate()
+        .filter_map(|(index, numbered): (usize, &(usize, Instruction))| {
+            match numbered.1 {
+                Instruction::Branching(Branching::Label { ref symbol }) => {
+                    Some((symbol.literal_representation(), index))
+                }
+                Instruction::Branching(_)
+                | Instruction::StackManipulation(_)
+                | Instruction::Functional(_)
+                | Instruction::Arithmetic(_)
+                | Instruction::Assembly(_)
+                | _ => None,
+            }
+        })
+        .collect();
+    let mut depths: Vec<Option<usize>> = vec![None; body.len()];
+    let mut pending: Vec<(usize, usize)> = vec![(0, 0)];
+    while let Some((index, depth)) = pending.pop() {
+        let Some(slot) = depths.get_mut(index) else {
+            continue;
+        };
+        if slot.is_some() {
+            continue;
+        }
+        *slot = Some(depth);
+        let Some(instruction) = body
+            .get(index)
+            .map(|numbered: &(usize, Instruction)| &numbered.1)
+        else {
+            continue;
+        };
+        let after: usize =
+            depth.saturating_add_signed(stack_effect(instruction));
+        let next: usize = index.saturating_add(1);
+        match *instruction {
+            Instruction::Branching(Branching::GoTo { ref symbol }) => {
+                if let Some(&target) =
+                    labels.get(symbol.literal_representation())
+                {
+                    pending.push((target, after));
+                }
+            }
+            Instruction::Branching(Branching::IfGoTo { ref symbol }) => {
+                if let Some(&target) =
+                    labels.get(symbol.literal_representation())
+                {
+                    pending.push((target, after));
+                }
+                pending.push((next, after));
+            }
+            Instruction::Functional(Functional::Return) => {}
+            Instruction::Branching(Branching::Label { .. })
+            | Instruction::StackManipulation(_)
+            | Instruction::Functional(_)
+            | Instruction::Arithmetic(_)
+            | Instruction::Assembly(_)
+            | _ => pending.push((next, after)),
+        }
+    }
+    depths
+}
+
+/// Splits the instructions of a file into the bodies of its functions, each
+/// starting with its `function` command. Any instructions before the first
+/// function form a body of their own.
+pub(crate) fn function_bodies(
+    instructions: &[(usize, Instruction)],
+) -> impl Iterator<Item = &[(usize, Instruction)]> {
+    instructions.chunk_by(|_, next: &(usize, Instruction)| {
+        !matches!(next.1, Instruction::Functional(Functional::Function { .. }))
+    })
+}
+
+/// Estimates the stack depth before each instruction of a file, as with
+/// [`stack_depths`] for each of its [`function_bodies`].
+pub(crate) fn file_stack_depths(
+    instructions: &[(usize, Instruction)],
+) -> Vec<Option<usize>> {
+    function_bodies(instructions)
+        .flat_map(stack_depths)
+        .collect()
+}
+
+/// Writes a comment describing an [`Instruction`] into `output`, along with
+/// its stack effect, the stack depth after it, given the `depth` before it if
+/// it can be reached, and its [`Provenance`].
+///
+/// A depth beyond the size of the `stack` is marked as overflowing it.
+///
+/// # Errors
+///
+/// Returns an error if writing to `output` fails.
+pub(crate) fn annotate<W: Write>(
+    output: &mut W,
+    provenance: &Provenance,
+    instruction: &Instruction,
+    depth: Option<usize>,
+    stack: UserSegment,
+) -> Result<(), HackError> {
+    let effect: isize = stack_effect(instruction);
+    let command: String = match *instruction {
+        Instruction::Assembly(_) => "inline assembly".to_owned(),
+        Instruction::StackManipulation(_)
+        | Instruction::Branching(_)
+        | Instruction::Functional(_)
+        | Instruction::Arithmetic(_)
+        | _ => instruction.to_string(),
+    };
+    let after: String = depth.map_or_else(
+        || "unreachable".to_owned(),
+        |depth: usize| {
+            let after: usize = depth.saturating_add_signed(effect);
+            if after > usize::from(stack.size()) {
+                format!("depth {after}, overflowing the stack")
+            } else {
+                format!("depth {after}")
+            }
+        },
+    );
+    writeln!(
+        output,
+        "// {command} (stack {effect:+}, {after}) at {provenance}"
+    )?;
+    Ok(())
+}
+
+/// Estimates the most values a function body keeps on the stack at once.
+fn stack_depth(body: &[(usize, Instruction)]) -> usize {
+    stack_depths(body)
+        .iter()
+        .zip(body)
+        .filter_map(|(depth, numbered): (&Option<usize>, _)| {
+            depth.map(|depth: usize| {
+                depth
+                    .max(depth.saturating_add_signed(stack_effect(&numbered.1)))
+            })
+        })
+        .max()
+        .unwrap_or_default()
+}
@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Namespace Module
+//!
+//! Renames namespaces during translation, so that third-party Hack VM
+//! libraries whose class names clash with a project's own can be used
+//! together without editing them.
+//!
+//! A mapping such as `Math=LibMath` renames the namespace `Math`: functions
+//! named `Math.multiply` are defined and called as `LibMath.multiply`
+//! instead, and the static variables of `Math.vm` are named `LibMath.i`.
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Display};
+use core::str::FromStr as _;
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::parser::{Functional, Instruction, Symbol};
+
+/// The namespaces to rename, and what to rename them to.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub(crate) struct Namespaces {
+    /// The new name of each renamed namespace, by its old name.
+    renames: BTreeMap<String, Symbol>,
+}
+
+impl Namespaces {
+    /// Parses a mapping written as `OLD=NEW`.
+    ///
+    /// If it is malformed, what it must be is described instead, to follow
+    /// "which" in an error message.
+    pub(crate) fn parse_mapping(
+        mapping: &str,
+    ) -> Result<(String, Symbol), String> {
+        let Some((old, new)) = mapping.split_once('=') else {
+            return Err("must be of the form OLD=NEW".to_owned());
+        };
+        for name in [old, new] {
+            if !Symbol::is_allowed_symbol(name) || name.contains('.') {
+                return Err(format!(
+                    "must name namespaces with symbols without dots, unlike \
+                    \"{name}\""
+                ));
+            }
+        }
+        let new: Symbol =
+            Symbol::from_str(new).map_err(|error| error.to_string())?;
+        Ok((old.to_owned(), new))
+    }
+
+    /// Renames the namespace `old` to `new`, replacing any earlier mapping
+    /// for it.
+    pub(crate) fn insert(&mut self, old: String, new: Symbol) {
+        let _previous: Option<Symbol> = self.renames.insert(old, new);
+    }
+
+    /// Adds the mappings from `other` for namespaces that are not already
+    /// renamed, such that mappings in `self` take precedence.
+    pub(crate) fn merge(&mut self, other: &Self) {
+        for (old, new) in &other.renames {
+            let _new: &mut Symbol = self
+                .renames
+                .entry(old.clone())
+                .or_insert_with(|| new.clone());
+        }
+    }
+
+    /// Whether no namespaces are renamed.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.renames.is_empty()
+    }
+
+    /// Gets what the namespace `name` is renamed to, if it is.
+    pub(crate) fn renamed(&self, name: &str) -> Option<&Symbol> {
+        self.renames.get(name)
+    }
+
+    /// Renames the namespace of the function an [`Instruction`] defines or
+    /// calls, if it has one that is renamed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the renamed function is not a valid [`Symbol`].
+    pub(crate) fn apply(
+        &self,
+        instruction: &mut Instruction,
+    ) -> Result<(), HackError> {
+        let Instruction::Functional(
+            Functional::Function { ref mut symbol, .. }
+            | Functional::Call { ref mut symbol, .. },
+        ) = *instruction
+        else {
+            return Ok(());
+        };
+        let (namespace, rest): (&str, &str) = symbol
+            .literal_representation()
+            .split_once('.')
+            .unwrap_or_else(|| (symbol.literal_representation(), ""));
+        if let Some(new) = self.renamed(namespace) {
+            let renamed: String = if rest.is_empty() {
+                new.literal_representation().to_owned()
+            } else {
+                format!("{new}.{rest}")
+            };
+            *symbol = Symbol::from_str(&renamed)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Namespaces {
+    /// Lists the mappings as `OLD=NEW`, separated by commas.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (old, new)) in self.renames.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{old}={new}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,456 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Outline Module
+//!
+//! The `outline` pass, which shrinks the assembly of a file by moving
+//! sequences repeated in it into subroutines, called wherever the sequences
+//! were; see [`crate::passes`]. It is meant for squeezing a program that is
+//! too big into the ROM, at the cost of running slower.
+//!
+//! A call stores where to return to in the variable `PREFIX$RETURN_ADDRESS`,
+//! named after the prefix of the file's static variables, and jumps to the
+//! subroutine, which jumps back through it when it is done:
+//!
+//! ```text
+//! @Main$RETURN.3, D=A, @Main$RETURN_ADDRESS, M=D, @Main$OUTLINED.0, 0;JMP
+//! (Main$RETURN.3)
+//! ```
+//!
+//! The subroutines are written at the start of the file, behind a jump over
+//! them. Only sequences that run straight through are outlined: they may not
+//! declare labels or jump, nor read `A`, `D`, or `M` before writing `A` or
+//! `D`, since calling the subroutine overwrites both, and the line after them
+//! must load `A` again, since returning overwrites it. Sequences are chosen
+//! from the longest down, and only when outlining them saves instructions.
+//!
+//! The labels and the variable are named after the file, so they cannot be
+//! declared by its VM code, whose labels are named after their function, nor
+//! be named like the labels generated for comparisons. A file whose assembly
+//! already uses names like them, as inline assembly may, is left alone.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use core::fmt::{self, Write as _};
+
+use crate::registers;
+
+/// The instructions a call takes.
+const CALL: usize = 6;
+
+/// The instructions a subroutine takes to return.
+const RETURN: usize = 3;
+
+/// The instructions taken to jump over the subroutines.
+const SKIP: usize = 2;
+
+/// The longest sequence outlined, in instructions.
+const MAX_LENGTH: usize = 128;
+
+/// Rewrites `assembly`, generated for the file whose static variables are
+/// prefixed with `prefix`, to call subroutines rather than repeat itself,
+/// as described in [`crate::outline`].
+pub(crate) fn outline(assembly: &str, prefix: &str) -> String {
+    let lines: Vec<&str> = assembly.lines().collect();
+    let codes: Vec<String> = lines
+        .iter()
+        .map(|line: &&str| registers::code(line))
+        .collect();
+    let in_use: bool = codes.iter().any(|code: &String| {
+        code.strip_prefix('@')
+            .or_else(|| code.strip_prefix('('))
+            .is_some_and(|symbol: &str| {
+                symbol.starts_with(&format!("{prefix}$OUTLINED"))
+                    || symbol.starts_with(&format!("{prefix}$RETURN"))
+            })
+    });
+    let sequences: Vec<(usize, Vec<usize>)> =
+        if in_use { Vec::new() } else { choose(&codes) };
+    if sequences.is_empty() {
+        return assembly.to_owned();
+    }
+
+    let variable: String = format!("{prefix}$RETURN_ADDRESS");
+    let mut output: String = String::with_capacity(assembly.len());
+    let mut calls: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+    let _written: fmt::Result =
+        writeln!(output, "@{prefix}$OUTLINED.END\n0;JMP");
+    for (number, &(length, ref starts)) in sequences.iter().enumerate() {
+        let _written: fmt::Result =
+            writeln!(output, "({prefix}$OUTLINED.{number})");
+        let first: usize = starts.first().copied().unwrap_or_default();
+        for line in lines.iter().skip(first).take(length) {
+            output.push_str(line);
+            output.push('\n');
+        }
+        let _written: fmt::Result = writeln!(output, "@{variable}\nA=M\n0;JMP");
+        for &start in starts {
+            let _previous: Option<(usize, usize)> =
+                calls.insert(start, (number, length));
+        }
+    }
+    let _written: fmt::Result = writeln!(output, "({prefix}$OUTLINED.END)");
+
+    let mut index: usize = 0;
+    let mut returns: usize = 0;
+    while let Some(line) = lines.get(index) {
+        if let Some(&(number, length)) = calls.get(&index) {
+            let _written: fmt::Result = writeln!(
+                output,
+                "@{prefix}$RETURN.{returns}\nD=A\n@{variable}\nM=D\n\
+                @{prefix}$OUTLINED.{number}\n0;JMP\n\
+                ({prefix}$RETURN.{returns})"
+            );
+            returns = returns.saturating_add(1);
+            index = index.saturating_add(length);
+            continue;
+        }
+        output.push_str(line);
+        output.push('\n');
+        index = index.saturating_add(1);
+    }
+    output
+}
+
+/// Chooses the sequences of `codes` to outline, from the longest down, as
+/// their lengths and the indices each occurrence starts at, in order.
+fn choose(codes: &[String]) -> Vec<(usize, Vec<usize>)> {
+    // Lines are compared by number rather than by their text.
+    let mut numbers: BTreeMap<&str, usize> = BTreeMap::new();
+    let numbered: Vec<usize> = codes
+        .iter()
+        .map(|code: &String| {
+            let next: usize = numbers.len();
+            *numbers.entry(code.as_str()).or_insert(next)
+        })
+        .collect();
+    let reloaded: Vec<bool> = reloaded(codes);
+    let mut starts_by_length: Vec<Vec<usize>> =
+        vec![Vec::new(); MAX_LENGTH.saturating_add(1)];
+    for start in 0..codes.len() {
+        for length in lengths(codes, &reloaded, start) {
+            if let Some(starts) = starts_by_length.get_mut(length) {
+                starts.push(start);
+            }
+        }
+    }
+
+    let mut taken: Vec<bool> = vec![false; codes.len()];
+    let mut chosen: Vec<(usize, Vec<usize>)> = Vec::new();
+    let mut saved: usize = 0;
+    for (length, starts) in starts_by_length.iter().enumerate().rev() {
+        let mut occurrences: BTreeMap<&[usize], Vec<usize>> = BTreeMap::new();
+        for &start in starts {
+            if is_free(&taken, start, length)
+                && let Some(window) =
+                    numbered.get(start..start.saturating_add(length))
+            {
+                occurrences.entry(window).or_default().push(start);
+            }
+        }
+        // Each occurrence is in a single group, so ordering the groups by
+        // their occurrences orders them by the first.
+        let mut groups: Vec<Vec<usize>> = occurrences.into_values().collect();
+        groups.sort_unstable();
+        for starts in groups {
+            // Occurrences may overlap each other, or sequences outlined
+            // since they were found.
+            let mut kept: Vec<usize> = Vec::new();
+            for start in starts {
+                let after_last: bool =
+                    kept.last().is_none_or(|&last: &usize| {
+                        start >= last.saturating_add(length)
+                    });
+                if after_last && is_free(&taken, start, length) {
+                    kept.push(start);
+                }
+            }
+            let before: usize = kept.len().saturating_mul(length);
+            let after: usize = kept
+                .len()
+                .saturating_mul(CALL)
+                .saturating_add(length)
+                .saturating_add(RETURN);
+            if before > after {
+                saved = saved.saturating_add(before.saturating_sub(after));
+                for &start in &kept {
+                    for flag in taken.iter_mut().skip(start).take(length) {
+                        *flag = true;
+                    }
+                }
+                chosen.push((length, kept));
+            }
+        }
+    }
+    if saved > SKIP { chosen } else { Vec::new() }
+}
+
+/// Whether none of the `length` instructions from `start` are `taken` by
+/// a sequence already outlined.
+fn is_free(taken: &[bool], start: usize, length: usize) -> bool {
+    taken
+        .get(start..start.saturating_add(length))
+        .is_some_and(|range: &[bool]| !range.contains(&true))
+}
+
+/// Finds, for each line of `codes`, whether the first instruction from it
+/// loads `A`, or there is none, such that a sequence may end before it.
+fn reloaded(codes: &[String]) -> Vec<bool> {
+    let mut reloaded: Vec<bool> = vec![true; codes.len()];
+    let mut next: bool = true;
+    for (code, flag) in codes.iter().zip(&mut reloaded).rev() {
+        if !code.is_empty() {
+            next = code.starts_with('@');
+        }
+        *flag = next;
+    }
+    reloaded
+}
+
+/// Gets the lengths of the sequences starting at `start` in `codes` that
+/// can be moved into a subroutine and save instructions by it, given which
+/// lines are `reloaded`.
+fn lengths(codes: &[String], reloaded: &[bool], start: usize) -> Vec<usize> {
+    let mut lengths: Vec<usize> = Vec::new();
+    let mut addressed: bool = false;
+    let mut computed: bool = false;
+    for (length, code) in (1..=MAX_LENGTH).zip(codes.iter().skip(start)) {
+        if code.is_empty() || code.starts_with('(') || code.contains(';') {
+            break;
+        }
+        if code.starts_with('@') {
+            addressed = true;
+        } else {
+            let (destination, computation): (&str, &str) =
+                code.split_once('=').unwrap_or(("", code));
+            let reads_address: bool =
+                computation.contains(['A', 'M']) || destination.contains('M');
+            if (reads_address && !addressed)
+                || (computation.contains('D') && !computed)
+            {
+                break;
+            }
+            addressed |= destination.contains('A');
+            computed |= destination.contains('D');
+        }
+        let end: usize = start.saturating_add(length);
+        if length > CALL && reloaded.get(end).is_none_or(|&flag: &bool| flag) {
+            lengths.push(length);
+        }
+    }
+    lengths
+}
+
+#[cfg(test)]
+mod tests {
+    //! Checks that outlined assembly still does what it did, and that the
+    //! labels it declares are its own.
+
+    use alloc::collections::BTreeMap;
+    use core::fmt::{self, Write as _};
+
+    use hack_vm_codegen::Options;
+    use hack_vm_codegen::labels::LabelScheme;
+    use hack_vm_codegen::translator::Translator;
+    use hack_vm_syntax::limits::Limits;
+    use hack_vm_syntax::mode::Mode;
+    use hack_vm_syntax::parser::Parser;
+
+    use super::outline;
+
+    /// The commands repeated in [`program`], which are long enough to
+    /// outline and compare their operands.
+    const REPEATED: &str = "push local 3\npush argument 2\nadd\npop this 1\n\
+        push constant 5\npush this 1\ngt\npop that 0\n";
+
+    /// Translates `vm_code` as the file `Main.vm`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `vm_code` cannot be parsed or translated.
+    fn translate(vm_code: &str) -> String {
+        let parser: Parser =
+            Parser::limited(vm_code.to_owned(), "Main.vm", Limits::default())
+                .unwrap();
+        let mut assembly: String = String::new();
+        for (line, instruction) in
+            parser.parse_numbered(Mode::default()).unwrap()
+        {
+            Translator::translate(
+                &mut assembly,
+                line,
+                &instruction,
+                "Main",
+                &Options::default(),
+            )
+            .unwrap();
+        }
+        assembly
+    }
+
+    /// The assembly of a file repeating [`REPEATED`] `times`, with other
+    /// commands between each.
+    fn program(times: usize) -> String {
+        let vm_code: String = (0..times).fold(
+            String::new(),
+            |mut vm_code: String, time: usize| {
+                let _written: fmt::Result =
+                    write!(vm_code, "{REPEATED}push constant {time}\nneg\n");
+                vm_code
+            },
+        );
+        translate(&vm_code)
+    }
+
+    /// Counts how often each label is declared in `assembly`.
+    fn declarations(assembly: &str) -> BTreeMap<&str, usize> {
+        let mut declarations: BTreeMap<&str, usize> = BTreeMap::new();
+        for line in assembly.lines() {
+            if let Some(label) = line
+                .strip_prefix('(')
+                .and_then(|rest| rest.strip_suffix(')'))
+            {
+                let count: &mut usize = declarations.entry(label).or_default();
+                *count = count.saturating_add(1);
+            }
+        }
+        declarations
+    }
+
+    /// Checks that every label of `assembly` is declared once, and that
+    /// every label it refers to is declared, other than the variable keeping
+    /// where to return to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a label is declared twice, or is missing.
+    fn assert_declared_once(assembly: &str) {
+        let declarations: BTreeMap<&str, usize> = declarations(assembly);
+        for (label, &count) in &declarations {
+            assert_eq!(count, 1, "({label}) is declared {count} times");
+        }
+        for line in assembly.lines() {
+            if let Some(label) = line.strip_prefix('@')
+                && (label.contains('$') || label.contains('_'))
+                && label != "Main$RETURN_ADDRESS"
+            {
+                assert!(
+                    declarations.contains_key(label),
+                    "{label} is referred to, but not declared"
+                );
+            }
+        }
+    }
+
+    /// Checks that `before` and `after` declare the same labels starting
+    /// with `prefix`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if they do not.
+    fn assert_same_labels(before: &str, after: &str, prefix: &str) {
+        let labels = |assembly| {
+            declarations(assembly)
+                .into_keys()
+                .filter(|label: &&str| label.starts_with(prefix))
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(labels(before), labels(after), "changed:\n{before}");
+    }
+
+    /// Checks that outlining `assembly` changed it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it did not.
+    fn assert_outlined(assembly: &str, outlined: &str) {
+        assert!(
+            outlined.contains("Main$OUTLINED.0")
+                && outlined.lines().count() < assembly.lines().count(),
+            "nothing was outlined in:\n{assembly}"
+        );
+    }
+
+    /// Every call returns to a label of its own, declared once, and the
+    /// labels generated for comparisons are kept as they were.
+    #[test]
+    fn labels_are_declared_once() {
+        let assembly: String = program(4);
+        let outlined: String = outline(&assembly, "Main");
+        assert_outlined(&assembly, &outlined);
+        assert_declared_once(&outlined);
+        for (label, _) in declarations(&assembly) {
+            assert_declared_once(&format!("@{label}\n{outlined}"));
+        }
+    }
+
+    /// Labels are still declared once after they are renumbered, which
+    /// leaves the labels of outlining alone.
+    #[test]
+    fn renumbering_after_keeps_labels() {
+        let mut labels: LabelScheme = LabelScheme::default();
+        labels.set_renumber(true);
+        let assembly: String = program(4);
+        let outlined: String = outline(&assembly, "Main");
+        let renumbered: String = labels.renumber(&outlined, 0);
+        assert_declared_once(&renumbered);
+        assert_same_labels(&outlined, &renumbered, "Main$");
+    }
+
+    /// Assembly already using the names of outlining, as inline assembly
+    /// may, is left alone rather than declaring them twice.
+    #[test]
+    fn names_in_use_are_left_alone() {
+        for used in ["(Main$RETURN.0)\n", "@Main$OUTLINED.END\n0;JMP\n"] {
+            let assembly: String = format!("{}{used}", program(4));
+            assert_same_labels(&assembly, &outline(&assembly, "Main"), "");
+        }
+    }
+
+    /// Running the outlined assembly leaves memory as running it did, so
+    /// every call returned where it was made, including once its labels are
+    /// renumbered.
+    #[cfg(feature = "fuzz")]
+    #[test]
+    fn calls_return_where_they_were_made() {
+        use crate::cpu::Cpu;
+
+        /// Checks that running `before` and `after` leaves the same memory.
+        ///
+        /// # Panics
+        ///
+        /// Panics if it does not.
+        fn assert_same_memory(before: &str, after: &str) {
+            assert!(run(before) == run(after), "memory changed:\n{after}");
+        }
+
+        /// Runs `assembly` from the usual pointers, and gets the memory it
+        /// leaves, other than its variables.
+        ///
+        /// # Panics
+        ///
+        /// Panics if it cannot be assembled or run.
+        fn run(assembly: &str) -> Vec<i16> {
+            let mut cpu: Cpu = assembly.parse().unwrap();
+            for (pointer, base) in
+                [256, 300, 400, 3000, 3010].iter().enumerate()
+            {
+                cpu.set(u16::try_from(pointer).unwrap(), *base);
+            }
+            let _cycles: usize = cpu.run().unwrap();
+            (0..16)
+                .chain(256..3100)
+                .map(|address: u16| cpu.get(address))
+                .collect()
+        }
+
+        let mut labels: LabelScheme = LabelScheme::default();
+        labels.set_renumber(true);
+        let assembly: String = program(4);
+        let outlined: String = outline(&assembly, "Main");
+        assert_outlined(&assembly, &outlined);
+        assert_same_memory(&assembly, &outlined);
+        assert_same_memory(&assembly, &labels.renumber(&outlined, 0));
+    }
+}
@@ -0,0 +1,547 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Passes Module
+//!
+//! Runs the passes that check and transform each file, in order, so that a
+//! new analysis or optimization is added as a [`Pass`] rather than wired into
+//! translation. Passes over the VM instructions run before they are
+//! translated, and passes over the assembly after:
+//!
+//! 1. `lint`: Warns about code that is likely to break once it is combined
+//!    with other files; see [`crate::lints`]. It changes nothing.
+//! 2. `rewrite`: Applies the rewrite rules given with `--rewrites`; see
+//!    [`crate::rewrite`].
+//! 3. `namespace`: Renames the namespaces given with `--namespace`; see
+//!    [`crate::namespace`].
+//! 4. `strength-reduce`: Folds constants into the arithmetic using them; see
+//!    [`crate::reduce`]. It is off unless enabled with
+//!    `--passes +strength-reduce`.
+//! 5. `allocate-registers`: Reuses the addresses kept in `R13` to `R15`
+//!    rather than computing them again; see [`crate::registers`]. It is off
+//!    unless enabled with `--passes +allocate-registers`.
+//! 6. `outline`: Moves sequences of assembly repeated in a file into
+//!    subroutines; see [`crate::outline`]. It is off unless enabled with
+//!    `--passes +outline`.
+//! 7. `renumber-labels`: Renumbers the generated labels of the assembly,
+//!    with `--renumber-labels`.
+//!
+//! A pass that has nothing to do, such as `rewrite` without any rules, is
+//! skipped. Each pass that runs reports how many instructions or lines of
+//! assembly it changed, which is traced with the `trace` feature:
+//!
+//! ```text
+//! DEBUG pass rewrite changed 2 instructions
+//! ```
+//!
+//! With `--passes`, passes can be disabled, enabled again, or reordered, to
+//! find which one breaks a program. A pass named with a `-`, as in
+//! `--passes -rewrite`, is disabled, and one named with a `+` is enabled,
+//! or enabled again, where it usually runs. Naming passes without either
+//! runs only those, in the order given, as in `--passes namespace,rewrite`.
+//! Passes over the assembly always run after translation, wherever they are
+//! named.
+//!
+//! With `--print-after <PASS>`, what a pass produced is printed to stderr
+//! for each file, to see what it did:
+//!
+//! ```text
+//! Main.vm: after rewrite, which changed 2 instructions:
+//! Main.vm:3:1: push constant 7
+//! ```
+//!
+//! The cache is not used while `--print-after` is given, so that every file
+//! goes through the passes and is printed.
+
+use core::fmt::{self, Display};
+use core::str::FromStr;
+use std::io::{self, Write as _};
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::parser::Instruction;
+use hack_vm_syntax::provenance::Provenance;
+use hack_vm_syntax::trace;
+
+use crate::lints::{self, Warning};
+use crate::{Settings, outline, reduce, registers};
+
+/// What a [`Pass`] works on.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub(crate) enum Ir {
+    /// The VM instructions of a file, each with where it was written.
+    Vm,
+    /// The assembly generated for a file.
+    Assembly,
+}
+
+/// A single pass over a file.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Pass {
+    /// Warns about likely mistakes.
+    Lint,
+    /// Applies the rewrite rules.
+    Rewrite,
+    /// Renames namespaces.
+    Namespace,
+    /// Folds constants into arithmetic.
+    StrengthReduce,
+    /// Reuses addresses kept in the general registers.
+    AllocateRegisters,
+    /// Moves repeated assembly into subroutines.
+    Outline,
+    /// Renumbers generated labels.
+    RenumberLabels,
+}
+
+impl Pass {
+    /// Every [`Pass`], in the order they usually run.
+    pub(crate) const ALL: [Self; 7] = [
+        Self::Lint,
+        Self::Rewrite,
+        Self::Namespace,
+        Self::StrengthReduce,
+        Self::AllocateRegisters,
+        Self::Outline,
+        Self::RenumberLabels,
+    ];
+
+    /// Gets the name of this [`Pass`] as used in options and reports.
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::Lint => "lint",
+            Self::Rewrite => "rewrite",
+            Self::Namespace => "namespace",
+            Self::StrengthReduce => "strength-reduce",
+            Self::AllocateRegisters => "allocate-registers",
+            Self::Outline => "outline",
+            Self::RenumberLabels => "renumber-labels",
+        }
+    }
+
+    /// Gets what this [`Pass`] works on.
+    pub(crate) const fn ir(self) -> Ir {
+        match self {
+            Self::Lint | Self::Rewrite | Self::Namespace => Ir::Vm,
+            Self::StrengthReduce
+            | Self::AllocateRegisters
+            | Self::Outline
+            | Self::RenumberLabels => Ir::Assembly,
+        }
+    }
+
+    /// Whether this [`Pass`] runs unless it is disabled, rather than only
+    /// once it is enabled.
+    const fn is_on_by_default(self) -> bool {
+        match self {
+            Self::StrengthReduce | Self::AllocateRegisters | Self::Outline => {
+                false
+            }
+            Self::Lint
+            | Self::Rewrite
+            | Self::Namespace
+            | Self::RenumberLabels => true,
+        }
+    }
+
+    /// Whether this [`Pass`] has anything to do with the given [`Settings`].
+    fn is_needed(self, settings: &Settings) -> bool {
+        match self {
+            Self::Lint
+            | Self::StrengthReduce
+            | Self::AllocateRegisters
+            | Self::Outline => true,
+            Self::Rewrite => !settings.rewrites.is_empty(),
+            Self::Namespace => !settings.namespaces.is_empty(),
+            Self::RenumberLabels => settings.translation.labels().renumbers(),
+        }
+    }
+}
+
+impl FromStr for Pass {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|pass: &Self| pass.name() == s)
+            .ok_or_else(|| {
+                HackError::FromStrError(format!(
+                    "\"{s}\" is not a recognized pass, expected one of {}",
+                    Self::ALL
+                        .map(|pass: Self| format!("\"{}\"", pass.name()))
+                        .join(", ")
+                ))
+            })
+    }
+}
+
+impl Display for Pass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Which passes run, in the order they run.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct Pipeline {
+    /// The passes that run, in order.
+    passes: Vec<Pass>,
+}
+
+impl Pipeline {
+    /// Changes which passes run according to a list of passes separated by
+    /// commas, as given to `--passes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::FromStrError`] if a pass is not recognized, or
+    /// is named twice without a `+` or `-`.
+    pub(crate) fn apply(&mut self, list: &str) -> Result<(), HackError> {
+        let mut ordered: bool = false;
+        for entry in list.split(',') {
+            if let Some(name) = entry.strip_prefix('-') {
+                let pass: Pass = Pass::from_str(name)?;
+                self.passes.retain(|kept: &Pass| *kept != pass);
+                continue;
+            }
+            if let Some(name) = entry.strip_prefix('+') {
+                self.enable(Pass::from_str(name)?);
+                continue;
+            }
+            let pass: Pass = Pass::from_str(entry)?;
+            if !ordered {
+                self.passes.clear();
+                ordered = true;
+            }
+            if self.passes.contains(&pass) {
+                return Err(HackError::FromStrError(format!(
+                    "the pass \"{pass}\" is named twice"
+                )));
+            }
+            self.passes.push(pass);
+        }
+        Ok(())
+    }
+
+    /// Whether the passes that run by default run, in the order they usually
+    /// run.
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Enables `pass` where it usually runs, before the first enabled pass
+    /// that usually runs after it, unless it is already enabled.
+    fn enable(&mut self, pass: Pass) {
+        if self.passes.contains(&pass) {
+            return;
+        }
+        let index: usize = self
+            .passes
+            .iter()
+            .position(|enabled: &Pass| *enabled > pass)
+            .unwrap_or(self.passes.len());
+        self.passes.insert(index, pass);
+    }
+}
+
+impl Default for Pipeline {
+    /// Every [`Pass`] that runs by default, in the order they usually run.
+    fn default() -> Self {
+        Self {
+            passes: Pass::ALL
+                .into_iter()
+                .filter(|pass: &Pass| pass.is_on_by_default())
+                .collect(),
+        }
+    }
+}
+
+impl Display for Pipeline {
+    /// Lists the passes, separated by commas.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, pass) in self.passes.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{pass}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What a [`Pass`] changed in a file.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub(crate) struct PassReport {
+    /// The pass that ran.
+    pass: Pass,
+    /// How many instructions or lines of assembly it changed.
+    changed: usize,
+}
+
+impl PassReport {
+    /// Describes what was changed, as `N instructions`, or lines for a pass
+    /// over the assembly.
+    fn changes(&self) -> String {
+        let what: &str = match self.pass.ir() {
+            Ir::Vm => "instructions",
+            Ir::Assembly => "lines",
+        };
+        format!("{} {what}", self.changed)
+    }
+}
+
+impl Display for PassReport {
+    /// Describes the report as `pass NAME changed N instructions`, or lines
+    /// for a pass over the assembly.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pass {} changed {}", self.pass, self.changes())
+    }
+}
+
+/// The file the passes run on.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub(crate) struct Unit<'file> {
+    /// The file, as displayed in warnings.
+    file: &'file str,
+    /// The stem of the file its functions are expected to be named after,
+    /// if it was read from a file.
+    stem: Option<&'file str>,
+    /// What its static variables are prefixed with, which is unique to it
+    /// within the program.
+    static_prefix: &'file str,
+    /// Whether it is the entry file of the program.
+    entry: bool,
+    /// What the numbers of its generated labels start from.
+    label_offset: usize,
+}
+
+impl<'file> Unit<'file> {
+    /// Describes the file displayed as `file`, whose functions are expected
+    /// to be named after `stem`, if it was read from a file, whose static
+    /// variables are prefixed with `static_prefix`, which is the `entry` file
+    /// of the program or not, and whose generated labels are numbered from
+    /// `label_offset`.
+    pub(crate) const fn new(
+        file: &'file str,
+        stem: Option<&'file str>,
+        static_prefix: &'file str,
+        entry: bool,
+        label_offset: usize,
+    ) -> Self {
+        Self {
+            file,
+            stem,
+            static_prefix,
+            entry,
+            label_offset,
+        }
+    }
+}
+
+/// Runs the passes over a single file, collecting the warnings of `lint`
+/// and printing what the passes produce, if asked to.
+#[derive(Debug, Clone)]
+pub(crate) struct PassManager<'run> {
+    /// The settings the file is translated with.
+    settings: &'run Settings,
+    /// The file the passes run on.
+    unit: Unit<'run>,
+    /// The warnings of the `lint` pass.
+    warnings: Vec<Warning>,
+}
+
+impl<'run> PassManager<'run> {
+    /// Prepares to run the passes over `unit`, with the given [`Settings`].
+    pub(crate) const fn new(
+        settings: &'run Settings,
+        unit: Unit<'run>,
+    ) -> Self {
+        Self {
+            settings,
+            unit,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Runs the passes over the VM `instructions` of the file.
+    ///
+    /// # Errors
+    ///
+    /// Any error from a pass is propagated.
+    pub(crate) fn run_vm(
+        &mut self,
+        mut instructions: Vec<(Provenance, Instruction)>,
+    ) -> Result<Vec<(Provenance, Instruction)>, HackError> {
+        for pass in self.passes(Ir::Vm) {
+            let mut report: PassReport = PassReport { pass, changed: 0 };
+            if pass.is_needed(self.settings) {
+                let before: Vec<(Provenance, Instruction)> =
+                    instructions.clone();
+                instructions = self.vm_pass(pass, instructions)?;
+                report.changed = changed(&before, &instructions);
+                trace::event!(Debug, "{report}");
+            }
+            if self.settings.print_after.contains(&pass) {
+                let mut stderr: io::StderrLock<'_> = io::stderr().lock();
+                let _printed: io::Result<()> =
+                    self.print_header(&mut stderr, &report);
+                for located in &instructions {
+                    let _printed: io::Result<()> =
+                        writeln!(stderr, "{}: {}", located.0, located.1);
+                }
+            }
+        }
+        Ok(instructions)
+    }
+
+    /// Runs the passes over the `assembly` generated for the file.
+    pub(crate) fn run_assembly(&self, mut assembly: String) -> String {
+        for pass in self.passes(Ir::Assembly) {
+            let mut report: PassReport = PassReport { pass, changed: 0 };
+            if pass.is_needed(self.settings) {
+                let before: String = assembly.clone();
+                assembly = self.assembly_pass(pass, assembly);
+                report.changed = changed(
+                    &before.lines().collect::<Vec<&str>>(),
+                    &assembly.lines().collect::<Vec<&str>>(),
+                );
+                trace::event!(Debug, "{report}");
+            }
+            if self.settings.print_after.contains(&pass) {
+                let mut stderr: io::StderrLock<'_> = io::stderr().lock();
+                let _printed: io::Result<()> = self
+                    .print_header(&mut stderr, &report)
+                    .and_then(|()| write!(stderr, "{assembly}"));
+            }
+        }
+        assembly
+    }
+
+    /// Takes the warnings of the `lint` pass.
+    pub(crate) fn into_warnings(self) -> Vec<Warning> {
+        self.warnings
+    }
+
+    /// The enabled passes over the given [`Ir`], in the order they run.
+    fn passes(&self, ir: Ir) -> impl Iterator<Item = Pass> + use<'run> {
+        self.settings
+            .passes
+            .passes
+            .iter()
+            .copied()
+            .filter(move |pass: &Pass| pass.ir() == ir)
+    }
+
+    /// Runs a pass over the VM `instructions` of the file.
+    fn vm_pass(
+        &mut self,
+        pass: Pass,
+        mut instructions: Vec<(Provenance, Instruction)>,
+    ) -> Result<Vec<(Provenance, Instruction)>, HackError> {
+        match pass {
+            Pass::Lint => {
+                let numbered: Vec<(usize, Instruction)> =
+                    crate::numbered(&instructions);
+                let warnings: Vec<Warning> = lints::lint(
+                    self.unit.file,
+                    self.unit.stem,
+                    self.unit.static_prefix,
+                    &numbered,
+                    self.settings.translation.labels(),
+                    self.unit.entry,
+                );
+                self.settings.print_warnings(&warnings);
+                self.warnings.extend(warnings);
+            }
+            Pass::Rewrite => {
+                instructions = self
+                    .settings
+                    .rewrites
+                    .apply(instructions, self.settings.mode)?;
+            }
+            Pass::Namespace => {
+                for located in &mut instructions {
+                    self.settings.namespaces.apply(&mut located.1)?;
+                }
+            }
+            Pass::StrengthReduce
+            | Pass::AllocateRegisters
+            | Pass::Outline
+            | Pass::RenumberLabels => {}
+        }
+        Ok(instructions)
+    }
+
+    /// Runs a pass over the `assembly` generated for the file.
+    fn assembly_pass(&self, pass: Pass, assembly: String) -> String {
+        match pass {
+            Pass::RenumberLabels => self
+                .settings
+                .translation
+                .labels()
+                .renumber(&assembly, self.unit.label_offset),
+            Pass::StrengthReduce => reduce::reduce(&assembly),
+            Pass::AllocateRegisters => registers::allocate(&assembly),
+            Pass::Outline => {
+                outline::outline(&assembly, self.unit.static_prefix)
+            }
+            Pass::Lint | Pass::Rewrite | Pass::Namespace => assembly,
+        }
+    }
+
+    /// Writes the line introducing what a pass produced, given its
+    /// [`PassReport`], to `stderr`.
+    fn print_header(
+        &self,
+        stderr: &mut io::StderrLock<'_>,
+        report: &PassReport,
+    ) -> io::Result<()> {
+        writeln!(
+            stderr,
+            "{}: after {}, which changed {}:",
+            self.unit.file,
+            report.pass,
+            report.changes()
+        )
+    }
+}
+
+/// Estimates how many items were changed, added, or removed to turn `before`
+/// into `after`.
+///
+/// Past the items they start and end with in common, items at the same
+/// position are compared if as many remain in both, and otherwise every
+/// remaining item of the longer one is counted.
+fn changed<T: PartialEq>(before: &[T], after: &[T]) -> usize {
+    let prefix: usize = before
+        .iter()
+        .zip(after)
+        .take_while(|&(before, after): &(&T, &T)| before == after)
+        .count();
+    let (before, after): (&[T], &[T]) = (
+        before.get(prefix..).unwrap_or_default(),
+        after.get(prefix..).unwrap_or_default(),
+    );
+    let suffix: usize = before
+        .iter()
+        .rev()
+        .zip(after.iter().rev())
+        .take_while(|&(before, after): &(&T, &T)| before == after)
+        .count();
+    let before: &[T] = before
+        .get(..before.len().saturating_sub(suffix))
+        .unwrap_or_default();
+    let after: &[T] = after
+        .get(..after.len().saturating_sub(suffix))
+        .unwrap_or_default();
+    if before.len() == after.len() {
+        before
+            .iter()
+            .zip(after)
+            .filter(|&(before, after): &(&T, &T)| before != after)
+            .count()
+    } else {
+        before.len().max(after.len())
+    }
+}
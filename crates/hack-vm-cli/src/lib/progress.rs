@@ -0,0 +1,147 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Progress Module
+//!
+//! Shows how far a run translating many files has come, on a single status
+//! line on stderr that is redrawn as each file is started:
+//!
+//! ```text
+//! [ 42/300]  14% ETA 9s Main.vm
+//! ```
+//!
+//! The estimate assumes the remaining files take as long as those so far did
+//! on average. The line is only shown when stderr is a terminal and more than
+//! one file is translated, and can be hidden with `--quiet`. It is cleared
+//! before a warning is printed and once the run is over, so it never mixes
+//! with what is printed in its place.
+
+use core::time::Duration;
+use std::io::{self, IsTerminal as _, Write as _};
+use std::path::Path;
+use std::time::Instant;
+
+use hack_vm_syntax::paths;
+
+/// Whether progress is shown.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub(crate) enum ProgressPolicy {
+    /// Progress is shown if stderr is a terminal.
+    #[default]
+    Auto,
+    /// Progress is never shown, as with `--quiet`.
+    Quiet,
+}
+
+/// The status line of a run.
+#[derive(Debug)]
+pub(crate) struct Progress {
+    /// The number of files to translate.
+    total: usize,
+    /// The number of files translated so far.
+    done: usize,
+    /// When the run started.
+    start: Instant,
+    /// When the line was last drawn, if it was.
+    drawn: Option<Instant>,
+    /// Whether the line is shown at all.
+    shown: bool,
+}
+
+impl Progress {
+    /// The least time between redrawing the line, so that translating many
+    /// small files is not slowed down by drawing it.
+    const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Starts showing the progress of translating `total` files, if `policy`
+    /// allows it and stderr is a terminal.
+    pub(crate) fn new(total: usize, policy: ProgressPolicy) -> Self {
+        Self {
+            total,
+            done: 0,
+            start: Instant::now(),
+            drawn: None,
+            shown: policy == ProgressPolicy::Auto
+                && total > 1
+                && io::stderr().is_terminal(),
+        }
+    }
+
+    /// Records that translating `file` has started, redrawing the line if it
+    /// has not been for a while.
+    pub(crate) fn start(&mut self, file: &Path) {
+        if !self.shown
+            || self.drawn.is_some_and(|drawn: Instant| {
+                drawn.elapsed() < Self::REDRAW_INTERVAL
+            })
+        {
+            self.done = self.done.saturating_add(1);
+            return;
+        }
+        let percent: usize = self
+            .done
+            .saturating_mul(100)
+            .checked_div(self.total)
+            .unwrap_or_default();
+        let width: usize = self.total.to_string().len();
+        let eta: String =
+            self.eta().map_or_else(String::new, |eta: Duration| {
+                format!(" ETA {}", Self::duration(eta))
+            });
+        let line: String = format!(
+            "[{:>width$}/{}] {percent:>3}%{eta} {}",
+            self.done.saturating_add(1),
+            self.total,
+            paths::display(file)
+        );
+        Self::draw(&line);
+        self.drawn = Some(Instant::now());
+        self.done = self.done.saturating_add(1);
+    }
+
+    /// Clears the line, once the run is over.
+    pub(crate) fn finish(&self) {
+        if self.drawn.is_some() {
+            Self::draw("");
+        }
+    }
+
+    /// Clears the line, if stderr is a terminal it may be shown on, so that
+    /// something else can be printed in its place. It is drawn again as a
+    /// later file is started.
+    pub(crate) fn clear() {
+        if io::stderr().is_terminal() {
+            Self::draw("");
+        }
+    }
+
+    /// Estimates the time left, once a file has been translated.
+    fn eta(&self) -> Option<Duration> {
+        let done: u32 =
+            u32::try_from(self.done).ok().filter(|&done| done > 0)?;
+        let left: u32 =
+            u32::try_from(self.total.saturating_sub(self.done)).ok()?;
+        self.start.elapsed().checked_div(done)?.checked_mul(left)
+    }
+
+    /// Formats `duration` in whole seconds, and minutes if there are any.
+    fn duration(duration: Duration) -> String {
+        let seconds: u64 = duration.as_secs();
+        match seconds.checked_div(60).unwrap_or_default() {
+            0 => format!("{seconds}s"),
+            minutes => format!(
+                "{minutes}m{:02}s",
+                seconds.checked_rem(60).unwrap_or_default()
+            ),
+        }
+    }
+
+    /// Replaces the status line with `line`.
+    fn draw(line: &str) {
+        let mut stderr: io::StderrLock<'_> = io::stderr().lock();
+        // The line is only a convenience, so failing to draw it is ignored.
+        let _drawn: io::Result<()> =
+            write!(stderr, "\r\x1b[K{line}").and_then(|()| stderr.flush());
+    }
+}
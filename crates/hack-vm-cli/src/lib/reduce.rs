@@ -0,0 +1,251 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Reduce Module
+//!
+//! The `strength-reduce` pass, which folds a constant pushed only to be
+//! added to, subtracted from, or combined with the value under it into a
+//! single change to that value in place; see [`crate::passes`].
+//!
+//! Compiled Jack code does this for every field and array access, as with
+//! `push constant 2` and `add`, which is written as:
+//!
+//! ```text
+//! @2, D=A, @SP, A=M-1, M=D+M
+//! ```
+//!
+//! rather than pushing 2 and popping it again. Adding or subtracting 1 does
+//! not even load the constant into `D`, unless the assembly after it reads
+//! `D` before writing it, as inline assembly may.
+
+use crate::registers;
+
+/// The lines of `push constant c`, after the one loading `c`.
+const PUSH: [&str; 5] = ["D=A", "@SP", "A=M", "M=D", "@SP"];
+
+/// The last line of `push constant c`, after [`PUSH`].
+const INCREMENT: &str = "M=M+1";
+
+/// The lines of a binary command before the one computing its result.
+const POP: [&str; 4] = ["@SP", "AM=M-1", "D=M", "A=A-1"];
+
+/// The lines computing the result of the binary commands that are folded:
+/// `add`, `sub`, `and`, and `or`.
+const FOLDED: [&str; 4] = ["M=D+M", "M=M-D", "M=D&M", "M=D|M"];
+
+/// Rewrites `assembly` to fold constants into the commands that use them, as
+/// described in [`crate::reduce`].
+pub(crate) fn reduce(assembly: &str) -> String {
+    let lines: Vec<&str> = assembly.lines().collect();
+    let codes: Vec<String> = lines
+        .iter()
+        .map(|line: &&str| registers::code(line))
+        .collect();
+    let mut output: String = String::with_capacity(assembly.len());
+    let mut index: usize = 0;
+    while let Some(line) = lines.get(index) {
+        if let Some((constant, end)) = push(&codes, index)
+            && let Some(start) = skip_comments(&codes, end)
+            && let Some(result) = binary(&codes, start)
+        {
+            // Keep the comments between the commands, such as annotations.
+            for comment in lines.iter().take(start).skip(end) {
+                output.push_str(comment);
+                output.push('\n');
+            }
+            let after: usize =
+                start.saturating_add(POP.len()).saturating_add(1);
+            output.push_str(&fold(constant, result, reads_data(&codes, after)));
+            index = after;
+            continue;
+        }
+        output.push_str(line);
+        output.push('\n');
+        index = index.saturating_add(1);
+    }
+    output
+}
+
+/// Matches `push constant c` at `index` in `codes`, as `c` and the index of
+/// the line after it.
+fn push(codes: &[String], index: usize) -> Option<(u16, usize)> {
+    let constant: u16 = codes.get(index)?.strip_prefix('@')?.parse().ok()?;
+    let start: usize = index.saturating_add(1);
+    let end: usize = start.saturating_add(PUSH.len());
+    let matches: bool =
+        codes.get(start..end)? == PUSH && codes.get(end)? == INCREMENT;
+    matches.then_some((constant, end.saturating_add(1)))
+}
+
+/// Gets the index of the first line from `index` in `codes` with code on it.
+fn skip_comments(codes: &[String], index: usize) -> Option<usize> {
+    codes
+        .iter()
+        .skip(index)
+        .position(|code: &String| !code.is_empty())
+        .map(|position: usize| index.saturating_add(position))
+}
+
+/// Matches a binary command that is folded at `index` in `codes`, as the
+/// line computing its result.
+fn binary(codes: &[String], index: usize) -> Option<&str> {
+    let end: usize = index.saturating_add(POP.len());
+    let result: &str = codes.get(end)?;
+    (codes.get(index..end)? == POP && FOLDED.contains(&result))
+        .then_some(result)
+}
+
+/// Writes the lines that apply `result` to the top of the stack and the
+/// `constant`, given whether the assembly after them `reads` `D`.
+fn fold(constant: u16, result: &str, reads: bool) -> String {
+    match (constant, result) {
+        // The command left the constant in `D`, so `D` must still be loaded
+        // with it if anything reads it afterwards.
+        (1, "M=D+M") if !reads => "@SP\nA=M-1\nM=M+1\n".to_owned(),
+        (1, "M=M-D") if !reads => "@SP\nA=M-1\nM=M-1\n".to_owned(),
+        _ => format!("@{constant}\nD=A\n@SP\nA=M-1\n{result}\n"),
+    }
+}
+
+/// Whether the instructions of `codes` from `index` may read `D` before
+/// writing it.
+///
+/// The translation of every command writes `D` before reading it, but
+/// inline assembly need not. Whatever may run after a jump, or after the
+/// end of the file, is assumed to read it.
+fn reads_data(codes: &[String], index: usize) -> bool {
+    for code in codes.iter().skip(index) {
+        if code.is_empty() || code.starts_with(['@', '(']) {
+            continue;
+        }
+        let (destination, rest): (&str, &str) =
+            code.split_once('=').unwrap_or(("", code));
+        let (computation, jump): (&str, &str) =
+            rest.split_once(';').unwrap_or((rest, ""));
+        if computation.contains('D') || !jump.is_empty() {
+            return true;
+        }
+        if destination.contains('D') {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    //! Checks each fold, and what is left alone.
+
+    use super::{INCREMENT, POP, PUSH, reduce};
+
+    /// The assembly of `push constant constant`.
+    fn push(constant: u16) -> String {
+        format!("@{constant}\n{}\n{INCREMENT}\n", PUSH.join("\n"))
+    }
+
+    /// The assembly of a binary command computing `result`.
+    fn binary(result: &str) -> String {
+        format!("{}\n{result}\n", POP.join("\n"))
+    }
+
+    /// The assembly of `push constant 2`, which writes `D` before reading it,
+    /// as every command does.
+    fn next() -> String {
+        push(2)
+    }
+
+    /// Checks that `assembly` is reduced to `reduced`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is not.
+    fn assert_reduces(assembly: &str, reduced: &str) {
+        assert_eq!(reduce(assembly), reduced, "reducing:\n{assembly}");
+    }
+
+    /// Checks that `push constant constant` and the binary command computing
+    /// `result`, followed by [`next`], fold into `folded`.
+    fn assert_folds(constant: u16, result: &str, folded: &str) {
+        assert_reduces(
+            &format!("{}{}{}", push(constant), binary(result), next()),
+            &format!("{folded}{}", next()),
+        );
+    }
+
+    /// Zero is folded as any other constant is.
+    #[test]
+    fn zero_is_loaded() {
+        assert_folds(0, "M=D+M", "@0\nD=A\n@SP\nA=M-1\nM=D+M\n");
+        assert_folds(0, "M=D|M", "@0\nD=A\n@SP\nA=M-1\nM=D|M\n");
+    }
+
+    /// Adding or subtracting one changes the top of the stack in place.
+    #[test]
+    fn one_is_added_in_place() {
+        assert_folds(1, "M=D+M", "@SP\nA=M-1\nM=M+1\n");
+        assert_folds(1, "M=M-D", "@SP\nA=M-1\nM=M-1\n");
+    }
+
+    /// One is still loaded to be combined with `and` or `or`.
+    #[test]
+    fn one_is_loaded_for_logic() {
+        assert_folds(1, "M=D&M", "@1\nD=A\n@SP\nA=M-1\nM=D&M\n");
+        assert_folds(1, "M=D|M", "@1\nD=A\n@SP\nA=M-1\nM=D|M\n");
+    }
+
+    /// Powers of two, and any other constant, are loaded into `D`.
+    #[test]
+    fn powers_of_two_are_loaded() {
+        for constant in [2, 4, 1024, 0x4000] {
+            for result in ["M=D+M", "M=M-D", "M=D&M", "M=D|M"] {
+                assert_folds(
+                    constant,
+                    result,
+                    &format!("@{constant}\nD=A\n@SP\nA=M-1\n{result}\n"),
+                );
+            }
+        }
+    }
+
+    /// One is still loaded into `D` when the assembly after it reads `D`,
+    /// as the command left it there.
+    #[test]
+    fn one_is_loaded_when_read_after() {
+        for after in ["@R13\nM=D\n", "(LOOP)\nD=D+1\n", "@END\nD;JGT\n"] {
+            assert_reduces(
+                &format!("{}{}{after}", push(1), binary("M=D+M")),
+                &format!("@1\nD=A\n@SP\nA=M-1\nM=D+M\n{after}"),
+            );
+        }
+    }
+
+    /// One is loaded at the end of the file, as whatever follows it may read
+    /// `D`.
+    #[test]
+    fn one_is_loaded_at_the_end() {
+        assert_reduces(
+            &format!("{}{}", push(1), binary("M=D+M")),
+            "@1\nD=A\n@SP\nA=M-1\nM=D+M\n",
+        );
+    }
+
+    /// Comments between the push and the command are kept.
+    #[test]
+    fn comments_are_kept() {
+        assert_reduces(
+            &format!("{}// add\n{}{}", push(3), binary("M=D+M"), next()),
+            &format!("// add\n@3\nD=A\n@SP\nA=M-1\nM=D+M\n{}", next()),
+        );
+    }
+
+    /// Pushes followed by anything but a folded command are left alone.
+    #[test]
+    fn other_commands_are_left_alone() {
+        for result in ["D=M-D", "M=D"] {
+            let assembly: String =
+                format!("{}{}{}", push(1), binary(result), next());
+            assert_reduces(&assembly, &assembly);
+        }
+    }
+}
@@ -0,0 +1,351 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Reference Module
+//!
+//! Compares what a program does when translated by this translator and by
+//! the reference translator of the nand2tetris course, for the `compare`
+//! subcommand:
+//!
+//! ```bash
+//! hack-vm-translator compare ./StackArithmetic/StackTest
+//! ```
+//!
+//! The directory holding the program, or the one holding the file given, is
+//! copied twice into a scratch directory, so that neither translator writes
+//! next to the original. Each copy is translated by one of the translators,
+//! following the conventions of the reference one; see [`crate::compat`].
+//! Every test script in the copies is then run by the CPU emulator against
+//! the assembly of either translator. Scripts for the VM emulator, whose
+//! names end with `VME`, don't use the assembly, and are skipped. For a
+//! single file, only the script named after it is run.
+//!
+//! The reference translator and the emulator are run as given with
+//! `--reference` and `--emulator`, by default `VMTranslator` and
+//! `CPUEmulator.sh` as found on the `PATH`. Neither is bundled with the
+//! translator; they come with the nand2tetris software suite.
+//!
+//! A script under which the two programs pass or fail differently, or write
+//! different output, is reported along with the first line of output where
+//! they differ:
+//!
+//! ```text
+//! StackTest.tst: behaves differently
+//!   reference: End of script - Comparison ended successfully
+//!   this translator: Comparison failure at line 2
+//!   output differs at line 2:
+//!   - |    266  |     -1  |
+//!   + |    266  |      0  |
+//! ```
+
+use core::fmt::{self, Display};
+use std::ffi::OsStr;
+use std::fs::{self, DirEntry};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output};
+use std::{env, io};
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::paths;
+
+/// The reference translator run if none is given.
+pub(crate) const DEFAULT_TRANSLATOR: &str = "VMTranslator";
+
+/// The CPU emulator run if none is given.
+pub(crate) const DEFAULT_EMULATOR: &str = "CPUEmulator.sh";
+
+/// A directory that is removed along with its contents when dropped.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Scratch(PathBuf);
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _removed: io::Result<()> = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// How a test script went for the assembly of one translator.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Run {
+    /// Whether the emulator reported that the script passed.
+    passed: bool,
+    /// The last line the emulator printed, such as why the script failed.
+    message: String,
+    /// The output file the script wrote, if any.
+    output: Option<String>,
+}
+
+/// How a test script went for the assembly of either translator.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Script {
+    /// The name of the script.
+    name: String,
+    /// How it went for the reference translator.
+    reference: Run,
+    /// How it went for this translator.
+    translated: Run,
+}
+
+impl Script {
+    /// Whether the programs behaved differently under the script.
+    fn differs(&self) -> bool {
+        self.reference != self.translated
+    }
+
+    /// Writes the first line of output where the programs differ, if they
+    /// differ in their output at all.
+    fn write_output_difference(
+        &self,
+        formatter: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let reference: &str = self.reference.output.as_deref().unwrap_or("");
+        let translated: &str = self.translated.output.as_deref().unwrap_or("");
+        let mut reference_lines = reference.lines();
+        let mut translated_lines = translated.lines();
+        let mut number: usize = 1;
+        loop {
+            match (reference_lines.next(), translated_lines.next()) {
+                (None, None) => return Ok(()),
+                (left, right) if left == right => {}
+                (left, right) => {
+                    writeln!(formatter, "  output differs at line {number}:")?;
+                    if let Some(left) = left {
+                        writeln!(formatter, "  - {left}")?;
+                    }
+                    if let Some(right) = right {
+                        writeln!(formatter, "  + {right}")?;
+                    }
+                    return Ok(());
+                }
+            }
+            number = number.saturating_add(1);
+        }
+    }
+}
+
+/// The results of running every test script of a program translated by
+/// either translator.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub(crate) struct Comparison {
+    /// The scripts run, in order of their names.
+    scripts: Vec<Script>,
+}
+
+impl Comparison {
+    /// Gets how many test scripts the programs behaved differently under.
+    pub(crate) fn differences(&self) -> usize {
+        self.scripts
+            .iter()
+            .filter(|script: &&Script| script.differs())
+            .count()
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for script in &self.scripts {
+            if !script.differs() {
+                let outcome: &str = if script.reference.passed {
+                    "pass"
+                } else {
+                    "fail"
+                };
+                writeln!(f, "{}: both {outcome}", script.name)?;
+                continue;
+            }
+            writeln!(f, "{}: behaves differently", script.name)?;
+            writeln!(f, "  reference: {}", script.reference.message)?;
+            writeln!(f, "  this translator: {}", script.translated.message)?;
+            script.write_output_difference(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares what the program at `path` does when translated by the reference
+/// `translator` and by this one.
+///
+/// `translate` translates the copy of the program at the path it is given.
+/// The test scripts of either copy are then run with the `emulator`.
+///
+/// # Errors
+///
+/// Returns a [`HackError::ToolFailed`] if the reference translator or the
+/// emulator cannot be run, or the translator fails. Returns a
+/// [`HackError::CannotReadFileFromPath`] if the program has no test scripts.
+/// Any error from copying the program or from `translate` is propagated.
+pub(crate) fn compare(
+    path: &Path,
+    translator: &Path,
+    emulator: &Path,
+    translate: impl FnOnce(&Path) -> Result<(), HackError>,
+) -> Result<Comparison, HackError> {
+    let path: PathBuf = paths::canonicalize(path)?;
+    let scripts: Vec<String> = scripts(&path)?;
+    if scripts.is_empty() {
+        return Err(HackError::CannotReadFileFromPath(format!(
+            "\"{}\" has no test scripts to compare the translators with",
+            paths::display(&path)
+        )));
+    }
+
+    let scratch: Scratch = Scratch(
+        env::temp_dir()
+            .join(format!("hack-vm-translator-compare-{}", process::id())),
+    );
+    let reference: PathBuf = copy(&path, &scratch.0.join("reference"))?;
+    let translated: PathBuf = copy(&path, &scratch.0.join("translated"))?;
+    run_translator(translator, &reference)?;
+    translate(&translated)?;
+
+    let mut comparison: Comparison = Comparison::default();
+    for name in scripts {
+        comparison.scripts.push(Script {
+            reference: run_script(
+                emulator,
+                &directory(&reference).join(&name),
+            )?,
+            translated: run_script(
+                emulator,
+                &directory(&translated).join(&name),
+            )?,
+            name,
+        });
+    }
+    Ok(comparison)
+}
+
+/// Gets the directory holding the program at `path`: the path itself if it
+/// is a directory, or else the one holding the file.
+fn directory(path: &Path) -> &Path {
+    if path.is_dir() {
+        path
+    } else {
+        path.parent().unwrap_or(path)
+    }
+}
+
+/// Lists the names of the test scripts for the CPU emulator run for the
+/// program at `path`, in order.
+///
+/// # Errors
+///
+/// Any error from reading the directory holding the program is propagated.
+fn scripts(path: &Path) -> Result<Vec<String>, HackError> {
+    let only: Option<&OsStr> =
+        (!path.is_dir()).then(|| path.file_stem()).flatten();
+    let mut scripts: Vec<String> = Vec::new();
+    for entry in fs::read_dir(directory(path))? {
+        let script: PathBuf = entry?.path();
+        let is_script: bool = script.is_file()
+            && script
+                .extension()
+                .is_some_and(|ext: &OsStr| ext.eq_ignore_ascii_case("tst"));
+        let Some(stem) = script.file_stem().filter(|_| is_script) else {
+            continue;
+        };
+        if only.is_some_and(|only: &OsStr| only != stem)
+            || stem.to_string_lossy().ends_with("VME")
+        {
+            continue;
+        }
+        if let Some(name) = script.file_name() {
+            scripts.push(name.to_string_lossy().into_owned());
+        }
+    }
+    scripts.sort();
+    Ok(scripts)
+}
+
+/// Copies the files of the directory holding the program at `path` into a
+/// directory of the same name in `into`, returning where the program is in
+/// the copy.
+///
+/// # Errors
+///
+/// Any error from reading the directory or copying its files is propagated.
+fn copy(path: &Path, into: &Path) -> Result<PathBuf, HackError> {
+    let original: &Path = directory(path);
+    let copied: PathBuf = into.join(original.file_name().unwrap_or_default());
+    fs::create_dir_all(&copied)?;
+    for entry in fs::read_dir(original)? {
+        let entry: DirEntry = entry?;
+        if !entry.file_type()?.is_dir() {
+            let _bytes: u64 =
+                fs::copy(entry.path(), copied.join(entry.file_name()))?;
+        }
+    }
+    Ok(if path.is_dir() {
+        copied
+    } else {
+        copied.join(path.file_name().unwrap_or_default())
+    })
+}
+
+/// Runs `program` with `argument`, naming it in any error.
+///
+/// # Errors
+///
+/// Returns a [`HackError::ToolFailed`] if the program cannot be run.
+fn execute(program: &Path, argument: &Path) -> Result<Output, HackError> {
+    Command::new(program)
+        .arg(argument)
+        .output()
+        .map_err(|error: io::Error| {
+            HackError::ToolFailed(format!(
+                "could not run \"{}\": {error}",
+                paths::display(program)
+            ))
+        })
+}
+
+/// Gets the last line a program printed, to standard output or else to
+/// standard error.
+fn last_line(output: &Output) -> String {
+    [&output.stdout, &output.stderr]
+        .into_iter()
+        .find_map(|printed: &Vec<u8>| {
+            String::from_utf8_lossy(printed)
+                .lines()
+                .map(str::trim)
+                .rfind(|line: &&str| !line.is_empty())
+                .map(str::to_owned)
+        })
+        .unwrap_or_else(|| output.status.to_string())
+}
+
+/// Translates the program at `path` with the reference `translator`.
+///
+/// # Errors
+///
+/// Returns a [`HackError::ToolFailed`] if the translator cannot be run or
+/// fails.
+fn run_translator(translator: &Path, path: &Path) -> Result<(), HackError> {
+    let output: Output = execute(translator, path)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(HackError::ToolFailed(format!(
+            "\"{}\" failed to translate \"{}\": {}",
+            paths::display(translator),
+            paths::display(path),
+            last_line(&output)
+        )))
+    }
+}
+
+/// Runs the test `script` with the `emulator`, reading the output file it
+/// writes, which is named after it.
+///
+/// # Errors
+///
+/// Returns a [`HackError::ToolFailed`] if the emulator cannot be run.
+fn run_script(emulator: &Path, script: &Path) -> Result<Run, HackError> {
+    let output: Output = execute(emulator, script)?;
+    Ok(Run {
+        passed: output.status.success(),
+        message: last_line(&output),
+        output: fs::read_to_string(script.with_extension("out")).ok(),
+    })
+}
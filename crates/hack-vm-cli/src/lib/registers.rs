@@ -0,0 +1,371 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Registers Module
+//!
+//! The `allocate-registers` pass, which keeps the addresses computed for
+//! `local`, `argument`, `this`, and `that` in the general registers `R13` to
+//! `R15` from one command to the next, rather than computing them again; see
+//! [`crate::passes`].
+//!
+//! A pop into one of these segments leaves the address it popped to in
+//! `R13`. While that address is known to still be there, a push from the
+//! same address reads it back from `R13`, and a pop to a nearby index adjusts
+//! `R13` rather than computing its address from the start:
+//!
+//! ```text
+//! pop local 0   @0, D=A, @LCL, D=D+M, @R13, M=D, ...
+//! pop local 1   @R13, M=M+1, ...
+//! push local 1  @R13, A=M, D=M, ...
+//! ```
+//!
+//! What is known is forgotten at every label, since it may be jumped to from
+//! anywhere, and whenever the register or the segment's pointer is written
+//! to. Writes to a computed address are assumed not to reach `R0` to `R15`,
+//! as the segments and the stack never overlap them.
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Write as _};
+
+use crate::asmdiff;
+
+/// The general registers whose contents are tracked.
+const GENERAL_REGISTERS: [u16; 3] = [13, 14, 15];
+
+/// The lines that pop into an address stored in a general register, after
+/// it is stored there, which do not read `D` before writing it.
+const POP: [&str; 3] = ["@SP", "AM=M-1", "D=M"];
+
+/// What the `A` register is known to hold.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+enum Addressed {
+    /// An address written as a number or predefined symbol.
+    Known(u16),
+    /// A variable or label, which is never one of `R0` to `R15`.
+    Symbol,
+    /// An address that was computed.
+    Computed,
+}
+
+/// Rewrites `assembly` to reuse the addresses held in the general registers,
+/// as described in [`crate::registers`].
+pub(crate) fn allocate(assembly: &str) -> String {
+    let lines: Vec<&str> = assembly.lines().collect();
+    let codes: Vec<String> =
+        lines.iter().map(|line: &&str| code(line)).collect();
+    // The segment pointer and the index whose address each register holds.
+    let mut known: BTreeMap<u16, (u16, u16)> = BTreeMap::new();
+    let mut addressed: Addressed = Addressed::Computed;
+    let mut output: String = String::with_capacity(assembly.len());
+    let mut index: usize = 0;
+    while let Some(line) = lines.get(index) {
+        let window: &[String] = codes.get(index..).unwrap_or_default();
+        if let Some((register, pointer, offset)) = store(window) {
+            if let Some(shorter) =
+                adjust(known.get(&register).copied(), register, pointer, offset)
+            {
+                output.push_str(&shorter);
+            } else {
+                for line in lines.iter().skip(index).take(6) {
+                    output.push_str(line);
+                    output.push('\n');
+                }
+            }
+            let _previous: Option<(u16, u16)> =
+                known.insert(register, (pointer, offset));
+            addressed = Addressed::Known(register);
+            index = index.saturating_add(6);
+            continue;
+        }
+        if let Some(held) = load(window)
+            && let Some((&register, _)) = known
+                .iter()
+                .find(|&(_, &address): &(&u16, &(u16, u16))| address == held)
+        {
+            let _written: fmt::Result =
+                writeln!(output, "@R{register}\nA=M\nD=M");
+            addressed = Addressed::Computed;
+            index = index.saturating_add(5);
+            continue;
+        }
+
+        output.push_str(line);
+        output.push('\n');
+        track(
+            codes.get(index).map_or("", String::as_str),
+            &mut known,
+            &mut addressed,
+        );
+        index = index.saturating_add(1);
+    }
+    output
+}
+
+/// Updates what is `known` to be in the general registers, and what is
+/// `addressed`, after the instruction `code` runs.
+fn track(
+    code: &str,
+    known: &mut BTreeMap<u16, (u16, u16)>,
+    addressed: &mut Addressed,
+) {
+    if code.starts_with('(') {
+        known.clear();
+        return;
+    }
+    if let Some(symbol) = code.strip_prefix('@') {
+        *addressed =
+            address(symbol).map_or(Addressed::Symbol, Addressed::Known);
+        return;
+    }
+    let destination: &str = code
+        .split_once('=')
+        .map_or("", |(destination, _)| destination);
+    if destination.contains('M')
+        && let Addressed::Known(written) = *addressed
+    {
+        known.retain(|&register: &u16, &mut (pointer, _)| {
+            register != written && pointer != written
+        });
+    }
+    if destination.contains('A') {
+        *addressed = Addressed::Computed;
+    }
+}
+
+/// Gets the instruction on `line`, without its comment or whitespace.
+pub(crate) fn code(line: &str) -> String {
+    line.split_once("//")
+        .map_or(line, |(code, _): (&str, &str)| code)
+        .chars()
+        .filter(|character: &char| !character.is_whitespace())
+        .collect()
+}
+
+/// Gets the address `symbol` stands for, if it is a number or a predefined
+/// symbol.
+fn address(symbol: &str) -> Option<u16> {
+    symbol.parse().ok().or_else(|| asmdiff::predefined(symbol))
+}
+
+/// Matches the lines at the start of `window` that store an address in a
+/// general register and then pop into it.
+///
+/// The address is that of index `offset` of the segment at `pointer`, and
+/// the general register, `pointer`, and `offset` are returned.
+fn store(window: &[String]) -> Option<(u16, u16, u16)> {
+    let [
+        ref offset,
+        ref load,
+        ref pointer,
+        ref add,
+        ref register,
+        ref save,
+        ref rest @ ..,
+    ] = *window
+    else {
+        return None;
+    };
+    let register: u16 = address(register.strip_prefix('@')?)?;
+    let matches: bool = load == "D=A"
+        && add == "D=D+M"
+        && save == "M=D"
+        && GENERAL_REGISTERS.contains(&register)
+        && rest
+            .get(..POP.len())
+            .is_some_and(|next: &[String]| next == POP);
+    let pointer: u16 = address(pointer.strip_prefix('@')?)?;
+    (matches && pointer != register).then_some(())?;
+    Some((register, pointer, offset.strip_prefix('@')?.parse().ok()?))
+}
+
+/// Matches the lines at the start of `window` that read index `offset` of
+/// the segment at `pointer` into `D`, as `pointer` and `offset`.
+fn load(window: &[String]) -> Option<(u16, u16)> {
+    let [ref offset, ref load, ref pointer, ref add, ref read, ..] = *window
+    else {
+        return None;
+    };
+    (load == "D=A" && add == "A=D+M" && read == "D=M").then_some(())?;
+    Some((
+        address(pointer.strip_prefix('@')?)?,
+        offset.strip_prefix('@')?.parse().ok()?,
+    ))
+}
+
+/// Writes the lines that change `register` from holding `held`, if known, to
+/// the address of index `offset` of the segment at `pointer`, if that is
+/// shorter than computing it.
+fn adjust(
+    held: Option<(u16, u16)>,
+    register: u16,
+    pointer: u16,
+    offset: u16,
+) -> Option<String> {
+    let (held_pointer, held_offset): (u16, u16) = held?;
+    if held_pointer != pointer {
+        return None;
+    }
+    Some(match offset.checked_sub(held_offset) {
+        Some(0) => String::new(),
+        Some(1) => format!("@R{register}\nM=M+1\n"),
+        Some(distance) => format!("@{distance}\nD=A\n@R{register}\nM=D+M\n"),
+        None if held_offset.abs_diff(offset) == 1 => {
+            format!("@R{register}\nM=M-1\n")
+        }
+        None => format!(
+            "@{}\nD=A\n@R{register}\nM=M-D\n",
+            held_offset.abs_diff(offset)
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    //! Checks where addresses kept in the general registers are reused, and
+    //! where they must be computed again.
+
+    use hack_vm_codegen::Options;
+    use hack_vm_codegen::translator::Translator;
+    use hack_vm_syntax::limits::Limits;
+    use hack_vm_syntax::mode::Mode;
+    use hack_vm_syntax::parser::Parser;
+
+    use super::allocate;
+
+    /// The lines reading an address kept in `R13` into `D`.
+    const REUSED: &str = "@R13\nA=M\nD=M\n";
+
+    /// The assembly of a call, after its arguments are pushed, which points
+    /// `ARG` and `LCL` at the callee's frame and declares where it returns.
+    const CALL: &str = "@SP\nD=M\n@5\nD=D-A\n@ARG\nM=D\n@SP\nD=M\n@LCL\nM=D\n\
+        @Main.f\n0;JMP\n(Main.main$ret.0)\n";
+
+    /// The assembly of a return, which keeps the frame in `R13` and restores
+    /// the caller's pointers from it.
+    const RETURN: &str = "@LCL\nD=M\n@R13\nM=D\n@5\nA=D-A\nD=M\n@R14\nM=D\n\
+        @SP\nAM=M-1\nD=M\n@ARG\nA=M\nM=D\n@ARG\nD=M+1\n@SP\nM=D\n\
+        @R13\nAM=M-1\nD=M\n@THAT\nM=D\n@R13\nAM=M-1\nD=M\n@THIS\nM=D\n\
+        @R13\nAM=M-1\nD=M\n@ARG\nM=D\n@R13\nAM=M-1\nD=M\n@LCL\nM=D\n\
+        @R14\nA=M\n0;JMP\n";
+
+    /// Translates `vm_code` as the file `Main.vm`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `vm_code` cannot be parsed or translated.
+    fn translate(vm_code: &str) -> String {
+        let parser: Parser =
+            Parser::limited(vm_code.to_owned(), "Main.vm", Limits::default())
+                .unwrap();
+        let mut assembly: String = String::new();
+        for (line, instruction) in
+            parser.parse_numbered(Mode::default()).unwrap()
+        {
+            Translator::translate(
+                &mut assembly,
+                line,
+                &instruction,
+                "Main",
+                &Options::default(),
+            )
+            .unwrap();
+        }
+        assembly
+    }
+
+    /// The assembly of `pop local 0`, `between`, and `push local 0`.
+    fn around(between: &str) -> String {
+        format!(
+            "{}{between}{}",
+            translate("pop local 0\n"),
+            translate("push local 0\n")
+        )
+    }
+
+    /// Checks whether the address kept in `R13` is `reused` in `assembly`
+    /// once registers are allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is not reused as expected.
+    fn assert_reused(assembly: &str, reused: bool) {
+        let allocated: String = allocate(assembly);
+        assert_eq!(
+            allocated.contains(REUSED),
+            reused,
+            "allocating registers in:\n{assembly}\nwrote:\n{allocated}"
+        );
+    }
+
+    /// Checks that addresses in `local` are computed from `LCL` `times` in
+    /// `assembly` once registers are allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if they are computed more or less often.
+    fn assert_computed(assembly: &str, times: usize) {
+        let allocated: String = allocate(assembly);
+        assert_eq!(
+            allocated.matches("@LCL").count(),
+            times,
+            "allocating registers in:\n{assembly}\nwrote:\n{allocated}"
+        );
+    }
+
+    /// The address a pop computed is read back by a push from it.
+    #[test]
+    fn stored_address_is_reused() {
+        assert_reused(&around(""), true);
+        assert_reused(&around(&translate("push constant 7\nadd\n")), true);
+    }
+
+    /// A pop to the next index adjusts the address kept rather than
+    /// computing it again.
+    #[test]
+    fn nearby_index_is_adjusted() {
+        assert_computed(&translate("pop local 0\npop local 1\n"), 1);
+        assert_computed(&translate("pop local 3\npop local 1\n"), 1);
+        assert_reused(
+            &translate("pop local 0\npop local 1\npush local 1\n"),
+            true,
+        );
+    }
+
+    /// Changing `THIS` or `THAT` through `pointer` forgets the addresses
+    /// computed from them.
+    #[test]
+    fn pointer_change_forgets() {
+        for (segment, pointer) in [("this", 0), ("that", 1)] {
+            let assembly: String = translate(&format!(
+                "pop {segment} 2\npush constant 3000\npop pointer {pointer}\n\
+                push {segment} 2\n"
+            ));
+            assert_reused(&assembly, false);
+        }
+        assert_reused(
+            &translate("pop this 2\npop pointer 1\npush this 2\n"),
+            true,
+        );
+    }
+
+    /// A label may be jumped to from anywhere, so nothing is known after it.
+    #[test]
+    fn label_forgets() {
+        assert_reused(&around("(LOOP)\n"), false);
+        assert_reused(&around("@LOOP\nD;JGT\n"), true);
+    }
+
+    /// A call points `LCL` at a new frame, and returns to a label.
+    #[test]
+    fn call_forgets() {
+        assert_reused(&around(CALL), false);
+    }
+
+    /// A return keeps its frame in `R13`, and restores every pointer.
+    #[test]
+    fn return_forgets() {
+        assert_reused(&around(RETURN), false);
+        assert_reused(&around("@R13\nM=0\n"), false);
+    }
+}
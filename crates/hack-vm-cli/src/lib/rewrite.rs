@@ -0,0 +1,389 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Rewrite Module
+//!
+//! Applies peephole rewrite rules to the instructions of each file before
+//! they are translated, so that the idioms of a course or compiler can be
+//! optimized without changing the translator. The rules are read from a
+//! TOML file given with `--rewrites`, or as `rewrites` in a project's
+//! manifest:
+//!
+//! ```toml
+//! # Pushing a value only to pop it back where it came from does nothing.
+//! [rules.push-pop]
+//! match = ["push $segment $index", "pop $segment $index"]
+//! replace = []
+//!
+//! [rules.double-negation]
+//! match = ["neg", "neg"]
+//! replace = []
+//!
+//! # Adding zero does nothing either.
+//! [rules.add-zero]
+//! match = ["push constant 0", "add"]
+//! replace = []
+//! ```
+//!
+//! Each rule replaces a run of instructions matching the lines of `match`
+//! with the lines of `replace`. Instructions are compared word by word as
+//! they are printed, so `push constant 0` matches however it was spaced. A
+//! word starting with `$` matches any one word, and must match the same word
+//! each time it appears in `match`; in `replace`, it stands for that word.
+//! Inline assembly is never matched.
+//!
+//! At each instruction, the rules are tried in order of their names, and the
+//! file is rewritten again until none match. A replacement may not be
+//! longer than what it replaces, nor generate more labels, as the labels of
+//! the instructions it replaces are numbered after them, and there may be no
+//! numbers left between them for more.
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Display};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use hack_vm_codegen::labels;
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::mode::Mode;
+use hack_vm_syntax::parser::{Instruction, Parser};
+use hack_vm_syntax::paths;
+use hack_vm_syntax::provenance::Provenance;
+
+use crate::toml::Table;
+
+/// The rewrite rules read from a file.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub(crate) struct Rewrites {
+    /// The file the rules were read from, for error messages.
+    path: PathBuf,
+    /// The rules, in the order they are tried.
+    rules: Vec<Rule>,
+}
+
+impl Rewrites {
+    /// The most times a file is rewritten before the rules are assumed to
+    /// undo each other.
+    const MAX_PASSES: usize = 64;
+
+    /// Reads the rules in the file at `path`.
+    pub(crate) fn load(path: &Path) -> Result<Self, HackError> {
+        let text: String = fs::read_to_string(path).map_err(|error| {
+            HackError::CannotReadFileFromPath(format!(
+                "could not read \"{}\": {error}",
+                paths::display(path)
+            ))
+        })?;
+        let mut table: Table = Table::parse(path, &text)?;
+        let mut rules: Vec<Rule> = Vec::new();
+        for name in table.tables("rules") {
+            let key: String = format!("rules.{name}");
+            let [pattern, replacement] =
+                ["match", "replace"].map(|field: &str| {
+                    let field: String = format!("{key}.{field}");
+                    table.take_strings(&field)?.ok_or_else(|| {
+                        table.unsupported(
+                            &key,
+                            &format!("is missing \"{field}\""),
+                        )
+                    })
+                });
+            rules.push(Rule::new(name, &pattern?, &replacement?).map_err(
+                |message: String| table.unsupported(&key, &message),
+            )?);
+        }
+        table.finish()?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            rules,
+        })
+    }
+
+    /// Whether there are no rules, such that nothing is rewritten.
+    pub(crate) const fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Uses the rules from `other` if there are none, such that rules in
+    /// `self` take precedence.
+    pub(crate) fn merge(&mut self, other: &Self) {
+        if self.is_empty() {
+            self.clone_from(other);
+        }
+    }
+
+    /// Rewrites `instructions`, each paired with where it was written, until
+    /// no rule matches. Replacements are parsed in the given [`Mode`], and
+    /// are located where the first instruction they replace was written.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::BadConfigFile`] if a replacement does not
+    /// parse, is longer or generates more labels than what it replaces, or
+    /// if the rules keep matching.
+    pub(crate) fn apply(
+        &self,
+        mut instructions: Vec<(Provenance, Instruction)>,
+        mode: Mode,
+    ) -> Result<Vec<(Provenance, Instruction)>, HackError> {
+        if self.is_empty() {
+            return Ok(instructions);
+        }
+        for _pass in 0..Self::MAX_PASSES {
+            let rewritten: Option<Vec<(Provenance, Instruction)>> =
+                self.rewrite(&instructions, mode)?;
+            match rewritten {
+                Some(rewritten) => instructions = rewritten,
+                None => return Ok(instructions),
+            }
+        }
+        Err(self.error(&format!(
+            "rules still matched after rewriting {} times, so some may undo \
+            others",
+            Self::MAX_PASSES
+        )))
+    }
+
+    /// Rewrites `instructions` once, from first to last, or returns [`None`]
+    /// if no rule matches.
+    fn rewrite(
+        &self,
+        instructions: &[(Provenance, Instruction)],
+        mode: Mode,
+    ) -> Result<Option<Vec<(Provenance, Instruction)>>, HackError> {
+        let mut rewritten: Vec<(Provenance, Instruction)> =
+            Vec::with_capacity(instructions.len());
+        let mut changed: bool = false;
+        let mut index: usize = 0;
+        while let Some(rest) = instructions.get(index..)
+            && let Some(located) = rest.first()
+        {
+            let Some((rule, bindings)) = self.rules.iter().find_map(|rule| {
+                rule.matches(rest)
+                    .map(|bindings: Bindings<'_>| (rule, bindings))
+            }) else {
+                rewritten.push(located.clone());
+                index = index.saturating_add(1);
+                continue;
+            };
+            let matched: &[(Provenance, Instruction)] =
+                rest.get(..rule.pattern.len()).unwrap_or_default();
+            let replacement: Vec<(Provenance, Instruction)> = rule
+                .replace(&bindings, &located.0, mode)
+                .map_err(|message: String| self.error(&message))?;
+            if replacement.len() > matched.len()
+                || labelled(&replacement) > labelled(matched)
+            {
+                return Err(self.error(&format!(
+                    "rule \"{}\" replaces {} with {}, which is longer or \
+                    generates more labels",
+                    rule.name,
+                    quoted(matched),
+                    quoted(&replacement)
+                )));
+            }
+            rewritten.extend(replacement);
+            index = index.saturating_add(matched.len());
+            changed = true;
+        }
+        Ok(changed.then_some(rewritten))
+    }
+
+    /// Creates a [`HackError::BadConfigFile`] about the rules.
+    fn error(&self, message: &str) -> HackError {
+        HackError::BadConfigFile(format!(
+            "{}: {message}",
+            paths::display(&self.path)
+        ))
+    }
+}
+
+impl Display for Rewrites {
+    /// Lists the rules, separated by commas.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, rule) in self.rules.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{rule}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What each variable of a [`Rule`] matched, by its name.
+type Bindings<'rule> = BTreeMap<&'rule str, String>;
+
+/// A single rewrite rule, with the words of each line of its pattern and
+/// replacement.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Rule {
+    /// The name of the rule, after `rules.`.
+    name: String,
+    /// The instructions to match.
+    pattern: Vec<Vec<String>>,
+    /// The instructions to replace them with.
+    replacement: Vec<Vec<String>>,
+}
+
+impl Rule {
+    /// The character starting a variable.
+    const VARIABLE: char = '$';
+
+    /// Splits the lines of the rule `name` into words.
+    ///
+    /// If the rule is malformed, why is described instead.
+    fn new(
+        name: String,
+        pattern: &[String],
+        replacement: &[String],
+    ) -> Result<Self, String> {
+        let pattern: Vec<Vec<String>> = words(pattern);
+        if pattern.is_empty() {
+            return Err("must match at least one instruction".to_owned());
+        }
+        let replacement: Vec<Vec<String>> = words(replacement);
+        if let Some(unbound) = replacement.iter().flatten().find(|word| {
+            word.starts_with(Self::VARIABLE)
+                && !pattern.iter().flatten().any(|bound| bound == *word)
+        }) {
+            return Err(format!(
+                "replaces with \"{unbound}\", which is not in \"match\""
+            ));
+        }
+        Ok(Self {
+            name,
+            pattern,
+            replacement,
+        })
+    }
+
+    /// Matches the pattern against the start of `instructions`, returning
+    /// what its variables matched if it does.
+    fn matches(
+        &self,
+        instructions: &[(Provenance, Instruction)],
+    ) -> Option<Bindings<'_>> {
+        let mut bindings: Bindings<'_> = BTreeMap::new();
+        let matched: &[(Provenance, Instruction)] =
+            instructions.get(..self.pattern.len())?;
+        for (expected, numbered) in self.pattern.iter().zip(matched) {
+            if matches!(numbered.1, Instruction::Assembly(_)) {
+                return None;
+            }
+            let printed: String = numbered.1.to_string();
+            let found: Vec<&str> = printed.split_whitespace().collect();
+            if found.len() != expected.len() {
+                return None;
+            }
+            for (word, found) in expected.iter().zip(found) {
+                let same: bool = if word.starts_with(Self::VARIABLE) {
+                    bindings
+                        .entry(word.as_str())
+                        .or_insert_with(|| found.to_owned())
+                        == found
+                } else {
+                    word == found
+                };
+                if !same {
+                    return None;
+                }
+            }
+        }
+        Some(bindings)
+    }
+
+    /// Parses the replacement, in the given [`Mode`], with its variables
+    /// standing for what they matched, locating each instruction at
+    /// `provenance`.
+    ///
+    /// If a line does not parse, why is described instead.
+    fn replace(
+        &self,
+        bindings: &Bindings<'_>,
+        provenance: &Provenance,
+        mode: Mode,
+    ) -> Result<Vec<(Provenance, Instruction)>, String> {
+        let mut replaced: Vec<(Provenance, Instruction)> = Vec::new();
+        for words in &self.replacement {
+            let line: String = words
+                .iter()
+                .map(|word: &String| {
+                    bindings.get(word.as_str()).unwrap_or(word).as_str()
+                })
+                .collect::<Vec<&str>>()
+                .join(" ");
+            let parsed: Vec<(usize, Instruction)> = Parser::from(line.clone())
+                .parse_numbered(mode)
+                .map_err(|error: HackError| {
+                    format!(
+                        "rule \"{}\" replaces with \"{line}\", but {error}",
+                        self.name
+                    )
+                })?;
+            replaced.extend(parsed.into_iter().map(
+                |(_, instruction): (usize, Instruction)| {
+                    (provenance.clone(), instruction)
+                },
+            ));
+        }
+        Ok(replaced)
+    }
+}
+
+impl Display for Rule {
+    /// Describes the rule as `NAME:MATCH>REPLACE`, with the lines of each
+    /// separated by slashes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = |lines: &[Vec<String>]| -> String {
+            lines
+                .iter()
+                .map(|words: &Vec<String>| words.join(" "))
+                .collect::<Vec<String>>()
+                .join("/")
+        };
+        write!(
+            f,
+            "{}:{}>{}",
+            self.name,
+            lines(&self.pattern),
+            lines(&self.replacement)
+        )
+    }
+}
+
+/// Splits each of `lines` into words, leaving out those that are blank.
+fn words(lines: &[String]) -> Vec<Vec<String>> {
+    lines
+        .iter()
+        .map(|line: &String| {
+            line.split_whitespace().map(str::to_owned).collect()
+        })
+        .filter(|words: &Vec<String>| !words.is_empty())
+        .collect()
+}
+
+/// Counts how many of `instructions` generate labels.
+fn labelled(instructions: &[(Provenance, Instruction)]) -> usize {
+    instructions
+        .iter()
+        .filter(|numbered: &&(Provenance, Instruction)| {
+            labels::generates_labels(&numbered.1)
+        })
+        .count()
+}
+
+/// Quotes `instructions` for an error message, separated by commas.
+fn quoted(instructions: &[(Provenance, Instruction)]) -> String {
+    if instructions.is_empty() {
+        return "nothing".to_owned();
+    }
+    instructions
+        .iter()
+        .map(|numbered: &(Provenance, Instruction)| {
+            format!("\"{}\"", numbered.1)
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Sandbox Module
+//!
+//! Refuses to write anything outside of a directory chosen with `--sandbox`,
+//! so that a grading host translating crafted submissions cannot be made to
+//! overwrite files elsewhere. Every file written is checked:
+//!
+//! - The assembly for each file, or the single file all of it is combined
+//!   into, whether named by a project's manifest or by `--compat`.
+//! - The directory given to `--cache-dir`, before it is created.
+//! - The file given to `--summary-json`.
+//!
+//! Paths are compared once they are canonicalized, so neither `..` nor a
+//! symbolic link can lead out of the directory. The part of a path that does
+//! not exist yet may not contain `..`, as it cannot be resolved.
+
+use std::io;
+use std::path::{self, Component, Path, PathBuf};
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::paths;
+
+/// The directory everything written must be inside of.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct Sandbox {
+    /// The canonical path of the directory.
+    root: PathBuf,
+}
+
+impl Sandbox {
+    /// Confines what is written to the existing directory `root`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::InvalidOption`] if `root` is not a directory.
+    pub(crate) fn new(root: &Path) -> Result<Self, HackError> {
+        paths::canonicalize(root)
+            .ok()
+            .filter(|root: &PathBuf| root.is_dir())
+            .map(|root: PathBuf| Self { root })
+            .ok_or_else(|| {
+                HackError::InvalidOption(format!(
+                    "option \"--sandbox\" must name an existing directory, \
+                    not \"{}\"",
+                    paths::display(root)
+                ))
+            })
+    }
+
+    /// Checks that writing to `path` stays inside of the directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::WriteError`] if it would not, or the path
+    /// cannot be resolved.
+    pub(crate) fn check(&self, path: &Path) -> Result<(), HackError> {
+        let refuse = |reason: &str| {
+            HackError::WriteError(format!(
+                "refusing to write \"{}\", {reason} \"{}\"",
+                paths::display(path),
+                paths::display(&self.root)
+            ))
+        };
+        let resolved: PathBuf = Self::resolve(path)
+            .map_err(|_error| refuse("which cannot be resolved inside of"))?;
+        if resolved.starts_with(&self.root) {
+            Ok(())
+        } else {
+            Err(refuse("which is outside of"))
+        }
+    }
+
+    /// Canonicalizes the longest part of `path` that exists, and appends the
+    /// rest, which must not contain `..`. A symbolic link that leads nowhere
+    /// exists, but cannot be canonicalized, so it is never written through.
+    fn resolve(path: &Path) -> Result<PathBuf, io::Error> {
+        let absolute: PathBuf = path::absolute(path)?;
+        let existing: &Path = absolute
+            .ancestors()
+            .find(|ancestor: &&Path| ancestor.symlink_metadata().is_ok())
+            .ok_or(io::ErrorKind::NotFound)?;
+        let rest: &Path = absolute
+            .strip_prefix(existing)
+            .map_err(|_error| io::ErrorKind::InvalidInput)?;
+        if rest
+            .components()
+            .any(|component: Component<'_>| component == Component::ParentDir)
+        {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        Ok(paths::canonicalize(existing)?.join(rest))
+    }
+}
@@ -0,0 +1,253 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Serve Module
+//!
+//! Hosts a playground for the `serve` subcommand: a page in the browser where
+//! Hack VM code can be pasted, showing the assembly it translates to, or why
+//! it does not, as it is edited. It needs nothing but the translator, for
+//! demonstrating it in a classroom. Only available with the `serve` feature.
+//!
+//! ```bash
+//! hack-vm-translator serve --listen 127.0.0.1:8080
+//! ```
+//!
+//! The code is translated as a file named `Main.vm`, with the options the
+//! server was started with, such as `--mode` or `--annotate`. Any warnings
+//! about it are written as comments above the assembly.
+//!
+//! The server handles one request at a time, and listens on the loopback
+//! address unless `--listen` is given another. It is meant for
+//! demonstrations, not for being exposed to a network.
+
+use std::io::{self, BufRead as _, BufReader, Read as _, Write as _};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::parser::Symbol;
+
+use crate::summary::FileSummary;
+use crate::symbols::SymbolTable;
+use crate::{Output, Settings, Source, run_for_file};
+
+/// The address listened on if none is given.
+pub(crate) const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+/// What the code pasted into the playground is translated as.
+const FILE_NAME: &str = "Main.vm";
+
+/// The most bytes of code accepted in a request, whatever the limit on the
+/// size of files.
+const MAX_BODY: usize = 1 << 20;
+
+/// The playground page.
+const PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Hack VM Translator</title>
+<style>
+  body {
+    display: flex; gap: 1em; margin: 1em; height: calc(100vh - 2em);
+    font-family: sans-serif;
+  }
+  section { flex: 1; display: flex; flex-direction: column; }
+  textarea, pre {
+    flex: 1; margin: 0; padding: 0.5em; font: 14px monospace;
+    border: 1px solid #888; overflow: auto;
+  }
+  pre.error { color: #b00; }
+</style>
+</head>
+<body>
+<section>
+  <h2>Main.vm</h2>
+  <textarea id="code" spellcheck="false">// Paste Hack VM code here.
+push constant 7
+push constant 8
+add
+</textarea>
+</section>
+<section>
+  <h2>Main.asm</h2>
+  <pre id="assembly"></pre>
+</section>
+<script>
+  const code = document.getElementById("code");
+  const assembly = document.getElementById("assembly");
+  let pending;
+  async function translate() {
+    const response =
+      await fetch("/translate", { method: "POST", body: code.value });
+    assembly.textContent = await response.text();
+    assembly.className = response.ok ? "" : "error";
+  }
+  code.addEventListener("input", () => {
+    clearTimeout(pending);
+    pending = setTimeout(translate, 200);
+  });
+  translate();
+</script>
+</body>
+</html>
+"#;
+
+/// A response to a request.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Response {
+    /// The status line's code and reason, such as `200 OK`.
+    status: &'static str,
+    /// The type of the body, such as `text/html`.
+    content_type: &'static str,
+    /// The body.
+    body: String,
+}
+
+impl Response {
+    /// Creates a response with a plain text `body`.
+    const fn text(status: &'static str, body: String) -> Self {
+        Self {
+            status,
+            content_type: "text/plain",
+            body,
+        }
+    }
+
+    /// Writes the response to `stream`, closing the connection after it.
+    ///
+    /// # Errors
+    ///
+    /// Any error from writing to the stream is propagated.
+    fn write(&self, stream: &mut TcpStream) -> Result<(), HackError> {
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}; charset=utf-8\r\n\
+            Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.content_type,
+            self.body.len(),
+            self.body
+        )
+        .map_err(|error: io::Error| HackError::WriteError(error.to_string()))
+    }
+}
+
+/// Serves the playground on `address`, translating code according to
+/// `settings`, until the process is stopped.
+///
+/// # Errors
+///
+/// Returns a [`HackError::InvalidOption`] if `address` cannot be listened
+/// on. Errors from handling a request are printed, and the next one served.
+pub(crate) fn serve(
+    address: &str,
+    settings: &Settings,
+) -> Result<(), HackError> {
+    let listener: TcpListener =
+        TcpListener::bind(address).map_err(|error| {
+            HackError::InvalidOption(format!(
+                "could not listen on \"{address}\": {error}"
+            ))
+        })?;
+    let mut stderr: io::Stderr = io::stderr();
+    writeln!(stderr, "Serving the playground at http://{address}/")?;
+    for stream in listener.incoming() {
+        if let Err(error) = stream
+            .map_err(HackError::from)
+            .and_then(|stream: TcpStream| handle(stream, settings))
+        {
+            writeln!(stderr, "Problem serving a request: {error}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a request from `stream` and answers it: the page for `GET /`, and
+/// the assembly for the code posted to `/translate`.
+///
+/// # Errors
+///
+/// Any error from reading the request or writing the response is
+/// propagated.
+fn handle(mut stream: TcpStream, settings: &Settings) -> Result<(), HackError> {
+    let mut reader: BufReader<&TcpStream> = BufReader::new(&stream);
+    let mut request_line: String = String::new();
+    let _read: usize = reader.read_line(&mut request_line)?;
+    let mut length: usize = 0;
+    loop {
+        let mut header: String = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse().unwrap_or_default();
+        }
+    }
+
+    let mut words = request_line.split_whitespace();
+    let response: Response = match (words.next(), words.next()) {
+        (Some("GET"), Some("/")) => Response {
+            status: "200 OK",
+            content_type: "text/html",
+            body: PAGE.to_owned(),
+        },
+        (Some("POST"), Some("/translate")) if length > MAX_BODY => {
+            Response::text(
+                "413 Content Too Large",
+                format!("the code is larger than {MAX_BODY} bytes"),
+            )
+        }
+        (Some("POST"), Some("/translate")) => {
+            let mut body: Vec<u8> = vec![0; length];
+            reader.read_exact(&mut body)?;
+            match translate(body, settings) {
+                Ok(assembly) => Response::text("200 OK", assembly),
+                Err(error) => Response::text(
+                    "422 Unprocessable Content",
+                    error.to_string(),
+                ),
+            }
+        }
+        _ => Response::text("404 Not Found", "not found".to_owned()),
+    };
+    response.write(&mut stream)
+}
+
+/// Translates the code in `body` as [`FILE_NAME`], according to
+/// `settings`, writing any warnings about it as comments above the
+/// assembly.
+///
+/// # Errors
+///
+/// Any error from decoding, parsing, or translating the code is propagated.
+fn translate(body: Vec<u8>, settings: &Settings) -> Result<String, HackError> {
+    settings.limits.check_size(FILE_NAME, body.len())?;
+    let source: Source = Source::Archived {
+        path: PathBuf::from(FILE_NAME),
+        contents: settings.limits.decode(FILE_NAME, body)?,
+    };
+    let static_prefix: Symbol = Symbol::sanitized("Main");
+    let mut output: Output = Output::Buffer(String::new());
+    let summary: FileSummary = run_for_file(
+        &source,
+        Some(&static_prefix),
+        settings,
+        None,
+        &mut output,
+        &mut SymbolTable::default(),
+        true,
+    )?;
+    let Output::Buffer(assembly) = output else {
+        return Err(HackError::Internal);
+    };
+    Ok(summary
+        .warnings()
+        .iter()
+        .map(|warning| format!("// {warning}\n"))
+        .chain([assembly])
+        .collect())
+}
@@ -0,0 +1,247 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Snapshot Module
+//!
+//! Helpers for regression testing assembly against golden `.asm` files, or
+//! snapshots, so downstream backends and passes can be checked the way the
+//! translator is. Only available with the `test-util` feature.
+//!
+//! Assembly is compared as by the `diff-asm` subcommand; see
+//! [`crate::asmdiff`]. Labels are renamed in order of their first appearance,
+//! so a snapshot still matches when the labels are named or numbered
+//! differently, such as after a line is inserted above them.
+//!
+//! A directory of fixtures is laid out as nand2tetris projects are, each
+//! `Foo.vm` next to its snapshot `Foo.asm`:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use hack_vm_cli::snapshot::{self, Mismatch};
+//! use hack_vm_codegen::Options;
+//! use hack_vm_syntax::mode::Mode;
+//!
+//! fn fixtures() -> Result<(), Mismatch> {
+//!     let checked: usize = snapshot::check_fixtures(
+//!         Path::new("tests/fixtures"),
+//!         Mode::Strict,
+//!         &Options::default(),
+//!     )?;
+//!     assert!(checked > 0);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! When the environment variable `UPDATE_SNAPSHOTS` is set, the snapshots
+//! are written with the assembly instead of compared to it, to accept a
+//! deliberate change.
+
+use core::fmt::{self, Debug, Display};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use hack_vm_codegen::Options;
+use hack_vm_codegen::labels::{self, LabelScheme};
+use hack_vm_codegen::translator::Translator;
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::limits::Limits;
+use hack_vm_syntax::mode::Mode;
+use hack_vm_syntax::parser::{Parser, Symbol};
+use hack_vm_syntax::paths;
+
+use crate::asmdiff::{Assembly, AssemblyDiff};
+
+/// The environment variable which, when set, makes snapshots be written
+/// rather than compared.
+pub const UPDATE: &str = "UPDATE_SNAPSHOTS";
+
+/// Why assembly did not match its snapshot.
+///
+/// Its [`Debug`] shows the same report as its [`Display`], so that a test
+/// which fails by returning it, or unwrapping it, prints the divergences as
+/// they are.
+#[derive(Clone, Default, Hash, PartialEq, Eq)]
+pub struct Mismatch {
+    /// What went wrong, one problem after another.
+    report: String,
+}
+
+impl Mismatch {
+    /// Adds `problem` to the report.
+    fn add(&mut self, problem: &str) {
+        self.report.push_str(problem);
+        if !problem.ends_with('\n') {
+            self.report.push('\n');
+        }
+    }
+
+    /// Whether nothing went wrong.
+    const fn is_empty(&self) -> bool {
+        self.report.is_empty()
+    }
+}
+
+impl From<HackError> for Mismatch {
+    fn from(value: HackError) -> Self {
+        let mut mismatch: Self = Self::default();
+        mismatch.add(&value.to_string());
+        mismatch
+    }
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report)
+    }
+}
+
+impl Debug for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\n{}", self.report)
+    }
+}
+
+/// Translates the contents of `parser`, parsed in the given [`Mode`], as
+/// the only file of a program, naming its static variables with
+/// `file_name`.
+///
+/// Namespaces, annotations, and the other settings of the executable are
+/// not applied; only `options` are.
+///
+/// # Errors
+///
+/// Any error from parsing or translating is propagated.
+pub fn translate(
+    parser: &Parser,
+    file_name: &str,
+    mode: Mode,
+    options: &Options,
+) -> Result<String, HackError> {
+    let scheme: &LabelScheme = options.labels();
+    let mut output: String = String::new();
+    let mut labelled: usize = 0;
+    for (line_number, instruction) in parser.parse(mode)? {
+        let label_number: usize = scheme.number(line_number, labelled);
+        if labels::generates_labels(&instruction) {
+            labelled = labelled.saturating_add(1);
+        }
+        Translator::translate(
+            &mut output,
+            label_number,
+            &instruction,
+            file_name,
+            options,
+        )?;
+        output.push('\n');
+    }
+    if scheme.renumbers() {
+        output = scheme.renumber(&output, 0);
+    }
+    Ok(output)
+}
+
+/// Compares the assembly `actual` with the snapshot at `snapshot`, or writes
+/// it there if [`UPDATE`] is set.
+///
+/// # Errors
+///
+/// Returns a [`Mismatch`] listing each divergence if they differ, or
+/// describing why the snapshot could not be read or written.
+pub fn compare(actual: &str, snapshot: &Path) -> Result<(), Mismatch> {
+    if env::var_os(UPDATE).is_some() {
+        return fs::write(snapshot, actual).map_err(|error| {
+            Mismatch::from(HackError::WriteError(format!(
+                "cannot write snapshot \"{}\": {error}",
+                paths::display(snapshot)
+            )))
+        });
+    }
+    let expected: String = fs::read_to_string(snapshot).map_err(|error| {
+        Mismatch::from(HackError::CannotReadFileFromPath(format!(
+            "cannot read snapshot \"{}\": {error}; set {UPDATE} to write it",
+            paths::display(snapshot)
+        )))
+    })?;
+    let diff: AssemblyDiff = AssemblyDiff::new(
+        Assembly::new(paths::display(snapshot).to_string(), &expected),
+        Assembly::new("actual".to_owned(), actual),
+    );
+    if diff.divergences() == 0 {
+        return Ok(());
+    }
+    let mut mismatch: Mismatch = Mismatch::default();
+    mismatch.add(&diff.to_string());
+    Err(mismatch)
+}
+
+/// Translates each Hack VM file directly inside `directory`, and compares
+/// its assembly with the snapshot next to it, of the same name with the
+/// `.asm` extension.
+///
+/// Static variables are named after each file, as when it is translated on
+/// its own. On success, how many files were checked is returned.
+///
+/// # Errors
+///
+/// Returns a [`Mismatch`] covering every file that failed to translate or
+/// did not match its snapshot, not only the first.
+pub fn check_fixtures(
+    directory: &Path,
+    mode: Mode,
+    options: &Options,
+) -> Result<usize, Mismatch> {
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(directory)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<PathBuf>, _>>()
+        })
+        .map_err(|error| {
+            Mismatch::from(HackError::CannotReadFileFromPath(format!(
+                "cannot read fixtures in \"{}\": {error}",
+                paths::display(directory)
+            )))
+        })?;
+    fixtures.retain(|path: &PathBuf| {
+        path.is_file() && path.extension() == Some(OsStr::new("vm"))
+    });
+    fixtures.sort();
+
+    let mut mismatch: Mismatch = Mismatch::default();
+    for fixture in &fixtures {
+        let stem: String = fixture
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let assembly: Result<String, HackError> =
+            Parser::read(fixture, Limits::default()).and_then(|parser| {
+                translate(
+                    &parser,
+                    Symbol::sanitized(&stem).literal_representation(),
+                    mode,
+                    options,
+                )
+            });
+        match assembly {
+            Ok(assembly) => {
+                if let Err(problem) =
+                    compare(&assembly, &fixture.with_extension("asm"))
+                {
+                    mismatch.add(&problem.report);
+                }
+            }
+            Err(error) => mismatch.add(&format!(
+                "cannot translate \"{}\": {error}",
+                paths::display(fixture)
+            )),
+        }
+    }
+    if mismatch.is_empty() {
+        Ok(fixtures.len())
+    } else {
+        Err(mismatch)
+    }
+}
@@ -0,0 +1,551 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Summary Module
+//!
+//! Records what happened during a run, so that it can be reported once the
+//! run is over.
+//!
+//! With `--summary-json <PATH>`, a summary of the run is written to `PATH`
+//! as a single JSON object, for dashboards tracking many runs:
+//!
+//! ```json
+//! {
+//!   "files": [
+//!     {
+//!       "path": "Main.vm",
+//!       "cached": false,
+//!       "instructions": 12,
+//!       "assembly_lines": 96,
+//!       "timings_ms": {"reading": 0.051, "parsing": 0.032, ...}
+//!     }
+//!   ],
+//!   "instructions": 12,
+//!   "assembly_lines": 96,
+//!   "warnings": [],
+//!   "errors": [],
+//!   "timings_ms": {"reading": 0.051, "parsing": 0.032, ...},
+//!   "elapsed_ms": 0.412
+//! }
+//! ```
+//!
+//! Files whose assembly was reused from the cache are not parsed, so their
+//! instructions are not counted. A run stops at its first error, which is
+//! then the only one listed, without any files.
+
+use core::fmt::{self, Display};
+use core::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::paths;
+
+use crate::json::Value;
+use crate::lints::Warning;
+use crate::symbols::SymbolTable;
+
+/// The phases each translated file goes through.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Phase {
+    /// Reading the file from disk.
+    Reading,
+    /// Deserializing the file contents into instructions.
+    Parsing,
+    /// Generating Hack assembly from the instructions.
+    Translating,
+    /// Writing the generated assembly to disk.
+    Writing,
+}
+
+impl Phase {
+    /// Every [`Phase`], in the order they happen.
+    pub const ALL: [Self; 4] = [
+        Self::Reading,
+        Self::Parsing,
+        Self::Translating,
+        Self::Writing,
+    ];
+
+    /// Get the name of this [`Phase`] as used in reports.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Reading => "reading",
+            Self::Parsing => "parsing",
+            Self::Translating => "translating",
+            Self::Writing => "writing",
+        }
+    }
+}
+
+/// The reports that may be printed once a run is over.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Report {
+    /// The time spent in each [`Phase`], requested with `--time`.
+    Time,
+    /// The symbols of the generated assembly, requested with
+    /// `--dump-symbols`; see [`crate::symbols`].
+    Symbols,
+}
+
+/// The wall-clock time spent in each [`Phase`].
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub struct Timings {
+    /// Time spent in [`Phase::Reading`].
+    reading: Duration,
+    /// Time spent in [`Phase::Parsing`].
+    parsing: Duration,
+    /// Time spent in [`Phase::Translating`].
+    translating: Duration,
+    /// Time spent in [`Phase::Writing`].
+    writing: Duration,
+}
+
+impl Timings {
+    /// Gets the time spent in the given [`Phase`].
+    pub const fn get(&self, phase: Phase) -> Duration {
+        match phase {
+            Phase::Reading => self.reading,
+            Phase::Parsing => self.parsing,
+            Phase::Translating => self.translating,
+            Phase::Writing => self.writing,
+        }
+    }
+
+    /// Gets a mutable reference to the time spent in the given [`Phase`].
+    const fn get_mut(&mut self, phase: Phase) -> &mut Duration {
+        match phase {
+            Phase::Reading => &mut self.reading,
+            Phase::Parsing => &mut self.parsing,
+            Phase::Translating => &mut self.translating,
+            Phase::Writing => &mut self.writing,
+        }
+    }
+
+    /// The time spent in all phases together.
+    pub fn total(&self) -> Duration {
+        Phase::ALL.iter().fold(
+            Duration::ZERO,
+            |total: Duration, &phase: &Phase| {
+                total.saturating_add(self.get(phase))
+            },
+        )
+    }
+
+    /// Runs `operation`, adding the time it took to the given [`Phase`].
+    pub(crate) fn measure<T, F: FnOnce() -> T>(
+        &mut self,
+        phase: Phase,
+        operation: F,
+    ) -> T {
+        let start: Instant = Instant::now();
+        let result: T = operation();
+        let slot: &mut Duration = self.get_mut(phase);
+        *slot = slot.saturating_add(start.elapsed());
+        result
+    }
+
+    /// Adds the times from `other` to these.
+    fn accumulate(&mut self, other: &Self) {
+        for phase in Phase::ALL {
+            let slot: &mut Duration = self.get_mut(phase);
+            *slot = slot.saturating_add(other.get(phase));
+        }
+    }
+}
+
+/// What happened to a single translated file.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct FileSummary {
+    /// The path of the `.vm` file that was translated.
+    path: PathBuf,
+    /// The time spent translating it.
+    timings: Timings,
+    /// Whether its assembly was reused from the cache.
+    cached: bool,
+    /// How many lines of it hold VM commands.
+    vm_lines: usize,
+    /// How many instructions were parsed from it.
+    instructions: usize,
+    /// How many lines of assembly were generated for it, not counting blank
+    /// lines and comments.
+    assembly_lines: usize,
+    /// How many of those lines are assembly instructions rather than label
+    /// declarations.
+    assembly_instructions: usize,
+    /// The warnings about it; see [`crate::lints`].
+    warnings: Vec<Warning>,
+}
+
+impl FileSummary {
+    /// Creates a new [`FileSummary`] for a file with `vm_lines` lines of VM
+    /// commands that was parsed into `instructions` instructions, or reused
+    /// from the cache if there are none, and translated into `assembly` with
+    /// the given `warnings`.
+    pub(crate) fn new(
+        path: PathBuf,
+        timings: Timings,
+        vm_lines: usize,
+        instructions: Option<usize>,
+        assembly: &str,
+        warnings: Vec<Warning>,
+    ) -> Self {
+        let lines: Vec<&str> = assembly
+            .lines()
+            .map(str::trim)
+            .filter(|line: &&str| !line.is_empty() && !line.starts_with("//"))
+            .collect();
+        Self {
+            path,
+            timings,
+            cached: instructions.is_none(),
+            vm_lines,
+            instructions: instructions.unwrap_or_default(),
+            assembly_lines: lines.len(),
+            assembly_instructions: lines
+                .iter()
+                .filter(|line: &&&str| !line.starts_with('('))
+                .count(),
+            warnings,
+        }
+    }
+
+    /// Gets the warnings about the file.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Gets how many lines of the file hold VM commands.
+    pub const fn vm_lines(&self) -> usize {
+        self.vm_lines
+    }
+
+    /// Gets how many assembly instructions were generated for the file, not
+    /// counting label declarations.
+    pub const fn assembly_instructions(&self) -> usize {
+        self.assembly_instructions
+    }
+
+    /// Whether the assembly for the file was reused from the cache.
+    pub const fn cached(&self) -> bool {
+        self.cached
+    }
+
+    /// Gets how many instructions were parsed from the file.
+    pub const fn instructions(&self) -> usize {
+        self.instructions
+    }
+
+    /// Gets how many lines of assembly were generated for the file, not
+    /// counting blank lines and comments.
+    pub const fn assembly_lines(&self) -> usize {
+        self.assembly_lines
+    }
+
+    /// Gets the path of the `.vm` file that was translated.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the time spent translating the file.
+    pub const fn timings(&self) -> &Timings {
+        &self.timings
+    }
+}
+
+/// What happened during a whole run.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct Summary {
+    /// Each file that was translated, in order.
+    files: Vec<FileSummary>,
+    /// The wall-clock time the whole run took.
+    elapsed: Duration,
+    /// The symbols of the generated assembly, if they were listed.
+    symbols: Option<SymbolTable>,
+    /// The directory whose files were translated, if one was.
+    directory: Option<PathBuf>,
+}
+
+impl Summary {
+    /// Records that a file was translated.
+    pub(crate) fn push(&mut self, file: FileSummary) {
+        self.files.push(file);
+    }
+
+    /// Records the wall-clock time the whole run took.
+    pub(crate) const fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+    }
+
+    /// Records that the files of `directory` were translated.
+    pub(crate) fn set_directory(&mut self, directory: PathBuf) {
+        self.directory = Some(directory);
+    }
+
+    /// Gets the directory whose files were translated, if one was.
+    pub fn directory(&self) -> Option<&Path> {
+        self.directory.as_deref()
+    }
+
+    /// A compact table of what happened to each file, for runs that
+    /// translated a directory.
+    pub const fn table(&self) -> Table<'_> {
+        Table { summary: self }
+    }
+
+    /// Records the symbols of the generated assembly.
+    pub(crate) fn set_symbols(&mut self, symbols: SymbolTable) {
+        self.symbols = Some(symbols);
+    }
+
+    /// Gets the symbols of the generated assembly, if they were listed.
+    pub const fn symbols(&self) -> Option<&SymbolTable> {
+        self.symbols.as_ref()
+    }
+
+    /// Gets each file that was translated, in order.
+    pub fn files(&self) -> &[FileSummary] {
+        &self.files
+    }
+
+    /// Gets the warnings about every file, in order.
+    pub fn warnings(&self) -> impl Iterator<Item = &Warning> {
+        self.files.iter().flat_map(FileSummary::warnings)
+    }
+
+    /// Gets the wall-clock time the whole run took.
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The time spent in each [`Phase`] across all files.
+    pub fn timings(&self) -> Timings {
+        let mut timings: Timings = Timings::default();
+        for file in &self.files {
+            timings.accumulate(file.timings());
+        }
+        timings
+    }
+
+    /// A human-readable report of the time spent in each [`Phase`], per file
+    /// and in total.
+    pub const fn timing_report(&self) -> TimingReport<'_> {
+        TimingReport { summary: self }
+    }
+
+    /// A machine-readable summary of the run, as a JSON object, listing
+    /// `error` if the run failed.
+    pub const fn json_report<'summary>(
+        &'summary self,
+        error: Option<&'summary HackError>,
+    ) -> JsonReport<'summary> {
+        JsonReport {
+            summary: self,
+            error,
+        }
+    }
+}
+
+/// A human-readable report of the time spent in each [`Phase`]. See
+/// [`Summary::timing_report`].
+#[derive(Debug, Clone, Copy)]
+pub struct TimingReport<'summary> {
+    /// The [`Summary`] being reported on.
+    summary: &'summary Summary,
+}
+
+impl TimingReport<'_> {
+    /// Writes a single line of the report.
+    fn write_line(
+        formatter: &mut fmt::Formatter<'_>,
+        name: &str,
+        timings: &Timings,
+    ) -> fmt::Result {
+        write!(formatter, "{name}:")?;
+        for phase in Phase::ALL {
+            write!(
+                formatter,
+                " {} {:.3} ms,",
+                phase.name(),
+                timings.get(phase).as_secs_f64() * 1000.0
+            )?;
+        }
+        writeln!(
+            formatter,
+            " total {:.3} ms",
+            timings.total().as_secs_f64() * 1000.0
+        )
+    }
+}
+
+impl Display for TimingReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for file in self.summary.files() {
+            Self::write_line(
+                f,
+                &paths::display(file.path()).to_string(),
+                &file.timings,
+            )?;
+        }
+        Self::write_line(f, "all files", &self.summary.timings())?;
+        writeln!(
+            f,
+            "wall-clock: {:.3} ms",
+            self.summary.elapsed().as_secs_f64() * 1000.0
+        )
+    }
+}
+
+/// A compact table of what happened to each file. See [`Summary::table`].
+#[derive(Debug, Clone, Copy)]
+pub struct Table<'summary> {
+    /// The [`Summary`] being reported on.
+    summary: &'summary Summary,
+}
+
+impl Display for Table<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = self
+            .summary
+            .files()
+            .iter()
+            .map(|file: &FileSummary| {
+                let path: &Path = self
+                    .summary
+                    .directory()
+                    .and_then(|directory: &Path| {
+                        file.path().strip_prefix(directory).ok()
+                    })
+                    .unwrap_or_else(|| file.path());
+                paths::display(path).to_string()
+            })
+            .collect();
+        let width: usize = names
+            .iter()
+            .map(String::len)
+            .chain([Self::FILE.len()])
+            .max()
+            .unwrap_or_default();
+        writeln!(
+            f,
+            "{:width$}  {:>8}  {:>16}  {:>8}  status",
+            Self::FILE,
+            "VM lines",
+            "asm instructions",
+            "warnings"
+        )?;
+        for (name, file) in names.iter().zip(self.summary.files()) {
+            writeln!(
+                f,
+                "{name:width$}  {:>8}  {:>16}  {:>8}  {}",
+                file.vm_lines(),
+                file.assembly_instructions(),
+                file.warnings().len(),
+                if file.cached() {
+                    "cached"
+                } else {
+                    "translated"
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Table<'_> {
+    /// The heading of the column of files.
+    const FILE: &'static str = "file";
+}
+
+/// A machine-readable summary of a run, as a JSON object. See
+/// [`Summary::json_report`].
+#[derive(Debug, Clone, Copy)]
+pub struct JsonReport<'summary> {
+    /// The [`Summary`] being reported on.
+    summary: &'summary Summary,
+    /// The error the run failed with, if it did.
+    error: Option<&'summary HackError>,
+}
+
+impl JsonReport<'_> {
+    /// Writes the time spent in each [`Phase`] as a JSON object.
+    fn write_timings(
+        formatter: &mut fmt::Formatter<'_>,
+        timings: &Timings,
+    ) -> fmt::Result {
+        write!(formatter, "{{")?;
+        for (index, phase) in Phase::ALL.into_iter().enumerate() {
+            if index > 0 {
+                write!(formatter, ", ")?;
+            }
+            write!(
+                formatter,
+                "\"{}\": {:.3}",
+                phase.name(),
+                timings.get(phase).as_secs_f64() * 1000.0
+            )?;
+        }
+        write!(formatter, "}}")
+    }
+}
+
+impl Display for JsonReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let files: &[FileSummary] = self.summary.files();
+        writeln!(f, "{{\n  \"files\": [")?;
+        for (index, file) in files.iter().enumerate() {
+            let path: String = paths::display(file.path()).to_string();
+            write!(
+                f,
+                "    {{\"path\": {}, \"cached\": {}, \"instructions\": {}, \
+                \"assembly_lines\": {}, \"timings_ms\": ",
+                Value::from(path.as_str()),
+                file.cached(),
+                file.instructions(),
+                file.assembly_lines()
+            )?;
+            Self::write_timings(f, file.timings())?;
+            let separator: &str = if index.saturating_add(1) < files.len() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(f, "}}{separator}")?;
+        }
+        writeln!(f, "  ],")?;
+        writeln!(
+            f,
+            "  \"instructions\": {},",
+            files.iter().map(FileSummary::instructions).sum::<usize>()
+        )?;
+        writeln!(
+            f,
+            "  \"assembly_lines\": {},",
+            files.iter().map(FileSummary::assembly_lines).sum::<usize>()
+        )?;
+        let warnings: Vec<String> = self
+            .summary
+            .warnings()
+            .map(|warning: &Warning| {
+                Value::from(warning.to_string().as_str()).to_string()
+            })
+            .collect();
+        writeln!(f, "  \"warnings\": [{}],", warnings.join(", "))?;
+        match self.error {
+            Some(error) => writeln!(
+                f,
+                "  \"errors\": [{}],",
+                Value::from(error.to_string().as_str())
+            )?,
+            None => writeln!(f, "  \"errors\": [],")?,
+        }
+        write!(f, "  \"timings_ms\": ")?;
+        Self::write_timings(f, &self.summary.timings())?;
+        writeln!(
+            f,
+            ",\n  \"elapsed_ms\": {:.3}\n}}",
+            self.summary.elapsed().as_secs_f64() * 1000.0
+        )
+    }
+}
@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Symbols Module
+//!
+//! Lists every symbol the generated assembly defines or refers to, requested
+//! with `--dump-symbols`, so that clashes with hand-written assembly linked
+//! alongside it can be caught before assembling:
+//!
+//! ```text
+//! labels:
+//!   CRASH_5
+//!     defined at Main.asm:12
+//!     referenced at Main.asm:9
+//! variables:
+//!   Main.0
+//!     referenced at Main.asm:20
+//! predefined:
+//!   SP
+//!     referenced at Main.asm:3, Main.asm:8
+//! ```
+//!
+//! Labels are the symbols declared with `(LABEL)`. Variables are the other
+//! symbols that are not predefined by the assembler, such as static
+//! variables, which the assembler allocates from address 16. Sites refer to
+//! lines of the assembly generated for each file, as if it were written to
+//! its own `.asm` file.
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Display};
+
+/// The symbols predefined by the Hack assembler.
+const PREDEFINED: [&str; 23] = [
+    "SP", "LCL", "ARG", "THIS", "THAT", "R0", "R1", "R2", "R3", "R4", "R5",
+    "R6", "R7", "R8", "R9", "R10", "R11", "R12", "R13", "R14", "R15", "SCREEN",
+    "KBD",
+];
+
+/// Where a single symbol is defined and referenced.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+struct Entry {
+    /// Where the symbol is declared as a label, as `FILE:LINE`.
+    definitions: Vec<String>,
+    /// Where the symbol is referred to, as `FILE:LINE`.
+    references: Vec<String>,
+}
+
+/// The symbols of the generated assembly.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct SymbolTable {
+    /// Every symbol, by name.
+    symbols: BTreeMap<String, Entry>,
+}
+
+impl SymbolTable {
+    /// Records the symbols in the assembly generated for a file, whose
+    /// assembly file is displayed as `file`.
+    pub(crate) fn add_assembly(&mut self, file: &str, assembly: &str) {
+        for (line, number) in assembly.lines().zip(1_usize..) {
+            let line: &str = line.trim();
+            let site: String = format!("{file}:{number}");
+            if let Some(label) = line
+                .strip_prefix('(')
+                .and_then(|rest| rest.strip_suffix(')'))
+            {
+                self.symbols
+                    .entry(label.to_owned())
+                    .or_default()
+                    .definitions
+                    .push(site);
+                continue;
+            }
+            if let Some(symbol) = line.strip_prefix('@')
+                && !symbol
+                    .starts_with(|character: char| character.is_ascii_digit())
+            {
+                self.symbols
+                    .entry(symbol.to_owned())
+                    .or_default()
+                    .references
+                    .push(site);
+            }
+        }
+    }
+
+    /// Counts the variables, which the assembler allocates a word each for.
+    pub(crate) fn variables(&self) -> usize {
+        self.symbols
+            .iter()
+            .filter(|&(name, entry)| is_variable(name, entry))
+            .count()
+    }
+
+    /// Writes one section of the table, listing the symbols for which
+    /// `include` holds.
+    fn write_section<F: Fn(&str, &Entry) -> bool>(
+        &self,
+        formatter: &mut fmt::Formatter<'_>,
+        heading: &str,
+        include: F,
+    ) -> fmt::Result {
+        writeln!(formatter, "{heading}:")?;
+        for (name, entry) in &self.symbols {
+            if !include(name, entry) {
+                continue;
+            }
+            writeln!(formatter, "  {name}")?;
+            if !entry.definitions.is_empty() {
+                writeln!(
+                    formatter,
+                    "    defined at {}",
+                    entry.definitions.join(", ")
+                )?;
+            }
+            if entry.references.is_empty() {
+                writeln!(formatter, "    never referenced")?;
+            } else {
+                writeln!(
+                    formatter,
+                    "    referenced at {}",
+                    entry.references.join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for SymbolTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_section(f, "labels", |_, entry: &Entry| {
+            !entry.definitions.is_empty()
+        })?;
+        self.write_section(f, "variables", is_variable)?;
+        self.write_section(f, "predefined", |name: &str, entry: &Entry| {
+            entry.definitions.is_empty() && PREDEFINED.contains(&name)
+        })
+    }
+}
+
+/// Whether the symbol `name` is a variable: neither declared as a label nor
+/// predefined by the assembler.
+fn is_variable(name: &str, entry: &Entry) -> bool {
+    entry.definitions.is_empty() && !PREDEFINED.contains(&name)
+}
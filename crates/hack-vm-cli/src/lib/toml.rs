@@ -0,0 +1,420 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - TOML Module
+//!
+//! Reads the subset of TOML used by the translator's configuration files:
+//! `[table]` headers, bare keys, and basic strings, literal strings,
+//! booleans, integers, and (possibly multi-line) arrays of those as values.
+//! Comments start with `#`.
+
+use alloc::collections::BTreeMap;
+use core::iter::Peekable;
+use core::str::Chars;
+use std::path::{Path, PathBuf};
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::paths;
+
+/// A value in a TOML document.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) enum Value {
+    /// A basic or literal string.
+    String(String),
+    /// A decimal integer.
+    Integer(i64),
+    /// `true` or `false`.
+    Boolean(bool),
+    /// An array of values.
+    Array(Vec<Self>),
+}
+
+impl Value {
+    /// Get the name of the type of this [`Value`] as used in error messages.
+    const fn type_name(&self) -> &'static str {
+        match *self {
+            Self::String(_) => "a string",
+            Self::Integer(_) => "an integer",
+            Self::Boolean(_) => "a boolean",
+            Self::Array(_) => "an array",
+        }
+    }
+}
+
+/// A parsed TOML document.
+///
+/// Keys are stored with their table prefixed, such that `b = 1` under `[a]`
+/// is found at `a.b`. Values are meant to be removed with the `take_*`
+/// methods as they are interpreted, after which [`Table::finish`] rejects any
+/// that were not understood.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct Table {
+    /// The file the document was read from, for error messages.
+    path: PathBuf,
+    /// The values that have not been taken yet, by their full key.
+    values: BTreeMap<String, Value>,
+}
+
+impl Table {
+    /// Parses `text`, which was read from the file at `path`.
+    pub(crate) fn parse(path: &Path, text: &str) -> Result<Self, HackError> {
+        let mut reader: Reader<'_> = Reader {
+            path,
+            chars: text.chars().peekable(),
+            line: 1,
+        };
+        let mut values: BTreeMap<String, Value> = BTreeMap::new();
+        let mut table: String = String::new();
+
+        while let Some(character) = reader.skip_whitespace(true) {
+            if character == '[' {
+                let _open: Option<char> = reader.next();
+                table = reader.key()?;
+                reader.expect(']')?;
+                reader.end_of_line()?;
+                continue;
+            }
+            let key: String = if table.is_empty() {
+                reader.key()?
+            } else {
+                format!("{table}.{}", reader.key()?)
+            };
+            let _whitespace: Option<char> = reader.skip_whitespace(false);
+            reader.expect('=')?;
+            let value: Value = reader.value()?;
+            reader.end_of_line()?;
+            if values.insert(key.clone(), value).is_some() {
+                return Err(reader.error(&format!("duplicate key \"{key}\"")));
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            values,
+        })
+    }
+
+    /// Creates a [`HackError::BadConfigFile`] about the value of `key`.
+    fn error(&self, key: &str, message: &str) -> HackError {
+        HackError::BadConfigFile(format!(
+            "{}: \"{key}\" {message}",
+            paths::display(&self.path)
+        ))
+    }
+
+    /// Lists the names of the tables directly within `table`, such as `b` for
+    /// a key `a.b.c` within `a`.
+    pub(crate) fn tables(&self, table: &str) -> Vec<String> {
+        let prefix: String = format!("{table}.");
+        let mut names: Vec<String> = self
+            .values
+            .keys()
+            .filter_map(|key: &String| {
+                key.strip_prefix(&prefix)?
+                    .split_once('.')
+                    .map(|(name, _): (&str, &str)| name.to_owned())
+            })
+            .collect();
+        names.dedup();
+        names
+    }
+
+    /// Removes the value of `key`, if present.
+    pub(crate) fn take(&mut self, key: &str) -> Option<Value> {
+        self.values.remove(key)
+    }
+
+    /// Removes the value of `key`, if present, requiring it to be a string.
+    pub(crate) fn take_string(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<String>, HackError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(Value::String(string)) => Ok(Some(string)),
+            Some(other) => Err(self.error(
+                key,
+                &format!("must be a string, found {}", other.type_name()),
+            )),
+        }
+    }
+
+    /// Removes the value of `key`, if present, requiring it to be a boolean.
+    pub(crate) fn take_bool(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<bool>, HackError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(Value::Boolean(boolean)) => Ok(Some(boolean)),
+            Some(other) => Err(self.error(
+                key,
+                &format!("must be a boolean, found {}", other.type_name()),
+            )),
+        }
+    }
+
+    /// Removes the value of `key`, if present, requiring it to be an integer.
+    pub(crate) fn take_integer(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<i64>, HackError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(Value::Integer(integer)) => Ok(Some(integer)),
+            Some(other) => Err(self.error(
+                key,
+                &format!("must be an integer, found {}", other.type_name()),
+            )),
+        }
+    }
+
+    /// Removes the value of `key`, if present, requiring it to be an array of
+    /// strings.
+    pub(crate) fn take_strings(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<Vec<String>>, HackError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(Value::Array(array)) => array
+                .into_iter()
+                .map(|value: Value| match value {
+                    Value::String(string) => Ok(string),
+                    other @ (Value::Integer(_)
+                    | Value::Boolean(_)
+                    | Value::Array(_)) => Err(self.error(
+                        key,
+                        &format!(
+                            "must only contain strings, found {}",
+                            other.type_name()
+                        ),
+                    )),
+                })
+                .collect::<Result<Vec<String>, HackError>>()
+                .map(Some),
+            Some(other) => Err(self.error(
+                key,
+                &format!("must be an array, found {}", other.type_name()),
+            )),
+        }
+    }
+
+    /// Creates a [`HackError::BadConfigFile`] saying the value of `key` is
+    /// unsupported, for the given reason.
+    pub(crate) fn unsupported(&self, key: &str, reason: &str) -> HackError {
+        self.error(key, reason)
+    }
+
+    /// Ensures every value has been taken, such that misspelled or
+    /// unsupported keys are not silently ignored.
+    pub(crate) fn finish(self) -> Result<(), HackError> {
+        self.values.keys().next().map_or(Ok(()), |key: &String| {
+            Err(self.error(key, "is not a recognized key"))
+        })
+    }
+}
+
+/// Reads a TOML document one character at a time.
+#[derive(Debug)]
+struct Reader<'text> {
+    /// The file being read, for error messages.
+    path: &'text Path,
+    /// The remaining characters.
+    chars: Peekable<Chars<'text>>,
+    /// The line currently being read, starting from 1.
+    line: usize,
+}
+
+impl Reader<'_> {
+    /// Creates a [`HackError::BadConfigFile`] at the current line.
+    fn error(&self, message: &str) -> HackError {
+        HackError::BadConfigFile(format!(
+            "{}:{}: {message}",
+            paths::display(self.path),
+            self.line
+        ))
+    }
+
+    /// Consumes the next character, tracking line numbers.
+    fn next(&mut self) -> Option<char> {
+        let character: Option<char> = self.chars.next();
+        if character == Some('\n') {
+            self.line = self.line.saturating_add(1);
+        }
+        character
+    }
+
+    /// Skips spaces, tabs, and comments, and also newlines if `newlines` is
+    /// set. Returns the next character without consuming it.
+    fn skip_whitespace(&mut self, newlines: bool) -> Option<char> {
+        while let Some(&character) = self.chars.peek() {
+            match character {
+                ' ' | '\t' | '\r' => {}
+                '\n' if newlines => {}
+                '#' => {
+                    while self.chars.peek().is_some_and(|&next| next != '\n') {
+                        let _comment: Option<char> = self.next();
+                    }
+                    continue;
+                }
+                _ => return Some(character),
+            }
+            let _whitespace: Option<char> = self.next();
+        }
+        None
+    }
+
+    /// Consumes `expected`, or fails.
+    fn expect(&mut self, expected: char) -> Result<(), HackError> {
+        let _whitespace: Option<char> = self.skip_whitespace(false);
+        match self.next() {
+            Some(character) if character == expected => Ok(()),
+            Some(character) => Err(self.error(&format!(
+                "expected \"{expected}\", found \"{character}\""
+            ))),
+            None => Err(self
+                .error(&format!("expected \"{expected}\", found end of file"))),
+        }
+    }
+
+    /// Ensures nothing but whitespace or a comment follows on this line.
+    fn end_of_line(&mut self) -> Result<(), HackError> {
+        match self.skip_whitespace(false) {
+            None | Some('\n') => Ok(()),
+            Some(character) => Err(self.error(&format!(
+                "expected the end of the line, found \"{character}\""
+            ))),
+        }
+    }
+
+    /// Reads a bare key, which may be dotted.
+    fn key(&mut self) -> Result<String, HackError> {
+        let _whitespace: Option<char> = self.skip_whitespace(false);
+        let mut key: String = String::new();
+        while let Some(&character) = self.chars.peek() {
+            if character.is_ascii_alphanumeric()
+                || character == '_'
+                || character == '-'
+                || character == '.'
+            {
+                key.push(character);
+                let _key: Option<char> = self.next();
+            } else {
+                break;
+            }
+        }
+        if key.is_empty() {
+            return Err(self.error("expected a key"));
+        }
+        let _whitespace: Option<char> = self.skip_whitespace(false);
+        Ok(key)
+    }
+
+    /// Reads a value.
+    fn value(&mut self) -> Result<Value, HackError> {
+        match self.skip_whitespace(false) {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array().map(Value::Array),
+            Some(_) => self.scalar(),
+            None => Err(self.error("expected a value, found end of file")),
+        }
+    }
+
+    /// Reads a double-quoted string, interpreting escapes.
+    fn basic_string(&mut self) -> Result<String, HackError> {
+        let _open: Option<char> = self.next();
+        let mut string: String = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(match self.next() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some(other) => {
+                        return Err(self.error(&format!(
+                            "unsupported escape sequence \"\\{other}\""
+                        )));
+                    }
+                    None => break,
+                }),
+                Some('\n') | None => break,
+                Some(character) => string.push(character),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    /// Reads a single-quoted string, which has no escapes.
+    fn literal_string(&mut self) -> Result<String, HackError> {
+        let _open: Option<char> = self.next();
+        let mut string: String = String::new();
+        loop {
+            match self.next() {
+                Some('\'') => return Ok(string),
+                Some('\n') | None => {
+                    return Err(self.error("unterminated string"));
+                }
+                Some(character) => string.push(character),
+            }
+        }
+    }
+
+    /// Reads an array, which may span several lines.
+    fn array(&mut self) -> Result<Vec<Value>, HackError> {
+        let _open: Option<char> = self.next();
+        let mut array: Vec<Value> = Vec::new();
+        loop {
+            if self.skip_whitespace(true) == Some(']') {
+                let _close: Option<char> = self.next();
+                return Ok(array);
+            }
+            array.push(self.value()?);
+            match self.skip_whitespace(true) {
+                Some(',') => {
+                    let _comma: Option<char> = self.next();
+                }
+                Some(']') => {}
+                Some(character) => {
+                    return Err(self.error(&format!(
+                        "expected \",\" or \"]\", found \"{character}\""
+                    )));
+                }
+                None => return Err(self.error("unterminated array")),
+            }
+        }
+    }
+
+    /// Reads a boolean or an integer.
+    fn scalar(&mut self) -> Result<Value, HackError> {
+        let mut word: String = String::new();
+        while let Some(&character) = self.chars.peek() {
+            if character.is_ascii_alphanumeric()
+                || character == '_'
+                || character == '-'
+                || character == '+'
+            {
+                word.push(character);
+                let _word: Option<char> = self.next();
+            } else {
+                break;
+            }
+        }
+        match word.as_str() {
+            "true" => Ok(Value::Boolean(true)),
+            "false" => Ok(Value::Boolean(false)),
+            number => number
+                .replace('_', "")
+                .parse::<i64>()
+                .map(Value::Integer)
+                .map_err(|error| {
+                    self.error(&format!("invalid value \"{number}\": {error}"))
+                }),
+        }
+    }
+}
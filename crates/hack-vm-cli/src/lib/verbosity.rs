@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Verbosity Module
+//!
+//! Prints what each file looks like between the stages of translating it, to
+//! stderr, which helps pin down where a bug in the translator is. Each `-v`
+//! prints one more stage, as in `-vv`, and `--verbose` may be given several
+//! times instead:
+//!
+//! 1. The tokens of each line, once comments and blank lines are removed:
+//!
+//!    ```text
+//!    Main.vm:3: tokens: "push" "constant" "7"
+//!    ```
+//!
+//! 2. The instructions they are parsed into, written as the official VM
+//!    language, after macros are expanded, files are included, and anything
+//!    accepted only in permissive mode is desugared, each located at the
+//!    line and column it was written, in whichever file it was written:
+//!
+//!    ```text
+//!    Main.vm:3:5: instruction: push constant 7
+//!    ```
+//!
+//! 3. The assembly, as generated and before anything rewrites it, such as
+//!    `--renumber-labels`.
+//!
+//! The cache is not used at any level above [`Verbosity::Quiet`], so that
+//! every file is translated and printed.
+
+use core::fmt::Display;
+use std::io::{self, Write as _};
+
+use hack_vm_syntax::parser::{Instruction, Parser};
+use hack_vm_syntax::provenance::Provenance;
+
+/// How many stages of translation are printed.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Verbosity {
+    /// Nothing is printed.
+    #[default]
+    Quiet,
+    /// The tokens of each line are printed, as with `-v`.
+    Tokens,
+    /// The parsed instructions are printed as well, as with `-vv`.
+    Instructions,
+    /// The generated assembly is printed as well, as with `-vvv`.
+    Assembly,
+}
+
+impl Verbosity {
+    /// Raises the verbosity by `levels`, up to [`Verbosity::Assembly`].
+    pub(crate) fn raised(self, levels: usize) -> Self {
+        (0..levels).fold(self, |verbosity: Self, _| match verbosity {
+            Self::Quiet => Self::Tokens,
+            Self::Tokens => Self::Instructions,
+            Self::Instructions | Self::Assembly => Self::Assembly,
+        })
+    }
+
+    /// Gets how many levels a flag such as `-vv` raises the verbosity by, if
+    /// it is one.
+    pub(crate) fn flag_levels(arg: &str) -> Option<usize> {
+        arg.strip_prefix('-')
+            .filter(|vs: &&str| {
+                !vs.is_empty() && vs.bytes().all(|byte: u8| byte == b'v')
+            })
+            .map(str::len)
+    }
+
+    /// Prints the tokens of each line read by `parser` from `file`, if they
+    /// are printed at this verbosity.
+    pub(crate) fn tokens(self, file: &dyn Display, parser: &Parser) {
+        if self < Self::Tokens {
+            return;
+        }
+        let mut stderr: io::StderrLock<'_> = io::stderr().lock();
+        for (line_number, parts) in parser.numbered_lines() {
+            let tokens: Vec<String> = parts
+                .iter()
+                .map(|part: &&str| format!("\"{part}\""))
+                .collect();
+            // Like tracing, this is only diagnostic, so failing to print it
+            // is ignored.
+            let _printed: io::Result<()> = writeln!(
+                stderr,
+                "{file}:{line_number}: tokens: {}",
+                tokens.join(" ")
+            );
+        }
+    }
+
+    /// Prints the parsed `instructions`, each where it was written, if they
+    /// are printed at this verbosity.
+    pub(crate) fn instructions(
+        self,
+        instructions: &[(Provenance, Instruction)],
+    ) {
+        if self < Self::Instructions {
+            return;
+        }
+        let mut stderr: io::StderrLock<'_> = io::stderr().lock();
+        for located in instructions {
+            let _printed: io::Result<()> =
+                writeln!(stderr, "{}: instruction: {}", located.0, located.1);
+        }
+    }
+
+    /// Prints the `assembly` generated for `file`, if it is printed at this
+    /// verbosity.
+    pub(crate) fn assembly(self, file: &dyn Display, assembly: &str) {
+        if self < Self::Assembly {
+            return;
+        }
+        let _printed: io::Result<()> =
+            write!(io::stderr().lock(), "{file}: assembly:\n{assembly}");
+    }
+}
@@ -0,0 +1,501 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Verify Module
+//!
+//! Checks a translation by running the same program twice from the same
+//! memory: through the interpreter of [`crate::eval`], and, translated,
+//! through the assembler and the CPU of [`crate::cpu`]. What the program
+//! can see of memory afterwards is then compared:
+//!
+//! ```
+//! use hack_vm_cli::eval::MachineState;
+//! use hack_vm_cli::verify::{self, Divergence};
+//! use hack_vm_syntax::error::HackError;
+//!
+//! let divergence: Option<Divergence> = verify::verify(
+//!     "push constant 7\npush constant 8\nlt\npop static 0",
+//!     &MachineState::default(),
+//! )?;
+//! assert!(divergence.is_none());
+//! # Ok::<(), HackError>(())
+//! ```
+//!
+//! That is the pointers, the `temp` segment, and the stack, as well as each
+//! word of the other segments that the program uses. Static variables are
+//! found by their names, as the assembler allocates them in order of their
+//! first use. The scratch memory of the assembly, `R13` to `R15` and the
+//! words above the stack, is not compared.
+//!
+//! If the two differ, both are run again one command at a time, with the
+//! assembly annotated to find where the code of each command starts. The
+//! first command after which they differ is reported, or after which the
+//! assembly does not go on to the code of the next command.
+//!
+//! Only programs that the interpreter can run are checked, which excludes
+//! functions, calls, and inline assembly. As the translator does not yet
+//! translate branching commands, a program with them is reported to have
+//! failed when translated.
+//!
+//! With `--verify`, each file is checked this way once it is translated,
+//! starting from [`MachineState::default`].
+
+use alloc::collections::BTreeSet;
+use core::fmt::{self, Debug, Display};
+use std::path::PathBuf;
+
+use hack_vm_codegen::Options;
+use hack_vm_codegen::labels::LabelScheme;
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::extensions::Extensions;
+use hack_vm_syntax::parser::{Instruction, StackManipulation, Symbol};
+use hack_vm_syntax::segments::Segments;
+use hack_vm_syntax::target::Target;
+
+use crate::cpu::Cpu;
+use crate::eval::{self, MAX_STEPS, MachineState, Program};
+use crate::summary::FileSummary;
+use crate::symbols::SymbolTable;
+use crate::verbosity::Verbosity;
+use crate::{Output, Settings, Source, run_for_file};
+
+/// Whether each file translated is checked to run the same when translated
+/// as when interpreted.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub(crate) enum Verification {
+    /// Files are only translated.
+    #[default]
+    Skipped,
+    /// Each file is checked once it is translated, as with `--verify`.
+    Checked,
+}
+
+/// The name of the file a program checked with [`verify`] is translated as.
+const FILE_NAME: &str = "Main";
+
+/// The address of the static variable `static 0` to the interpreter.
+const STATIC_BASE: u16 = 16;
+
+/// How a program ran differently when translated than when interpreted.
+///
+/// Its [`Debug`] shows the same report as its [`Display`], so that a fuzz
+/// target which unwraps it prints the program and the differences as
+/// they are.
+#[derive(Clone, Default, Hash, PartialEq, Eq)]
+pub struct Divergence {
+    /// The program, as VM code.
+    program: String,
+    /// What differed, one difference after another.
+    differences: Vec<String>,
+}
+
+impl Divergence {
+    /// Reports that `program` ran with the given `differences`.
+    pub(crate) const fn new(program: String, differences: Vec<String>) -> Self {
+        Self {
+            program,
+            differences,
+        }
+    }
+
+    /// Gets the differences, one after another.
+    pub fn differences(&self) -> &[String] {
+        &self.differences
+    }
+}
+
+impl Display for Divergence {
+    /// Writes the program, then each difference on a line of its own.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.program.trim_end())?;
+        for difference in &self.differences {
+            writeln!(f, "{difference}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\n{self}")
+    }
+}
+
+/// Checks that the Hack VM commands in `vm_code` run the same translated as
+/// interpreted, and gets how they ran differently, if they did.
+///
+/// Both start from `initial_state`. The code is translated with the usual
+/// passes, as a file named `Main`,
+/// with the extended arithmetic commands enabled.
+///
+/// # Errors
+///
+/// Any error from parsing or interpreting the code is propagated, as a
+/// program that the interpreter cannot run cannot be checked.
+pub fn verify(
+    vm_code: &str,
+    initial_state: &MachineState,
+) -> Result<Option<Divergence>, HackError> {
+    let settings: Settings = Settings {
+        translation: Options::new(
+            LabelScheme::default(),
+            Extensions::Inline,
+            false,
+            Segments::default(),
+            None,
+        ),
+        ..Settings::default()
+    };
+    let source: Source = Source::Archived {
+        path: PathBuf::from(format!("{FILE_NAME}.vm")),
+        contents: vm_code.to_owned(),
+    };
+    let differences: Vec<String> = check(
+        &source,
+        &Symbol::sanitized(FILE_NAME),
+        initial_state,
+        &settings,
+    )?;
+    Ok((!differences.is_empty())
+        .then(|| Divergence::new(vm_code.to_owned(), differences)))
+}
+
+/// Gets how `source` runs differently translated than interpreted, or
+/// nothing if it runs the same.
+///
+/// Both start from `initial_state`, and it is translated according to
+/// `settings`, with its static variables prefixed with `static_prefix`. The
+/// first command after which they differ is described first, if it can be
+/// found, then every difference in the memory they leave.
+///
+/// # Errors
+///
+/// Any error from reading the file, or from parsing or interpreting it, is
+/// propagated. Errors from translating it, assembling it, or running it are
+/// differences instead.
+pub(crate) fn check(
+    source: &Source,
+    static_prefix: &Symbol,
+    initial_state: &MachineState,
+    settings: &Settings,
+) -> Result<Vec<String>, HackError> {
+    let vm_code: String = source.read(settings.limits)?.contents().to_owned();
+    let program: Program = Program::parse(&vm_code)?;
+    let interpreted: MachineState =
+        eval::evaluate_snippet(&vm_code, initial_state.clone())?;
+    // Nothing but the assembly is wanted from translating the file again.
+    let mut settings: Settings = Settings {
+        print_after: BTreeSet::new(),
+        verbosity: Verbosity::default(),
+        warnings: None,
+        ..settings.clone()
+    };
+    let translated: Result<Cpu, HackError> =
+        assemble(source, static_prefix, initial_state, &settings)
+            .and_then(|mut cpu: Cpu| cpu.run().map(|_cycles: usize| cpu));
+    let differences: Vec<String> = match translated {
+        Ok(translated) => {
+            compare(&program, static_prefix, &interpreted, &translated)
+        }
+        Err(error) => vec![format!("the translated program failed: {error}")],
+    };
+    if differences.is_empty() {
+        return Ok(differences);
+    }
+    settings.annotate = true;
+    let first: Option<String> =
+        assemble(source, static_prefix, initial_state, &settings)
+            .ok()
+            .and_then(|cpu: Cpu| {
+                first_difference(&program, static_prefix, initial_state, cpu)
+            });
+    Ok(first.into_iter().chain(differences).collect())
+}
+
+/// Translates `source` according to `settings`, with its static variables
+/// prefixed with `static_prefix`, and assembles it into a CPU whose memory is
+/// `initial_state`, ready to run.
+///
+/// # Errors
+///
+/// Any error from translating or assembling the file is propagated.
+fn assemble(
+    source: &Source,
+    static_prefix: &Symbol,
+    initial_state: &MachineState,
+    settings: &Settings,
+) -> Result<Cpu, HackError> {
+    let mut output: Output = Output::Buffer(String::new());
+    let _summary: FileSummary = run_for_file(
+        source,
+        Some(static_prefix),
+        settings,
+        None,
+        &mut output,
+        &mut SymbolTable::default(),
+        true,
+    )?;
+    let Output::Buffer(assembly) = output else {
+        return Err(HackError::Internal);
+    };
+    let mut cpu: Cpu = assembly.parse()?;
+    let stack: u16 = Target::default().stack().base();
+    for (address, &word) in (0_u16..).zip(initial_state.ram()) {
+        if word != 0 && !(STATIC_BASE..stack).contains(&address) {
+            cpu.set(address, word);
+        }
+    }
+    for index in 0..stack.saturating_sub(STATIC_BASE) {
+        if let Some(address) = cpu.variable(&format!("{static_prefix}.{index}"))
+        {
+            cpu.set(
+                address,
+                initial_state.get(STATIC_BASE.saturating_add(index)),
+            );
+        }
+    }
+    Ok(cpu)
+}
+
+/// Runs `program` through the interpreter and `translated` one command at a
+/// time, both starting from `initial_state`, and describes the first command
+/// after which they differ, if it can be found.
+///
+/// The `translated` assembly must be annotated, such that the code of each
+/// command follows a comment; see [`Cpu::comments`].
+fn first_difference(
+    program: &Program,
+    static_prefix: &Symbol,
+    initial_state: &MachineState,
+    mut translated: Cpu,
+) -> Option<String> {
+    let starts: Vec<usize> = translated.comments().to_vec();
+    if starts.len() != program.instructions().len() {
+        return None;
+    }
+    let stops: BTreeSet<usize> = starts.iter().copied().collect();
+    let mut interpreted: MachineState = initial_state.clone();
+    let mut index: usize = 0;
+    for _ in 0..MAX_STEPS {
+        let &(line, ref instruction) = program.instructions().get(index)?;
+        let command: String = format!("line {line}, \"{instruction}\"");
+        let next: usize = program.step(&mut interpreted, index).ok()?;
+        // Commands such as `label` have no code of their own to run.
+        if starts.get(index.saturating_add(1)) != starts.get(index)
+            && let Err(error) = translated.run_to(&stops)
+        {
+            return Some(format!("{command}, failed when translated: {error}"));
+        }
+        if !compare(program, static_prefix, &interpreted, &translated)
+            .is_empty()
+        {
+            return Some(format!(
+                "memory first differs after {command}, as follows:"
+            ));
+        }
+        if let Some(&start) = starts.get(next)
+            && start != translated.counter()
+        {
+            return Some(format!(
+                "after {command}, the translated program went to \
+                instruction {} rather than to instruction {start}",
+                translated.counter()
+            ));
+        }
+        index = next;
+    }
+    None
+}
+
+/// Gets every difference between the memory left by the `interpreted` and
+/// `translated` program, of what `program` can see of it.
+fn compare(
+    program: &Program,
+    static_prefix: &Symbol,
+    interpreted: &MachineState,
+    translated: &Cpu,
+) -> Vec<String> {
+    let target: Target = Target::default();
+    let temp_end: u16 =
+        target.temp().base().saturating_add(target.temp().size());
+    let stack: u16 = target.stack().base();
+    let top: u16 = interpreted
+        .get(MachineState::SP)
+        .cast_unsigned()
+        .max(translated.get(MachineState::SP).cast_unsigned());
+    let mut addresses: BTreeSet<u16> =
+        (0..temp_end).chain(stack..top).collect();
+    let mut statics: BTreeSet<u16> = BTreeSet::new();
+    for &(_line, ref instruction) in program.instructions() {
+        let Instruction::StackManipulation(
+            StackManipulation::Push { ref symbol, value }
+            | StackManipulation::Pop { ref symbol, value },
+        ) = *instruction
+        else {
+            continue;
+        };
+        let index: u16 = value.literal_representation();
+        let pointer: u16 = match symbol.literal_representation() {
+            "local" => MachineState::LCL,
+            "argument" => MachineState::ARG,
+            "this" => MachineState::THIS,
+            "that" => MachineState::THAT,
+            "static" => {
+                let _new: bool = statics.insert(index);
+                continue;
+            }
+            _ => continue,
+        };
+        let base: u16 = interpreted.get(pointer).cast_unsigned();
+        let _new: bool = addresses.insert(base.saturating_add(index));
+    }
+
+    let mut differences: Vec<String> = Vec::new();
+    let mut differ = |name: String, interpreted: i16, translated: i16| {
+        if interpreted != translated {
+            differences.push(format!(
+                "{name}: {interpreted} interpreted, {translated} translated"
+            ));
+        }
+    };
+    for address in addresses {
+        differ(
+            format!("RAM[{address}]"),
+            interpreted.get(address),
+            translated.get(address),
+        );
+    }
+    for index in statics {
+        let name: String = format!("{static_prefix}.{index}");
+        let value: i16 = translated
+            .variable(&name)
+            .map_or(0, |address: u16| translated.get(address));
+        differ(
+            name,
+            interpreted.get(STATIC_BASE.saturating_add(index)),
+            value,
+        );
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    //! Checks that translations are verified, and that the differences of
+    //! one that is wrong are found.
+
+    use hack_vm_syntax::parser::Symbol;
+
+    use super::{Cpu, MachineState, Program, compare, first_difference};
+    use crate::eval;
+
+    /// A program that [`WRONG_ASSEMBLY`] translates, but for its `add`.
+    const PROGRAM: &str =
+        "push constant 1\npush constant 2\nadd\npop temp 0\npush constant 4\n";
+
+    /// [`PROGRAM`], annotated, with `add` translated as `sub`.
+    const WRONG_ASSEMBLY: &str = "\
+        // push constant 1\n@1\nD=A\n@SP\nM=M+1\nA=M-1\nM=D\n\
+        // push constant 2\n@2\nD=A\n@SP\nM=M+1\nA=M-1\nM=D\n\
+        // add\n@SP\nAM=M-1\nD=M\nA=A-1\nM=M-D\n\
+        // pop temp 0\n@SP\nAM=M-1\nD=M\n@5\nM=D\n\
+        // push constant 4\n@4\nD=A\n@SP\nM=M+1\nA=M-1\nM=D\n";
+
+    /// Assembles [`WRONG_ASSEMBLY`] into a CPU whose memory is that of
+    /// [`MachineState::default`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the assembly cannot be assembled.
+    fn wrong_translation() -> Cpu {
+        let mut cpu: Cpu = WRONG_ASSEMBLY.parse().unwrap();
+        for (address, &word) in (0_u16..).zip(MachineState::default().ram()) {
+            cpu.set(address, word);
+        }
+        cpu
+    }
+
+    /// Checks that `vm_code` runs the same translated as interpreted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it does not, or if it cannot be checked.
+    fn assert_verified(vm_code: &str) {
+        let divergence: Option<super::Divergence> =
+            super::verify(vm_code, &MachineState::default()).unwrap();
+        assert_eq!(divergence, None);
+    }
+
+    /// Checks that the memory left by [`WRONG_ASSEMBLY`] differs from that
+    /// left by [`PROGRAM`] as `expected`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it does not.
+    fn assert_differences(expected: &[&str]) {
+        let program: Program = Program::parse(PROGRAM).unwrap();
+        let interpreted: MachineState =
+            eval::evaluate_snippet(PROGRAM, MachineState::default()).unwrap();
+        let mut translated: Cpu = wrong_translation();
+        let _cycles: usize = translated.run().unwrap();
+        assert_eq!(
+            compare(
+                &program,
+                &Symbol::sanitized("Main"),
+                &interpreted,
+                &translated
+            ),
+            expected
+        );
+    }
+
+    /// Checks that the first command [`WRONG_ASSEMBLY`] runs differently is
+    /// described as `expected`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is not.
+    fn assert_first_difference(expected: &str) {
+        let program: Program = Program::parse(PROGRAM).unwrap();
+        assert_eq!(
+            first_difference(
+                &program,
+                &Symbol::sanitized("Main"),
+                &MachineState::default(),
+                wrong_translation(),
+            )
+            .as_deref(),
+            Some(expected)
+        );
+    }
+
+    /// Every segment is translated as it is interpreted, including the
+    /// pointers, which move `this` and `that`.
+    #[test]
+    fn segments_verify() {
+        assert_verified(
+            "push constant 7\npop local 2\npush local 2\npop argument 1\n\
+            push argument 1\npush constant 3\nsub\npop this 4\n\
+            push constant 4000\npop pointer 1\npush this 4\npop that 2\n\
+            push that 2\npop temp 6\npush temp 6\npop static 3\n\
+            push static 3\npush pointer 1\nadd\n",
+        );
+    }
+
+    /// The memory left by a wrong translation differs where the result of
+    /// its `add` was popped to.
+    #[test]
+    fn differences_are_found() {
+        assert_differences(&["RAM[5]: 3 interpreted, -1 translated"]);
+    }
+
+    /// The command that a wrong translation first runs differently is the
+    /// `add` it translates wrong, not the `pop` that shows it.
+    #[test]
+    fn the_first_difference_is_found() {
+        assert_first_difference(
+            "memory first differs after line 3, \"add\", as follows:",
+        );
+    }
+}
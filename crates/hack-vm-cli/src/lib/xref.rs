@@ -0,0 +1,206 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Cross-Reference Module
+//!
+//! Lists where every function, label, and static variable of a program is
+//! defined and referenced, to help audit large programs spread over many
+//! files. The report is produced by the `xref` subcommand:
+//!
+//! ```text
+//! functions:
+//!   Main.main
+//!     defined at Main.vm:1
+//!     referenced at Sys.vm:3
+//! labels:
+//!   Main.main$LOOP
+//!     defined at Main.vm:4
+//!     referenced at Main.vm:9, Main.vm:12
+//! statics:
+//!   Main.0
+//!     referenced at Main.vm:6, Main.vm:10
+//! ```
+//!
+//! Labels are named after the function they are in, as they are only visible
+//! within it. Static variables have no definition, so only their references
+//! are listed, and names without a definition are marked as undefined.
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Display};
+
+use hack_vm_syntax::parser::{
+    Branching, Functional, Instruction, StackManipulation,
+};
+
+/// A place in a file.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Site {
+    /// The file, as it is displayed.
+    file: String,
+    /// The line, counting from 1.
+    line: usize,
+}
+
+impl Display for Site {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Where a single name is defined and referenced.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+struct Entry {
+    /// Where the name is defined.
+    definitions: Vec<Site>,
+    /// Where the name is referenced.
+    references: Vec<Site>,
+}
+
+/// The sites of every function, label, and static variable of a program.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub(crate) struct CrossReference {
+    /// Functions, by name.
+    functions: BTreeMap<String, Entry>,
+    /// Labels, by name, qualified with the function they are in.
+    labels: BTreeMap<String, Entry>,
+    /// Static variables, by name.
+    statics: BTreeMap<String, Entry>,
+}
+
+impl CrossReference {
+    /// Records the definitions and references in a file, given its
+    /// instructions paired with their line numbers. Its static variables are
+    /// named with `static_prefix`.
+    pub(crate) fn add_file(
+        &mut self,
+        file: &str,
+        static_prefix: &str,
+        instructions: &[(usize, Instruction)],
+    ) {
+        let mut function: Option<&str> = None;
+        for &(line, ref instruction) in instructions {
+            let site: Site = Site {
+                file: file.to_owned(),
+                line,
+            };
+            match *instruction {
+                Instruction::Functional(Functional::Function {
+                    ref symbol,
+                    ..
+                }) => {
+                    function = Some(symbol.literal_representation());
+                    Self::entry(&mut self.functions, symbol.to_string())
+                        .definitions
+                        .push(site);
+                }
+                Instruction::Functional(Functional::Call {
+                    ref symbol,
+                    ..
+                }) => {
+                    Self::entry(&mut self.functions, symbol.to_string())
+                        .references
+                        .push(site);
+                }
+                Instruction::Branching(ref branching) => {
+                    let (symbol, is_definition) = match *branching {
+                        Branching::Label { ref symbol } => (symbol, true),
+                        Branching::GoTo { ref symbol }
+                        | Branching::IfGoTo { ref symbol } => (symbol, false),
+                    };
+                    let name: String = function.map_or_else(
+                        || symbol.to_string(),
+                        |function: &str| format!("{function}${symbol}"),
+                    );
+                    let entry: &mut Entry = Self::entry(&mut self.labels, name);
+                    if is_definition {
+                        entry.definitions.push(site);
+                    } else {
+                        entry.references.push(site);
+                    }
+                }
+                Instruction::StackManipulation(
+                    StackManipulation::Push { ref symbol, value }
+                    | StackManipulation::Pop { ref symbol, value },
+                ) if symbol.literal_representation() == "static" => {
+                    Self::entry(
+                        &mut self.statics,
+                        format!("{static_prefix}.{value}"),
+                    )
+                    .references
+                    .push(site);
+                }
+                Instruction::Functional(Functional::Return)
+                | Instruction::StackManipulation(_)
+                | Instruction::Arithmetic(_)
+                | Instruction::Assembly(_)
+                | _ => {}
+            }
+        }
+    }
+
+    /// Gets the entry for `name`, adding an empty one if there is none.
+    fn entry(
+        entries: &mut BTreeMap<String, Entry>,
+        name: String,
+    ) -> &mut Entry {
+        entries.entry(name).or_default()
+    }
+
+    /// Writes one section of the report.
+    fn write_section(
+        formatter: &mut fmt::Formatter<'_>,
+        heading: &str,
+        entries: &BTreeMap<String, Entry>,
+        definable: bool,
+    ) -> fmt::Result {
+        writeln!(formatter, "{heading}:")?;
+        for (name, entry) in entries {
+            writeln!(formatter, "  {name}")?;
+            if definable {
+                if entry.definitions.is_empty() {
+                    writeln!(formatter, "    undefined")?;
+                } else {
+                    writeln!(
+                        formatter,
+                        "    defined at {}",
+                        List(&entry.definitions)
+                    )?;
+                }
+            }
+            if entry.references.is_empty() {
+                writeln!(formatter, "    never referenced")?;
+            } else {
+                writeln!(
+                    formatter,
+                    "    referenced at {}",
+                    List(&entry.references)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for CrossReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::write_section(f, "functions", &self.functions, true)?;
+        Self::write_section(f, "labels", &self.labels, true)?;
+        Self::write_section(f, "statics", &self.statics, false)
+    }
+}
+
+/// Displays sites separated by commas.
+struct List<'sites>(&'sites [Site]);
+
+impl Display for List<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, site) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{site}")?;
+        }
+        Ok(())
+    }
+}
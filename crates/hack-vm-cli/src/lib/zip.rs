@@ -0,0 +1,510 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Zip Module
+//!
+//! Reads Hack VM files out of zip archives, such as student submissions, so
+//! they can be translated without extracting them first.
+//!
+//! Only what such archives need is supported: entries that are stored or
+//! compressed with DEFLATE, without encryption or Zip64 extensions.
+//!
+//! Their assembly is written next to the archive, as though each file was
+//! extracted there, so a Hack VM file whose name could reach outside of the
+//! archive's directory is rejected: one with a `\` or `:`, as in `C:`, a
+//! `..` directory, or a leading `/`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::limits::Limits;
+use hack_vm_syntax::paths;
+
+use crate::inflate;
+
+/// The signature of the end of central directory record.
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4B50;
+
+/// The signature of a central directory file header.
+const CENTRAL_DIRECTORY_HEADER: u32 = 0x0201_4B50;
+
+/// The signature of a local file header.
+const LOCAL_FILE_HEADER: u32 = 0x0403_4B50;
+
+/// The size of the end of central directory record, without its comment.
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+
+/// The size of a central directory file header, without its variable-length
+/// fields.
+const CENTRAL_DIRECTORY_HEADER_SIZE: usize = 46;
+
+/// The size of a local file header, without its variable-length fields.
+const LOCAL_FILE_HEADER_SIZE: usize = 30;
+
+/// The compression method of entries that are stored as-is.
+const STORED: u16 = 0;
+
+/// The compression method of entries compressed with DEFLATE.
+const DEFLATED: u16 = 8;
+
+/// A Hack VM file read from a zip archive.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct ArchivedFile {
+    /// The name of the entry within the archive.
+    name: String,
+    /// The decompressed contents of the file.
+    contents: String,
+}
+
+impl ArchivedFile {
+    /// Gets the name of the entry within the archive, which may include
+    /// directories.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the file name of the entry, without any directories.
+    pub(crate) fn file_name(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+
+    /// Takes the decompressed contents of the file.
+    pub(crate) fn into_contents(self) -> String {
+        self.contents
+    }
+}
+
+/// Reads every Hack VM file in the zip archive at `path`.
+///
+/// Entries in any directory of the archive are included, except for the
+/// `__MACOSX` metadata that macOS adds when creating archives.
+///
+/// # Errors
+///
+/// Returns a [`HackError::CannotReadFileFromPath`] if the archive cannot be
+/// read, or a [`HackError::BadArchive`] if it is malformed, uses unsupported
+/// features, or contains a Hack VM file that is not valid UTF-8. Returns a
+/// [`HackError::LimitExceeded`] if a Hack VM file is larger than `limits`
+/// allow, before it is decompressed.
+pub(crate) fn read_vm_files(
+    path: &Path,
+    limits: Limits,
+) -> Result<Vec<ArchivedFile>, HackError> {
+    let data: Vec<u8> = fs::read(path).map_err(|error| {
+        HackError::CannotReadFileFromPath(format!(
+            "could not read \"{}\": {error}",
+            paths::display(path)
+        ))
+    })?;
+    let archive: Archive<'_> = Archive {
+        path,
+        data: &data,
+        limits,
+    };
+    archive.vm_files()
+}
+
+/// The raw bytes of a zip archive.
+#[derive(Debug, Clone, Copy)]
+struct Archive<'data> {
+    /// The file the archive was read from, for error messages.
+    path: &'data Path,
+    /// The contents of the archive.
+    data: &'data [u8],
+    /// The limits on the files in the archive.
+    limits: Limits,
+}
+
+impl Archive<'_> {
+    /// Creates a [`HackError::BadArchive`] about this archive.
+    fn error(&self, message: &str) -> HackError {
+        HackError::BadArchive(format!(
+            "{}: {message}",
+            paths::display(self.path)
+        ))
+    }
+
+    /// Reads the bytes at `offset..offset + length`.
+    fn bytes(&self, offset: usize, length: usize) -> Result<&[u8], HackError> {
+        offset
+            .checked_add(length)
+            .and_then(|end: usize| self.data.get(offset..end))
+            .ok_or_else(|| self.error("unexpected end of archive"))
+    }
+
+    /// Reads the little-endian [`u16`] at `offset`.
+    fn u16_at(&self, offset: usize) -> Result<u16, HackError> {
+        let bytes: &[u8] = self.bytes(offset, 2)?;
+        let bytes: [u8; 2] =
+            bytes.try_into().map_err(|_error| HackError::Internal)?;
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    /// Reads the little-endian [`u32`] at `offset`.
+    fn u32_at(&self, offset: usize) -> Result<u32, HackError> {
+        let bytes: &[u8] = self.bytes(offset, 4)?;
+        let bytes: [u8; 4] =
+            bytes.try_into().map_err(|_error| HackError::Internal)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// Reads the little-endian [`u16`] at `offset` as a [`usize`].
+    fn u16_size_at(&self, offset: usize) -> Result<usize, HackError> {
+        self.u16_at(offset).map(usize::from)
+    }
+
+    /// Reads the little-endian [`u32`] at `offset` as a [`usize`].
+    fn u32_size_at(&self, offset: usize) -> Result<usize, HackError> {
+        usize::try_from(self.u32_at(offset)?)
+            .map_err(|_error| self.error("archive is too large"))
+    }
+
+    /// Finds the end of central directory record, which is at the very end
+    /// of the archive unless followed by a comment.
+    fn end_of_central_directory(&self) -> Result<usize, HackError> {
+        let last: usize = self
+            .data
+            .len()
+            .checked_sub(END_OF_CENTRAL_DIRECTORY_SIZE)
+            .ok_or_else(|| self.error("not a zip archive"))?;
+        (0..=last)
+            .rev()
+            .take(usize::from(u16::MAX).saturating_add(1))
+            .find(|&offset: &usize| {
+                self.u32_at(offset).ok() == Some(END_OF_CENTRAL_DIRECTORY)
+            })
+            .ok_or_else(|| self.error("not a zip archive"))
+    }
+
+    /// Reads every Hack VM file listed in the central directory.
+    fn vm_files(&self) -> Result<Vec<ArchivedFile>, HackError> {
+        let end: usize = self.end_of_central_directory()?;
+        let entries: u16 = self.u16_at(end.saturating_add(10))?;
+        let mut offset: usize = self.u32_size_at(end.saturating_add(16))?;
+
+        let mut files: Vec<ArchivedFile> = Vec::new();
+        for _ in 0..entries {
+            if self.u32_at(offset)? != CENTRAL_DIRECTORY_HEADER {
+                return Err(self.error("malformed central directory"));
+            }
+            let name_length: usize =
+                self.u16_size_at(offset.saturating_add(28))?;
+            let extra_length: usize =
+                self.u16_size_at(offset.saturating_add(30))?;
+            let comment_length: usize =
+                self.u16_size_at(offset.saturating_add(32))?;
+            let name: String = String::from_utf8_lossy(self.bytes(
+                offset.saturating_add(CENTRAL_DIRECTORY_HEADER_SIZE),
+                name_length,
+            )?)
+            .into_owned();
+
+            if Path::new(&name).extension().is_some_and(|ext| ext == "vm")
+                && !name.split('/').any(|part: &str| part == "__MACOSX")
+            {
+                if escapes(&name) {
+                    return Err(self.error(&format!(
+                        "\"{name}\" is named with a path that could reach \
+                        outside of the archive"
+                    )));
+                }
+                files.push(ArchivedFile {
+                    contents: self.read_entry(offset, &name)?,
+                    name,
+                });
+            }
+
+            offset = offset
+                .saturating_add(CENTRAL_DIRECTORY_HEADER_SIZE)
+                .saturating_add(name_length)
+                .saturating_add(extra_length)
+                .saturating_add(comment_length);
+        }
+        Ok(files)
+    }
+
+    /// Reads and decompresses the entry whose central directory file header
+    /// is at `header`.
+    fn read_entry(
+        &self,
+        header: usize,
+        name: &str,
+    ) -> Result<String, HackError> {
+        let entry_error = |message: &str| -> HackError {
+            self.error(&format!("\"{name}\" {message}"))
+        };
+
+        let flags: u16 = self.u16_at(header.saturating_add(8))?;
+        if flags & 1 != 0 {
+            return Err(entry_error("is encrypted"));
+        }
+        let method: u16 = self.u16_at(header.saturating_add(10))?;
+        let crc: u32 = self.u32_at(header.saturating_add(16))?;
+        let compressed_size: usize =
+            self.u32_size_at(header.saturating_add(20))?;
+        let size: usize = self.u32_size_at(header.saturating_add(24))?;
+        let local: usize = self.u32_size_at(header.saturating_add(42))?;
+        if [compressed_size, size, local]
+            .contains(&usize::try_from(u32::MAX).unwrap_or(usize::MAX))
+        {
+            return Err(entry_error("uses unsupported Zip64 extensions"));
+        }
+
+        self.limits.check_size(
+            &format!("{}/{name}", paths::display(self.path)),
+            size,
+        )?;
+        if self.u32_at(local)? != LOCAL_FILE_HEADER {
+            return Err(entry_error("has a malformed local file header"));
+        }
+        let start: usize = local
+            .saturating_add(LOCAL_FILE_HEADER_SIZE)
+            .saturating_add(self.u16_size_at(local.saturating_add(26))?)
+            .saturating_add(self.u16_size_at(local.saturating_add(28))?);
+        let compressed: &[u8] = self.bytes(start, compressed_size)?;
+
+        let contents: Vec<u8> = match method {
+            STORED => compressed.to_vec(),
+            DEFLATED => inflate::inflate(compressed, size)
+                .map_err(|error| entry_error(&error.to_string()))?,
+            other => {
+                return Err(entry_error(&format!(
+                    "uses unsupported compression method {other}"
+                )));
+            }
+        };
+        if contents.len() != size || crc32(&contents) != crc {
+            return Err(entry_error("is corrupt"));
+        }
+        self.limits
+            .decode(&format!("{}/{name}", paths::display(self.path)), contents)
+    }
+}
+
+/// Whether the entry `name` could refer to a file outside of the directory
+/// the archive is extracted to, on any platform.
+///
+/// A `:` is rejected anywhere, as it names a drive, or a stream of a file,
+/// on Windows.
+fn escapes(name: &str) -> bool {
+    name.contains(['\\', ':'])
+        || name.starts_with('/')
+        || name.split('/').any(|part: &str| part == "..")
+}
+
+/// Computes the CRC-32 checksum zip archives use to check entries.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = !0;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1_u32) ^ 0xEDB8_8320
+            } else {
+                crc >> 1_u32
+            };
+        }
+    }
+    !crc
+}
+
+/// Determines where the assembly for an [`ArchivedFile`] is written: next to
+/// the archive, as though it had been extracted there.
+pub(crate) fn extracted_path(archive: &Path, file: &ArchivedFile) -> PathBuf {
+    archive
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(file.file_name())
+}
+
+#[cfg(test)]
+mod tests {
+    //! Checks which entries are read from archives built for each test.
+
+    use std::path::{Path, PathBuf};
+    use std::{env, fs, process};
+
+    use hack_vm_syntax::error::HackError;
+    use hack_vm_syntax::limits::Limits;
+
+    use super::{
+        ArchivedFile, CENTRAL_DIRECTORY_HEADER, END_OF_CENTRAL_DIRECTORY,
+        LOCAL_FILE_HEADER, crc32, read_vm_files,
+    };
+
+    /// The contents of every entry.
+    const CONTENTS: &[u8] = b"push constant 1\n";
+
+    /// Builds a zip archive storing [`CONTENTS`] under each of `names`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the archive would need the ZIP64 extensions.
+    fn archive(names: &[&str]) -> Vec<u8> {
+        let size: u32 = u32::try_from(CONTENTS.len()).unwrap();
+        let mut local: Vec<u8> = Vec::new();
+        let mut central: Vec<u8> = Vec::new();
+        for name in names {
+            let offset: u32 = u32::try_from(local.len()).unwrap();
+            let length: u16 = u16::try_from(name.len()).unwrap();
+            // Signature, version needed, flags, method, time, and date.
+            local.extend(LOCAL_FILE_HEADER.to_le_bytes());
+            local.extend([20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            local.extend(crc32(CONTENTS).to_le_bytes());
+            local.extend(size.to_le_bytes());
+            local.extend(size.to_le_bytes());
+            local.extend(length.to_le_bytes());
+            local.extend([0, 0]);
+            local.extend(name.bytes());
+            local.extend(CONTENTS);
+            // Signature, versions, flags, method, time, and date.
+            central.extend(CENTRAL_DIRECTORY_HEADER.to_le_bytes());
+            central.extend([20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            central.extend(crc32(CONTENTS).to_le_bytes());
+            central.extend(size.to_le_bytes());
+            central.extend(size.to_le_bytes());
+            central.extend(length.to_le_bytes());
+            // Extra field, comment, disk, and attributes.
+            central.extend([0; 12]);
+            central.extend(offset.to_le_bytes());
+            central.extend(name.bytes());
+        }
+        let entries: u16 = u16::try_from(names.len()).unwrap();
+        let central_size: u32 = u32::try_from(central.len()).unwrap();
+        let central_offset: u32 = u32::try_from(local.len()).unwrap();
+        let mut data: Vec<u8> = local;
+        data.extend(central);
+        data.extend(END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        data.extend([0, 0, 0, 0]);
+        data.extend(entries.to_le_bytes());
+        data.extend(entries.to_le_bytes());
+        data.extend(central_size.to_le_bytes());
+        data.extend(central_offset.to_le_bytes());
+        data.extend([0, 0]);
+        data
+    }
+
+    /// Writes an archive of `names` for the `test`, reads it, and passes
+    /// what was read to `check`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the archive cannot be written or removed, or if `check`
+    /// does.
+    fn read(
+        test: &str,
+        names: &[&str],
+        check: impl FnOnce(Result<Vec<ArchivedFile>, HackError>),
+    ) {
+        let path: PathBuf = env::temp_dir()
+            .join(format!("hack-vm-translator-{test}-{}.zip", process::id()));
+        fs::write(&path, archive(names)).unwrap();
+        let files: Result<Vec<ArchivedFile>, HackError> =
+            read_vm_files(&path, Limits::default());
+        fs::remove_file(&path).unwrap();
+        check(files);
+    }
+
+    /// Checks that reading an archive of `names` for the `test` fails with
+    /// a [`HackError::BadArchive`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if it does not.
+    fn assert_rejected(test: &str, names: &[&str]) {
+        read(
+            test,
+            names,
+            |files: Result<Vec<ArchivedFile>, HackError>| {
+                assert!(
+                    matches!(files, Err(HackError::BadArchive(_))),
+                    "expected {names:?} to be rejected, got {files:?}"
+                );
+            },
+        );
+    }
+
+    /// Checks that reading an archive of `names` for the `test` reads the
+    /// files named `file_names`, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it does not.
+    fn assert_read(test: &str, names: &[&str], file_names: &[&str]) {
+        read(
+            test,
+            names,
+            |files: Result<Vec<ArchivedFile>, HackError>| {
+                assert_eq!(
+                    files
+                        .unwrap()
+                        .iter()
+                        .map(ArchivedFile::file_name)
+                        .collect::<Vec<&str>>(),
+                    file_names,
+                    "reading {names:?}"
+                );
+            },
+        );
+    }
+
+    /// Files in the archive's directories are read, other than the metadata
+    /// of macOS and files that are not Hack VM files.
+    #[test]
+    fn files_in_directories_are_read() {
+        assert_read(
+            "zip-directories",
+            &[
+                "Main.vm",
+                "project/Sys.vm",
+                "__MACOSX/._Main.vm",
+                "notes.txt",
+            ],
+            &["Main.vm", "Sys.vm"],
+        );
+    }
+
+    /// A Hack VM file whose name could reach outside of the directory the
+    /// archive is extracted to is rejected, whether on Windows or not.
+    #[test]
+    fn escaping_names_are_rejected() {
+        for (test, name) in [
+            ("zip-backslash", "..\\..\\Evil.vm"),
+            ("zip-drive", "C:\\x\\Evil.vm"),
+            ("zip-relative-drive", "C:Evil.vm"),
+            ("zip-parent", "project/../../Evil.vm"),
+            ("zip-absolute", "/tmp/Evil.vm"),
+        ] {
+            assert_rejected(test, &["Main.vm", name]);
+        }
+    }
+
+    /// Checks that the assembly of each file in `files` is written next to
+    /// an archive in `submissions`, without the archive's directories.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is not, or if the archive could not be read.
+    fn assert_written_next_to_archive(
+        files: Result<Vec<ArchivedFile>, HackError>,
+    ) {
+        for file in &files.unwrap() {
+            assert_eq!(
+                super::extracted_path(Path::new("submissions/a.zip"), file),
+                Path::new("submissions/Main.vm")
+            );
+        }
+    }
+
+    /// Assembly is written next to the archive, without its directories.
+    #[test]
+    fn assembly_is_written_next_to_the_archive() {
+        read(
+            "zip-extracted",
+            &["project/Main.vm"],
+            assert_written_next_to_archive,
+        );
+    }
+}
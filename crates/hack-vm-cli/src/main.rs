@@ -0,0 +1,97 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator
+//!
+//! A VM translator that parses Hack VM commands and generates Hack assembly.
+//! Based on the nand2tetris course.
+
+#![expect(unused_crate_dependencies, reason = "error_set is not in use yet")]
+// Lints that are still unstable, and so only checked with the `nightly`
+// feature, keeping the default build working on stable.
+#![cfg_attr(
+    feature = "nightly",
+    feature(
+        multiple_supertrait_upcastable,
+        must_not_suspend,
+        non_exhaustive_omitted_patterns_lint,
+        strict_provenance_lints,
+        supertrait_item_shadowing,
+        unqualified_local_imports
+    ),
+    // Casting integers to pointers messes with provenance (#130351)
+    warn(fuzzy_provenance_casts),
+    // Casting pointers to integers loses with provenance (#130351)
+    warn(lossy_provenance_casts),
+    // Adds overhead. Is it worth it? (#150833)
+    warn(multiple_supertrait_upcastable),
+    // Causes problems in async (#83310)
+    warn(must_not_suspend),
+    // Explicit matches are preferred (#89554)
+    warn(non_exhaustive_omitted_patterns),
+    // Shadowing silently leads to unexpected behavior (#89151)
+    warn(shadowing_supertrait_items),
+    warn(resolving_to_items_shadowing_supertrait_items),
+    // Explicitness provides clarity, and this assists import grouping in
+    // rustfmt (#138299)
+    warn(unqualified_local_imports)
+)]
+
+use std::{env, fs, process};
+
+use hack_vm_cli::color::{ColorChoice, Palette};
+use hack_vm_cli::summary::Summary;
+use hack_vm_cli::{Config, run};
+// The binary only uses these through the library.
+use {hack_vm_codegen as _, hack_vm_syntax as _};
+
+/// The entrypoint of the translator executable.
+pub(crate) fn main() {
+    let args: env::Args = env::args();
+
+    let config: Config = Config::build(args).unwrap_or_else(|error| {
+        let palette: Palette = Palette::new(ColorChoice::Auto);
+        eprintln!("{} {error}", palette.error("Problem parsing arguments:"));
+        process::exit(1);
+    });
+    let palette: Palette = config.palette();
+
+    match run(&config) {
+        Ok(summary) => {
+            if summary.directory().is_some() {
+                eprint!("{}", summary.table());
+            }
+            if config.time() {
+                eprint!("{}", summary.timing_report());
+            }
+            if let Some(symbols) = summary.symbols() {
+                eprint!("{symbols}");
+            }
+            if let Some(path) = config.summary_json() {
+                let report: String = summary.json_report(None).to_string();
+                if let Err(error) = fs::write(path, report) {
+                    eprintln!(
+                        "{} {error}",
+                        palette.error("Problem writing the summary:")
+                    );
+                    process::exit(1);
+                }
+            }
+        }
+        Err(error) => {
+            eprintln!("{}", config.diagnostics().error(&error, palette));
+            if let Some(path) = config.summary_json() {
+                let report: String =
+                    Summary::default().json_report(Some(&error)).to_string();
+                if let Err(error) = fs::write(path, report) {
+                    eprintln!(
+                        "{} {error}",
+                        palette.error("Problem writing the summary:")
+                    );
+                }
+            }
+            process::exit(1);
+        }
+    }
+}
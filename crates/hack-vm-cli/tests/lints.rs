@@ -0,0 +1,181 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Checks that the warnings of the lints reach the user, even about files
+//! that then fail to translate.
+
+#![expect(unused_crate_dependencies, reason = "error_set is not in use yet")]
+#![expect(
+    clippy::tests_outside_test_module,
+    reason = "Integration tests are a crate of their own, only built to test."
+)]
+
+use std::path::PathBuf;
+use std::process::{self, Command, Output};
+use std::{env, fs, io};
+
+// The tests only use these through the binary.
+use {hack_vm_cli as _, hack_vm_codegen as _, hack_vm_syntax as _};
+
+/// Translates a directory named after the `test` holding `files`, as their
+/// names and contents, and gets what was printed to stderr.
+///
+/// # Errors
+///
+/// Any error from writing the files, running the translator, or removing
+/// the directory is propagated.
+fn translate(test: &str, files: &[(&str, &str)]) -> io::Result<String> {
+    let directory: PathBuf = env::temp_dir()
+        .join(format!("hack-vm-translator-{test}-{}", process::id()));
+    fs::create_dir_all(&directory)?;
+    for &(name, contents) in files {
+        fs::write(directory.join(name), contents)?;
+    }
+    let output: io::Result<Output> =
+        Command::new(env!("CARGO_BIN_EXE_hack-vm-translator"))
+            .arg(&directory)
+            .output();
+    fs::remove_dir_all(&directory)?;
+    Ok(String::from_utf8_lossy(&output?.stderr).into_owned())
+}
+
+/// Checks that `stderr` shows a warning ending in `warning`, and then the
+/// error that translating failed with.
+///
+/// # Panics
+///
+/// Panics if either is missing, or they are the other way around.
+fn assert_warned_before_error(stderr: &str, warning: &str) {
+    let warned: Option<usize> = stderr.find(warning);
+    let failed: Option<usize> = stderr.find("Problem running:");
+    assert!(
+        warned.is_some() && failed.is_some() && warned < failed,
+        "expected the warning \"{warning}\" before the error, got:\n{stderr}"
+    );
+}
+
+/// Checks that `stderr` shows a warning ending in `warning`.
+///
+/// # Panics
+///
+/// Panics if it does not.
+fn assert_warned(stderr: &str, warning: &str) {
+    assert!(
+        stderr.contains(warning),
+        "expected the warning \"{warning}\", got:\n{stderr}"
+    );
+}
+
+/// Checks that `stderr` shows no warning of the lint named `lint`.
+///
+/// # Panics
+///
+/// Panics if it does.
+fn assert_not_warned(stderr: &str, lint: &str) {
+    assert!(
+        !stderr.contains(&format!("[{lint}]")),
+        "expected no {lint} warning, got:\n{stderr}"
+    );
+}
+
+/// A function named after another file is warned about.
+///
+/// # Errors
+///
+/// Any error from translating the file is propagated.
+#[test]
+fn naming_is_warned_about_before_translation_fails() -> io::Result<()> {
+    let stderr: String = translate(
+        "naming",
+        &[(
+            "Main.vm",
+            "function Math.double 0\npush argument 0\nreturn\n",
+        )],
+    )?;
+    assert_warned_before_error(
+        &stderr,
+        "warning: function \"Math.double\" should be named \"Main.double\", \
+        after its file [naming]",
+    );
+    Ok(())
+}
+
+/// A label named like the return address of a call is warned about.
+///
+/// # Errors
+///
+/// Any error from translating the file is propagated.
+#[test]
+fn shadowing_is_warned_about_before_translation_fails() -> io::Result<()> {
+    let stderr: String = translate(
+        "shadowing",
+        &[(
+            "Main.vm",
+            "function Main.main 0\nlabel ret.0\npush constant 0\nreturn\n",
+        )],
+    )?;
+    assert_warned_before_error(
+        &stderr,
+        "warning: label \"ret.0\" becomes \"Main.main$ret.0\", named like the \
+        return address of a call [shadowing]",
+    );
+    Ok(())
+}
+
+/// Files without any function are not warned about for being outside of
+/// one, as they are not meant to start at `Sys.init`.
+///
+/// # Errors
+///
+/// Any error from translating the files is propagated.
+#[test]
+fn files_without_functions_are_not_outside_one() -> io::Result<()> {
+    let stderr: String = translate(
+        "outside-function",
+        &[
+            ("A.vm", "push constant 1\n"),
+            ("B.vm", "push constant 2\n"),
+            ("C.vm", "push constant 3\n"),
+        ],
+    )?;
+    assert_not_warned(&stderr, "outside-function");
+    Ok(())
+}
+
+/// A file whose name is not a valid symbol is warned about where it first
+/// uses a static variable, which is named after the file made valid.
+///
+/// # Errors
+///
+/// Any error from translating the file is propagated.
+#[test]
+fn invalid_file_names_are_warned_about_at_statics() -> io::Result<()> {
+    let stderr: String = translate(
+        "sanitized",
+        &[(
+            "Main-2.vm",
+            "push constant 1\npush constant 2\npop static 0\n",
+        )],
+    )?;
+    assert_warned(
+        &stderr,
+        "Main-2.vm:3: warning: file name \"Main-2\" is not a valid symbol, so \
+        its static variables are named \"Main_2.i\" instead [naming]",
+    );
+    Ok(())
+}
+
+/// A file whose name is not a valid symbol is not warned about if it does
+/// not use any static variables.
+///
+/// # Errors
+///
+/// Any error from translating the file is propagated.
+#[test]
+fn invalid_file_names_without_statics_are_not_warned_about() -> io::Result<()> {
+    let stderr: String =
+        translate("unsanitized", &[("Main-2.vm", "push constant 1\n")])?;
+    assert_not_warned(&stderr, "naming");
+    Ok(())
+}
@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Checks that `--verify` runs each translated file, and reports what it
+//! cannot run.
+
+#![expect(unused_crate_dependencies, reason = "error_set is not in use yet")]
+#![expect(
+    clippy::tests_outside_test_module,
+    reason = "Integration tests are a crate of their own, only built to test."
+)]
+
+use std::path::PathBuf;
+use std::process::{self, Command, Output};
+use std::{env, fs, io};
+
+// The tests only use these through the binary.
+use {hack_vm_cli as _, hack_vm_codegen as _, hack_vm_syntax as _};
+
+/// Translates a directory named after the `test` holding `files`, as their
+/// names and contents, with `--verify` and the other `options`.
+///
+/// # Errors
+///
+/// Any error from writing the files, running the translator, or removing
+/// the directory is propagated.
+fn verify(
+    test: &str,
+    options: &[&str],
+    files: &[(&str, &str)],
+) -> io::Result<Output> {
+    let directory: PathBuf = env::temp_dir().join(format!(
+        "hack-vm-translator-verify-{test}-{}",
+        process::id()
+    ));
+    fs::create_dir_all(&directory)?;
+    for &(name, contents) in files {
+        fs::write(directory.join(name), contents)?;
+    }
+    let output: io::Result<Output> =
+        Command::new(env!("CARGO_BIN_EXE_hack-vm-translator"))
+            .arg("--verify")
+            .args(options)
+            .arg(&directory)
+            .output();
+    fs::remove_dir_all(&directory)?;
+    output
+}
+
+/// Checks that the run succeeded, and that its stderr shows `expected`, if
+/// anything.
+///
+/// # Panics
+///
+/// Panics if it failed, or `expected` is missing.
+fn assert_verified(output: &Output, expected: Option<&str>) {
+    let stderr: String = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(
+        output.status.success()
+            && expected.is_none_or(|expected: &str| stderr.contains(expected))
+            && !stderr.contains("runs differently"),
+        "expected the files to verify, got:\n{stderr}"
+    );
+}
+
+/// Files using every segment the interpreter knows run the same.
+///
+/// # Errors
+///
+/// Any error from translating the files is propagated.
+#[test]
+fn segments_are_verified() -> io::Result<()> {
+    let output: Output = verify(
+        "segments",
+        &["--extensions"],
+        &[
+            (
+                "Main.vm",
+                "push constant 7\npush constant 8\nlt\npop static 0\n\
+                push constant 3\npop local 1\npush local 1\npop that 2\n",
+            ),
+            (
+                "Math.vm",
+                "push constant 6\npush constant 7\nmul\npop temp 2\n\
+                push constant 3000\npop pointer 0\npush temp 2\npop this 1\n\
+                push static 1\nneg\n",
+            ),
+        ],
+    )?;
+    assert_verified(&output, None);
+    Ok(())
+}
+
+/// A file with inline assembly cannot be interpreted, which is said rather
+/// than failing the run.
+///
+/// # Errors
+///
+/// Any error from translating the file is propagated.
+#[test]
+fn uninterpretable_files_are_skipped() -> io::Result<()> {
+    let output: Output = verify(
+        "skipped",
+        &["--inline-asm"],
+        &[("Asm.vm", "push constant 1\n//!asm D=A\npop temp 0\n")],
+    )?;
+    assert_verified(
+        &output,
+        Some("Asm.vm: not verified, as the interpreter cannot run it"),
+    );
+    Ok(())
+}
@@ -0,0 +1,383 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Labels Module
+//!
+//! Names the labels generated for comparisons, which jump to one label when
+//! the comparison holds and to another afterwards. Extensions that loop name
+//! their labels after the second, as in `BURN_n_LOOP`; see
+//! [`hack_vm_syntax::extensions`]. So do `gt` and `lt`, which check the signs
+//! of their operands before subtracting them, as in `BURN_n_NEGATIVE`.
+//!
+//! Both their style and how they are numbered can be chosen, so the output
+//! can be compared line by line with that of other translators:
+//!
+//! - `crash-burn` (the default): `CRASH_n` and `BURN_n`.
+//! - `true-end`: `TRUE_n` and `END_n`.
+//! - Any other symbol `PREFIX`: `PREFIX_TRUE_n` and `PREFIX_END_n`.
+//!
+//! Labels are numbered after the `line` of the comparison (the default), or
+//! `sequential`ly from zero.
+//!
+//! Labels numbered after their line change whenever a line is inserted above
+//! them, so a small edit to a file changes labels throughout its assembly.
+//! To keep such diffs reviewable, the assembly can instead be renumbered
+//! after translation, in order of each label's first appearance; see
+//! [`LabelScheme::renumber`].
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::parser::{Instruction, Symbol};
+
+/// The names given to the pair of labels generated for a comparison.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub enum LabelStyle {
+    /// `CRASH_n` and `BURN_n`.
+    #[default]
+    CrashBurn,
+    /// `TRUE_n` and `END_n`.
+    TrueEnd,
+    /// `PREFIX_TRUE_n` and `PREFIX_END_n`.
+    Prefix(Symbol),
+}
+
+impl LabelStyle {
+    /// The name of the `crash-burn` style.
+    const CRASH_BURN: &str = "crash-burn";
+    /// The name of the `true-end` style.
+    const TRUE_END: &str = "true-end";
+}
+
+impl FromStr for LabelStyle {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::CRASH_BURN => Ok(Self::CrashBurn),
+            Self::TRUE_END => Ok(Self::TrueEnd),
+            prefix => Symbol::from_str(prefix).map(Self::Prefix),
+        }
+    }
+}
+
+impl Display for LabelStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::CrashBurn => write!(f, "{}", Self::CRASH_BURN),
+            Self::TrueEnd => write!(f, "{}", Self::TRUE_END),
+            Self::Prefix(ref prefix) => write!(f, "{prefix}"),
+        }
+    }
+}
+
+/// How the labels generated for comparisons are numbered.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum Numbering {
+    /// After the line of the comparison.
+    #[default]
+    Line,
+    /// Counting up from zero, one for each comparison.
+    Sequential,
+}
+
+impl Numbering {
+    /// The name of the `line` numbering.
+    const LINE: &str = "line";
+    /// The name of the `sequential` numbering.
+    const SEQUENTIAL: &str = "sequential";
+}
+
+impl FromStr for Numbering {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::LINE => Ok(Self::Line),
+            Self::SEQUENTIAL => Ok(Self::Sequential),
+            other => Err(HackError::FromStrError(format!(
+                "\"{other}\" is not a recognized numbering, expected \"{}\" \
+                or \"{}\"",
+                Self::LINE,
+                Self::SEQUENTIAL
+            ))),
+        }
+    }
+}
+
+impl Display for Numbering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Line => write!(f, "{}", Self::LINE),
+            Self::Sequential => write!(f, "{}", Self::SEQUENTIAL),
+        }
+    }
+}
+
+/// How the labels generated for comparisons are named, where either part
+/// may be left unset to be taken from elsewhere; see [`LabelScheme::merge`].
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct LabelScheme {
+    /// The names given to each pair of labels.
+    style: Option<LabelStyle>,
+    /// How each pair of labels is numbered.
+    numbering: Option<Numbering>,
+    /// Whether labels are renumbered in order of appearance after
+    /// translation.
+    renumber: Option<bool>,
+}
+
+impl LabelScheme {
+    /// Sets the names given to each pair of labels.
+    pub fn set_style(&mut self, style: LabelStyle) {
+        self.style = Some(style);
+    }
+
+    /// Sets how each pair of labels is numbered.
+    pub const fn set_numbering(&mut self, numbering: Numbering) {
+        self.numbering = Some(numbering);
+    }
+
+    /// Sets whether labels are renumbered in order of appearance after
+    /// translation.
+    pub const fn set_renumber(&mut self, renumber: bool) {
+        self.renumber = Some(renumber);
+    }
+
+    /// Sets whatever is unset in `self` from `other`, such that `self` takes
+    /// precedence.
+    pub fn merge(&mut self, other: &Self) {
+        if self.style.is_none() {
+            self.style.clone_from(&other.style);
+        }
+        if self.numbering.is_none() {
+            self.numbering = other.numbering;
+        }
+        if self.renumber.is_none() {
+            self.renumber = other.renumber;
+        }
+    }
+
+    /// Gets how each pair of labels is numbered.
+    fn numbering(&self) -> Numbering {
+        self.numbering.unwrap_or_default()
+    }
+
+    /// Gets whether labels are renumbered in order of appearance after
+    /// translation.
+    pub fn renumbers(&self) -> bool {
+        self.renumber.unwrap_or_default()
+    }
+
+    /// Gets the prefixes of the pair of labels, to which their number is
+    /// appended.
+    fn prefixes(&self) -> (String, String) {
+        match self.style {
+            None | Some(LabelStyle::CrashBurn) => {
+                ("CRASH_".to_owned(), "BURN_".to_owned())
+            }
+            Some(LabelStyle::TrueEnd) => {
+                ("TRUE_".to_owned(), "END_".to_owned())
+            }
+            Some(LabelStyle::Prefix(ref prefix)) => {
+                (format!("{prefix}_TRUE_"), format!("{prefix}_END_"))
+            }
+        }
+    }
+
+    /// Names the pair of labels numbered `number`: the one jumped to when a
+    /// comparison holds, and the one jumped to afterwards.
+    pub fn names(&self, number: usize) -> (String, String) {
+        let (when_true, after): (String, String) = self.prefixes();
+        (format!("{when_true}{number}"), format!("{after}{number}"))
+    }
+
+    /// Gets whether `symbol` is named like the labels generated for
+    /// comparisons, whatever their number.
+    pub fn is_generated(&self, symbol: &str) -> bool {
+        let (when_true, after): (String, String) = self.prefixes();
+        generated(&format!("({symbol})"), &when_true, &after).is_some()
+    }
+
+    /// Renumbers the labels generated in `assembly` in order of their first
+    /// appearance, starting from `first`.
+    ///
+    /// The new numbers only depend on how many labels come before, not on
+    /// which lines they were generated from, so inserting an instruction
+    /// that generates no labels leaves every label unchanged.
+    pub fn renumber(&self, assembly: &str, first: usize) -> String {
+        let (when_true, after): (String, String) = self.prefixes();
+        let mut numbers: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut renumbered: String = String::with_capacity(assembly.len());
+        for line in assembly.split_inclusive('\n') {
+            let Some((prefix, number, suffix)) =
+                generated(line, &when_true, &after)
+            else {
+                renumbered.push_str(line);
+                continue;
+            };
+            let next: usize = first.saturating_add(numbers.len());
+            let new: usize = *numbers.entry(number).or_insert(next);
+            renumbered.push_str(prefix);
+            renumbered.push_str(&new.to_string());
+            renumbered.push_str(suffix);
+        }
+        renumbered
+    }
+
+    /// Determines the number of the labels for an instruction, from its line
+    /// and how many instructions that generate labels came before it in the
+    /// file.
+    pub fn number(&self, line_number: usize, labelled: usize) -> usize {
+        match self.numbering() {
+            Numbering::Line => line_number,
+            Numbering::Sequential => labelled,
+        }
+    }
+
+    /// Counts how many numbers from `first` the labels generated in the
+    /// `assembly` of a file use, by which later files translated into the
+    /// same output are offset.
+    ///
+    /// The labels are read from the assembly as it is written, after any
+    /// rewriting, expansion, or renumbering, so every number in use is
+    /// counted up to the highest, however the labels came to be numbered.
+    pub fn numbers_used(&self, assembly: &str, first: usize) -> usize {
+        let (when_true, after): (String, String) = self.prefixes();
+        assembly
+            .lines()
+            .filter_map(|line: &str| generated(line, &when_true, &after))
+            .filter_map(|(_, number, _): (&str, &str, &str)| {
+                number.parse::<usize>().ok()
+            })
+            .max()
+            .map_or(0, |highest: usize| {
+                highest.saturating_add(1).saturating_sub(first)
+            })
+    }
+}
+
+impl Display for LabelScheme {
+    /// Describes the scheme as `STYLE/NUMBERING`, followed by `/renumbered`
+    /// if labels are renumbered after translation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}",
+            self.style.clone().unwrap_or_default(),
+            self.numbering()
+        )?;
+        if self.renumbers() {
+            write!(f, "/renumbered")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the translation of an [`Instruction`] generates labels.
+pub const fn generates_labels(instruction: &Instruction) -> bool {
+    match *instruction {
+        Instruction::Arithmetic(arithmetic) => arithmetic.generates_labels(),
+        Instruction::StackManipulation(_)
+        | Instruction::Branching(_)
+        | Instruction::Functional(_)
+        | Instruction::Assembly(_)
+        | _ => false,
+    }
+}
+
+/// Splits a line of assembly that refers to or declares a generated label
+/// around the label's number.
+///
+/// The label is named with one of the prefixes `when_true` or `after`, and
+/// may be followed by a part such as `_LOOP`.
+fn generated<'line>(
+    line: &'line str,
+    when_true: &str,
+    after: &str,
+) -> Option<(&'line str, &'line str, &'line str)> {
+    let label: &str = line
+        .strip_prefix('@')
+        .or_else(|| line.strip_prefix('('))?
+        .trim_end_matches(['\n', ')']);
+    let numbered: &str = label
+        .strip_prefix(when_true)
+        .or_else(|| label.strip_prefix(after))?;
+    let part: &str = numbered
+        .trim_start_matches(|character: char| character.is_ascii_digit());
+    let number: &str = numbered.strip_suffix(part)?;
+    let is_part: bool = part.strip_prefix('_').is_some_and(|name: &str| {
+        !name.is_empty()
+            && name.bytes().all(|byte: u8| byte.is_ascii_uppercase())
+    });
+    if number.is_empty() || !(part.is_empty() || is_part) {
+        return None;
+    }
+    // The label follows the one character of `@` or `(`.
+    let start: usize =
+        label.len().saturating_sub(numbered.len()).saturating_add(1);
+    let (prefix, rest): (&str, &str) = line.split_at_checked(start)?;
+    let (number, suffix): (&str, &str) = rest.split_at_checked(number.len())?;
+    Some((prefix, number, suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    //! Checks how many numbers the labels generated in assembly use.
+
+    use super::{LabelScheme, LabelStyle};
+
+    /// Assembly with the labels of two comparisons, one of them a loop,
+    /// alongside labels that were not generated.
+    const ASSEMBLY: &str = "@BURN_3_LOOP\n0;JMP\n(CRASH_7)\n(BURN_7)\n\
+        (LOOP_9)\n(BURN_12_loop)\n@R13\n";
+
+    /// Checks that the labels of the `scheme` in `assembly` use `used`
+    /// numbers from `first`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if they do not.
+    fn assert_used(
+        scheme: &LabelScheme,
+        assembly: &str,
+        first: usize,
+        used: usize,
+    ) {
+        assert_eq!(
+            scheme.numbers_used(assembly, first),
+            used,
+            "counting from {first} in:\n{assembly}"
+        );
+    }
+
+    /// Every number up to the highest is used, whichever part of a label it
+    /// is in, and labels that were not generated are not counted.
+    #[test]
+    fn numbers_are_used_up_to_the_highest() {
+        let scheme: LabelScheme = LabelScheme::default();
+        assert_used(&scheme, ASSEMBLY, 0, 8);
+        assert_used(&scheme, ASSEMBLY, 5, 3);
+        assert_used(&scheme, ASSEMBLY, 8, 0);
+        assert_used(&scheme, "@SP\nM=M+1\n", 4, 0);
+    }
+
+    /// Only labels named in the style of the scheme are counted.
+    #[test]
+    fn only_labels_of_the_style_are_counted() {
+        let mut scheme: LabelScheme = LabelScheme::default();
+        scheme.set_style(LabelStyle::TrueEnd);
+        assert_used(&scheme, ASSEMBLY, 0, 0);
+        assert_used(&scheme, "(TRUE_2)\n@END_2\n", 0, 3);
+    }
+
+    /// Renumbered labels use one number each, from where they start.
+    #[test]
+    fn renumbered_labels_use_one_number_each() {
+        let scheme: LabelScheme = LabelScheme::default();
+        assert_used(&scheme, &scheme.renumber(ASSEMBLY, 20), 20, 2);
+    }
+}
@@ -0,0 +1,132 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Codegen
+//!
+//! Generates Hack assembly from the [`Instruction`]s parsed by
+//! `hack-vm-syntax`, naming the labels it generates with a [`LabelScheme`].
+//! Based on the nand2tetris course.
+//!
+//! [`Instruction`]: hack_vm_syntax::parser::Instruction
+
+#![expect(
+    clippy::module_name_repetitions,
+    clippy::missing_errors_doc,
+    reason = "todo later"
+)]
+// Lints that are still unstable, and so only checked with the `nightly`
+// feature, keeping the default build working on stable.
+#![cfg_attr(
+    feature = "nightly",
+    feature(
+        multiple_supertrait_upcastable,
+        must_not_suspend,
+        non_exhaustive_omitted_patterns_lint,
+        strict_provenance_lints,
+        supertrait_item_shadowing,
+        unqualified_local_imports
+    ),
+    // Casting integers to pointers messes with provenance (#130351)
+    warn(fuzzy_provenance_casts),
+    // Casting pointers to integers loses with provenance (#130351)
+    warn(lossy_provenance_casts),
+    // Adds overhead. Is it worth it? (#150833)
+    warn(multiple_supertrait_upcastable),
+    // Causes problems in async (#83310)
+    warn(must_not_suspend),
+    // Explicit matches are preferred (#89554)
+    warn(non_exhaustive_omitted_patterns),
+    // Shadowing silently leads to unexpected behavior (#89151)
+    warn(shadowing_supertrait_items),
+    warn(resolving_to_items_shadowing_supertrait_items),
+    // Explicitness provides clarity, and this assists import grouping in
+    // rustfmt (#138299)
+    warn(unqualified_local_imports)
+)]
+
+extern crate alloc;
+
+use hack_vm_syntax::extensions::Extensions;
+use hack_vm_syntax::segments::Segments;
+use hack_vm_syntax::target::Target;
+
+use crate::labels::LabelScheme;
+
+pub mod labels;
+pub mod translator;
+
+/// How instructions are translated into assembly.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Options {
+    /// How the labels generated for comparisons are named.
+    labels: LabelScheme,
+    /// Whether and how the extended arithmetic commands are translated.
+    extensions: Extensions,
+    /// Whether Hack assembly written inline is passed through.
+    inline_asm: bool,
+    /// The segments declared beyond those of the VM language.
+    segments: Segments,
+    /// The variant of the Hack CPU to generate assembly for.
+    target: Option<Target>,
+}
+
+impl Options {
+    /// Gathers how instructions are translated.
+    pub const fn new(
+        labels: LabelScheme,
+        extensions: Extensions,
+        inline_asm: bool,
+        segments: Segments,
+        target: Option<Target>,
+    ) -> Self {
+        Self {
+            labels,
+            extensions,
+            inline_asm,
+            segments,
+            target,
+        }
+    }
+
+    /// Gets a shared reference to [`Options::labels`].
+    pub const fn labels(&self) -> &LabelScheme {
+        &self.labels
+    }
+
+    /// Gets a mutable reference to [`Options::labels`].
+    pub const fn labels_mut(&mut self) -> &mut LabelScheme {
+        &mut self.labels
+    }
+
+    /// Gets [`Options::extensions`].
+    pub const fn extensions(&self) -> Extensions {
+        self.extensions
+    }
+
+    /// Gets [`Options::inline_asm`].
+    pub const fn inline_asm(&self) -> bool {
+        self.inline_asm
+    }
+
+    /// Gets a shared reference to [`Options::segments`].
+    pub const fn segments(&self) -> &Segments {
+        &self.segments
+    }
+
+    /// Gets a mutable reference to [`Options::segments`].
+    pub const fn segments_mut(&mut self) -> &mut Segments {
+        &mut self.segments
+    }
+
+    /// Sets [`Options::target`] to `target`, unless one was already chosen.
+    pub fn default_target(&mut self, target: Option<Target>) {
+        self.target = self.target.or(target);
+    }
+
+    /// Gets [`Options::target`], or the standard Hack CPU if none was
+    /// chosen.
+    pub fn target(&self) -> Target {
+        self.target.unwrap_or_default()
+    }
+}
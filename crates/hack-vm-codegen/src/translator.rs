@@ -0,0 +1,498 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator
+//!
+//! A VM translator that parses Hack VM commands and generates Hack assembly.
+//! Based on the nand2tetris course.
+
+use core::fmt::Write;
+use core::ops::RangeInclusive;
+
+use hack_vm_syntax::error::HackError;
+use hack_vm_syntax::extensions::{self, Extensions};
+use hack_vm_syntax::indices::{PointerIndex, StaticIndex, TempIndex};
+use hack_vm_syntax::parser::{self, Arithmetic, Constant, Instruction, Symbol};
+use hack_vm_syntax::segments::{Segments, UserSegment};
+use hack_vm_syntax::target::Target;
+use hack_vm_syntax::trace;
+
+use crate::Options;
+use crate::labels::LabelScheme;
+
+/// Each Segment is a virtual memory location, represented by predefined
+/// symbols.
+pub(crate) enum Segment {
+    /// Constant is for constants. You can push a constant on to the stack, but
+    /// you can't pop something off the stack into constant.
+    Constant,
+
+    /// Local, the local variables of the current function.
+    Local,
+
+    /// Argument, the arguments of the current function.
+    Argument,
+
+    /// This, the object pointed to by `pointer 0`, with that index.
+    This(PointerIndex),
+
+    /// That, the array pointed to by `pointer 1`, with that index.
+    That(PointerIndex),
+
+    /// Static, with the index into it.
+    Static(StaticIndex),
+
+    /// Temp, with the index into it.
+    Temp(TempIndex),
+
+    /// Pointer, with the index into it.
+    Pointer(PointerIndex),
+
+    /// A segment declared by the user, with its name; see
+    /// [`hack_vm_syntax::segments`].
+    User(Symbol, UserSegment),
+}
+
+impl Segment {
+    /// Some segments have special predefined symbols which point to the memory
+    /// which points to their location. This returns that symbol, if it exists,
+    /// or the address of the word in `pointer` if that segment was moved.
+    pub(crate) fn base(&self) -> Result<String, HackError> {
+        match *self {
+            Self::Local => Ok("LCL".to_owned()),
+            Self::Argument => Ok("ARG".to_owned()),
+            Self::This(index) | Self::That(index) => Ok(index.symbol()),
+            Self::Constant
+            | Self::Static(_)
+            | Self::Temp(_)
+            | Self::Pointer(_)
+            | Self::User(..) => Err(HackError::Internal),
+        }
+    }
+
+    /// Resolves the segment named by `symbol`, which may be one declared in
+    /// the [`Options`], to be accessed at index `i`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no such segment exists, a declared or moved one
+    /// extends past the highest address of the target, or `i` is beyond the
+    /// end of a segment whose size is fixed; see [`hack_vm_syntax::indices`].
+    pub(crate) fn resolve(
+        symbol: &Symbol,
+        i: Constant,
+        options: &Options,
+    ) -> Result<Self, HackError> {
+        let target: Target = options.target();
+        let name: &str = symbol.literal_representation();
+        if let Some(segment) = options
+            .segments()
+            .get(name)
+            .or_else(|| options.segments().remapped(name))
+            && segment.last_address() > target.highest_address()
+        {
+            return Err(HackError::IllegalInstruction(format!(
+                "segment \"{symbol}\" ends at address {}, past the highest \
+                address of target \"{target}\", {}",
+                segment.last_address(),
+                target.highest_address()
+            )));
+        }
+        options.segments().get(name).map_or_else(
+            || Self::built_in(symbol, i, options),
+            |segment: UserSegment| Ok(Self::User(symbol.clone(), segment)),
+        )
+    }
+
+    /// Resolves the segment of the VM language named by `symbol`, to be
+    /// accessed at index `i` where the [`Options`] place it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no such segment exists, or `i` is beyond the end
+    /// of a segment whose size is fixed.
+    fn built_in(
+        symbol: &Symbol,
+        i: Constant,
+        options: &Options,
+    ) -> Result<Self, HackError> {
+        let segments: &Segments = options.segments();
+        match symbol.literal_representation() {
+            "constant" => Ok(Self::Constant),
+            "local" => Ok(Self::Local),
+            "argument" => Ok(Self::Argument),
+            "this" => {
+                Ok(Self::This(PointerIndex::THIS.within(segments.pointer())))
+            }
+            "that" => {
+                Ok(Self::That(PointerIndex::THAT.within(segments.pointer())))
+            }
+            "static" => Ok(Self::Static(StaticIndex::within(
+                i,
+                segments.statics(options.target()),
+            )?)),
+            "temp" => Ok(Self::Temp(TempIndex::within(
+                i,
+                segments.temp(options.target()),
+            )?)),
+            "pointer" => Ok(Self::Pointer(
+                PointerIndex::new(i)?.within(segments.pointer()),
+            )),
+            bad => Err(HackError::FromStrError(format!(
+                "\"{bad}\" is not a recognized segment"
+            ))),
+        }
+    }
+}
+
+/// An empty enum with associated methods for translating Hack VM instructions
+/// into Hack assembly.
+#[derive(Debug, Clone, Copy)]
+pub enum Translator {}
+
+impl Translator {
+    /// The general use registers are 13-15.
+    const GENERAL_REGISTERS: RangeInclusive<u8> = 13..=15;
+
+    /// Translate the Hack VM instruction given into Hack assembly, writing
+    /// each assembly instruction on its own line into `output`.
+    ///
+    /// Any labels generated are named according to the [`Options`] and
+    /// numbered `label_number`. Extended arithmetic commands and inline
+    /// assembly are only translated if the [`Options`] enable them.
+    pub fn translate<W: Write>(
+        output: &mut W,
+        label_number: usize,
+        instruction: &Instruction,
+        file_name: &str,
+        options: &Options,
+    ) -> Result<(), HackError> {
+        trace::event!(Trace, "translating \"{instruction}\" ({label_number})");
+        match *instruction {
+            Instruction::StackManipulation(ref stack_manipulation) => {
+                match *stack_manipulation {
+                    parser::StackManipulation::Push { ref symbol, value } => {
+                        let seg: Segment =
+                            Segment::resolve(symbol, value, options)?;
+                        Self::push(output, &seg, value, file_name)
+                    }
+                    parser::StackManipulation::Pop { ref symbol, value } => {
+                        let seg: Segment =
+                            Segment::resolve(symbol, value, options)?;
+                        Self::pop(output, &seg, value, file_name)
+                    }
+                }
+            }
+            // Semester ended before we got to these.
+            Instruction::Branching(_) | Instruction::Functional(_) => {
+                Err(HackError::IllegalInstruction(format!(
+                    "\"{instruction}\" cannot be translated yet, as only \
+                    stack manipulation and arithmetic commands are"
+                )))
+            }
+            Instruction::Arithmetic(arithmetic)
+                if arithmetic.is_extension()
+                    && options.extensions() == Extensions::Disabled =>
+            {
+                Err(HackError::IllegalInstruction(format!(
+                    "\"{}\" is an extension, enable it with \"--extensions\"",
+                    arithmetic.identify()[0]
+                )))
+            }
+            Instruction::Arithmetic(arithmetic) => Self::arithmetic(
+                output,
+                arithmetic,
+                label_number,
+                options.labels(),
+                options.target(),
+            ),
+            Instruction::Assembly(_) if !options.inline_asm() => {
+                Err(HackError::IllegalInstruction(
+                    "inline assembly is only passed through with \
+                    \"--inline-asm\""
+                        .to_owned(),
+                ))
+            }
+            Instruction::Assembly(ref lines) => {
+                for line in lines {
+                    writeln!(output, "{line}")?;
+                }
+                Ok(())
+            }
+            // Instructions added to the syntax since this was written.
+            _ => {
+                Err(HackError::UnrecognizedInstruction(instruction.to_string()))
+            }
+        }
+    }
+
+    /// Sets `D` to a value with the sign of `x - y`, given `y` in `D` and `x`
+    /// in `M`, for `gt` and `lt` to jump on.
+    ///
+    /// Subtracting overflows when `x` and `y` have opposite signs, as for
+    /// `32767 - -1`, so `x` alone decides then, and they are only subtracted
+    /// when their signs are the same. The labels are named after `base`.
+    fn difference<W: Write>(
+        output: &mut W,
+        base: &str,
+    ) -> Result<(), HackError> {
+        writeln!(
+            output,
+            "@{base}_NEGATIVE\n\
+            D;JLT\n\
+            @SP\n\
+            A=M-1\n\
+            D=M\n\
+            @{base}_COMPARE\n\
+            D;JLT\n\
+            ({base}_SUBTRACT)\n\
+            @SP\n\
+            A=M\n\
+            D=M\n\
+            A=A-1\n\
+            D=M-D\n\
+            @{base}_COMPARE\n\
+            0;JMP\n\
+            ({base}_NEGATIVE)\n\
+            @SP\n\
+            A=M-1\n\
+            D=M\n\
+            @{base}_SUBTRACT\n\
+            D;JLT\n\
+            D=1\n\
+            ({base}_COMPARE)"
+        )?;
+        Ok(())
+    }
+
+    /// Translate arithmetic/logic Hack VM instructions into Hack assembly.
+    pub(crate) fn arithmetic<W: Write>(
+        output: &mut W,
+        op: Arithmetic,
+        label_number: usize,
+        labels: &LabelScheme,
+        target: Target,
+    ) -> Result<(), HackError> {
+        match op {
+            Arithmetic::Negative | Arithmetic::Not => {
+                writeln!(output, "@SP\nA=M-1\nM={}M", op.identify()[1])?;
+            }
+            Arithmetic::ShiftLeft | Arithmetic::ShiftRight
+                if target.shifts() =>
+            {
+                writeln!(output, "@SP\nA=M-1\nM=M{}", op.identify()[1])?;
+            }
+            Arithmetic::Multiply
+            | Arithmetic::Divide
+            | Arithmetic::Modulo
+            | Arithmetic::ShiftLeft
+            | Arithmetic::ShiftRight => {
+                let (_, base): (String, String) = labels.names(label_number);
+                extensions::inline(output, op, &base)?;
+            }
+            Arithmetic::Add
+            | Arithmetic::Subtract
+            | Arithmetic::Equal
+            | Arithmetic::GreaterThan
+            | Arithmetic::Lessthan
+            | Arithmetic::And
+            | Arithmetic::Or => {
+                output.write_str("@SP\nAM=M-1\nD=M\nA=A-1\n")?;
+                let impossible: &str = "[`Arithmetic::Not`], \
+                [`Arithmetic::Negative`], and extensions should have already \
+                been matched";
+                match op {
+                    Arithmetic::Lessthan
+                    | Arithmetic::GreaterThan
+                    | Arithmetic::Equal => {
+                        let (when_true, after): (String, String) =
+                            labels.names(label_number);
+                        if matches!(op, Arithmetic::Equal) {
+                            output.write_str("D=M-D\n")?;
+                        } else {
+                            Self::difference(output, &after)?;
+                        }
+                        writeln!(
+                            output,
+                            "@{when_true}\n\
+                            D;{}\n\
+                            @SP\n\
+                            A=M-1\n\
+                            M=0\n\
+                            @{after}\n\
+                            0;JMP\n\
+                            ({when_true})\n\
+                            @SP\n\
+                            A=M-1\n\
+                            M=-1\n\
+                            ({after})",
+                            op.identify()[1]
+                        )?;
+                    }
+                    Arithmetic::And | Arithmetic::Add | Arithmetic::Or => {
+                        writeln!(output, "M=D{}M", op.identify()[1])?;
+                    }
+                    Arithmetic::Subtract => {
+                        writeln!(output, "M=M{}D", op.identify()[1])?;
+                    }
+                    #[expect(
+                        clippy::unreachable,
+                        reason = "This branch should never happen unless some
+                        sort of UB or memory corruption is going on."
+                    )]
+                    Arithmetic::Not
+                    | Arithmetic::Negative
+                    | Arithmetic::Multiply
+                    | Arithmetic::Divide
+                    | Arithmetic::Modulo
+                    | Arithmetic::ShiftLeft
+                    | Arithmetic::ShiftRight
+                    | _ => {
+                        unreachable!("{impossible}")
+                    }
+                }
+            }
+            // Operations added to the syntax since this was written.
+            _ => {
+                return Err(HackError::UnrecognizedInstruction(op.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Helper function. Writes the Hack assembly to push the current value of
+    /// the data register onto the stack.
+    pub(crate) fn push_from_data_register<W: Write>(
+        output: &mut W,
+    ) -> Result<(), HackError> {
+        // RAM[SP] <- D
+        output.write_str("@SP\nA=M\nM=D\n")?;
+        // SP++
+        output.write_str("@SP\nM=M+1\n")?;
+        Ok(())
+    }
+
+    /// Push a value  from the chosen segment onto the stack.
+    pub(crate) fn push<W: Write>(
+        output: &mut W,
+        segment: &Segment,
+        i: Constant,
+        file_name: &str,
+    ) -> Result<(), HackError> {
+        match *segment {
+            Segment::Constant => {
+                // D = i
+                writeln!(output, "@{i}\nD=A")?;
+            }
+            Segment::Argument
+            | Segment::This(_)
+            | Segment::That(_)
+            | Segment::Local => {
+                // D = segment[i]
+                writeln!(
+                    output,
+                    "@{i}\nD=A\n@{}\nA=D+M\nD=M",
+                    segment.base()?
+                )?;
+            }
+            Segment::Static(index) => {
+                // D = RAM[Xxx.i]
+                writeln!(output, "@{file_name}.{index}\nD=M")?;
+            }
+            Segment::User(ref name, segment) => {
+                // D = RAM[base + i]
+                writeln!(output, "@{}\nD=M", segment.address(name, i)?)?;
+            }
+            Segment::Temp(index) => {
+                // D = RAM[5 + i]
+                writeln!(output, "@{}\nD=M", index.address())?;
+            }
+            Segment::Pointer(index) => {
+                // D = RAM[3] or RAM[4]
+                writeln!(output, "@{}\nD=M", index.symbol())?;
+            }
+        }
+
+        Self::push_from_data_register(output)
+    }
+
+    /// Helper function. Takes the current value in the data register and moves
+    /// it into the general register selected.
+    pub(crate) fn save_data_register_in_general<W: Write>(
+        output: &mut W,
+        number: u8,
+    ) -> Result<(), HackError> {
+        if Self::GENERAL_REGISTERS.contains(&number) {
+            // RAM[R{number}] <- D
+            writeln!(output, "@R{number}\nM=D")?;
+            Ok(())
+        } else {
+            Err(HackError::Internal)
+        }
+    }
+
+    /// Helper function. Pops a value off the stack and stores it in the
+    /// general register selected.
+    pub(crate) fn pop_to_general<W: Write>(
+        output: &mut W,
+        number: u8,
+    ) -> Result<(), HackError> {
+        if Self::GENERAL_REGISTERS.contains(&number) {
+            // SP--
+            output.write_str("@SP\nAM=M-1\n")?;
+            // D=stack.pop!
+            output.write_str("D=M\n")?;
+            // RAM[R{number}] <- stack.pop!
+            writeln!(output, "@R{number}\nA=M\nM=D")?;
+            Ok(())
+        } else {
+            Err(HackError::Internal)
+        }
+    }
+
+    /// Pops a value off the stack and into the selected segment. Segments
+    /// whose address is known before the program runs are stored to
+    /// directly; the others go through `R13`.
+    pub(crate) fn pop<W: Write>(
+        output: &mut W,
+        segment: &Segment,
+        i: Constant,
+        file_name: &str,
+    ) -> Result<(), HackError> {
+        let address: String = match *segment {
+            Segment::That(_)
+            | Segment::Local
+            | Segment::Argument
+            | Segment::This(_) => {
+                // D = RAM[segment_base] + i == segment[i].address
+                writeln!(output, "@{i}\nD=A\n@{}\nD=D+M", segment.base()?)?;
+                Self::save_data_register_in_general(output, 13)?;
+                return Self::pop_to_general(output, 13);
+            }
+            // Xxx.i
+            Segment::Static(index) => format!("{file_name}.{index}"),
+            // base + i
+            Segment::User(ref name, segment) => {
+                segment.address(name, i)?.to_string()
+            }
+            // 5 + i
+            Segment::Temp(index) => index.address().to_string(),
+            // 3 or 4
+            Segment::Pointer(index) => index.symbol(),
+            Segment::Constant => {
+                return Err(HackError::IllegalInstruction(
+                    "\"pop constant n\" is never a valid instruction, \
+                    regardless of the value of n"
+                        .to_owned(),
+                ));
+            }
+        };
+
+        // SP--
+        output.write_str("@SP\nAM=M-1\n")?;
+        // RAM[address] <- stack.pop!
+        writeln!(output, "D=M\n@{address}\nM=D")?;
+        Ok(())
+    }
+}
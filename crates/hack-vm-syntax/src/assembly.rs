@@ -0,0 +1,119 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Assembly Module
+//!
+//! Validates the Hack assembly written inline in Hack VM files, which is
+//! passed through verbatim when enabled with `--inline-asm`. It is written
+//! either as a block:
+//!
+//! ```text
+//! asm {
+//!     @SP
+//!     AM=M-1
+//! }
+//! ```
+//!
+//! or one instruction at a time, after `//!asm`, which other translators
+//! ignore as a comment:
+//!
+//! ```text
+//! //!asm @SP
+//! ```
+//!
+//! Each instruction must be a valid A-instruction, C-instruction, or label
+//! declaration, though nothing checks that it leaves the VM's state intact.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::HackError;
+use crate::parser::Constant;
+
+/// The computations a C-instruction may perform.
+const COMPUTATIONS: [&str; 28] = [
+    "0", "1", "-1", "D", "A", "!D", "!A", "-D", "-A", "D+1", "A+1", "D-1",
+    "A-1", "D+A", "D-A", "A-D", "D&A", "D|A", "M", "!M", "-M", "M+1", "M-1",
+    "D+M", "D-M", "M-D", "D&M", "D|M",
+];
+
+/// The destinations a C-instruction may store into.
+const DESTINATIONS: [&str; 7] = ["M", "D", "MD", "A", "AM", "AD", "AMD"];
+
+/// The jumps a C-instruction may make.
+const JUMPS: [&str; 7] = ["JGT", "JEQ", "JGE", "JLT", "JNE", "JLE", "JMP"];
+
+/// Validates a single line of inline assembly, split by whitespace, and
+/// returns its instruction without any trailing comment.
+///
+/// # Errors
+///
+/// Returns a [`HackError::IllegalInstruction`] if the line is not a single
+/// valid instruction.
+pub fn instruction(parts: &[&str]) -> Result<String, HackError> {
+    let code: Vec<&str> = parts
+        .iter()
+        .take_while(|part: &&&str| !part.starts_with("//"))
+        .copied()
+        .collect();
+    let instruction: String = code.concat();
+    let is_valid: bool = match *instruction.as_bytes() {
+        [b'@', ..] => instruction.get(1..).is_some_and(is_address),
+        [b'(', .., b')'] => instruction
+            .get(1..instruction.len().saturating_sub(1))
+            .is_some_and(is_symbol),
+        _ => is_computation(&instruction),
+    };
+    if is_valid && code.len() == 1 {
+        Ok(instruction)
+    } else {
+        Err(HackError::IllegalInstruction(format!(
+            "\"{}\" is not a valid Hack assembly instruction",
+            parts.join(" ")
+        )))
+    }
+}
+
+/// Whether `address` may follow the `@` of an A-instruction.
+fn is_address(address: &str) -> bool {
+    if address.bytes().all(|byte: u8| byte.is_ascii_digit()) {
+        address
+            .parse::<u16>()
+            .is_ok_and(|value: u16| Constant::try_from(value).is_ok())
+    } else {
+        is_symbol(address)
+    }
+}
+
+/// Whether `symbol` is a valid assembly symbol: letters, digits, `_`, `.`,
+/// `$`, and `:`, not starting with a digit.
+fn is_symbol(symbol: &str) -> bool {
+    symbol
+        .bytes()
+        .next()
+        .is_some_and(|first: u8| !first.is_ascii_digit())
+        && symbol.bytes().all(|byte: u8| {
+            byte.is_ascii_alphanumeric() || b"_.$:".contains(&byte)
+        })
+}
+
+/// Whether `instruction` is a valid C-instruction, `dest=comp;jump`, where
+/// either `dest=` or `;jump` may be left out.
+fn is_computation(instruction: &str) -> bool {
+    let (destination, rest): (Option<&str>, &str) = instruction
+        .split_once('=')
+        .map_or((None, instruction), |(destination, rest): (&str, &str)| {
+            (Some(destination), rest)
+        });
+    let (computation, jump): (&str, Option<&str>) = rest
+        .split_once(';')
+        .map_or((rest, None), |(computation, jump): (&str, &str)| {
+            (computation, Some(jump))
+        });
+    COMPUTATIONS.contains(&computation)
+        && destination
+            .is_none_or(|destination: &str| DESTINATIONS.contains(&destination))
+        && jump.is_none_or(|jump: &str| JUMPS.contains(&jump))
+}
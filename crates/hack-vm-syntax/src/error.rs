@@ -0,0 +1,426 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack Errors
+//!
+//! A submodule containing the various [`HackError`]s that can occur.
+
+use alloc::string::{String, ToString as _};
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+#[cfg(feature = "std")]
+use std::io::Error;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use crate::parser::Constant;
+
+/// An enum containing all [`HackError`]s.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HackError {
+    /// A [`HackError`] returned when failing to read the file provided. The
+    /// [`String`] within is meant to hold some arbitrary, message: typically,
+    /// this will be the string representation of the original error,
+    /// potentially with added context.
+    CannotReadFileFromPath(String),
+    /// A [`HackError`] returned when a label or address uses characters not
+    /// permitted in valid symbols. Symbols must be a sequences of letters
+    /// (a-z || A-Z), digits (0-9), underscores (_), dots (.), dollar signs ($),
+    /// and/or colons (:) that do not begin with a digit.
+    SymbolHasForbiddenCharacter,
+    /// A [`HackError`] returned whenever we get an instruction we honestly
+    /// aren't sure what to do with, which is contained in its [`String`].
+    UnrecognizedInstruction(String),
+    /// A [`HackError`] returned if the number of arguments received was
+    /// unexpected. Contains the number of arguments received as a [`usize`], up
+    /// to [`usize::MAX`]. Anything above will simply be represented as
+    /// [`usize::MAX`].
+    Misconfiguration(usize),
+    /// A [`HackError`] returned if a command-line option was not recognized or
+    /// was given an invalid value. The [`String`] within describes the
+    /// problem.
+    InvalidOption(String),
+    /// A [`HackError`] returned if we aren't able to write to the output file,
+    /// either because it doesn't exist or something else.
+    FileExistsError {
+        /// True if we're certain it does not exist.
+        certain: bool,
+    },
+    /// A [`HackError`] returned if the target Hack ASM file doesn't end in the
+    /// extension `.asm`.
+    BadFileTypeError,
+    /// A [`HackError`] returned if a configuration file, such as a project
+    /// manifest, could not be understood. The [`String`] within names the file
+    /// and describes the problem.
+    BadConfigFile(String),
+    /// A [`HackError`] returned if a zip archive could not be read, either
+    /// because it is malformed or uses unsupported features. The [`String`]
+    /// within names the archive and describes the problem.
+    BadArchive(String),
+    /// A [`HackError`] returned if a directory was given to translate, but it
+    /// does not contain any Hack VM files.
+    NoVmFiles {
+        /// The directory that was searched.
+        directory: String,
+        /// The names of the entries that were found instead.
+        found: Vec<String>,
+    },
+    /// A [`HackError`] returned if two files translated together would name
+    /// their static variables with the same prefix, such that they would
+    /// share them.
+    StaticPrefixCollision {
+        /// The prefix both files would use.
+        prefix: String,
+        /// The first file using the prefix.
+        first: String,
+        /// The second file using the prefix.
+        second: String,
+    },
+    /// A [`HackError`] returned if two assembly files compared with the
+    /// `diff-asm` subcommand diverge. Contains how many times they do.
+    AssemblyDiverges(usize),
+    /// A [`HackError`] returned if a program behaves differently when
+    /// translated by the reference translator, as found by the `compare`
+    /// subcommand. Contains how many test scripts it does under.
+    BehaviorDiverges(usize),
+    /// A [`HackError`] returned if another program run by the translator,
+    /// such as the reference translator, could not be run or failed. The
+    /// [`String`] within names the program and describes the problem.
+    ToolFailed(String),
+    /// A [`HackError`] returned if any errors are thrown when trying to write
+    /// the output. The [`String`] within is meant to hold some arbitrary,
+    /// message: typically, this will be the string representation of the
+    /// original error, potentially with added context.
+    WriteError(String),
+    /// A [`HackError`] returned if any errors are thrown due to some internal
+    /// misuse or logic error. Report this!
+    Internal,
+    /// A [`HackError`] returned if any errors are thrown while trying to create
+    /// internal data structures from a borrowed [`str`] slice. The [`String`]
+    /// it holds should contain additional information.
+    FromStrError(String),
+    /// A [`HackError`] returned if an attempt to call
+    /// [`Constant::try_from<u16>`] uses a [`u16`] which exceeds
+    /// [`Constant::MAX_VALID_CONSTANT`].
+    Overflow,
+    /// A [`HackError`] returned if a [`crate::parser::Instruction`] has been
+    /// determined to be illegal, such as by accessing an index it is not
+    /// permitted to.
+    IllegalInstruction(String),
+    /// A [`HackError`] returned if a line of Hack VM code does not follow the
+    /// grammar of the language; see [`crate::grammar`].
+    UnexpectedToken {
+        /// What was expected, such as "an index".
+        expected: String,
+        /// The words of the line before what was expected.
+        after: String,
+        /// The word found instead, or [`None`] at the end of the line.
+        found: Option<String>,
+        /// Where the line is, as `FILE:LINE`, if that is known.
+        location: Option<String>,
+    },
+    /// A [`HackError`] returned if a `push pointer i` or `pop pointer i`
+    /// command has an index other than 0 or 1.
+    PointerIndex {
+        /// The index used.
+        index: u16,
+        /// Where the command is, as `FILE:LINE`, if that is known.
+        location: Option<String>,
+    },
+    /// A [`HackError`] returned if an input exceeds one of the limits set on
+    /// it; see [`crate::limits`]. The [`String`] within names the file and
+    /// the limit exceeded.
+    LimitExceeded(String),
+    /// A [`HackError`] returned if a program runs differently when translated
+    /// than when interpreted, as found by `--verify`. Contains how many files
+    /// do.
+    TranslationDiverges(usize),
+}
+
+impl HackError {
+    /// The most directory entries listed in a [`HackError::NoVmFiles`]
+    /// message, after the first.
+    const MAX_LISTED_ENTRIES: usize = 9;
+
+    /// Gets the code identifying the kind of error, such as `E0007` for a
+    /// [`HackError::BadFileTypeError`], which diagnostics are tagged with so
+    /// that scripts can match on it rather than on the message.
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match *self {
+            Self::CannotReadFileFromPath(_) => "E0001",
+            Self::SymbolHasForbiddenCharacter => "E0002",
+            Self::UnrecognizedInstruction(_) => "E0003",
+            Self::Misconfiguration(_) => "E0004",
+            Self::InvalidOption(_) => "E0005",
+            Self::FileExistsError { .. } => "E0006",
+            Self::BadFileTypeError => "E0007",
+            Self::BadConfigFile(_) => "E0008",
+            Self::BadArchive(_) => "E0009",
+            Self::NoVmFiles { .. } => "E0010",
+            Self::StaticPrefixCollision { .. } => "E0011",
+            Self::AssemblyDiverges(_) => "E0012",
+            Self::BehaviorDiverges(_) => "E0013",
+            Self::ToolFailed(_) => "E0014",
+            Self::WriteError(_) => "E0015",
+            Self::Internal => "E0016",
+            Self::FromStrError(_) => "E0017",
+            Self::Overflow => "E0018",
+            Self::IllegalInstruction(_) => "E0019",
+            Self::UnexpectedToken { .. } => "E0020",
+            Self::PointerIndex { .. } => "E0021",
+            Self::LimitExceeded(_) => "E0022",
+            Self::TranslationDiverges(_) => "E0023",
+        }
+    }
+
+    /// Records that this error occurred at `location`, as `FILE:LINE`, if it
+    /// is an error that reports where it occurred and does not know yet.
+    pub(crate) fn at(mut self, location: impl FnOnce() -> String) -> Self {
+        if let Self::PointerIndex {
+            location: ref mut slot @ None,
+            ..
+        }
+        | Self::UnexpectedToken {
+            location: ref mut slot @ None,
+            ..
+        } = self
+        {
+            *slot = Some(location());
+        }
+        self
+    }
+
+    /// Writes the message of a [`HackError::NoVmFiles`].
+    fn write_no_vm_files(
+        formatter: &mut fmt::Formatter<'_>,
+        directory: &str,
+        found: &[String],
+    ) -> fmt::Result {
+        write!(formatter, "no \".vm\" files found in \"{directory}\"")?;
+        let Some((first, rest)) = found.split_first() else {
+            return write!(formatter, ", which is empty");
+        };
+        write!(formatter, ", only found: {first}")?;
+        for name in rest.iter().take(Self::MAX_LISTED_ENTRIES) {
+            write!(formatter, ", {name}")?;
+        }
+        if let Some(unlisted) = rest
+            .len()
+            .checked_sub(Self::MAX_LISTED_ENTRIES)
+            .filter(|&unlisted| unlisted > 0)
+        {
+            write!(formatter, ", and {unlisted} more")?;
+        }
+        Ok(())
+    }
+
+    /// Whether the files `first` and `second` have the same name, once their
+    /// directories and extensions are removed.
+    #[cfg(feature = "std")]
+    fn same_stem(first: &str, second: &str) -> bool {
+        Path::new(first).file_stem() == Path::new(second).file_stem()
+    }
+
+    /// Whether the files `first` and `second` have the same name, once their
+    /// directories and extensions are removed. Without the `std` feature,
+    /// directories are only separated by `/`.
+    #[cfg(not(feature = "std"))]
+    fn same_stem(first: &str, second: &str) -> bool {
+        /// Removes the directories and extension from `path`.
+        fn stem(path: &str) -> &str {
+            let name: &str = path.rsplit('/').next().unwrap_or(path);
+            name.rsplit_once('.')
+                .filter(|&(stem, _): &(&str, &str)| !stem.is_empty())
+                .map_or(name, |(stem, _): (&str, &str)| stem)
+        }
+        stem(first) == stem(second)
+    }
+
+    /// Writes the message of a [`HackError::UnexpectedToken`], given what
+    /// was expected and after what.
+    fn write_unexpected_token(
+        formatter: &mut fmt::Formatter<'_>,
+        [expected, after]: [&str; 2],
+        found: Option<&str>,
+        location: Option<&str>,
+    ) -> fmt::Result {
+        if let Some(location) = location {
+            write!(formatter, "{location}: ")?;
+        }
+        write!(formatter, "expected {expected}")?;
+        if !after.is_empty() {
+            write!(formatter, " after \"{after}\"")?;
+        }
+        match found {
+            Some(found) => write!(formatter, ", found \"{found}\""),
+            None => write!(formatter, ", found end of line"),
+        }
+    }
+
+    /// Writes the message of a [`HackError::AssemblyDiverges`], a
+    /// [`HackError::BehaviorDiverges`], or a
+    /// [`HackError::TranslationDiverges`], given how many times it diverges.
+    fn write_divergences(
+        &self,
+        formatter: &mut fmt::Formatter<'_>,
+        count: usize,
+    ) -> fmt::Result {
+        let plural: &str = if count == 1 { "" } else { "s" };
+        if matches!(*self, Self::BehaviorDiverges(_)) {
+            write!(
+                formatter,
+                "the program behaves differently under {count} test \
+                script{plural}"
+            )
+        } else if matches!(*self, Self::TranslationDiverges(_)) {
+            write!(
+                formatter,
+                "{count} file{plural} ran differently when translated than \
+                when interpreted"
+            )
+        } else {
+            write!(formatter, "the assembly diverges in {count} place{plural}")
+        }
+    }
+
+    /// Writes the message of a [`HackError::PointerIndex`].
+    fn write_pointer_index(
+        formatter: &mut fmt::Formatter<'_>,
+        index: u16,
+        location: Option<&str>,
+    ) -> fmt::Result {
+        if let Some(location) = location {
+            write!(formatter, "{location}: ")?;
+        }
+        write!(
+            formatter,
+            "\"{index}\" is not a valid index for pointer, which only has 0 \
+            (THIS) and 1 (THAT)"
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for HackError {
+    /// Creates a [`HackError::CannotReadFileFromPath`] from the [`Error`]
+    /// returned by failed file reading operations.
+    fn from(value: Error) -> Self {
+        Self::CannotReadFileFromPath(value.to_string())
+    }
+}
+
+impl From<fmt::Error> for HackError {
+    /// Creates a [`HackError::WriteError`] from the [`fmt::Error`] returned by
+    /// failed formatting operations.
+    fn from(value: fmt::Error) -> Self {
+        Self::WriteError(value.to_string())
+    }
+}
+
+impl Display for HackError {
+    /// Determines the error message for displaying [`HackError`]s.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message: &str = match *self {
+            Self::SymbolHasForbiddenCharacter => {
+                "symbols must be a sequence of letters (a-z || A-Z), digits \
+                (0-9), underscores (_), dots (.), dollar signs ($), and/or \
+                colons (:) that does not begin with a digit"
+            }
+            Self::UnrecognizedInstruction(ref bad_instruction) => {
+                return write!(
+                    f,
+                    "could not determine instruction type for \
+                    \"{bad_instruction}\""
+                );
+            }
+            Self::Misconfiguration(args) => {
+                return write!(
+                    f,
+                    "expected 1 argument (file.asm), found {args} arguments",
+                );
+            }
+            Self::FileExistsError { certain } => {
+                if certain {
+                    "the target output file already exists, and this program \
+                    refuses to overwrite it"
+                } else {
+                    "there is uncertainty about whether or not it is safe to \
+                    create a new file of the target name"
+                }
+            }
+            Self::BadFileTypeError => {
+                "the target file must have the \".asm\" extension"
+            }
+            Self::NoVmFiles {
+                ref directory,
+                ref found,
+            } => return Self::write_no_vm_files(f, directory, found),
+            Self::StaticPrefixCollision {
+                ref prefix,
+                ref first,
+                ref second,
+            } => {
+                write!(
+                    f,
+                    "\"{first}\" and \"{second}\" would both name their static \
+                    variables \"{prefix}.i\""
+                )?;
+                if !Self::same_stem(first, second) {
+                    write!(f, "; use \"--static-prefix\" to rename one")?;
+                }
+                return Ok(());
+            }
+            Self::AssemblyDiverges(count)
+            | Self::BehaviorDiverges(count)
+            | Self::TranslationDiverges(count) => {
+                return self.write_divergences(f, count);
+            }
+            Self::UnexpectedToken {
+                ref expected,
+                ref after,
+                ref found,
+                ref location,
+            } => {
+                return Self::write_unexpected_token(
+                    f,
+                    [expected, after],
+                    found.as_deref(),
+                    location.as_deref(),
+                );
+            }
+            Self::PointerIndex {
+                index,
+                ref location,
+            } => {
+                return Self::write_pointer_index(
+                    f,
+                    index,
+                    location.as_deref(),
+                );
+            }
+            Self::Overflow => {
+                return write!(
+                    f,
+                    "constants much be non-negative integers which are less \
+                    than or equal to {}",
+                    Constant::MAX_VALID_CONSTANT
+                );
+            }
+            Self::IllegalInstruction(ref error_message)
+            | Self::InvalidOption(ref error_message)
+            | Self::BadConfigFile(ref error_message)
+            | Self::BadArchive(ref error_message)
+            | Self::LimitExceeded(ref error_message)
+            | Self::FromStrError(ref error_message)
+            | Self::WriteError(ref error_message)
+            | Self::ToolFailed(ref error_message)
+            | Self::CannotReadFileFromPath(ref error_message) => error_message,
+            Self::Internal => "internal error, please report this incident",
+        };
+
+        write!(f, "{message}")
+    }
+}
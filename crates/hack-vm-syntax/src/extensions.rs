@@ -0,0 +1,365 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Extensions Module
+//!
+//! Translates the extended arithmetic commands some courses add to the Hack
+//! VM language, which are only accepted when enabled with `--extensions`:
+//!
+//! - `mul`: Pop two values and push their product.
+//! - `div`: Pop two values and push their quotient, rounded towards zero.
+//! - `mod`: Pop two values and push the remainder of their division, which
+//!   has the sign of the dividend.
+//! - `shiftleft`: Pop a value and push it shifted left by one bit.
+//! - `shiftright`: Pop a value and push it shifted right by one bit, keeping
+//!   its sign.
+//!
+//! Results wrap around on overflow, like `add`. Dividing by zero pushes 0 for
+//! `div`, and the dividend for `mod`.
+//!
+//! The commands are translated into loops written inline, using `R13`
+//! through `R15` and the free memory just above the stack as scratch space.
+
+use alloc::format;
+use core::fmt::{self, Display, Write};
+use core::str::FromStr;
+
+use crate::error::HackError;
+use crate::parser::Arithmetic;
+
+/// Whether and how the extended arithmetic commands are translated.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum Extensions {
+    /// The commands are rejected.
+    #[default]
+    Disabled,
+    /// The commands are translated into inline loops.
+    Inline,
+}
+
+impl Extensions {
+    /// The name of the inline translation.
+    const INLINE: &str = "inline";
+}
+
+impl FromStr for Extensions {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::INLINE => Ok(Self::Inline),
+            other => Err(HackError::FromStrError(format!(
+                "\"{other}\" is not a recognized way to translate extensions, \
+                expected \"{}\"",
+                Self::INLINE
+            ))),
+        }
+    }
+}
+
+impl Display for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Disabled => write!(f, "disabled"),
+            Self::Inline => write!(f, "{}", Self::INLINE),
+        }
+    }
+}
+
+/// Writes the Hack assembly for an extended arithmetic command into
+/// `output`, naming its labels `{base}_PART`.
+///
+/// # Errors
+///
+/// Returns a [`HackError::Internal`] if `op` is not an extension.
+pub fn inline<W: Write>(
+    output: &mut W,
+    op: Arithmetic,
+    base: &str,
+) -> Result<(), HackError> {
+    match op {
+        Arithmetic::Multiply => multiply(output, base),
+        Arithmetic::Divide | Arithmetic::Modulo => divide(output, op, base),
+        Arithmetic::ShiftLeft => {
+            writeln!(output, "@SP\nA=M-1\nD=M\nM=D+M")?;
+            Ok(())
+        }
+        Arithmetic::ShiftRight => shift_right(output, base),
+        Arithmetic::Add
+        | Arithmetic::Subtract
+        | Arithmetic::Negative
+        | Arithmetic::Equal
+        | Arithmetic::GreaterThan
+        | Arithmetic::Lessthan
+        | Arithmetic::And
+        | Arithmetic::Or
+        | Arithmetic::Not => Err(HackError::Internal),
+    }
+}
+
+/// Multiplies by adding the multiplicand, doubled once per bit, for each bit
+/// set in the multiplier. The product replaces the multiplicand on the stack.
+fn multiply<W: Write>(output: &mut W, base: &str) -> Result<(), HackError> {
+    writeln!(
+        output,
+        "@SP\n\
+        AM=M-1\n\
+        D=M\n\
+        @R13\n\
+        M=D\n\
+        @SP\n\
+        A=M-1\n\
+        D=M\n\
+        @R15\n\
+        M=D\n\
+        @SP\n\
+        A=M-1\n\
+        M=0\n\
+        @R14\n\
+        M=1\n\
+        ({base}_LOOP)\n\
+        @R14\n\
+        D=M\n\
+        @R13\n\
+        D=D&M\n\
+        @{base}_SKIP\n\
+        D;JEQ\n\
+        @R15\n\
+        D=M\n\
+        @SP\n\
+        A=M-1\n\
+        M=D+M\n\
+        ({base}_SKIP)\n\
+        @R15\n\
+        D=M\n\
+        M=D+M\n\
+        @R14\n\
+        D=M\n\
+        M=D+M\n\
+        D=M\n\
+        @{base}_LOOP\n\
+        D;JNE"
+    )?;
+    Ok(())
+}
+
+/// Divides the magnitudes of the operands, then applies their signs.
+///
+/// The magnitudes are kept negative, since the magnitude of -32768 does not
+/// fit otherwise. The divisor is doubled as many times as it still fits
+/// within what remains of the dividend, and subtracted, until what remains
+/// is smaller than the divisor. That is the remainder, and the quotient is
+/// the sum of the multiples subtracted.
+fn divide<W: Write>(
+    output: &mut W,
+    op: Arithmetic,
+    base: &str,
+) -> Result<(), HackError> {
+    writeln!(
+        output,
+        "@SP\n\
+        AM=M-1\n\
+        D=M\n\
+        A=A+1\n\
+        A=A+1\n\
+        M=D\n\
+        @{base}_ZERO\n\
+        D;JEQ\n\
+        @{base}_NY\n\
+        D;JLT\n\
+        D=-D\n\
+        ({base}_NY)\n\
+        @R14\n\
+        M=D\n\
+        @SP\n\
+        A=M-1\n\
+        D=M\n\
+        @{base}_NX\n\
+        D;JLE\n\
+        D=-D\n\
+        ({base}_NX)\n\
+        @R13\n\
+        M=D\n\
+        @R15\n\
+        M=0\n\
+        ({base}_OUTER)\n\
+        @R13\n\
+        D=M\n\
+        @{base}_DONE\n\
+        D;JEQ\n\
+        @R14\n\
+        D=D-M\n\
+        @{base}_DONE\n\
+        D;JGT\n\
+        @R14\n\
+        D=M\n\
+        @SP\n\
+        A=M\n\
+        M=D\n\
+        A=A+1\n\
+        M=1\n\
+        ({base}_INNER)\n\
+        @SP\n\
+        A=M\n\
+        D=M\n\
+        @16384\n\
+        D=D+A\n\
+        @{base}_STEP\n\
+        D;JLT\n\
+        @SP\n\
+        A=M\n\
+        D=M\n\
+        D=D+M\n\
+        @R13\n\
+        D=M-D\n\
+        @{base}_STEP\n\
+        D;JGT\n\
+        @SP\n\
+        A=M\n\
+        D=M\n\
+        M=D+M\n\
+        A=A+1\n\
+        D=M\n\
+        M=D+M\n\
+        @{base}_INNER\n\
+        0;JMP\n\
+        ({base}_STEP)\n\
+        @SP\n\
+        A=M\n\
+        D=M\n\
+        @R13\n\
+        M=M-D\n\
+        @SP\n\
+        A=M+1\n\
+        D=M\n\
+        @R15\n\
+        M=D+M\n\
+        @{base}_OUTER\n\
+        0;JMP\n\
+        ({base}_DONE)"
+    )?;
+    signs(output, op, base)
+}
+
+/// Applies the signs of the operands to the result of [`divide`], replacing
+/// the dividend on the stack.
+fn signs<W: Write>(
+    output: &mut W,
+    op: Arithmetic,
+    base: &str,
+) -> Result<(), HackError> {
+    if matches!(op, Arithmetic::Divide) {
+        writeln!(
+            output,
+            "@SP\n\
+            A=M+1\n\
+            A=A+1\n\
+            D=M\n\
+            @{base}_YNEG\n\
+            D;JLT\n\
+            @SP\n\
+            A=M-1\n\
+            D=M\n\
+            @{base}_POS\n\
+            D;JGE\n\
+            @{base}_NEG\n\
+            0;JMP\n\
+            ({base}_YNEG)\n\
+            @SP\n\
+            A=M-1\n\
+            D=M\n\
+            @{base}_POS\n\
+            D;JLT\n\
+            ({base}_NEG)\n\
+            @R15\n\
+            M=-M\n\
+            ({base}_POS)\n\
+            @R15\n\
+            D=M\n\
+            @SP\n\
+            A=M-1\n\
+            M=D\n\
+            @{base}_END\n\
+            0;JMP\n\
+            ({base}_ZERO)\n\
+            @SP\n\
+            A=M-1\n\
+            M=0\n\
+            ({base}_END)"
+        )?;
+    } else {
+        writeln!(
+            output,
+            "@SP\n\
+            A=M-1\n\
+            D=M\n\
+            @{base}_NEG\n\
+            D;JLT\n\
+            @R13\n\
+            M=-M\n\
+            ({base}_NEG)\n\
+            @R13\n\
+            D=M\n\
+            @SP\n\
+            A=M-1\n\
+            M=D\n\
+            ({base}_ZERO)"
+        )?;
+    }
+    Ok(())
+}
+
+/// Shifts right by setting each bit of the result from the bit above it in
+/// the operand, then copying the sign bit.
+fn shift_right<W: Write>(output: &mut W, base: &str) -> Result<(), HackError> {
+    writeln!(
+        output,
+        "@SP\n\
+        A=M-1\n\
+        D=M\n\
+        @R13\n\
+        M=D\n\
+        @SP\n\
+        A=M-1\n\
+        M=0\n\
+        @R14\n\
+        M=1\n\
+        M=M+1\n\
+        @R15\n\
+        M=1\n\
+        ({base}_LOOP)\n\
+        @R14\n\
+        D=M\n\
+        @R13\n\
+        D=D&M\n\
+        @{base}_SKIP\n\
+        D;JEQ\n\
+        @R15\n\
+        D=M\n\
+        @SP\n\
+        A=M-1\n\
+        M=D+M\n\
+        ({base}_SKIP)\n\
+        @R14\n\
+        D=M\n\
+        @R15\n\
+        M=D\n\
+        @R14\n\
+        M=D+M\n\
+        D=M\n\
+        @{base}_LOOP\n\
+        D;JNE\n\
+        @R13\n\
+        D=M\n\
+        @{base}_END\n\
+        D;JGE\n\
+        @32767\n\
+        D=!A\n\
+        @SP\n\
+        A=M-1\n\
+        M=D|M\n\
+        ({base}_END)"
+    )?;
+    Ok(())
+}
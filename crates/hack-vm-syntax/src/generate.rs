@@ -0,0 +1,454 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Generate Module
+//!
+//! Implements [`Arbitrary`] for the instructions and everything they are made
+//! of, so fuzzers and property tests can generate them from unstructured
+//! bytes. Only available with the `arbitrary` feature.
+//!
+//! Everything generated is valid: [`Symbol`]s are allowed symbols,
+//! [`Constant`]s are in range, and each [`StackManipulation`] uses a segment of
+//! the VM language with an index that fits it. Inline assembly is never
+//! generated.
+//!
+//! A [`Program`] goes further, and is valid as a whole, so what it does once
+//! translated is well defined:
+//!
+//! - It starts with `Sys.init`, which ends in an endless loop as the course
+//!   expects, and every other function is called with as many arguments as
+//!   it takes.
+//! - Only the VM language is used, so it is accepted in every
+//!   [`crate::mode::Mode`] and with no extensions.
+//! - Functions only call the functions declared after them, and only jump
+//!   forward to labels in the same function, so it never recurses or loops
+//!   outside of `Sys.init`.
+//! - Nothing is popped from a function's stack that it did not push, labels
+//!   are only placed and jumped to when that stack is empty, and only
+//!   segments whose addresses are already set up are used.
+//!
+//! Branching and functional instructions are not translated yet, so to fuzz
+//! translation, generate [`StackManipulation`]s and [`Arithmetic`]s.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use core::str::FromStr as _;
+
+use arbitrary::{Arbitrary, Error, Unstructured};
+
+use crate::error::HackError;
+use crate::indices::StaticIndex;
+use crate::parser::{
+    Arithmetic, Branching, Constant, Functional, Instruction,
+    StackManipulation, Symbol, VmSource,
+};
+use crate::segments::Segments;
+use crate::target::Target;
+
+/// Every arithmetic and logical instruction.
+const ARITHMETIC: [Arithmetic; 14] = [
+    Arithmetic::Add,
+    Arithmetic::Subtract,
+    Arithmetic::Negative,
+    Arithmetic::Equal,
+    Arithmetic::GreaterThan,
+    Arithmetic::Lessthan,
+    Arithmetic::And,
+    Arithmetic::Or,
+    Arithmetic::Not,
+    Arithmetic::Multiply,
+    Arithmetic::Divide,
+    Arithmetic::Modulo,
+    Arithmetic::ShiftLeft,
+    Arithmetic::ShiftRight,
+];
+
+/// The characters a [`Symbol`] may begin with.
+const FIRST_CHARACTERS: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_.$:";
+
+/// The characters that may follow the first character of a [`Symbol`].
+const CHARACTERS: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_.$:0123456789";
+
+/// The longest [`Symbol`] generated.
+const MAX_SYMBOL_LENGTH: usize = 16;
+
+/// Creates the [`Symbol`] `string`, which is known to be valid.
+fn symbol(string: &str) -> Result<Symbol, Error> {
+    Symbol::from_str(string).map_err(|_error: HackError| Error::IncorrectFormat)
+}
+
+/// Creates the [`Constant`] `value`, which is known to be in range.
+fn constant(value: u16) -> Result<Constant, Error> {
+    Constant::try_from(value)
+        .map_err(|_error: HackError| Error::IncorrectFormat)
+}
+
+/// Chooses an index that fits `segment`, a segment of the VM language.
+fn index(
+    unstructured: &mut Unstructured<'_>,
+    segment: &str,
+) -> Result<Constant, Error> {
+    let highest: u16 = match segment {
+        "pointer" => 1,
+        "temp" => Target::HACK.temp_indices(),
+        "static" => StaticIndex::MAX,
+        _ => Constant::MAX_VALID_CONSTANT,
+    };
+    constant(unstructured.int_in_range(0..=highest)?)
+}
+
+impl<'data> Arbitrary<'data> for Symbol {
+    fn arbitrary(u: &mut Unstructured<'data>) -> Result<Self, Error> {
+        let length: usize = u.int_in_range(1..=MAX_SYMBOL_LENGTH)?;
+        let mut string: String = String::with_capacity(length);
+        string.push(char::from(*u.choose(FIRST_CHARACTERS)?));
+        for _ in 1..length {
+            string.push(char::from(*u.choose(CHARACTERS)?));
+        }
+        symbol(&string)
+    }
+}
+
+impl<'data> Arbitrary<'data> for Constant {
+    fn arbitrary(u: &mut Unstructured<'data>) -> Result<Self, Error> {
+        constant(u.int_in_range(0..=Self::MAX_VALID_CONSTANT)?)
+    }
+}
+
+impl<'data> Arbitrary<'data> for Arithmetic {
+    /// Generates any arithmetic or logical instruction, including the
+    /// extensions.
+    fn arbitrary(u: &mut Unstructured<'data>) -> Result<Self, Error> {
+        u.choose(&ARITHMETIC).copied()
+    }
+}
+
+impl<'data> Arbitrary<'data> for StackManipulation {
+    /// Generates a push from any segment of the VM language, or a pop to any
+    /// but `constant`, with an index that fits the segment.
+    fn arbitrary(u: &mut Unstructured<'data>) -> Result<Self, Error> {
+        let segment: &str = u.choose(&Segments::BUILT_IN)?;
+        let value: Constant = index(u, segment)?;
+        if segment == "constant" || u.arbitrary()? {
+            Ok(Self::Push {
+                symbol: symbol(segment)?,
+                value,
+            })
+        } else {
+            Ok(Self::Pop {
+                symbol: symbol(segment)?,
+                value,
+            })
+        }
+    }
+}
+
+impl<'data> Arbitrary<'data> for Branching {
+    fn arbitrary(u: &mut Unstructured<'data>) -> Result<Self, Error> {
+        let symbol: Symbol = u.arbitrary()?;
+        Ok(match u.int_in_range(0_u8..=2)? {
+            0 => Self::Label { symbol },
+            1 => Self::GoTo { symbol },
+            _ => Self::IfGoTo { symbol },
+        })
+    }
+}
+
+impl<'data> Arbitrary<'data> for Functional {
+    fn arbitrary(u: &mut Unstructured<'data>) -> Result<Self, Error> {
+        Ok(match u.int_in_range(0_u8..=2)? {
+            0 => Self::Function {
+                symbol: u.arbitrary()?,
+                value: u.arbitrary()?,
+            },
+            1 => Self::Call {
+                symbol: u.arbitrary()?,
+                value: u.arbitrary()?,
+            },
+            _ => Self::Return,
+        })
+    }
+}
+
+impl<'data> Arbitrary<'data> for Instruction {
+    /// Generates any instruction but inline assembly.
+    fn arbitrary(u: &mut Unstructured<'data>) -> Result<Self, Error> {
+        Ok(match u.int_in_range(0_u8..=3)? {
+            0 => Self::StackManipulation(u.arbitrary()?),
+            1 => Self::Branching(u.arbitrary()?),
+            2 => Self::Functional(u.arbitrary()?),
+            _ => Self::Arithmetic(u.arbitrary()?),
+        })
+    }
+}
+
+/// A whole program that is valid to translate and run; see
+/// [`crate::generate`] for what it guarantees.
+///
+/// Displays as VM code, as with [`VmSource`].
+#[derive(Debug, Clone, Hash)]
+pub struct Program {
+    /// The instructions, in order.
+    instructions: Vec<Instruction>,
+}
+
+impl Program {
+    /// The most functions generated, including `Sys.init`.
+    const MAX_FUNCTIONS: usize = 4;
+    /// The most arguments a function takes.
+    const MAX_ARGUMENTS: u16 = 3;
+    /// The most local variables a function has.
+    const MAX_LOCALS: u16 = 4;
+    /// The most steps taken to generate the body of a function.
+    const MAX_STEPS: usize = 32;
+
+    /// Borrows the instructions, in order.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Takes the instructions, in order.
+    pub fn into_instructions(self) -> Vec<Instruction> {
+        self.instructions
+    }
+}
+
+impl<'data> Arbitrary<'data> for Program {
+    fn arbitrary(u: &mut Unstructured<'data>) -> Result<Self, Error> {
+        let count: usize = u.int_in_range(1..=Self::MAX_FUNCTIONS)?;
+        let mut signatures: Vec<(Symbol, u16)> = Vec::with_capacity(count);
+        signatures.push((symbol("Sys.init")?, 0));
+        for number in 1..count {
+            signatures.push((
+                symbol(&format!("Main.f{number}"))?,
+                u.int_in_range(0..=Self::MAX_ARGUMENTS)?,
+            ));
+        }
+
+        let mut instructions: Vec<Instruction> = Vec::new();
+        for (number, &(ref name, arguments)) in signatures.iter().enumerate() {
+            let locals: u16 = u.int_in_range(0..=Self::MAX_LOCALS)?;
+            instructions.push(Instruction::from(Functional::Function {
+                symbol: name.clone(),
+                value: constant(locals)?,
+            }));
+            let mut body: Body<'_> = Body {
+                instructions: &mut instructions,
+                callees: signatures
+                    .get(number.saturating_add(1)..)
+                    .unwrap_or_default(),
+                locals,
+                arguments,
+                depth: 0,
+                labels: 0,
+                pending: 0,
+            };
+            for _ in 0..u.int_in_range(0..=Self::MAX_STEPS)? {
+                body.step(u)?;
+            }
+            body.finish(number == 0)?;
+        }
+        Ok(Self { instructions })
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", VmSource::new(&self.instructions))
+    }
+}
+
+/// The body of a function of a [`Program`], as it is generated.
+///
+/// Labels are named `L0`, `L1`, and so on, in the order they are placed. A
+/// jump always goes to a label that is not placed yet, so those labels are
+/// pending until they are.
+struct Body<'program> {
+    /// The instructions of the program, which the body is added to.
+    instructions: &'program mut Vec<Instruction>,
+    /// The functions that may be called, and how many arguments each takes.
+    callees: &'program [(Symbol, u16)],
+    /// How many local variables the function has.
+    locals: u16,
+    /// How many arguments the function takes.
+    arguments: u16,
+    /// How many values the function has pushed and not popped.
+    depth: usize,
+    /// How many labels have been placed.
+    labels: usize,
+    /// How many labels have been jumped to, but not placed.
+    pending: usize,
+}
+
+impl Body<'_> {
+    /// Adds `instruction` to the body.
+    fn push(&mut self, instruction: impl Into<Instruction>) {
+        self.instructions.push(instruction.into());
+    }
+
+    /// Adds a push from `segment`.
+    fn push_from(&mut self, segment: &str, i: u16) -> Result<(), Error> {
+        self.push(StackManipulation::Push {
+            symbol: symbol(segment)?,
+            value: constant(i)?,
+        });
+        self.depth = self.depth.saturating_add(1);
+        Ok(())
+    }
+
+    /// Adds a pop to `segment`.
+    fn pop_to(&mut self, segment: &str, i: u16) -> Result<(), Error> {
+        self.push(StackManipulation::Pop {
+            symbol: symbol(segment)?,
+            value: constant(i)?,
+        });
+        self.depth = self.depth.saturating_sub(1);
+        Ok(())
+    }
+
+    /// Chooses a segment whose addresses are set up, and an index into it.
+    ///
+    /// `constant` is only chosen if `push` is set.
+    fn segment(
+        &self,
+        unstructured: &mut Unstructured<'_>,
+        push: bool,
+    ) -> Result<(&'static str, u16), Error> {
+        let mut segments: Vec<(&str, u16)> = Vec::with_capacity(5);
+        segments.push(("temp", Target::HACK.temp_indices()));
+        segments.push(("static", StaticIndex::MAX));
+        if push {
+            segments.push(("constant", Constant::MAX_VALID_CONSTANT));
+        }
+        if let Some(highest) = self.locals.checked_sub(1) {
+            segments.push(("local", highest));
+        }
+        if let Some(highest) = self.arguments.checked_sub(1) {
+            segments.push(("argument", highest));
+        }
+        let &(segment, highest) = unstructured.choose(&segments)?;
+        Ok((segment, unstructured.int_in_range(0..=highest)?))
+    }
+
+    /// Names the label placed `number`th.
+    fn label(number: usize) -> Result<Symbol, Error> {
+        symbol(&format!("L{number}"))
+    }
+
+    /// Places the first pending label, or a new one if none are pending.
+    fn place(&mut self) -> Result<(), Error> {
+        self.push(Branching::Label {
+            symbol: Self::label(self.labels)?,
+        });
+        self.labels = self.labels.saturating_add(1);
+        self.pending = self.pending.saturating_sub(1);
+        Ok(())
+    }
+
+    /// Names a label that is placed later.
+    fn forward(&mut self) -> Result<Symbol, Error> {
+        let symbol: Symbol =
+            Self::label(self.labels.saturating_add(self.pending))?;
+        self.pending = self.pending.saturating_add(1);
+        Ok(symbol)
+    }
+
+    /// Adds one instruction that keeps the body valid, or a few that do so
+    /// together.
+    fn step(
+        &mut self,
+        unstructured: &mut Unstructured<'_>,
+    ) -> Result<(), Error> {
+        match unstructured.int_in_range(0_u8..=6)? {
+            0 => {
+                let (segment, i): (&str, u16) =
+                    self.segment(unstructured, true)?;
+                self.push_from(segment, i)
+            }
+            1 if self.depth >= 1 => {
+                let (segment, i): (&str, u16) =
+                    self.segment(unstructured, false)?;
+                self.pop_to(segment, i)
+            }
+            2 if self.depth >= 1 => {
+                let arithmetic: Arithmetic = *unstructured.choose(
+                    &ARITHMETIC
+                        .into_iter()
+                        .filter(|arithmetic| {
+                            !arithmetic.is_extension()
+                                && (arithmetic.is_unary() || self.depth >= 2)
+                        })
+                        .collect::<Vec<Arithmetic>>(),
+                )?;
+                self.push(arithmetic);
+                if !arithmetic.is_unary() {
+                    self.depth = self.depth.saturating_sub(1);
+                }
+                Ok(())
+            }
+            3 if self.depth == 0 => self.place(),
+            4 if self.depth == 0 => {
+                let symbol: Symbol = self.forward()?;
+                self.push(Branching::GoTo { symbol });
+                Ok(())
+            }
+            5 if self.depth == 1 => {
+                let symbol: Symbol = self.forward()?;
+                self.push(Branching::IfGoTo { symbol });
+                self.depth = 0;
+                Ok(())
+            }
+            6 => {
+                let callees: Vec<&(Symbol, u16)> = self
+                    .callees
+                    .iter()
+                    .filter(|&&(_, arguments)| {
+                        usize::from(arguments) <= self.depth
+                    })
+                    .collect();
+                let Ok(&&(ref name, arguments)) = unstructured.choose(&callees)
+                else {
+                    return Ok(());
+                };
+                self.push(Functional::Call {
+                    symbol: name.clone(),
+                    value: constant(arguments)?,
+                });
+                self.depth = self
+                    .depth
+                    .saturating_sub(usize::from(arguments))
+                    .saturating_add(1);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Ends the body, placing the pending labels with the stack empty.
+    ///
+    /// `Sys.init` then loops forever, and other functions return.
+    fn finish(mut self, entry: bool) -> Result<(), Error> {
+        while self.depth > 0 {
+            self.pop_to("temp", 0)?;
+        }
+        while self.pending > 0 {
+            self.place()?;
+        }
+        if entry {
+            let symbol: Symbol = Self::label(self.labels)?;
+            self.push(Branching::Label {
+                symbol: symbol.clone(),
+            });
+            self.push(Branching::GoTo { symbol });
+        } else {
+            self.push_from("constant", 0)?;
+            self.push(Functional::Return);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Grammar Module
+//!
+//! Parses the [`Token`]s of a line into an [`Instruction`], by recursive
+//! descent over the grammar of the VM language:
+//!
+//! ```text
+//! instruction := arithmetic | "return"
+//!              | ("push" | "pop") segment index
+//!              | ("label" | "goto" | "if-goto") label
+//!              | ("function" | "call") function count
+//! segment, label, function := SYMBOL
+//! index, count := INTEGER
+//! ```
+//!
+//! A line that does not follow the grammar is reported by what was expected
+//! where, as a [`HackError::UnexpectedToken`]:
+//!
+//! ```text
+//! expected an index after "push constant", found end of line
+//! ```
+
+use alloc::borrow::ToOwned as _;
+use alloc::string::{String, ToString as _};
+use alloc::vec::Vec;
+use core::str::FromStr as _;
+
+use crate::error::HackError;
+use crate::lexer::{Token, TokenKind};
+use crate::parser::{
+    Branching, Constant, Functional, Instruction, StackManipulation, Symbol,
+};
+
+/// Parses the `tokens` of a line into an [`Instruction`].
+///
+/// # Errors
+///
+/// Returns a [`HackError::UnexpectedToken`] if the line does not follow the
+/// grammar, or a [`HackError::UnrecognizedInstruction`] if it does not start
+/// with a command. Errors from checking the operands, such as a
+/// [`HackError::PointerIndex`], are propagated.
+pub fn instruction(tokens: &[Token<'_>]) -> Result<Instruction, HackError> {
+    let mut stream: Stream<'_, '_> = Stream {
+        tokens,
+        position: 0,
+    };
+    let instruction: Instruction = stream.instruction()?;
+    stream.end()?;
+    Ok(instruction)
+}
+
+/// The tokens of a line, and how many of them were parsed.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+struct Stream<'tokens, 'line> {
+    /// Every token of the line.
+    tokens: &'tokens [Token<'line>],
+    /// How many tokens were parsed.
+    position: usize,
+}
+
+impl<'line> Stream<'_, 'line> {
+    /// Parses `instruction`.
+    fn instruction(&mut self) -> Result<Instruction, HackError> {
+        let Some(command) = self.tokens.get(self.position).copied() else {
+            return Err(self.expected("a command"));
+        };
+        self.position = self.position.saturating_add(1);
+        match command.text() {
+            StackManipulation::PUSH | StackManipulation::POP => {
+                let segment: Symbol = self.symbol("a segment")?;
+                let index: Constant = self.constant("an index")?;
+                StackManipulation::try_from(&(command.text(), segment, index))
+                    .map(Instruction::from)
+            }
+            text if Branching::COMMANDS.contains(&text) => {
+                let label: Symbol = self.symbol("a label")?;
+                Branching::try_from(&(text, label)).map(Instruction::from)
+            }
+            text @ (Functional::FUNCTION | Functional::CALL) => {
+                let function: Symbol = self.symbol("a function name")?;
+                let count: Constant =
+                    self.constant(if text == Functional::CALL {
+                        "a count of arguments"
+                    } else {
+                        "a count of local variables"
+                    })?;
+                Functional::try_from(&(text, function, count))
+                    .map(Instruction::from)
+            }
+            text => Instruction::from_str(text).map_err(|_error: HackError| {
+                HackError::UnrecognizedInstruction(self.line())
+            }),
+        }
+    }
+
+    /// Parses a `SYMBOL`, described as `what`.
+    fn symbol(&mut self, what: &str) -> Result<Symbol, HackError> {
+        let token: Token<'line> = self.expect(what, TokenKind::Symbol)?;
+        Symbol::from_str(token.text())
+    }
+
+    /// Parses an `INTEGER`, described as `what`.
+    fn constant(&mut self, what: &str) -> Result<Constant, HackError> {
+        let token: Token<'line> = self.expect(what, TokenKind::Integer)?;
+        Constant::from_str(token.text())
+    }
+
+    /// Ensures every token was parsed.
+    fn end(&self) -> Result<(), HackError> {
+        if self.position < self.tokens.len() {
+            return Err(self.expected("end of line"));
+        }
+        Ok(())
+    }
+
+    /// Takes the next token, which must be of the given [`TokenKind`] and is
+    /// described as `what`.
+    fn expect(
+        &mut self,
+        what: &str,
+        kind: TokenKind,
+    ) -> Result<Token<'line>, HackError> {
+        match self.tokens.get(self.position) {
+            Some(&token) if token.kind() == kind => {
+                self.position = self.position.saturating_add(1);
+                Ok(token)
+            }
+            Some(_) | None => Err(self.expected(what)),
+        }
+    }
+
+    /// The error for finding something other than what was expected, which
+    /// is described as `what`, after the tokens parsed so far.
+    fn expected(&self, what: &str) -> HackError {
+        let found: Option<Token<'line>> =
+            self.tokens.get(self.position).copied();
+        HackError::UnexpectedToken {
+            expected: what.to_owned(),
+            after: self.parsed().join(" "),
+            found: found.map(|token: Token<'line>| token.text().to_owned()),
+            location: None,
+        }
+    }
+
+    /// Gets the words of the tokens parsed so far.
+    fn parsed(&self) -> Vec<&'line str> {
+        self.tokens
+            .get(..self.position)
+            .unwrap_or_default()
+            .iter()
+            .map(Token::text)
+            .collect()
+    }
+
+    /// Gets the whole line, with its words separated by spaces.
+    fn line(&self) -> String {
+        self.tokens
+            .iter()
+            .map(Token::to_string)
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
@@ -0,0 +1,188 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Indices Module
+//!
+//! Indices into the segments of the VM language whose size is fixed, which
+//! can only be created once they are checked to be in range:
+//!
+//! - [`PointerIndex`]: `pointer` has the indices 0 (`THIS`) and 1 (`THAT`).
+//!   This is checked as soon as the command is parsed.
+//! - [`TempIndex`]: `temp` has as many indices as the [`Target`] gives it,
+//!   8 on the standard Hack computer.
+//! - [`StaticIndex`]: `static` has the 240 indices from 0 to 239, since the
+//!   assembler allocates static variables from address 16 and the stack
+//!   starts at address 256.
+//!
+//! Translating a command with one of these segments takes the checked index,
+//! so the range is never checked again. Where `temp` and `static` were moved,
+//! as described in [`crate::segments`], their indices are checked against
+//! where they were moved to instead.
+
+use alloc::borrow::ToOwned as _;
+use alloc::format;
+use alloc::string::{String, ToString as _};
+use core::fmt::{self, Display};
+
+use crate::error::HackError;
+use crate::parser::Constant;
+use crate::segments::UserSegment;
+use crate::target::Target;
+
+/// An index of the `pointer` segment, which is 0 or 1.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct PointerIndex {
+    /// Whether the index is 1, which refers to `THAT` rather than `THIS`.
+    that: bool,
+    /// The address of the first word of the segment, if it was moved.
+    base: Option<u16>,
+}
+
+impl PointerIndex {
+    /// Where the `pointer` segment is, unless it was moved.
+    pub const SEGMENT: UserSegment = UserSegment::fixed(3, 2);
+    /// Index 0, the word `this` is based at.
+    pub const THIS: Self = Self {
+        that: false,
+        base: None,
+    };
+    /// Index 1, the word `that` is based at.
+    pub const THAT: Self = Self {
+        that: true,
+        base: None,
+    };
+
+    /// Checks that `i` is an index of the `pointer` segment.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::PointerIndex`] if it is not 0 or 1.
+    pub const fn new(i: Constant) -> Result<Self, HackError> {
+        match i.literal_representation() {
+            0 => Ok(Self::THIS),
+            1 => Ok(Self::THAT),
+            index => Err(HackError::PointerIndex {
+                index,
+                location: None,
+            }),
+        }
+    }
+
+    /// Refers to the same index of the segment `pointer`, which may have
+    /// been moved.
+    #[must_use]
+    pub const fn within(mut self, pointer: UserSegment) -> Self {
+        if pointer.base() != Self::SEGMENT.base() {
+            self.base = Some(pointer.base());
+        }
+        self
+    }
+
+    /// Gets how the word the index refers to is addressed: by its predefined
+    /// symbol, or by its address if the segment was moved.
+    pub fn symbol(self) -> String {
+        match self.base {
+            Some(base) => base.saturating_add(u16::from(self.that)).to_string(),
+            None if self.that => "THAT".to_owned(),
+            None => "THIS".to_owned(),
+        }
+    }
+}
+
+/// An index of the `temp` segment of some [`Target`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct TempIndex {
+    /// The address of the word the index refers to.
+    address: u16,
+}
+
+impl TempIndex {
+    /// Checks that `i` is an index of the `temp` segment of `target`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] if `i` is beyond the end
+    /// of the segment.
+    pub fn new(i: Constant, target: Target) -> Result<Self, HackError> {
+        Self::within(i, target.temp())
+    }
+
+    /// Checks that `i` is an index of the segment `temp`, which may have
+    /// been moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] if `i` is beyond the end
+    /// of the segment.
+    pub fn within(i: Constant, temp: UserSegment) -> Result<Self, HackError> {
+        if i.literal_representation() < temp.size() {
+            Ok(Self {
+                address: temp.base().saturating_add(i.literal_representation()),
+            })
+        } else {
+            Err(HackError::IllegalInstruction(format!(
+                "\"{i}\" is not a valid index for temp, must be 0 <= i <= {}",
+                temp.size().saturating_sub(1)
+            )))
+        }
+    }
+
+    /// Gets the address of the word the index refers to.
+    pub const fn address(self) -> u16 {
+        self.address
+    }
+}
+
+/// An index of the `static` segment.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct StaticIndex {
+    /// The index.
+    index: u16,
+}
+
+impl StaticIndex {
+    /// The highest index of the `static` segment, unless it was moved.
+    pub const MAX: u16 = 239;
+    /// Where the `static` segment is, unless it was moved.
+    pub const SEGMENT: UserSegment =
+        UserSegment::fixed(16, Self::MAX.saturating_add(1));
+
+    /// Checks that `i` is an index of the `static` segment.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] if `i` is beyond the end
+    /// of the segment.
+    pub fn new(i: Constant) -> Result<Self, HackError> {
+        Self::within(i, Self::SEGMENT)
+    }
+
+    /// Checks that `i` is an index of the segment `statics`, which may have
+    /// been moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] if `i` is beyond the end
+    /// of the segment.
+    pub fn within(
+        i: Constant,
+        statics: UserSegment,
+    ) -> Result<Self, HackError> {
+        let index: u16 = i.literal_representation();
+        if index < statics.size() {
+            Ok(Self { index })
+        } else {
+            Err(HackError::IllegalInstruction(format!(
+                "\"{i}\" is not a valid index for static, must be 0 <= i <= {}",
+                statics.size().saturating_sub(1)
+            )))
+        }
+    }
+}
+
+impl Display for StaticIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.index)
+    }
+}
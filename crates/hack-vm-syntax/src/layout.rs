@@ -0,0 +1,119 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Layout Module
+//!
+//! Describes how the RAM of a [`crate::target::Target`] is divided between
+//! static variables, the stack, the heap, and memory-mapped devices, as named
+//! profiles:
+//!
+//! - `standard`: The RAM of the nand2tetris course. Static variables take
+//!   addresses 16 to 255, the stack 256 to 2047, and the heap 2048 to 16383,
+//!   followed by the screen from 16384 to 24575 and the keyboard at 24576.
+//! - `big-ram`: The same, except the heap extends to address 32767, as there
+//!   are no memory-mapped devices.
+//!
+//! Moving a segment of the VM language or the stack into another region is
+//! an error; see [`crate::segments::Segments::check`].
+
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+
+use crate::indices::StaticIndex;
+use crate::parser::Constant;
+use crate::segments::UserSegment;
+
+/// A named layout of the RAM.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Layout {
+    /// The name of the profile.
+    name: &'static str,
+    /// Where the assembler allocates static variables.
+    statics: UserSegment,
+    /// Where the stack starts, which `SP` is initialized to, and how far it
+    /// may grow.
+    stack: UserSegment,
+    /// Where the OS allocates objects and arrays.
+    heap: UserSegment,
+    /// The memory-mapped screen, if there is one.
+    screen: Option<UserSegment>,
+    /// The memory-mapped keyboard, if there is one.
+    keyboard: Option<UserSegment>,
+}
+
+impl Layout {
+    /// The RAM of the standard Hack computer.
+    pub const STANDARD: Self = Self {
+        name: "standard",
+        statics: StaticIndex::SEGMENT,
+        stack: UserSegment::fixed(256, 1792),
+        heap: UserSegment::fixed(2048, 14336),
+        screen: Some(UserSegment::fixed(0x4000, 0x2000)),
+        keyboard: Some(UserSegment::fixed(0x6000, 1)),
+    };
+    /// The RAM of a Hack computer whose heap takes every word after the
+    /// stack.
+    pub const BIG_RAM: Self = Self {
+        name: "big-ram",
+        heap: UserSegment::fixed(
+            2048,
+            Constant::MAX_VALID_CONSTANT.saturating_sub(2047),
+        ),
+        screen: None,
+        keyboard: None,
+        ..Self::STANDARD
+    };
+
+    /// Gets where the assembler allocates static variables.
+    pub const fn statics(self) -> UserSegment {
+        self.statics
+    }
+
+    /// Gets where the stack is. `SP` is initialized to its base.
+    pub const fn stack(self) -> UserSegment {
+        self.stack
+    }
+
+    /// Gets where the OS allocates objects and arrays.
+    pub const fn heap(self) -> UserSegment {
+        self.heap
+    }
+
+    /// Gets the memory-mapped screen, if there is one.
+    pub const fn screen(self) -> Option<UserSegment> {
+        self.screen
+    }
+
+    /// Gets the memory-mapped keyboard, if there is one.
+    pub const fn keyboard(self) -> Option<UserSegment> {
+        self.keyboard
+    }
+
+    /// Lists the regions that the segments of the VM language and the stack
+    /// may not be moved into, by name.
+    pub fn reserved(self) -> Vec<(&'static str, UserSegment)> {
+        [
+            Some(("the heap", self.heap)),
+            self.screen
+                .map(|screen: UserSegment| ("the screen", screen)),
+            self.keyboard
+                .map(|keyboard: UserSegment| ("the keyboard", keyboard)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Lexer Module
+//!
+//! Splits lines of Hack VM code into [`Token`]s, which [`crate::grammar`]
+//! parses into instructions. Blank lines and comments are dropped, except
+//! for comments starting with `//!asm`, which hold inline assembly; see
+//! [`crate::assembly`].
+//!
+//! Each token is classified by what it could stand for, so that the grammar
+//! can say what it expected when a line does not follow it.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+
+use crate::parser::{Parser, Symbol};
+
+/// What a [`Token`] could stand for.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A decimal integer, such as an index or a count.
+    Integer,
+    /// A valid [`Symbol`], such as the name of a segment, label, or
+    /// function, or most commands.
+    Symbol,
+    /// Anything else, such as `if-goto`, or a name with a forbidden
+    /// character.
+    Other,
+}
+
+/// A single word of a line of Hack VM code.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Token<'line> {
+    /// The word as it was written.
+    text: &'line str,
+    /// What it could stand for.
+    kind: TokenKind,
+}
+
+impl<'line> Token<'line> {
+    /// Classifies the word `text` as a [`Token`].
+    pub fn new(text: &'line str) -> Self {
+        let kind: TokenKind = if !text.is_empty()
+            && text.bytes().all(|byte| byte.is_ascii_digit())
+        {
+            TokenKind::Integer
+        } else if Symbol::is_allowed_symbol(text) {
+            TokenKind::Symbol
+        } else {
+            TokenKind::Other
+        };
+        Self { text, kind }
+    }
+
+    /// Gets the word as it was written.
+    pub const fn text(&self) -> &'line str {
+        self.text
+    }
+
+    /// Gets what the word could stand for.
+    pub const fn kind(&self) -> TokenKind {
+        self.kind
+    }
+}
+
+impl Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Splits a line of Hack VM code into words, without its comment, or
+/// returns [`None`] if it holds no code.
+pub fn words(line: &str) -> Option<Vec<&str>> {
+    let line: &str = line.trim();
+    if (line.starts_with("//") && !line.starts_with(Parser::ASM_LINE))
+        || line.is_empty()
+    {
+        return None;
+    }
+    let code: &str = if line.starts_with(Parser::ASM_LINE) {
+        line
+    } else {
+        line.split("//").next().unwrap_or_default()
+    };
+    Some(code.split_whitespace().collect())
+}
+
+/// Gets the column the code of a line starts at, counting characters from 1.
+pub fn column(line: &str) -> usize {
+    line.chars()
+        .take_while(|character: &char| character.is_whitespace())
+        .count()
+        .saturating_add(1)
+}
+
+/// Classifies each of `words` as a [`Token`].
+pub fn tokens<'line>(words: &[&'line str]) -> Vec<Token<'line>> {
+    words.iter().map(|word: &&str| Token::new(word)).collect()
+}
@@ -0,0 +1,85 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Syntax
+//!
+//! Parses Hack VM commands into [`parser::Instruction`]s, along with
+//! everything they may refer to: segments, indices, macros, included files,
+//! and inline Hack assembly. Also holds the [`error::HackError`] shared by
+//! every stage of translation. Based on the nand2tetris course.
+//!
+//! Generating assembly from the instructions is left to `hack-vm-codegen`.
+//!
+//! Only `core` and `alloc` are needed to parse contents that were already
+//! read, so the crate can be used without the standard library, such as in a
+//! `no_std` WASM sandbox. Reading files from disk, `.include`, [`paths`], and
+//! [`trace`] need the `std` feature, which is enabled by default.
+//!
+//! New errors, instructions, and arithmetic operations are added over time,
+//! so [`error::HackError`], [`parser::Instruction`], and
+//! [`parser::Arithmetic`] are `#[non_exhaustive]`: match them with a wildcard
+//! arm, which adding a variant never breaks. Structs keep their fields
+//! private, behind constructors and accessors, so how they are represented
+//! can change as well. Anything else that changes what is public is a
+//! breaking change, and bumps the minor version while below 1.0.
+
+#![expect(
+    clippy::module_name_repetitions,
+    clippy::missing_errors_doc,
+    reason = "todo later"
+)]
+#![no_std]
+// Lints that are still unstable, and so only checked with the `nightly`
+// feature, keeping the default build working on stable.
+#![cfg_attr(
+    feature = "nightly",
+    feature(
+        multiple_supertrait_upcastable,
+        must_not_suspend,
+        non_exhaustive_omitted_patterns_lint,
+        strict_provenance_lints,
+        supertrait_item_shadowing,
+        unqualified_local_imports
+    ),
+    // Casting integers to pointers messes with provenance (#130351)
+    warn(fuzzy_provenance_casts),
+    // Casting pointers to integers loses with provenance (#130351)
+    warn(lossy_provenance_casts),
+    // Adds overhead. Is it worth it? (#150833)
+    warn(multiple_supertrait_upcastable),
+    // Causes problems in async (#83310)
+    warn(must_not_suspend),
+    // Explicit matches are preferred (#89554)
+    warn(non_exhaustive_omitted_patterns),
+    // Shadowing silently leads to unexpected behavior (#89151)
+    warn(shadowing_supertrait_items),
+    warn(resolving_to_items_shadowing_supertrait_items),
+    // Explicitness provides clarity, and this assists import grouping in
+    // rustfmt (#138299)
+    warn(unqualified_local_imports)
+)]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod assembly;
+pub mod error;
+pub mod extensions;
+#[cfg(feature = "arbitrary")]
+pub mod generate;
+pub mod grammar;
+pub mod indices;
+pub mod layout;
+pub mod lexer;
+pub mod limits;
+pub mod macros;
+pub mod mode;
+pub mod parser;
+#[cfg(feature = "std")]
+pub mod paths;
+pub mod provenance;
+pub mod segments;
+pub mod target;
+pub mod trace;
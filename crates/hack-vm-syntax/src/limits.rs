@@ -0,0 +1,235 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Limits Module
+//!
+//! Caps how much input is accepted, so that the translator can be run on
+//! untrusted files, such as uploads to a grading server, without exhausting
+//! its memory. Each limit is off unless given:
+//!
+//! - `--max-file-size <BYTES>`: The size of each file read, whether from
+//!   disk, standard input, a zip archive, or `.include`. Files are read no
+//!   further than the limit, so a file that never ends is stopped as well.
+//! - `--max-line-length <CHARS>`: The length of each line of each file.
+//! - `--max-instructions <N>`: The number of instructions each file is parsed
+//!   into, counting those of the files it includes and of the macros it
+//!   expands.
+//!
+//! Exceeding a limit is a [`HackError::LimitExceeded`] naming the file, and
+//! where possible the line, that exceeded it.
+//!
+//! Files must also be valid UTF-8. One that is not is rejected with the
+//! offset of its first invalid byte, such as a file saved as Latin-1, unless
+//! `--lossy-utf8` is given, in which case invalid bytes are replaced with
+//! `\u{FFFD}` and the file is translated anyway. Files with a NUL byte near
+//! their start, such as a `.class` file submitted by mistake, are always
+//! rejected as binary.
+
+use alloc::format;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::string::ToString as _;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::{self, Read as _};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use crate::error::HackError;
+#[cfg(feature = "std")]
+use crate::paths;
+use crate::trace;
+
+/// The limits on the input accepted.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub struct Limits {
+    /// The most bytes a file may have, if limited.
+    file_size: Option<usize>,
+    /// The most characters a line may have, if limited.
+    line_length: Option<usize>,
+    /// The most instructions a file may be parsed into, if limited.
+    instructions: Option<usize>,
+    /// Whether invalid UTF-8 is replaced, rather than rejected.
+    lossy_utf8: bool,
+}
+
+impl Limits {
+    /// How many bytes at the start of a file are searched for a NUL byte,
+    /// which marks it as binary.
+    const BINARY_PREFIX: usize = 8000;
+
+    /// Sets the most bytes a file may have.
+    pub const fn set_file_size(&mut self, bytes: usize) {
+        self.file_size = Some(bytes);
+    }
+
+    /// Sets the most characters a line may have.
+    pub const fn set_line_length(&mut self, characters: usize) {
+        self.line_length = Some(characters);
+    }
+
+    /// Sets the most instructions a file may be parsed into.
+    pub const fn set_instructions(&mut self, instructions: usize) {
+        self.instructions = Some(instructions);
+    }
+
+    /// Sets whether invalid UTF-8 is replaced with `\u{FFFD}`, rather than
+    /// rejected.
+    pub const fn set_lossy_utf8(&mut self, lossy: bool) {
+        self.lossy_utf8 = lossy;
+    }
+
+    /// Decodes the `bytes` of the file `name` as UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::CannotReadFileFromPath`] if the file looks
+    /// binary, or if it is not valid UTF-8 and invalid bytes are not
+    /// replaced.
+    pub fn decode(
+        self,
+        name: &str,
+        bytes: Vec<u8>,
+    ) -> Result<String, HackError> {
+        if let Some(offset) = bytes
+            .iter()
+            .take(Self::BINARY_PREFIX)
+            .position(|&byte| byte == 0)
+        {
+            return Err(HackError::CannotReadFileFromPath(format!(
+                "{name}: looks like a binary file rather than Hack VM code, \
+                as byte {offset} is NUL"
+            )));
+        }
+        String::from_utf8(bytes).or_else(|error| {
+            let offset: usize = error.utf8_error().valid_up_to();
+            let line: usize = error
+                .as_bytes()
+                .iter()
+                .take(offset)
+                .filter(|&&byte| byte == b'\n')
+                .count()
+                .saturating_add(1);
+            if !self.lossy_utf8 {
+                return Err(HackError::CannotReadFileFromPath(format!(
+                    "{name}:{line}: not valid UTF-8 at byte {offset}; save \
+                    the file as UTF-8, or replace invalid bytes with \
+                    \"--lossy-utf8\""
+                )));
+            }
+            trace::event!(
+                Warn,
+                "{name}:{line}: replacing invalid UTF-8 from byte {offset}"
+            );
+            Ok(String::from_utf8_lossy(error.as_bytes()).into_owned())
+        })
+    }
+
+    /// Reads the file at `path`, reading no further than the limit on its
+    /// size, and checks its contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or exceeds a limit.
+    #[cfg(feature = "std")]
+    pub fn read_file(self, path: &Path) -> Result<String, HackError> {
+        let name: String = paths::display(path).to_string();
+        let contents: String = self.read(File::open(path)?, &name)?;
+        self.check_lines(&name, &contents)?;
+        Ok(contents)
+    }
+
+    /// Reads standard input, reading no further than the limit on its size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if standard input cannot be read or is too large.
+    #[cfg(feature = "std")]
+    pub fn read_stdin(self) -> Result<String, HackError> {
+        self.read(io::stdin().lock(), "<stdin>")
+    }
+
+    /// Reads `reader` to the end, or one byte past the limit on the size of
+    /// the file `name`, if there is one.
+    #[cfg(feature = "std")]
+    fn read<R: io::Read>(
+        self,
+        reader: R,
+        name: &str,
+    ) -> Result<String, HackError> {
+        let past_limit: u64 = self.file_size.map_or(u64::MAX, |limit| {
+            u64::try_from(limit)
+                .map_or(u64::MAX, |limit| limit.saturating_add(1))
+        });
+        let mut contents: Vec<u8> = Vec::new();
+        let _read: usize =
+            reader.take(past_limit).read_to_end(&mut contents)?;
+        self.check_size(name, contents.len())?;
+        self.decode(name, contents)
+    }
+
+    /// Checks that the file `name`, which is `size` bytes long, is within the
+    /// limit on its size.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::LimitExceeded`] if it is not.
+    pub fn check_size(self, name: &str, size: usize) -> Result<(), HackError> {
+        match self.file_size {
+            Some(limit) if size > limit => Err(HackError::LimitExceeded(
+                format!("{name}: file is larger than {limit} bytes"),
+            )),
+            Some(_) | None => Ok(()),
+        }
+    }
+
+    /// Checks that every line of `contents`, from the file `name`, is within
+    /// the limit on its length.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::LimitExceeded`] for the first that is not.
+    pub fn check_lines(
+        self,
+        name: &str,
+        contents: &str,
+    ) -> Result<(), HackError> {
+        let Some(limit) = self.line_length else {
+            return Ok(());
+        };
+        contents.lines().zip(1_usize..).try_for_each(
+            |(line, line_number): (&str, usize)| {
+                if line.chars().count() > limit {
+                    Err(HackError::LimitExceeded(format!(
+                        "{name}:{line_number}: line is longer than {limit} \
+                        characters"
+                    )))
+                } else {
+                    Ok(())
+                }
+            },
+        )
+    }
+
+    /// Checks that the file `name`, parsed into `count` instructions, is
+    /// within the limit on their number.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::LimitExceeded`] if it is not.
+    pub fn check_instructions(
+        self,
+        name: &str,
+        count: usize,
+    ) -> Result<(), HackError> {
+        match self.instructions {
+            Some(limit) if count > limit => Err(HackError::LimitExceeded(
+                format!("{name}: file has more than {limit} instructions"),
+            )),
+            Some(_) | None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,247 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Macros Module
+//!
+//! Expands parameterized macros, so that repetitive patterns in hand-written
+//! Hack VM code, such as bounds checks or struct field access, need not be
+//! copied out each time:
+//!
+//! ```text
+//! .macro field(object, index)
+//!     push object
+//!     pop pointer 1
+//!     push that index
+//! .endmacro
+//!
+//! field(argument 0, 2)
+//! ```
+//!
+//! Each word of the body that names a parameter is replaced by the argument
+//! given for it, which may be several words. A macro may use those defined
+//! before it, but not itself.
+//!
+//! Expansion is hygienic: labels declared in the body are renamed for each
+//! expansion, as `LABEL:name:n`, so a macro using labels can be expanded
+//! more than once in a function.
+
+use alloc::borrow::ToOwned as _;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::HackError;
+use crate::parser::Branching;
+
+/// A macro's definition.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct Macro {
+    /// The names of its parameters.
+    parameters: Vec<String>,
+    /// The lines of its body, split by whitespace.
+    body: Vec<Vec<String>>,
+}
+
+impl Macro {
+    /// Gets the argument for `word`, if it names a parameter, or else `word`
+    /// itself.
+    fn argument<'word>(
+        &self,
+        arguments: &'word [String],
+        word: &'word str,
+    ) -> &'word str {
+        self.parameters
+            .iter()
+            .position(|parameter: &String| parameter == word)
+            .and_then(|index: usize| arguments.get(index))
+            .map_or(word, String::as_str)
+    }
+}
+
+/// The macros defined so far in a file.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct Macros {
+    /// Each macro, by name.
+    definitions: BTreeMap<String, Macro>,
+    /// How many expansions there have been, which numbers their labels.
+    expansions: usize,
+}
+
+impl Macros {
+    /// The word starting a definition.
+    pub const DEFINE: &str = ".macro";
+    /// The word ending a definition.
+    const END: &str = ".endmacro";
+
+    /// Parses a signature, `name(first, second)`, from the words of a line,
+    /// into the name and what is between the parentheses, separated by
+    /// commas. Returns [`None`] if the line is not a signature.
+    fn signature(parts: &[&str]) -> Option<(String, Vec<String>)> {
+        let line: String = parts.join(" ");
+        let (name, rest): (&str, &str) = line.split_once('(')?;
+        let list: &str = rest.strip_suffix(')')?.trim();
+        let name: &str = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return None;
+        }
+        let list: Vec<String> = if list.is_empty() {
+            Vec::new()
+        } else {
+            list.split(',')
+                .map(|item: &str| item.trim().to_owned())
+                .collect()
+        };
+        Some((name.to_owned(), list))
+    }
+
+    /// Defines a macro, given the words of its signature after
+    /// [`Macros::DEFINE`], consuming its body from `lines` up to and
+    /// including [`Macros::END`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] if the signature is
+    /// malformed, or the definition is never ended.
+    pub fn define<'file, L: Iterator<Item = (usize, Vec<&'file str>)>>(
+        &mut self,
+        signature: &[&str],
+        lines: &mut L,
+        line_number: usize,
+    ) -> Result<(), HackError> {
+        let Some((name, parameters)) = Self::signature(signature) else {
+            return Err(HackError::IllegalInstruction(format!(
+                "the macro defined on line {line_number} must be named as \
+                \"{} name(first, second)\", not \"{} {}\"",
+                Self::DEFINE,
+                Self::DEFINE,
+                signature.join(" ")
+            )));
+        };
+        if parameters.iter().any(|parameter: &String| {
+            parameter.is_empty() || parameter.contains(char::is_whitespace)
+        }) {
+            return Err(HackError::IllegalInstruction(format!(
+                "the parameters of macro \"{name}\" must each be a single word"
+            )));
+        }
+        let mut body: Vec<Vec<String>> = Vec::new();
+        loop {
+            let Some((_, parts)) = lines.next() else {
+                return Err(HackError::IllegalInstruction(format!(
+                    "macro \"{name}\" is never ended with \"{}\"",
+                    Self::END
+                )));
+            };
+            match parts[..] {
+                [Self::END] => break,
+                [Self::DEFINE, ..] => {
+                    return Err(HackError::IllegalInstruction(format!(
+                        "macro \"{name}\" must be ended with \"{}\" before \
+                        another is defined",
+                        Self::END
+                    )));
+                }
+                _ => body
+                    .push(parts.iter().map(|&part| part.to_owned()).collect()),
+            }
+        }
+        let _previous: Option<Macro> =
+            self.definitions.insert(name, Macro { parameters, body });
+        Ok(())
+    }
+
+    /// Expands a line if it uses a macro, returning the lines it expands
+    /// to, split by whitespace. Returns [`None`] if it does not use a macro.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] if the macro is given the
+    /// wrong number of arguments, or uses itself.
+    pub fn expand(
+        &mut self,
+        parts: &[&str],
+    ) -> Result<Option<Vec<Vec<String>>>, HackError> {
+        self.expand_within(parts, &mut Vec::new())
+    }
+
+    /// Like [`Macros::expand`], within the expansions of the macros named
+    /// in `active`, which may not be used again.
+    fn expand_within(
+        &mut self,
+        parts: &[&str],
+        active: &mut Vec<String>,
+    ) -> Result<Option<Vec<Vec<String>>>, HackError> {
+        let Some((name, arguments)) = Self::signature(parts) else {
+            return Ok(None);
+        };
+        let Some(definition) = self.definitions.get(&name).cloned() else {
+            return Ok(None);
+        };
+        if active.contains(&name) {
+            return Err(HackError::IllegalInstruction(format!(
+                "macro \"{name}\" uses itself, so it never finishes expanding"
+            )));
+        }
+        if arguments.len() != definition.parameters.len() {
+            return Err(HackError::IllegalInstruction(format!(
+                "macro \"{name}\" takes {} arguments, but was given {}",
+                definition.parameters.len(),
+                arguments.len()
+            )));
+        }
+        let expansion: usize = self.expansions;
+        self.expansions = self.expansions.saturating_add(1);
+
+        let labels: Vec<&String> = definition
+            .body
+            .iter()
+            .filter_map(|line: &Vec<String>| match line[..] {
+                [ref command, ref label] if command == Branching::LABEL => {
+                    Some(label)
+                }
+                _ => None,
+            })
+            .collect();
+        active.push(name.clone());
+        let mut expanded: Vec<Vec<String>> = Vec::new();
+        for line in &definition.body {
+            let line: Vec<&str> = line.iter().map(String::as_str).collect();
+            // Arguments passed on to another macro are substituted too.
+            let substituted: String = match Self::signature(&line) {
+                Some((inner, items))
+                    if self.definitions.contains_key(&inner) =>
+                {
+                    let items: Vec<&str> = items
+                        .iter()
+                        .map(|item: &String| {
+                            definition.argument(&arguments, item)
+                        })
+                        .collect();
+                    format!("{inner}({})", items.join(", "))
+                }
+                Some(_) | None => line
+                    .iter()
+                    .map(|word: &&str| definition.argument(&arguments, word))
+                    .collect::<Vec<&str>>()
+                    .join(" "),
+            };
+            let mut words: Vec<String> =
+                substituted.split_whitespace().map(str::to_owned).collect();
+            if let [ref command, ref mut label] = words[..]
+                && Branching::COMMANDS.contains(&command.as_str())
+                && labels.contains(&&*label)
+            {
+                *label = format!("{label}:{name}:{expansion}");
+            }
+            let parts: Vec<&str> = words.iter().map(String::as_str).collect();
+            match self.expand_within(&parts, active)? {
+                Some(lines) => expanded.extend(lines),
+                None => expanded.push(words),
+            }
+        }
+        let _name: Option<String> = active.pop();
+        Ok(Some(expanded))
+    }
+}
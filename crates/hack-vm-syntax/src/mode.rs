@@ -0,0 +1,68 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Mode Module
+//!
+//! Chooses how strictly Hack VM code is parsed.
+//!
+//! - `strict` (the default): Only the official VM language is accepted.
+//! - `permissive`: Conveniences common in hand-written code are accepted as
+//!   well, and desugared into the official language, for interoperating with
+//!   other tools:
+//!   - A negative constant, as in `push constant -5`, is pushed as `push
+//!     constant 5` followed by `neg`.
+//!   - Comments may also start with `#` or `;`, on their own line or after a
+//!     command.
+//!   - Commands and the segments of the VM language may be written in any
+//!     case, as in `PUSH Constant 5`. The names of functions and labels are
+//!     still case-sensitive.
+
+use alloc::format;
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+use crate::error::HackError;
+
+/// How strictly Hack VM code is parsed.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum Mode {
+    /// Only the official VM language is accepted.
+    #[default]
+    Strict,
+    /// Conveniences beyond the official VM language are accepted.
+    Permissive,
+}
+
+impl Mode {
+    /// The name of the `strict` mode.
+    const STRICT: &str = "strict";
+    /// The name of the `permissive` mode.
+    const PERMISSIVE: &str = "permissive";
+}
+
+impl FromStr for Mode {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::STRICT => Ok(Self::Strict),
+            Self::PERMISSIVE => Ok(Self::Permissive),
+            other => Err(HackError::FromStrError(format!(
+                "\"{other}\" is not a recognized mode, expected \"{}\" or \
+                \"{}\"",
+                Self::STRICT,
+                Self::PERMISSIVE
+            ))),
+        }
+    }
+}
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Strict => write!(f, "{}", Self::STRICT),
+            Self::Permissive => write!(f, "{}", Self::PERMISSIVE),
+        }
+    }
+}
@@ -0,0 +1,1501 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Parser Module
+//!
+//! Parses Hack VM commands. Based on the nand2tetris course.
+
+use alloc::borrow::ToOwned as _;
+use alloc::format;
+use alloc::string::{String, ToString as _};
+use alloc::vec::{IntoIter, Vec};
+use core::fmt::{self, Display};
+use core::iter::Enumerate;
+use core::num;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::ffi::OsStr;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+use crate::error::HackError;
+use crate::indices::PointerIndex;
+use crate::limits::Limits;
+use crate::macros::Macros;
+use crate::mode::Mode;
+#[cfg(feature = "std")]
+use crate::paths;
+use crate::provenance::Provenance;
+use crate::segments::Segments;
+use crate::trace::{self, Span};
+use crate::{assembly, grammar, lexer};
+
+/// The canonical paths of the files being included, outermost first, so that
+/// a file including itself is found.
+#[cfg(feature = "std")]
+type Including = Vec<PathBuf>;
+
+/// Nothing, as files can only be included from disk with the `std` feature.
+#[cfg(not(feature = "std"))]
+type Including = ();
+
+/// Reads the contents of a file and deserializes them.
+///
+/// The [`Parser`] is used to read the contents of a file line by
+/// line, parsing the text into [`Instruction`]s that can be translated into
+/// assembly.
+///
+/// Files saved by Windows editors, which may start with a byte order mark and
+/// end their lines with `\r\n`, are read like any other:
+///
+/// ```
+/// use hack_vm_syntax::error::HackError;
+/// use hack_vm_syntax::mode::Mode;
+/// use hack_vm_syntax::parser::Parser;
+///
+/// let windows: Parser =
+///     Parser::from("\u{feff}push constant 7\r\nneg\r\n".to_owned());
+/// let unix: Parser = Parser::from("push constant 7\nneg\n".to_owned());
+/// assert_eq!(windows.contents(), unix.contents());
+/// assert_eq!(
+///     windows.parse_numbered(Mode::Strict)?,
+///     unix.parse_numbered(Mode::Strict)?
+/// );
+/// # Ok::<(), HackError>(())
+/// ```
+#[derive(Debug, Clone, Hash)]
+pub struct Parser {
+    /// The contents of the file as a String.
+    file: String,
+    /// The path the file was read from, if it was read from disk, which
+    /// files it includes are found relative to.
+    #[cfg(feature = "std")]
+    path: Option<PathBuf>,
+    /// The path the file was read from, as it is displayed, if it was read
+    /// from disk.
+    name: Option<String>,
+    /// The limits on the file and those it includes.
+    limits: Limits,
+}
+
+impl Parser {
+    /// Reads the file at `path` within `limits`, which also apply to the
+    /// files it includes.
+    ///
+    /// A byte order mark at the start of the file is removed, and its lines
+    /// are made to end with `\n`, whether they ended with `\r\n` or `\r`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or exceeds a limit.
+    #[cfg(feature = "std")]
+    pub fn read(path: &Path, limits: Limits) -> Result<Self, HackError> {
+        Ok(Self {
+            file: Self::normalize_text(limits.read_file(path)?),
+            path: Some(path.to_path_buf()),
+            name: Some(paths::display(path).to_string()),
+            limits,
+        })
+    }
+
+    /// Uses `file`, named `name`, as the contents of a file that was already
+    /// read, such as from standard input or an archive, checking that it is
+    /// within `limits`. Its byte order mark is removed and its line endings
+    /// normalized, as with [`Parser::read`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::LimitExceeded`] if it exceeds a limit.
+    pub fn limited(
+        file: String,
+        name: &str,
+        limits: Limits,
+    ) -> Result<Self, HackError> {
+        limits.check_size(name, file.len())?;
+        limits.check_lines(name, &file)?;
+        Ok(Self {
+            file: Self::normalize_text(file),
+            #[cfg(feature = "std")]
+            path: None,
+            name: None,
+            limits,
+        })
+    }
+
+    /// The byte order mark some editors, mostly on Windows, start files with.
+    const BYTE_ORDER_MARK: char = '\u{feff}';
+
+    /// Removes the byte order mark from the start of `file`, if it has one,
+    /// and ends its lines with `\n` rather than `\r\n` or `\r`.
+    fn normalize_text(file: String) -> String {
+        let text: &str =
+            file.strip_prefix(Self::BYTE_ORDER_MARK).unwrap_or(&file);
+        if text.len() == file.len() && !text.contains('\r') {
+            return file;
+        }
+        text.replace("\r\n", "\n").replace('\r', "\n")
+    }
+
+    /// The comment marking a line of inline assembly.
+    pub(crate) const ASM_LINE: &str = "//!asm";
+    /// The directive including another file, as in `.include "Util.vm"`.
+    const INCLUDE: &str = ".include";
+
+    /// Borrows the unparsed contents of the file.
+    pub fn contents(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns a more workable form of the file contents.
+    ///
+    /// Returns an [`Iterator`] over the lines of a the held file contents,
+    /// trimmed, filtered for comments, and split by whitespace as vectors of
+    /// string slices.
+    pub fn lines(&self) -> impl Iterator<Item = Vec<&str>> {
+        self.numbered_lines()
+            .map(|(_, parts): (usize, Vec<&str>)| parts)
+    }
+
+    /// Like [`Parser::lines`], but pairs each line with its line number in
+    /// the file, counting from 1.
+    ///
+    /// Comments at the end of a line are removed as well. Comments starting
+    /// with `//!asm` are kept, as they hold inline assembly; see
+    /// [`crate::assembly`].
+    pub fn numbered_lines(&self) -> impl Iterator<Item = (usize, Vec<&str>)> {
+        self.file.lines().zip(1_usize..).filter_map(
+            |(line, line_number): (&str, usize)| {
+                lexer::words(line).map(|words: Vec<&str>| (line_number, words))
+            },
+        )
+    }
+
+    /// Deserializes a single line, split by whitespace, into an
+    /// [`Instruction`]; see [`crate::grammar`].
+    fn instruction(parts: &[&str]) -> Result<Instruction, HackError> {
+        grammar::instruction(&lexer::tokens(parts))
+    }
+
+    /// Deserializes a single line, split by whitespace, into the
+    /// [`Instruction`]s it stands for in the given [`Mode`].
+    ///
+    /// This is a single [`Instruction`], unless the line uses a convenience
+    /// that is desugared into several.
+    fn instructions(
+        parts: &[&str],
+        mode: Mode,
+    ) -> Result<Vec<Instruction>, HackError> {
+        let normalized: Vec<String> = match mode {
+            Mode::Strict => Vec::new(),
+            Mode::Permissive => Self::normalized(parts),
+        };
+        let parts: Vec<&str> = match mode {
+            Mode::Strict => parts.to_vec(),
+            Mode::Permissive => normalized.iter().map(String::as_str).collect(),
+        };
+        if parts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let [command @ "push", segment @ "constant", constant] = parts[..]
+        else {
+            return Ok(alloc::vec![Self::instruction(&parts)?]);
+        };
+        let Some(magnitude) = constant.strip_prefix('-') else {
+            return Ok(alloc::vec![Self::instruction(&parts)?]);
+        };
+        match mode {
+            Mode::Strict => Err(HackError::IllegalInstruction(format!(
+                "\"{}\" pushes a negative constant, which is only accepted \
+                with \"--mode permissive\"",
+                parts.join(" ")
+            ))),
+            Mode::Permissive => Ok(alloc::vec![
+                Self::instruction(&[command, segment, magnitude])?,
+                Instruction::from(Arithmetic::Negative),
+            ]),
+        }
+    }
+
+    /// Rewrites a line, split by whitespace, in the official VM language,
+    /// undoing the conveniences [`Mode::Permissive`] accepts in how it is
+    /// written.
+    ///
+    /// Comments starting with `#` or `;` are removed, and the command and the
+    /// name of a segment of the VM language are lowercased, as in `PUSH
+    /// Constant 5`. The names of functions, labels, and declared segments
+    /// keep their case.
+    fn normalized(parts: &[&str]) -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::with_capacity(parts.len());
+        for part in parts {
+            let code: &str = part.split(['#', ';']).next().unwrap_or_default();
+            if !code.is_empty() {
+                normalized.push(code.to_owned());
+            }
+            if code.len() < part.len() {
+                break;
+            }
+        }
+        if let Some(command) = normalized.first_mut() {
+            command.make_ascii_lowercase();
+        }
+        if let [ref command, ref mut segment, ..] = normalized[..]
+            && matches!(
+                command.as_str(),
+                StackManipulation::PUSH | StackManipulation::POP
+            )
+            && Segments::BUILT_IN
+                .contains(&segment.to_ascii_lowercase().as_str())
+        {
+            segment.make_ascii_lowercase();
+        }
+        normalized
+    }
+
+    /// Deserializes a block of inline assembly, whose opening line has
+    /// already been consumed from `lines`, up to and including its closing
+    /// line.
+    fn assembly_block<'file, L: Iterator<Item = (usize, Vec<&'file str>)>>(
+        lines: &mut L,
+        line_number: usize,
+    ) -> Result<Instruction, HackError> {
+        let mut block: Vec<String> = Vec::new();
+        loop {
+            let Some((_, parts)) = lines.next() else {
+                return Err(HackError::IllegalInstruction(format!(
+                    "the \"asm {{\" block on line {line_number} is never \
+                    closed with \"}}\""
+                )));
+            };
+            if parts[..] == ["}"] {
+                return Ok(Instruction::Assembly(block));
+            }
+            block.push(assembly::instruction(&parts)?);
+        }
+    }
+
+    /// Deserializes a single line into [`Instruction`]s, as with
+    /// [`Parser::instructions`], pushing each onto `instructions` paired
+    /// with its [`Provenance`].
+    fn push_line(
+        instructions: &mut Vec<(Provenance, Instruction)>,
+        provenance: &Provenance,
+        parts: &[&str],
+        mode: Mode,
+    ) -> Result<(), HackError> {
+        instructions.extend(
+            Self::instructions(parts, mode)?.into_iter().map(
+                |instruction: Instruction| (provenance.clone(), instruction),
+            ),
+        );
+        Ok(())
+    }
+
+    /// The canonical paths of the files being included, starting from this
+    /// one, to detect a file including itself.
+    #[cfg(feature = "std")]
+    fn including(&self) -> Including {
+        self.path
+            .iter()
+            .map(|path: &PathBuf| {
+                paths::canonicalize(path).unwrap_or_else(|_| path.clone())
+            })
+            .collect()
+    }
+
+    /// Nothing, as files can only be included from disk with the `std`
+    /// feature.
+    #[cfg(not(feature = "std"))]
+    #[expect(
+        clippy::unused_self,
+        reason = "matches the signature with the `std` feature"
+    )]
+    const fn including(&self) -> Including {}
+
+    /// Gets the name, in quotes, of the file included by the words after
+    /// [`Parser::INCLUDE`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not quoted.
+    fn included_name(name: &[&str]) -> Result<String, HackError> {
+        let name: String = name.join(" ");
+        name.strip_prefix('"')
+            .and_then(|name: &str| name.strip_suffix('"'))
+            .map(str::to_owned)
+            .ok_or_else(|| {
+                HackError::IllegalInstruction(format!(
+                    "\"{} {name}\" must name the file in quotes, as in \
+                    `{} \"Util.vm\"`",
+                    Self::INCLUDE,
+                    Self::INCLUDE
+                ))
+            })
+    }
+
+    /// The error for including the file `name` into one that was not read
+    /// from disk.
+    fn not_from_disk(name: &str) -> HackError {
+        HackError::IllegalInstruction(format!(
+            "\"{}\" can only be used in files read from disk, so that \
+            \"{name}\" can be found next to them",
+            Self::INCLUDE
+        ))
+    }
+
+    /// Reads the file named, in quotes, by the words after
+    /// [`Parser::INCLUDE`], relative to this one, and passes it to `then`
+    /// while its canonical path is on top of `including`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not quoted, this file was not read
+    /// from disk, the file cannot be read, or it is already being included.
+    /// Any error from `then` is propagated.
+    #[cfg(feature = "std")]
+    fn include<F: FnOnce(&Self, &mut Including) -> Result<(), HackError>>(
+        &self,
+        name: &[&str],
+        including: &mut Including,
+        then: F,
+    ) -> Result<(), HackError> {
+        let name: String = Self::included_name(name)?;
+        let Some(directory) = self.path.as_deref().and_then(Path::parent)
+        else {
+            return Err(Self::not_from_disk(&name));
+        };
+        let path: PathBuf = paths::join(directory, &name);
+        let path: PathBuf = paths::canonicalize(&path).map_err(|error| {
+            HackError::CannotReadFileFromPath(format!(
+                "could not include \"{}\": {error}",
+                paths::display(&path)
+            ))
+        })?;
+        if including.contains(&path) {
+            let cycle: Vec<String> = including
+                .iter()
+                .chain([&path])
+                .map(|path: &PathBuf| paths::display(path).to_string())
+                .collect();
+            return Err(HackError::IllegalInstruction(format!(
+                "files include each other in a cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        let included: Self = Self::read(&path, self.limits)?;
+        including.push(path);
+        then(&included, including)?;
+        let _included: Option<PathBuf> = including.pop();
+        Ok(())
+    }
+
+    /// Rejects the file named, in quotes, by the words after
+    /// [`Parser::INCLUDE`], as files can only be read from disk with the
+    /// `std` feature.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error, as to why the file cannot be included.
+    #[cfg(not(feature = "std"))]
+    #[expect(
+        clippy::unused_self,
+        reason = "matches the signature with the `std` feature"
+    )]
+    fn include<F: FnOnce(&Self, &mut Including) -> Result<(), HackError>>(
+        &self,
+        name: &[&str],
+        _including: &mut Including,
+        _then: F,
+    ) -> Result<(), HackError> {
+        Err(Self::not_from_disk(&Self::included_name(name)?))
+    }
+
+    /// Gets the contents of the file followed by those of every file it
+    /// includes, directly or not, which together determine its translation.
+    ///
+    /// # Errors
+    ///
+    /// Any error from including a file is propagated.
+    pub fn contents_with_includes(&self) -> Result<String, HackError> {
+        let mut contents: String = String::new();
+        self.push_contents(&mut contents, &mut self.including())?;
+        Ok(contents)
+    }
+
+    /// Pushes the contents of the file, followed by those of every file it
+    /// includes, onto `contents`.
+    fn push_contents(
+        &self,
+        contents: &mut String,
+        including: &mut Including,
+    ) -> Result<(), HackError> {
+        contents.push_str(&self.file);
+        for parts in self.lines() {
+            if let [Self::INCLUDE, ref name @ ..] = parts[..] {
+                self.include(
+                    name,
+                    including,
+                    |included: &Self, including: &mut Including| {
+                        included.push_contents(contents, including)
+                    },
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deserializes the file contents into [`Instruction`]s, as accepted in
+    /// the given [`Mode`], each paired with the [`Provenance`] of the line it
+    /// starts on.
+    ///
+    /// Macros are expanded as they are used; see [`crate::macros`]. Files
+    /// are included where they are named, as though their instructions were
+    /// all on that line of this file.
+    fn located_instructions(
+        &self,
+        mode: Mode,
+    ) -> Result<Vec<(Provenance, Instruction)>, HackError> {
+        let mut instructions: Vec<(Provenance, Instruction)> = Vec::new();
+        self.push_instructions(
+            &mut instructions,
+            mode,
+            &mut Macros::default(),
+            &mut self.including(),
+            None,
+        )?;
+        self.limits.check_instructions(
+            self.name.as_deref().unwrap_or("<stdin>"),
+            instructions.len(),
+        )?;
+        Ok(instructions)
+    }
+
+    /// Deserializes the file contents as with
+    /// [`Parser::located_instructions`], pushing them onto `instructions`.
+    ///
+    /// The file may use the `macros` defined before it, and is being included
+    /// by the files in `including`, on line `included_on` of the first.
+    fn push_instructions(
+        &self,
+        instructions: &mut Vec<(Provenance, Instruction)>,
+        mode: Mode,
+        macros: &mut Macros,
+        including: &mut Including,
+        included_on: Option<usize>,
+    ) -> Result<(), HackError> {
+        let columns: Vec<usize> =
+            self.file.lines().map(lexer::column).collect();
+        let mut lines = self.numbered_lines();
+        while let Some((own_line_number, parts)) = lines.next() {
+            let line_number: usize = included_on.unwrap_or(own_line_number);
+            let provenance: Provenance = Provenance::new(
+                self.name.clone(),
+                own_line_number,
+                columns
+                    .get(own_line_number.saturating_sub(1))
+                    .copied()
+                    .unwrap_or(1),
+            )
+            .included_on(line_number);
+            let locate = |error: HackError| {
+                error.at(|| {
+                    self.name.as_deref().map_or_else(
+                        || format!("line {own_line_number}"),
+                        |name: &str| format!("{name}:{own_line_number}"),
+                    )
+                })
+            };
+            if let Some(expanded) = macros.expand(&parts)? {
+                for words in expanded {
+                    let parts: Vec<&str> =
+                        words.iter().map(String::as_str).collect();
+                    Self::push_line(instructions, &provenance, &parts, mode)
+                        .map_err(locate)?;
+                }
+                continue;
+            }
+            match parts[..] {
+                [Macros::DEFINE, ref signature @ ..] => {
+                    macros.define(signature, &mut lines, line_number)?;
+                }
+                ["asm", "{"] => instructions.push((
+                    provenance,
+                    Self::assembly_block(&mut lines, line_number)?,
+                )),
+                [Self::ASM_LINE, ref rest @ ..] => instructions.push((
+                    provenance,
+                    Instruction::Assembly(alloc::vec![assembly::instruction(
+                        rest
+                    )?]),
+                )),
+                [Self::INCLUDE, ref name @ ..] => self.include(
+                    name,
+                    including,
+                    |included: &Self, including: &mut Including| {
+                        included.push_instructions(
+                            instructions,
+                            mode,
+                            macros,
+                            including,
+                            Some(line_number),
+                        )
+                    },
+                )?,
+                _ => Self::push_line(instructions, &provenance, &parts, mode)
+                    .map_err(locate)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Deserializes the file contents into [`Instruction`]s, as accepted in
+    /// the given [`Mode`].
+    pub fn to_internal_types(
+        &self,
+        mode: Mode,
+    ) -> Result<Enumerate<IntoIter<Instruction>>, HackError> {
+        let _span: Span = trace::span!(Debug, "parse");
+        let iterator: Vec<Instruction> = self
+            .located_instructions(mode)?
+            .into_iter()
+            .map(|(_, instruction): (Provenance, Instruction)| instruction)
+            .collect();
+        trace::event!(Debug, "parsed {} instructions", iterator.len());
+        Ok(iterator.into_iter().enumerate())
+    }
+
+    /// Deserializes the file contents into [`Instruction`]s, as accepted in
+    /// the given [`Mode`], returning an iterator over tuples for each line
+    /// with an associated index and the [`Instruction`] received from it.
+    pub fn parse(
+        &self,
+        mode: Mode,
+    ) -> Result<Enumerate<IntoIter<Instruction>>, HackError> {
+        self.to_internal_types(mode)
+    }
+
+    /// Deserializes the file contents into [`Instruction`]s, as accepted in
+    /// the given [`Mode`], each paired with the number of the line it is on,
+    /// counting from 1.
+    pub fn parse_numbered(
+        &self,
+        mode: Mode,
+    ) -> Result<Vec<(usize, Instruction)>, HackError> {
+        Ok(self
+            .located_instructions(mode)?
+            .into_iter()
+            .map(|(provenance, instruction): (Provenance, Instruction)| {
+                (provenance.entry_line(), instruction)
+            })
+            .collect())
+    }
+
+    /// Deserializes the file contents into [`Instruction`]s, as accepted in
+    /// the given [`Mode`], each paired with the [`Provenance`] of where it
+    /// was written.
+    ///
+    /// ```
+    /// use hack_vm_syntax::error::HackError;
+    /// use hack_vm_syntax::mode::Mode;
+    /// use hack_vm_syntax::parser::Parser;
+    ///
+    /// let parser: Parser =
+    ///     Parser::from("// Pushes 7.\n    push constant 7\n".to_owned());
+    /// let located = parser.parse_located(Mode::Strict)?;
+    /// assert_eq!(located[0].0.to_string(), "<stdin>:2:5");
+    /// # Ok::<(), HackError>(())
+    /// ```
+    pub fn parse_located(
+        &self,
+        mode: Mode,
+    ) -> Result<Vec<(Provenance, Instruction)>, HackError> {
+        self.located_instructions(mode)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<&OsStr> for Parser {
+    type Error = HackError;
+
+    /// Tries to read the contents of a file located at the path indicated by
+    /// `value`.
+    fn try_from(value: &OsStr) -> Result<Self, Self::Error> {
+        Self::read(Path::new(value), Limits::default())
+    }
+}
+
+impl From<String> for Parser {
+    /// Uses `value` as the contents of a file that was already read, such as
+    /// from an archive, without its byte order mark and with its line endings
+    /// normalized.
+    fn from(value: String) -> Self {
+        Self {
+            file: Self::normalize_text(value),
+            #[cfg(feature = "std")]
+            path: None,
+            name: None,
+            limits: Limits::default(),
+        }
+    }
+}
+
+/// A representation of a valid Hack VM instruction.
+///
+/// [`Instruction::StackManipulation`] can contain [`StackManipulation::Push`]
+/// and [`StackManipulation::Pop`].
+///
+/// [`Instruction::Branching`] can contain [`Branching::Label`],
+/// [`Branching::GoTo`], and [`Branching::IfGoTo`].
+///
+/// [`Instruction::Functional`] can contain [`Functional::Function`],
+/// [`Functional::Call`], and [`Functional::Return`].
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Instruction {
+    /// A discriminant for stack manipulating instructions.
+    StackManipulation(StackManipulation),
+    /// A discriminant for branching instructions.
+    Branching(Branching),
+    /// A discriminant for functional instructions.
+    Functional(Functional),
+    /// A discriminant for arithmetic and logical instructions.
+    Arithmetic(Arithmetic),
+    /// Hack assembly written inline, one instruction per line; see
+    /// [`crate::assembly`].
+    Assembly(Vec<String>),
+}
+
+impl FromStr for Instruction {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let both: (
+            Result<Arithmetic, HackError>,
+            Result<Functional, HackError>,
+        ) = (Arithmetic::from_str(s), Functional::from_str(s));
+
+        match both {
+            (Ok(arithmetic), Err(_)) => Ok(Self::from(arithmetic)),
+            (Err(_), Ok(return_command)) => Ok(Self::from(return_command)),
+            (Err(_), Err(_)) => {
+                Err(HackError::UnrecognizedInstruction(s.to_owned()))
+            }
+            (Ok(_), Ok(_)) => Err(HackError::Internal),
+        }
+    }
+}
+
+impl TryFrom<&(&str, Symbol)> for Instruction {
+    type Error = HackError;
+
+    fn try_from(value: &(&str, Symbol)) -> Result<Self, Self::Error> {
+        match Branching::try_from(value) {
+            Ok(branching) => Ok(Self::from(branching)),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl TryFrom<&(&str, Symbol, Constant)> for Instruction {
+    type Error = HackError;
+
+    fn try_from(value: &(&str, Symbol, Constant)) -> Result<Self, Self::Error> {
+        let both: (
+            Result<StackManipulation, HackError>,
+            Result<Functional, HackError>,
+        ) = (
+            StackManipulation::try_from(value),
+            Functional::try_from(value),
+        );
+
+        match both {
+            (Ok(stack_manipulation), Err(_)) => {
+                Ok(Self::from(stack_manipulation))
+            }
+            (Err(_), Ok(functional)) => Ok(Self::from(functional)),
+            (Err(error @ HackError::PointerIndex { .. }), Err(_)) => Err(error),
+            (Err(_), Err(_)) => Err(HackError::UnrecognizedInstruction(
+                format!("{} {} {}", value.0, value.1, value.2),
+            )),
+            (Ok(_), Ok(_)) => Err(HackError::Internal),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::StackManipulation(ref stack_manipulation) => {
+                write!(f, "{stack_manipulation}")
+            }
+            Self::Branching(ref branching) => write!(f, "{branching}"),
+            Self::Functional(ref functional) => write!(f, "{functional}"),
+            Self::Arithmetic(arithmetic) => write!(f, "{arithmetic}"),
+            Self::Assembly(ref lines) => {
+                writeln!(f, "asm {{")?;
+                for line in lines {
+                    writeln!(f, "    {line}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl From<StackManipulation> for Instruction {
+    fn from(value: StackManipulation) -> Self {
+        Self::StackManipulation(value)
+    }
+}
+
+impl From<Branching> for Instruction {
+    fn from(value: Branching) -> Self {
+        Self::Branching(value)
+    }
+}
+
+impl From<Functional> for Instruction {
+    fn from(value: Functional) -> Self {
+        Self::Functional(value)
+    }
+}
+
+impl From<Arithmetic> for Instruction {
+    fn from(value: Arithmetic) -> Self {
+        Self::Arithmetic(value)
+    }
+}
+
+impl Instruction {
+    /// Writes `instructions` as Hack VM code, one instruction per line; see
+    /// [`VmSource`].
+    pub fn to_vm_source(instructions: &[Self]) -> String {
+        VmSource::new(instructions).to_string()
+    }
+}
+
+/// A sequence of [`Instruction`]s, displayed as the Hack VM code that parses
+/// back into them.
+///
+/// Each instruction is written on its own line in the official VM language,
+/// and inline assembly as an `asm` block, so parsing the code in either
+/// [`Mode`] gives back the same instructions. This holds for any instructions
+/// that were parsed, whatever conveniences, macros, and included files they
+/// came from: parsing, printing, and parsing again is the identity, and
+/// printing what was parsed from printed code gives the same code.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct VmSource<'instructions> {
+    /// The instructions, in order.
+    instructions: &'instructions [Instruction],
+}
+
+impl<'instructions> VmSource<'instructions> {
+    /// Displays `instructions` as Hack VM code.
+    pub const fn new(instructions: &'instructions [Instruction]) -> Self {
+        Self { instructions }
+    }
+}
+
+impl Display for VmSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for instruction in self.instructions {
+            writeln!(f, "{instruction}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A valid symbol.
+///
+/// See [`Symbol::is_allowed_symbol`] for the criteria.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Symbol {
+    /// The actual String containing the value of this [`Symbol`].
+    literal_representation: String,
+}
+
+impl Symbol {
+    /// Borrows a [`str`] slice containing the value of this [`Symbol`].
+    pub fn literal_representation(&self) -> &str {
+        &self.literal_representation
+    }
+
+    /// Determine if a character may appear in a symbol: a letter (a-z ||
+    /// A-Z), digit (0-9), underscore (_), dot (.), dollar sign ($), or colon
+    /// (:).
+    const fn is_allowed_character(character: char) -> bool {
+        character.is_ascii_alphanumeric()
+            || matches!(character, '_' | ':' | '.' | '$')
+    }
+
+    /// Determine if a given string is a valid symbol.
+    ///
+    /// A symbol must be a sequence of letters (a-z || A-Z), digits (0-9),
+    /// underscores (_), dots (.), dollar signs ($), and/or colons (:) that does
+    /// not begin with a digit.
+    pub fn is_allowed_symbol(string: &str) -> bool {
+        !string.is_empty()
+            && string.chars().all(Self::is_allowed_character)
+            && !string
+                .starts_with(|character: char| char::is_ascii_digit(&character))
+    }
+
+    /// Creates a [`Symbol`] from an arbitrary string, such as a file name,
+    /// which may not be a valid symbol.
+    ///
+    /// Each forbidden character is replaced with an underscore, and an
+    /// underscore is prepended if the string is empty or begins with a digit.
+    /// Valid symbols are left as they are.
+    pub fn sanitized(string: &str) -> Self {
+        let mut literal_representation: String = string
+            .chars()
+            .map(|character: char| {
+                if Self::is_allowed_character(character) {
+                    character
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        if !Self::is_allowed_symbol(&literal_representation) {
+            literal_representation.insert(0, '_');
+        }
+        Self {
+            literal_representation,
+        }
+    }
+}
+
+impl FromStr for Symbol {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if Self::is_allowed_symbol(s) {
+            Ok(Self {
+                literal_representation: s.to_owned(),
+            })
+        } else {
+            Err(HackError::SymbolHasForbiddenCharacter)
+        }
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.literal_representation)
+    }
+}
+
+/// A valid constant.
+///
+/// See [`Constant::MAX_VALID_CONSTANT`] for the upper limit.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Constant {
+    /// The actual [`u16`] storing the value of this [`Constant`].
+    literal_representation: u16,
+}
+
+impl Constant {
+    /// The highest valid constant in the Hack computer.
+    pub const MAX_VALID_CONSTANT: u16 = 0x7FFF;
+
+    /// Gets a [`u16`] representing the value of this [`Constant`].
+    pub const fn literal_representation(self) -> u16 {
+        self.literal_representation
+    }
+}
+
+impl TryFrom<u16> for Constant {
+    type Error = HackError;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        if value <= Self::MAX_VALID_CONSTANT {
+            Ok(Self {
+                literal_representation: value,
+            })
+        } else {
+            Err(HackError::Overflow)
+        }
+    }
+}
+
+impl FromStr for Constant {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let together: (&str, Result<u16, num::ParseIntError>) =
+            (s, s.parse::<u16>());
+
+        match together {
+            (_, Ok(value)) => Self::try_from(value),
+            (_, Err(error)) => Err(HackError::FromStrError(format!(
+                "invalid constant: \"{s}\" for reason: {error}"
+            ))),
+        }
+    }
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.literal_representation)
+    }
+}
+
+/// Stack manipulation instructions.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum StackManipulation {
+    /// Push a value on to the stack.
+    Push {
+        /// Where to get the value from.
+        symbol: Symbol,
+        /// The index.
+        value: Constant,
+    },
+    /// Pop a value off of the stack.
+    Pop {
+        /// Where to store the value.
+        symbol: Symbol,
+        /// The index.
+        value: Constant,
+    },
+}
+
+impl StackManipulation {
+    /// The string representation of a push command base.
+    pub(crate) const PUSH: &str = "push";
+    /// The string representation of a pop command base.
+    pub(crate) const POP: &str = "pop";
+
+    /// Get the string representation of the base command of this
+    /// [`StackManipulation`] instruction.
+    pub const fn name(&self) -> &'static str {
+        match *self {
+            Self::Push { .. } => Self::PUSH,
+            Self::Pop { .. } => Self::POP,
+        }
+    }
+}
+
+impl TryFrom<&(&str, Symbol, Constant)> for StackManipulation {
+    type Error = HackError;
+
+    fn try_from(value: &(&str, Symbol, Constant)) -> Result<Self, Self::Error> {
+        // The only segment whose indices do not depend on the target or
+        // the project, so they are checked as soon as possible.
+        if matches!(value.0, Self::PUSH | Self::POP)
+            && value.1.literal_representation() == "pointer"
+        {
+            let _index: PointerIndex = PointerIndex::new(value.2)?;
+        }
+        match *value {
+            (Self::PUSH, ref symbol, value) => Ok(Self::Push {
+                symbol: symbol.clone(),
+                value,
+            }),
+            (Self::POP, ref symbol, value) => Ok(Self::Pop {
+                symbol: symbol.clone(),
+                value,
+            }),
+            (command, ref symbol, value) => {
+                Err(HackError::FromStrError(format!(
+                    "invalid stack manipulation operation: \
+                    \"{command} {symbol} {value}\""
+                )))
+            }
+        }
+    }
+}
+
+impl Display for StackManipulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Pop { ref symbol, value }
+            | Self::Push { ref symbol, value } => {
+                write!(f, "{} {} {}", self.name(), symbol, value)
+            }
+        }
+    }
+}
+
+/// Branching instructions.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Branching {
+    /// Marks a position in the current function that can be jumped to.
+    Label {
+        /// The name of the label.
+        symbol: Symbol,
+    },
+
+    /// Jumps to a label unconditionally.
+    GoTo {
+        /// The name of the label jumped to.
+        symbol: Symbol,
+    },
+
+    /// Pops the topmost value off the stack, and jumps to a label if it is
+    /// not zero.
+    IfGoTo {
+        /// The name of the label jumped to.
+        symbol: Symbol,
+    },
+}
+
+impl Branching {
+    /// The string representation of a label command base.
+    pub const LABEL: &str = "label";
+    /// The string representation of a goto command base.
+    const GO_TO: &str = "goto";
+    /// The string representation of an if-goto command base.
+    const IF_GO_TO: &str = "if-goto";
+    /// The string representations of every branching command base.
+    pub const COMMANDS: [&str; 3] = [Self::LABEL, Self::GO_TO, Self::IF_GO_TO];
+
+    /// Get the string representation of the base command of this [`Branching`]
+    /// instruction.
+    pub const fn name(&self) -> &'static str {
+        match *self {
+            Self::Label { .. } => Self::LABEL,
+            Self::GoTo { .. } => Self::GO_TO,
+            Self::IfGoTo { .. } => Self::IF_GO_TO,
+        }
+    }
+}
+
+impl TryFrom<&(&str, Symbol)> for Branching {
+    type Error = HackError;
+
+    fn try_from(value: &(&str, Symbol)) -> Result<Self, Self::Error> {
+        match *value {
+            (Self::LABEL, ref symbol) => Ok(Self::Label {
+                symbol: symbol.clone(),
+            }),
+            (Self::GO_TO, ref symbol) => Ok(Self::GoTo {
+                symbol: symbol.clone(),
+            }),
+            (Self::IF_GO_TO, ref symbol) => Ok(Self::IfGoTo {
+                symbol: symbol.clone(),
+            }),
+            (command, ref symbol) => Err(HackError::FromStrError(format!(
+                "invalid branching operation: \"{command} {symbol}\""
+            ))),
+        }
+    }
+}
+
+impl Display for Branching {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::GoTo { ref symbol }
+            | Self::Label { ref symbol }
+            | Self::IfGoTo { ref symbol } => {
+                write!(f, "{} {}", self.name(), symbol)
+            }
+        }
+    }
+}
+
+/// Functional instructions.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Functional {
+    /// Declares a function.
+    Function {
+        /// The name of the function.
+        symbol: Symbol,
+        /// The number of local variables it has.
+        value: Constant,
+    },
+
+    /// Calls a function.
+    Call {
+        /// The name of the function called.
+        symbol: Symbol,
+        /// The number of arguments pushed onto the stack for it.
+        value: Constant,
+    },
+
+    /// Returns from the current function to its caller.
+    Return,
+}
+
+impl Functional {
+    /// The string representation of a function command base.
+    pub(crate) const FUNCTION: &str = "function";
+    /// The string representation of a call command base.
+    pub(crate) const CALL: &str = "call";
+    /// The string representation of a return command.
+    const RETURN: &str = "return";
+
+    /// Get the string representation of the base command of this [`Functional`]
+    /// instruction.
+    pub const fn name(&self) -> &'static str {
+        match *self {
+            Self::Function { .. } => Self::FUNCTION,
+            Self::Call { .. } => Self::CALL,
+            Self::Return => Self::RETURN,
+        }
+    }
+}
+
+impl TryFrom<&(&str, Symbol, Constant)> for Functional {
+    type Error = HackError;
+
+    fn try_from(value: &(&str, Symbol, Constant)) -> Result<Self, Self::Error> {
+        match *value {
+            (Self::CALL, ref symbol, value) => Ok(Self::Call {
+                symbol: symbol.clone(),
+                value,
+            }),
+            (Self::FUNCTION, ref symbol, value) => Ok(Self::Function {
+                symbol: symbol.clone(),
+                value,
+            }),
+            (command, ref symbol, value) => {
+                Err(HackError::FromStrError(format!(
+                    "invalid functional operation: \
+                    \"{command} {symbol} {value}\""
+                )))
+            }
+        }
+    }
+}
+
+impl FromStr for Functional {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, HackError> {
+        match s {
+            Self::RETURN => Ok(Self::Return),
+            _ => Err(HackError::FromStrError(format!(
+                "invalid functional operation: \"{s}\""
+            ))),
+        }
+    }
+}
+
+impl Display for Functional {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Call { ref symbol, value }
+            | Self::Function { ref symbol, value } => {
+                write!(f, "{} {} {}", self.name(), symbol, value)
+            }
+            Self::Return => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+/// Arithmetic and logic instructions.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Arithmetic {
+    /// Pop two values off the stack, add them, and push the sum back.
+    Add,
+    /// Pop two values off the stack, subtract them, and push the difference
+    /// back.
+    Subtract,
+    /// Pop a value off the stack, make it negative, and push the result back.
+    Negative,
+    /// Pop two values off the stack, compare them for equality, and push the
+    /// result back.
+    Equal,
+    /// Pop two values off the stack, do a greater than comparison, and push the
+    /// result back.
+    GreaterThan,
+    /// Pop two values off the stack, do a less than comparison, and push the
+    /// result back.
+    Lessthan,
+    /// Pop two values off the stack, perform a bitwise AND on them, and push
+    /// the result back.
+    And,
+    /// Pop two values off the stack, perform a bitwise OR on them, and push the
+    /// result back.
+    Or,
+    /// Pop a value off the stack, perform a bitwise NOT on it, and push the
+    /// result back.
+    Not,
+    /// Pop two values off the stack, multiply them, and push the product
+    /// back. An extension; see [`crate::extensions`].
+    Multiply,
+    /// Pop two values off the stack, divide them, and push the quotient back.
+    /// An extension; see [`crate::extensions`].
+    Divide,
+    /// Pop two values off the stack, divide them, and push the remainder
+    /// back. An extension; see [`crate::extensions`].
+    Modulo,
+    /// Pop a value off the stack, shift it left by one bit, and push the
+    /// result back. An extension; see [`crate::extensions`].
+    ShiftLeft,
+    /// Pop a value off the stack, shift it right by one bit, and push the
+    /// result back. An extension; see [`crate::extensions`].
+    ShiftRight,
+}
+
+impl Arithmetic {
+    /// The string representation of an add command, and the associated
+    /// operator.
+    const ADD: [&str; 2] = ["add", "+"];
+    /// The string representation of a subtract command, and the associated
+    /// operator.
+    const SUBTRACT: [&str; 2] = ["sub", "-"];
+    /// The string representation of an arithmetic negation command, and the
+    /// associated operator.
+    const NEGATIVE: [&str; 2] = ["neg", "-"];
+    /// The string representation of an equality comparison command, and the
+    /// associated "operator".
+    const EQUAL: [&str; 2] = ["eq", "JEQ"];
+    /// The string representation of a greater than comparison command, and the
+    /// associated "operator".
+    const GREATER_THAN: [&str; 2] = ["gt", "JGT"];
+    /// The string representation of a less than comparison command, and the
+    /// associated "operator".
+    const LESS_THAN: [&str; 2] = ["lt", "JLT"];
+    /// The string representation of a bitwise AND command, and the associated
+    /// operator.
+    const AND: [&str; 2] = ["and", "&"];
+    /// The string representation of a bitwise OR command, and the associated
+    /// operator.
+    const OR: [&str; 2] = ["or", "|"];
+    /// The string representation of a bitwise NOT command, and the associated
+    /// operator.
+    const NOT: [&str; 2] = ["not", "!"];
+    /// The string representation of a multiply command, and the associated
+    /// operator.
+    const MULTIPLY: [&str; 2] = ["mul", "*"];
+    /// The string representation of a divide command, and the associated
+    /// operator.
+    const DIVIDE: [&str; 2] = ["div", "/"];
+    /// The string representation of a modulo command, and the associated
+    /// operator.
+    const MODULO: [&str; 2] = ["mod", "%"];
+    /// The string representation of a shift left command, and the associated
+    /// operator.
+    const SHIFT_LEFT: [&str; 2] = ["shiftleft", "<<"];
+    /// The string representation of a shift right command, and the associated
+    /// operator.
+    const SHIFT_RIGHT: [&str; 2] = ["shiftright", ">>"];
+
+    /// Get the string representation of the base command of this [`Arithmetic`]
+    /// instruction and the associated operator. Note that the "operator" for
+    /// comparisons is the respective assembly jump command, i.e. "JLT" for less
+    /// than.
+    pub const fn identify(self) -> [&'static str; 2] {
+        match self {
+            Self::Add => Self::ADD,
+            Self::Subtract => Self::SUBTRACT,
+            Self::Negative => Self::NEGATIVE,
+            Self::Equal => Self::EQUAL,
+            Self::GreaterThan => Self::GREATER_THAN,
+            Self::Lessthan => Self::LESS_THAN,
+            Self::And => Self::AND,
+            Self::Or => Self::OR,
+            Self::Not => Self::NOT,
+            Self::Multiply => Self::MULTIPLY,
+            Self::Divide => Self::DIVIDE,
+            Self::Modulo => Self::MODULO,
+            Self::ShiftLeft => Self::SHIFT_LEFT,
+            Self::ShiftRight => Self::SHIFT_RIGHT,
+        }
+    }
+
+    /// Whether this is a comparison, which pushes true or false.
+    pub const fn is_comparison(self) -> bool {
+        matches!(self, Self::Equal | Self::GreaterThan | Self::Lessthan)
+    }
+
+    /// Whether this takes one operand rather than two.
+    pub const fn is_unary(self) -> bool {
+        matches!(
+            self,
+            Self::Negative | Self::Not | Self::ShiftLeft | Self::ShiftRight
+        )
+    }
+
+    /// Whether this is an extension, which must be enabled to be translated.
+    pub const fn is_extension(self) -> bool {
+        matches!(
+            self,
+            Self::Multiply
+                | Self::Divide
+                | Self::Modulo
+                | Self::ShiftLeft
+                | Self::ShiftRight
+        )
+    }
+
+    /// Whether the translation of this generates labels.
+    pub const fn generates_labels(self) -> bool {
+        self.is_comparison()
+            || matches!(
+                self,
+                Self::Multiply | Self::Divide | Self::Modulo | Self::ShiftRight
+            )
+    }
+}
+
+impl FromStr for Arithmetic {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, HackError> {
+        match s {
+            add if Self::ADD[0] == add => Ok(Self::Add),
+            sub if Self::SUBTRACT[0] == sub => Ok(Self::Subtract),
+            neg if Self::NEGATIVE[0] == neg => Ok(Self::Negative),
+            eq if Self::EQUAL[0] == eq => Ok(Self::Equal),
+            gt if Self::GREATER_THAN[0] == gt => Ok(Self::GreaterThan),
+            lt if Self::LESS_THAN[0] == lt => Ok(Self::Lessthan),
+            and if Self::AND[0] == and => Ok(Self::And),
+            or if Self::OR[0] == or => Ok(Self::Or),
+            not if Self::NOT[0] == not => Ok(Self::Not),
+            mul if Self::MULTIPLY[0] == mul => Ok(Self::Multiply),
+            div if Self::DIVIDE[0] == div => Ok(Self::Divide),
+            rem if Self::MODULO[0] == rem => Ok(Self::Modulo),
+            shl if Self::SHIFT_LEFT[0] == shl => Ok(Self::ShiftLeft),
+            shr if Self::SHIFT_RIGHT[0] == shr => Ok(Self::ShiftRight),
+            _ => Err(HackError::FromStrError(format!(
+                "invalid arithmetic operation: \"{s}\""
+            ))),
+        }
+    }
+}
+
+impl Display for Arithmetic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.identify()[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Checks that files saved by Windows editors are read like any other.
+
+    use alloc::borrow::ToOwned as _;
+    #[cfg(feature = "std")]
+    use alloc::format;
+    #[cfg(feature = "std")]
+    use alloc::string::String;
+    #[cfg(feature = "std")]
+    use alloc::vec::Vec;
+    #[cfg(feature = "std")]
+    use std::path::PathBuf;
+    #[cfg(feature = "std")]
+    use std::{env, fs, process};
+
+    #[cfg(feature = "std")]
+    use super::Instruction;
+    use super::Parser;
+    use crate::limits::Limits;
+    use crate::mode::Mode;
+
+    /// Checks that `windows` is read with the same contents and into the
+    /// same instructions as `unix`, whether it was already read or not.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is not, or either cannot be parsed.
+    fn assert_read_alike(windows: &str, unix: &str) {
+        let expected: Parser = Parser::from(unix.to_owned());
+        for parser in [
+            Parser::from(windows.to_owned()),
+            Parser::limited(windows.to_owned(), "Main.vm", Limits::default())
+                .unwrap(),
+        ] {
+            assert_eq!(parser.contents(), expected.contents());
+            assert_eq!(
+                parser.parse_numbered(Mode::Strict).unwrap(),
+                expected.parse_numbered(Mode::Strict).unwrap()
+            );
+        }
+    }
+
+    /// Checks that the `windows` files are read, with those they include,
+    /// with the same contents and into the same instructions as the `unix`
+    /// files, as in [`read_included`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if they are not, or either cannot be read.
+    #[cfg(feature = "std")]
+    fn assert_included_alike(windows: &[(&str, &str)], unix: &[(&str, &str)]) {
+        assert_eq!(
+            read_included("crlf-include-windows", windows),
+            read_included("crlf-include-unix", unix)
+        );
+    }
+
+    /// Writes `files`, as their names and contents, to a directory named
+    /// after the `test`, and gets the contents of the first with those of
+    /// the files it includes, and its instructions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the files cannot be written, read, or parsed.
+    #[cfg(feature = "std")]
+    fn read_included(
+        test: &str,
+        files: &[(&str, &str)],
+    ) -> (String, Vec<(usize, Instruction)>) {
+        let directory: PathBuf = env::temp_dir()
+            .join(format!("hack-vm-syntax-{test}-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        for &(name, contents) in files {
+            fs::write(directory.join(name), contents).unwrap();
+        }
+        let parser: Parser =
+            Parser::read(&directory.join(files[0].0), Limits::default())
+                .unwrap();
+        let read: (String, Vec<(usize, Instruction)>) = (
+            parser.contents_with_includes().unwrap(),
+            parser.parse_numbered(Mode::Strict).unwrap(),
+        );
+        fs::remove_dir_all(&directory).unwrap();
+        read
+    }
+
+    /// A byte order mark is not part of the first command.
+    #[test]
+    fn byte_order_marks_are_removed() {
+        assert_read_alike(
+            "\u{feff}push constant 7\nneg\n",
+            "push constant 7\nneg\n",
+        );
+    }
+
+    /// Lines may end with `\r\n`, including the last.
+    #[test]
+    fn crlf_line_endings_are_normalized() {
+        assert_read_alike(
+            "push constant 7\r\n// comment\r\nneg\r\n",
+            "push constant 7\n// comment\nneg\n",
+        );
+    }
+
+    /// Lines may end with a lone `\r`, as old Mac editors end them, without
+    /// changing which line each command is on.
+    #[test]
+    fn lone_cr_line_endings_are_normalized() {
+        assert_read_alike(
+            "push constant 7\r\rneg\rpush constant 8",
+            "push constant 7\n\nneg\npush constant 8",
+        );
+    }
+
+    /// A file saved on Windows is included like any other, and may include
+    /// one saved on Windows as well.
+    #[cfg(feature = "std")]
+    #[test]
+    fn crlf_line_endings_are_normalized_in_included_files() {
+        assert_included_alike(
+            &[
+                (
+                    "Main.vm",
+                    "\u{feff}.include \"Util.vm\"\r\npush constant 1\r\n",
+                ),
+                ("Util.vm", "\u{feff}push constant 2\r\nneg\r\nadd\r\n"),
+            ],
+            &[
+                ("Main.vm", ".include \"Util.vm\"\npush constant 1\n"),
+                ("Util.vm", "push constant 2\nneg\nadd\n"),
+            ],
+        );
+    }
+}
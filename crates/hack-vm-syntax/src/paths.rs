@@ -0,0 +1,150 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Paths Module
+//!
+//! Smooths over platform differences in paths.
+//!
+//! On Windows, [`Path::canonicalize`] returns verbatim paths, such as
+//! `\\?\C:\foo` or `\\?\UNC\server\share\foo`. These are unfamiliar to most
+//! people, and behave differently from ordinary paths: `/` is not a separator
+//! in them, and `..` is not resolved. The functions here turn them back into
+//! ordinary paths where that is safe, and handle them correctly where it is
+//! not. On other platforms, they have no effect.
+
+use alloc::format;
+use core::fmt::{self, Display};
+use core::iter::Peekable;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Component, Components, Path, PathBuf, Prefix};
+
+/// The longest ordinary path Windows accepts, which longer paths must be
+/// verbatim to exceed.
+const MAX_PATH: usize = 260;
+
+/// The names of devices on Windows, which cannot be used as the name of a
+/// file in an ordinary path, with or without an extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6",
+    "COM7", "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6",
+    "LPT7", "LPT8", "LPT9",
+];
+
+/// Canonicalizes `path`, preferring an ordinary path to a verbatim one where
+/// both refer to the same file.
+///
+/// # Errors
+///
+/// Any error from [`Path::canonicalize`] is propagated.
+pub fn canonicalize(path: &Path) -> Result<PathBuf, io::Error> {
+    let canonical: PathBuf = path.canonicalize()?;
+    Ok(strip_verbatim(&canonical)
+        .filter(|ordinary: &PathBuf| is_safe(ordinary))
+        .unwrap_or(canonical))
+}
+
+/// Joins `relative`, a path written in a configuration file, onto `base`.
+///
+/// Unlike [`Path::join`], this resolves `.` and `..` and splits on every
+/// separator of the platform, so the result is correct even if `base` is a
+/// verbatim path.
+pub fn join(base: &Path, relative: &str) -> PathBuf {
+    let mut joined: PathBuf = base.to_path_buf();
+    for component in Path::new(relative).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                let _popped: bool = joined.pop();
+            }
+            Component::Prefix(_)
+            | Component::RootDir
+            | Component::Normal(_) => {
+                joined.push(component);
+            }
+        }
+    }
+    joined
+}
+
+/// Displays `path` as an ordinary path, even if it is verbatim, for use in
+/// messages.
+pub const fn display(path: &Path) -> PathDisplay<'_> {
+    PathDisplay { path }
+}
+
+/// Displays a path as an ordinary path. See [`display`].
+#[derive(Debug, Clone, Copy)]
+pub struct PathDisplay<'path> {
+    /// The path being displayed.
+    path: &'path Path,
+}
+
+impl Display for PathDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match strip_verbatim(self.path) {
+            Some(ordinary) => write!(f, "{}", ordinary.display()),
+            None => write!(f, "{}", self.path.display()),
+        }
+    }
+}
+
+/// Rewrites a verbatim path to a disk or network share as the equivalent
+/// ordinary path, if it is one. The result may not refer to the same file;
+/// see [`is_safe`].
+fn strip_verbatim(path: &Path) -> Option<PathBuf> {
+    let mut components: Peekable<Components<'_>> = path.components().peekable();
+    let Some(Component::Prefix(prefix)) = components.next() else {
+        return None;
+    };
+    let mut ordinary: OsString = match prefix.kind() {
+        Prefix::VerbatimDisk(letter) => {
+            OsString::from(format!("{}:", char::from(letter)))
+        }
+        Prefix::VerbatimUNC(server, share) => {
+            let mut unc: OsString = OsString::from(r"\\");
+            unc.push(server);
+            unc.push(r"\");
+            unc.push(share);
+            unc
+        }
+        Prefix::Verbatim(_)
+        | Prefix::DeviceNS(_)
+        | Prefix::UNC(..)
+        | Prefix::Disk(_) => return None,
+    };
+    let _root: Option<Component<'_>> =
+        components.next_if_eq(&Component::RootDir);
+    for component in components {
+        let Component::Normal(name) = component else {
+            return None;
+        };
+        ordinary.push(r"\");
+        ordinary.push(name);
+    }
+    // A bare drive letter is relative to the current directory on that drive.
+    if ordinary.to_string_lossy().ends_with(':') {
+        ordinary.push(r"\");
+    }
+    Some(PathBuf::from(ordinary))
+}
+
+/// Whether an ordinary path produced by [`strip_verbatim`] is equivalent.
+///
+/// That is not the case if it is too long, or if Windows would interpret any
+/// of its components differently.
+fn is_safe(ordinary: &Path) -> bool {
+    ordinary.as_os_str().len() < MAX_PATH
+        && ordinary.components().all(|component: Component<'_>| {
+            let Component::Normal(name) = component else {
+                return true;
+            };
+            let name: &str = &name.to_string_lossy();
+            let stem: &str = name.split('.').next().unwrap_or(name);
+            !name.ends_with(['.', ' '])
+                && !RESERVED_NAMES
+                    .iter()
+                    .any(|reserved: &&str| reserved.eq_ignore_ascii_case(stem))
+        })
+}
@@ -0,0 +1,91 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Provenance Module
+//!
+//! Records where each instruction was written, as a [`Provenance`], so that
+//! everything describing an instruction, from errors to annotated output,
+//! points at the same place.
+//!
+//! An instruction from an included file is located in that file, and an
+//! instruction expanded from a macro is located where the macro was used.
+//! Either way, the line of the file being parsed that it came from is kept
+//! as well, which is what [`crate::parser::Parser::parse_numbered`] pairs it
+//! with.
+
+use alloc::string::String;
+use core::fmt::{self, Display};
+
+/// Where an instruction was written.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Provenance {
+    /// The name of the file it was written in, or [`None`] if it was not
+    /// read from a file.
+    file: Option<String>,
+    /// The line it was written on, counting from 1.
+    line: usize,
+    /// The column its command starts at, counting characters from 1.
+    column: usize,
+    /// The line of the file being parsed that it came from, such as the
+    /// line including the file it was written in.
+    entry_line: usize,
+}
+
+impl Provenance {
+    /// Locates an instruction written at `line` and `column` of `file`, in
+    /// the file being parsed.
+    pub const fn new(file: Option<String>, line: usize, column: usize) -> Self {
+        Self {
+            file,
+            line,
+            column,
+            entry_line: line,
+        }
+    }
+
+    /// Records that the instruction came from `entry_line` of the file being
+    /// parsed, such as when the file it was written in was included there.
+    #[must_use]
+    pub const fn included_on(mut self, entry_line: usize) -> Self {
+        self.entry_line = entry_line;
+        self
+    }
+
+    /// Gets the name of the file the instruction was written in, if it was
+    /// read from a file.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// Gets the line the instruction was written on, counting from 1.
+    pub const fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Gets the column the command of the instruction starts at, counting
+    /// characters from 1.
+    pub const fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Gets the line of the file being parsed that the instruction came
+    /// from, counting from 1.
+    pub const fn entry_line(&self) -> usize {
+        self.entry_line
+    }
+}
+
+impl Display for Provenance {
+    /// Describes the location as `FILE:LINE:COLUMN`, naming contents that
+    /// were not read from a file `<stdin>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.file.as_deref().unwrap_or("<stdin>"),
+            self.line,
+            self.column
+        )
+    }
+}
@@ -0,0 +1,314 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Segments Module
+//!
+//! Declares memory segments beyond those of the VM language, for courses that
+//! extend the Hack platform with custom memory-mapped devices. Each is
+//! declared in the project's manifest with its base address and size:
+//!
+//! ```toml
+//! [segments.leds]
+//! base = 24577
+//! size = 8
+//! ```
+//!
+//! Then `push leds 3` pushes the value at address 24580, and `pop leds 3`
+//! stores into it. Indices are checked against the segment's size.
+//!
+//! For courses running a modified memory map, `temp`, `pointer`, and
+//! `static` can be moved the same way:
+//!
+//! ```toml
+//! [segments.temp]
+//! base = 16
+//! size = 16
+//!
+//! [segments.static]
+//! base = 32
+//! size = 224
+//! ```
+//!
+//! Then `push temp 9` pushes the value at address 25. Moving `pointer`, which
+//! must hold 2 words, also moves the words `this` and `that` are based at,
+//! which are then addressed by number rather than as `THIS` and `THAT`. The
+//! assembler allocates static variables, so moving `static` only changes how
+//! many indices it has and where it is expected to be; the assembler must
+//! allocate from its base for that to hold.
+//!
+//! The stack, which starts at address 256 on every target, can be moved with
+//! a `[stack]` table of the same form; see [`crate::layout`]. `SP` is
+//! initialized to its base, and the stack depths estimated for `--annotate`
+//! are checked against its size.
+//!
+//! The segments of the VM language may not overlap each other, the stack, the
+//! heap, the screen, the keyboard, the words `SP`, `LCL`, and `ARG` at
+//! addresses 0 to 2, or `R13` to `R15`, which the generated code uses; nor
+//! may declared segments overlap any of them. Declared segments may overlap
+//! each other, such as to name parts of a device.
+
+use alloc::borrow::ToOwned as _;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString as _};
+use core::fmt::{self, Display};
+
+use crate::error::HackError;
+use crate::indices::PointerIndex;
+use crate::parser::{Constant, Symbol};
+use crate::target::Target;
+
+/// A segment declared by the user.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct UserSegment {
+    /// The address of its first word.
+    base: u16,
+    /// How many words it holds.
+    size: u16,
+}
+
+impl UserSegment {
+    /// Declares a segment of `size` words starting at `base`.
+    ///
+    /// If it does not fit in memory, why is described instead, to follow the
+    /// segment's name in an error message.
+    pub fn new(base: i64, size: i64) -> Result<Self, String> {
+        let highest: i64 = i64::from(Constant::MAX_VALID_CONSTANT);
+        if !(0..=highest).contains(&base) {
+            return Err(format!(
+                "has base {base}, but addresses must be 0 <= base <= \
+                {highest}"
+            ));
+        }
+        if size < 1 || base.saturating_add(size).saturating_sub(1) > highest {
+            return Err(format!(
+                "has size {size}, but it must hold at least one word and end \
+                at or below address {highest}"
+            ));
+        }
+        Ok(Self {
+            base: u16::try_from(base).map_err(|error| error.to_string())?,
+            size: u16::try_from(size).map_err(|error| error.to_string())?,
+        })
+    }
+
+    /// Declares a segment of `size` words starting at `base`, which are
+    /// known to fit in memory.
+    pub(crate) const fn fixed(base: u16, size: u16) -> Self {
+        Self { base, size }
+    }
+
+    /// Gets the address of the first word of the segment.
+    pub const fn base(self) -> u16 {
+        self.base
+    }
+
+    /// Gets how many words the segment holds.
+    pub const fn size(self) -> u16 {
+        self.size
+    }
+
+    /// Gets the address of the last word of the segment.
+    pub const fn last_address(self) -> u16 {
+        self.base.saturating_add(self.size).saturating_sub(1)
+    }
+
+    /// Gets the address of word `i` of the segment named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HackError::IllegalInstruction`] if `i` is beyond the end
+    /// of the segment.
+    pub fn address(self, name: &Symbol, i: Constant) -> Result<u16, HackError> {
+        let index: u16 = i.literal_representation();
+        if index < self.size {
+            Ok(self.base.saturating_add(index))
+        } else {
+            Err(HackError::IllegalInstruction(format!(
+                "\"{i}\" is not a valid index for {name}, must be 0 <= i <= {}",
+                self.size.saturating_sub(1)
+            )))
+        }
+    }
+
+    /// Whether the segment shares any word with `other`.
+    const fn overlaps(self, other: Self) -> bool {
+        self.base <= other.last_address() && other.base <= self.last_address()
+    }
+}
+
+impl Display for UserSegment {
+    /// Describes the segment by the addresses of its first and last words.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "addresses {} to {}", self.base, self.last_address())
+    }
+}
+
+/// The segments declared by the user, and those of the VM language they
+/// moved, by name.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct Segments {
+    /// Each segment, by name.
+    declared: BTreeMap<String, UserSegment>,
+    /// Each segment of the VM language that was moved, by name.
+    remapped: BTreeMap<String, UserSegment>,
+    /// Where the stack was moved, if it was.
+    stack: Option<UserSegment>,
+}
+
+impl Segments {
+    /// The segments of the VM language, which cannot be redeclared.
+    pub const BUILT_IN: [&str; 8] = [
+        "constant", "local", "argument", "this", "that", "static", "temp",
+        "pointer",
+    ];
+    /// The segments of the VM language which can be moved.
+    pub const REMAPPABLE: [&str; 3] = ["temp", "pointer", "static"];
+    /// The words at the start of memory which hold `SP`, `LCL`, and `ARG`.
+    const STACK_POINTERS: UserSegment = UserSegment::fixed(0, 3);
+    /// The words `R13` to `R15`, which the generated code uses.
+    const GENERAL_REGISTERS: UserSegment = UserSegment::fixed(13, 3);
+
+    /// Declares the segment `name`, or moves it if it is one of
+    /// [`Segments::REMAPPABLE`].
+    ///
+    /// If it cannot be declared, why is described instead, to follow the
+    /// segment's name in an error message.
+    pub fn insert(
+        &mut self,
+        name: &str,
+        segment: UserSegment,
+    ) -> Result<(), String> {
+        if !Symbol::is_allowed_symbol(name) {
+            return Err("is not a valid symbol".to_owned());
+        }
+        if Self::REMAPPABLE.contains(&name) {
+            if name == "pointer" && segment.size != 2 {
+                return Err(format!(
+                    "has size {}, but pointer must hold 2 words, for this and \
+                    that",
+                    segment.size
+                ));
+            }
+            let _previous: Option<UserSegment> =
+                self.remapped.insert(name.to_owned(), segment);
+            return Ok(());
+        }
+        if Self::BUILT_IN.contains(&name) {
+            return Err("is already a segment of the VM language".to_owned());
+        }
+        let _previous: Option<UserSegment> =
+            self.declared.insert(name.to_owned(), segment);
+        Ok(())
+    }
+
+    /// Adds the segments from `other` that are not already declared, such
+    /// that segments in `self` take precedence.
+    pub fn merge(&mut self, other: &Self) {
+        for (name, segment) in &other.declared {
+            let _segment: &mut UserSegment =
+                self.declared.entry(name.clone()).or_insert(*segment);
+        }
+        for (name, segment) in &other.remapped {
+            let _segment: &mut UserSegment =
+                self.remapped.entry(name.clone()).or_insert(*segment);
+        }
+        self.stack = self.stack.or(other.stack);
+    }
+
+    /// Moves the stack to `stack`.
+    pub const fn set_stack(&mut self, stack: UserSegment) {
+        self.stack = Some(stack);
+    }
+
+    /// Gets where the stack is on the `target`.
+    pub fn stack(&self, target: Target) -> UserSegment {
+        self.stack.unwrap_or_else(|| target.stack())
+    }
+
+    /// Gets the segment named `name`, if it was declared.
+    pub fn get(&self, name: &str) -> Option<UserSegment> {
+        self.declared.get(name).copied()
+    }
+
+    /// Gets where the segment of the VM language named `name` was moved, if
+    /// it was.
+    pub fn remapped(&self, name: &str) -> Option<UserSegment> {
+        self.remapped.get(name).copied()
+    }
+
+    /// Gets where the `temp` segment is on the `target`.
+    pub fn temp(&self, target: Target) -> UserSegment {
+        self.remapped("temp").unwrap_or_else(|| target.temp())
+    }
+
+    /// Gets where the `pointer` segment is.
+    pub fn pointer(&self) -> UserSegment {
+        self.remapped("pointer").unwrap_or(PointerIndex::SEGMENT)
+    }
+
+    /// Gets where the `static` segment is on the `target`.
+    pub fn statics(&self, target: Target) -> UserSegment {
+        self.remapped("static")
+            .unwrap_or_else(|| target.layout().statics())
+    }
+
+    /// Checks that no segment of the VM language overlaps another, the stack,
+    /// the words the generated code relies on, or a declared segment, nor
+    /// any of the regions reserved by the [`crate::layout::Layout`] of the
+    /// `target`.
+    ///
+    /// If any do, which is described instead, to follow the word "segments"
+    /// in an error message.
+    pub fn check(&self, target: Target) -> Result<(), String> {
+        let fixed: [(&str, UserSegment); 6] = [
+            ("SP, LCL, and ARG", Self::STACK_POINTERS),
+            ("pointer", self.pointer()),
+            ("temp", self.temp(target)),
+            ("R13 to R15", Self::GENERAL_REGISTERS),
+            ("static", self.statics(target)),
+            ("the stack", self.stack(target)),
+        ];
+        for (index, first) in fixed.iter().enumerate() {
+            let others =
+                fixed.iter().copied().skip(index.saturating_add(1)).chain(
+                    self.declared.iter().map(
+                        |(name, segment): (&String, &UserSegment)| {
+                            (name.as_str(), *segment)
+                        },
+                    ),
+                );
+            let others = others.chain(target.layout().reserved());
+            for second in others {
+                if first.1.overlaps(second.1) {
+                    return Err(format!(
+                        "overlap: {} at {} and {} at {}",
+                        first.0, first.1, second.0, second.1
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Segments {
+    /// Lists the segments, then those moved, as `NAME@BASE+SIZE`, separated
+    /// by commas, followed by the stack as `|stack@BASE+SIZE` if it was
+    /// moved.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (name, segment)) in
+            self.declared.iter().chain(&self.remapped).enumerate()
+        {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{name}@{}+{}", segment.base, segment.size)?;
+        }
+        if let Some(stack) = self.stack {
+            write!(f, "|stack@{}+{}", stack.base, stack.size)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Target Module
+//!
+//! Describes the variants of the Hack CPU that assembly can be generated for,
+//! so that everything the generated code depends on about the platform is
+//! defined in one place:
+//!
+//! - `hack` (the default): The Hack computer of the nand2tetris course, with
+//!   16K words of RAM followed by the memory-mapped screen and keyboard, laid
+//!   out as the `standard` [`Layout`].
+//! - `hack-extended`: The same computer, with an ALU that can also shift, as
+//!   supported by the course's CPU emulator. `shiftleft` and `shiftright` are
+//!   translated into single instructions rather than loops.
+//! - `hack-32k`: A variant without memory-mapped devices, where all 32K
+//!   addressable words are RAM, laid out as the `big-ram` [`Layout`].
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+use crate::error::HackError;
+use crate::layout::Layout;
+use crate::parser::Constant;
+use crate::segments::UserSegment;
+
+/// A variant of the Hack CPU, and the platform around it.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Target {
+    /// The name of the profile.
+    name: &'static str,
+    /// The address of the first word of the temp segment.
+    temp_base: u16,
+    /// The address of the last word of the temp segment.
+    temp_max: u16,
+    /// How the RAM is laid out.
+    layout: Layout,
+    /// The highest address that may be read or written.
+    highest_address: u16,
+    /// Whether the ALU can shift.
+    shifts: bool,
+}
+
+impl Target {
+    /// The standard Hack computer.
+    pub const HACK: Self = Self {
+        name: "hack",
+        temp_base: 5,
+        temp_max: 12,
+        layout: Layout::STANDARD,
+        highest_address: 24576,
+        shifts: false,
+    };
+    /// The Hack computer with an ALU that can shift.
+    pub const HACK_EXTENDED: Self = Self {
+        name: "hack-extended",
+        shifts: true,
+        ..Self::HACK
+    };
+    /// The Hack computer with 32K words of RAM and no memory-mapped devices.
+    pub const HACK_32K: Self = Self {
+        name: "hack-32k",
+        highest_address: Constant::MAX_VALID_CONSTANT,
+        layout: Layout::BIG_RAM,
+        ..Self::HACK
+    };
+    /// Every profile.
+    const PROFILES: [Self; 3] =
+        [Self::HACK, Self::HACK_EXTENDED, Self::HACK_32K];
+
+    /// Gets the address of the first word of the temp segment.
+    pub const fn temp_base(self) -> u16 {
+        self.temp_base
+    }
+
+    /// Gets the highest index of the temp segment.
+    pub const fn temp_indices(self) -> u16 {
+        self.temp_max.saturating_sub(self.temp_base)
+    }
+
+    /// Gets where the temp segment is, unless it was moved; see
+    /// [`crate::segments`].
+    pub const fn temp(self) -> UserSegment {
+        UserSegment::fixed(
+            self.temp_base,
+            self.temp_indices().saturating_add(1),
+        )
+    }
+
+    /// Gets how the RAM is laid out.
+    pub const fn layout(self) -> Layout {
+        self.layout
+    }
+
+    /// Gets where the stack is, unless it was moved; see
+    /// [`crate::segments`]. `SP` is initialized to its base.
+    pub const fn stack(self) -> UserSegment {
+        self.layout.stack()
+    }
+
+    /// Gets the highest address that may be read or written.
+    pub const fn highest_address(self) -> u16 {
+        self.highest_address
+    }
+
+    /// Gets whether the ALU can shift.
+    pub const fn shifts(self) -> bool {
+        self.shifts
+    }
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Self::HACK
+    }
+}
+
+impl FromStr for Target {
+    type Err = HackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::PROFILES
+            .into_iter()
+            .find(|target: &Self| target.name == s)
+            .ok_or_else(|| {
+                let names: Vec<String> = Self::PROFILES
+                    .iter()
+                    .map(|target: &Self| format!("\"{}\"", target.name))
+                    .collect();
+                HackError::FromStrError(format!(
+                    "\"{s}\" is not a recognized target, expected one of {}",
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
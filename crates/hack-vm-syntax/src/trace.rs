@@ -0,0 +1,253 @@
+// SPDX-FileCopyrightText: Copyright © 2025 hashcatHitman
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # Hack VM Translator - Trace Module
+//!
+//! Diagnostic spans and events for debugging the translator on specific
+//! inputs. These are only compiled in with the `trace` feature, which needs
+//! `std` to read the environment and write to stderr, and are
+//! filtered at runtime by the `RUST_LOG` environment variable using the same
+//! directive syntax as `env_logger` and `tracing-subscriber`:
+//!
+//! ```bash
+//! RUST_LOG=debug hack-vm-translator ./foo.vm
+//! RUST_LOG=hack_vm_translator=trace hack-vm-translator ./foo.vm
+//! ```
+//!
+//! This is intentionally dependency-free, so that enabling it does not pull
+//! any additional crates into the build.
+
+#[doc(hidden)]
+pub use alloc::format;
+use alloc::string::String;
+#[cfg(feature = "trace")]
+use alloc::vec::Vec;
+#[cfg(feature = "trace")]
+use core::cell::RefCell;
+use core::fmt::{self, Display};
+#[cfg(feature = "trace")]
+use std::env;
+#[cfg(feature = "trace")]
+use std::sync::OnceLock;
+#[cfg(feature = "trace")]
+use std::time::Instant;
+
+/// The name events are emitted under, and which `RUST_LOG` directives can
+/// target.
+#[cfg(feature = "trace")]
+const TARGET: &str = "hack_vm_translator";
+
+/// The verbosity of a span or event, from least to most verbose.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Something went wrong.
+    Error,
+    /// Something may have gone wrong.
+    Warn,
+    /// A high-level step of the run.
+    Info,
+    /// Details useful for debugging a single file.
+    Debug,
+    /// Details useful for debugging a single instruction.
+    Trace,
+}
+
+impl Level {
+    /// Get the name of this [`Level`] as it appears in output.
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+
+    /// Parses a level as written in a `RUST_LOG` directive. Returns [`None`]
+    /// for `off` or for anything unrecognized.
+    #[cfg(feature = "trace")]
+    fn parse(level: &str) -> Option<Self> {
+        match level.trim().to_ascii_lowercase().as_str() {
+            "error" => Some(Self::Error),
+            "warn" => Some(Self::Warn),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            "trace" => Some(Self::Trace),
+            _ => None,
+        }
+    }
+
+    /// Determines the most verbose [`Level`] enabled by a `RUST_LOG` filter.
+    ///
+    /// A directive naming this crate takes precedence over a bare level.
+    /// Directives for other targets are ignored.
+    #[cfg(feature = "trace")]
+    fn from_filter(filter: &str) -> Option<Self> {
+        let mut level: Option<Self> = None;
+        for directive in filter.split(',').map(str::trim) {
+            match directive.split_once('=') {
+                Some((target, target_level)) if target.trim() == TARGET => {
+                    return Self::parse(target_level);
+                }
+                Some(_) => {}
+                None if directive == TARGET => return Some(Self::Trace),
+                None => level = Self::parse(directive),
+            }
+        }
+        level
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>5}", self.name())
+    }
+}
+
+#[cfg(feature = "trace")]
+std::thread_local! {
+    /// The names of the spans currently entered on this thread, outermost
+    /// first.
+    static SPANS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The most verbose [`Level`] enabled by `RUST_LOG`, read once.
+#[cfg(feature = "trace")]
+fn max_level() -> Option<Level> {
+    /// The cached result of reading `RUST_LOG`.
+    static MAX_LEVEL: OnceLock<Option<Level>> = OnceLock::new();
+    *MAX_LEVEL.get_or_init(|| {
+        let filter: String = env::var("RUST_LOG").ok()?;
+        Level::from_filter(&filter)
+    })
+}
+
+/// Whether spans and events at the given [`Level`] should be emitted.
+#[cfg(feature = "trace")]
+pub fn enabled(level: Level) -> bool {
+    max_level().is_some_and(|max| level <= max)
+}
+
+/// Whether spans and events at the given [`Level`] should be emitted.
+///
+/// Always [`false`] without the `trace` feature, so the surrounding code is
+/// optimized away entirely.
+#[cfg(not(feature = "trace"))]
+pub const fn enabled(_level: Level) -> bool {
+    false
+}
+
+/// Writes an event to stderr, prefixed by the spans it occurred in.
+#[cfg(feature = "trace")]
+pub fn emit(level: Level, message: fmt::Arguments<'_>) {
+    SPANS.with_borrow(|spans: &Vec<String>| {
+        let mut prefix: String = String::new();
+        for span in spans {
+            prefix.push_str(span);
+            prefix.push_str(": ");
+        }
+        std::eprintln!("{level} {TARGET}: {prefix}{message}");
+    });
+}
+
+/// Writes nothing, as events are never enabled without the `trace` feature.
+#[cfg(not(feature = "trace"))]
+pub const fn emit(_level: Level, _message: fmt::Arguments<'_>) {}
+
+/// A span of time during which events are attributed to a named operation.
+/// The span is exited, reporting how long it took, when this is dropped.
+#[derive(Debug)]
+pub struct Span {
+    /// The level of the span, if it was entered.
+    entered: Option<Level>,
+    /// When the span was entered.
+    #[cfg(feature = "trace")]
+    start: Instant,
+}
+
+impl Span {
+    /// Enters a span at the given [`Level`], named by the result of `name`.
+    /// If the level is not enabled, `name` is never called.
+    pub fn enter<F: FnOnce() -> String>(level: Level, name: F) -> Self {
+        let entered: Option<Level> = enabled(level).then(|| {
+            Self::push(name());
+            emit(level, format_args!("enter"));
+            level
+        });
+        Self {
+            entered,
+            #[cfg(feature = "trace")]
+            start: Instant::now(),
+        }
+    }
+
+    /// Records that the span named `name` was entered on this thread.
+    #[cfg(feature = "trace")]
+    fn push(name: String) {
+        SPANS.with_borrow_mut(|spans: &mut Vec<String>| spans.push(name));
+    }
+
+    /// Records nothing, as spans are never entered without the `trace`
+    /// feature.
+    #[cfg(not(feature = "trace"))]
+    fn push(_name: String) {}
+
+    /// Exits the span, which was entered at the given [`Level`].
+    #[cfg(feature = "trace")]
+    fn exit(&self, level: Level) {
+        emit(
+            level,
+            format_args!(
+                "exit after {:.3} ms",
+                self.start.elapsed().as_secs_f64() * 1000.0
+            ),
+        );
+        let _exited: Option<String> = SPANS.with_borrow_mut(Vec::<String>::pop);
+    }
+
+    /// Exits nothing, as spans are never entered without the `trace`
+    /// feature.
+    #[cfg(not(feature = "trace"))]
+    #[expect(
+        clippy::unused_self,
+        reason = "matches the signature with the `trace` feature"
+    )]
+    const fn exit(&self, _level: Level) {}
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(level) = self.entered {
+            self.exit(level);
+        }
+    }
+}
+
+/// Emits an event at the given [`Level`] if it is enabled. The message is
+/// only formatted if it will be emitted.
+#[macro_export]
+macro_rules! event {
+    ($level:ident, $($message:tt)+) => {
+        if $crate::trace::enabled($crate::trace::Level::$level) {
+            $crate::trace::emit(
+                $crate::trace::Level::$level,
+                format_args!($($message)+),
+            );
+        }
+    };
+}
+
+/// Enters a [`Span`] at the given [`Level`], which lasts until the returned
+/// guard is dropped. The name is only formatted if it will be emitted.
+#[macro_export]
+macro_rules! span {
+    ($level:ident, $($name:tt)+) => {
+        $crate::trace::Span::enter($crate::trace::Level::$level, || {
+            $crate::trace::format!($($name)+)
+        })
+    };
+}
+
+pub use crate::{event, span};